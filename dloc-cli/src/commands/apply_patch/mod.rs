@@ -0,0 +1,69 @@
+use std::{
+    fs::File,
+    io::{BufReader, BufWriter, Write},
+    path::PathBuf,
+};
+
+use clap::{Parser, ValueHint};
+use dloc_core::{
+    games::{ds::DSLocal, hzd::HZDLocal},
+    logger::Logger,
+    patch::Patch,
+};
+
+use crate::{logger::CliLogger, Game};
+
+use super::{shared::resolve_game, utils};
+
+#[derive(Debug, Parser)]
+#[command(arg_required_else_help = true)]
+pub struct ApplyPatch {
+    /// Vanilla core file the patch was made against
+    #[arg(value_hint = ValueHint::FilePath, value_parser = utils::is_file)]
+    input_core: PathBuf,
+    /// Patch file made by create-patch
+    #[arg(value_hint = ValueHint::FilePath, value_parser = utils::is_file)]
+    patch: PathBuf,
+    /// Output core file, by default its next to the input core
+    #[arg(value_hint = ValueHint::FilePath)]
+    output: Option<PathBuf>,
+}
+
+impl ApplyPatch {
+    pub fn command(self, game: Game, mut logger: CliLogger) -> anyhow::Result<()> {
+        let game = resolve_game(game, &self.input_core, &mut logger)?;
+        logger.info(format!("Selected game: {game:#?}"));
+
+        let patch = Patch::read(&mut BufReader::new(File::open(&self.patch)?))?;
+        let reader = BufReader::new(File::open(&self.input_core)?);
+        let output = utils::resolve_output(self.output, &self.input_core, "new.core");
+
+        // only create the output after the patch is applied, so a patch
+        // that doesn't fit doesn't leave an empty core behind
+        let count = match game {
+            Game::Hzd => {
+                let mut core = HZDLocal::new(reader)?;
+                let count = core.apply_patch(&patch)?;
+                let mut writer = BufWriter::new(File::create(&output)?);
+                core.write(&mut writer)?;
+                writer.flush()?;
+                count
+            }
+            Game::Ds => {
+                let mut core = DSLocal::new(reader)?;
+                let count = core.apply_patch(&patch)?;
+                let mut writer = BufWriter::new(File::create(&output)?);
+                core.write(&mut writer)?;
+                writer.flush()?;
+                count
+            }
+            Game::Auto => unreachable!(),
+        };
+
+        logger.good(format!(
+            "Patched {count} strings, new core written to {output:?}."
+        ));
+
+        Ok(())
+    }
+}
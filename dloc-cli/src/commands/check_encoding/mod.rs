@@ -0,0 +1,181 @@
+use std::{
+    fs::File,
+    io::BufReader,
+    path::{Path, PathBuf},
+};
+
+use anyhow::bail;
+use clap::{Parser, ValueHint};
+use dloc_core::{
+    games::{ds::DSLocal, hzd::HZDLocal, StringEntry},
+    logger::Logger,
+    serialize::DecimaGroup,
+};
+
+use crate::{logger::CliLogger, Game};
+
+use super::shared::resolve_game;
+
+#[derive(Debug, Parser)]
+#[command(arg_required_else_help = true)]
+pub struct CheckEncoding {
+    /// Input core file or a folder that have core files inside it
+    #[arg(value_hint = ValueHint::AnyPath)]
+    input: PathBuf,
+}
+
+impl CheckEncoding {
+    pub fn command(self, game: Game, mut logger: CliLogger) -> anyhow::Result<()> {
+        if !self.input.exists() {
+            bail!("You need to pass a valid file or dir path.");
+        }
+
+        let game = resolve_game(game, &self.input, &mut logger)?;
+        logger.info(format!("Selected game: {game:#?}"));
+
+        let mut issues = Vec::new();
+        match game {
+            Game::Hzd => {
+                if self.input.is_dir() {
+                    DecimaGroup::<HZDLocal, _>::new(&self.input, &mut logger)?
+                        .for_each(|path, core| check(&mut issues, Some(path), core.strings()))?;
+                } else {
+                    let core = HZDLocal::new(BufReader::new(File::open(&self.input)?))?;
+                    check(&mut issues, None, core.strings());
+                }
+            }
+            Game::Ds => {
+                if self.input.is_dir() {
+                    DecimaGroup::<DSLocal, _>::new(&self.input, &mut logger)?
+                        .for_each(|path, core| check(&mut issues, Some(path), core.strings()))?;
+                } else {
+                    let core = DSLocal::new(BufReader::new(File::open(&self.input)?))?;
+                    check(&mut issues, None, core.strings());
+                }
+            }
+            Game::Auto => unreachable!(),
+        }
+
+        if issues.is_empty() {
+            logger.good("Didn't found any string with a broken encoding.");
+            return Ok(());
+        }
+
+        for issue in issues.iter() {
+            logger.warn(issue);
+        }
+        logger.warn(format!(
+            "Found {} strings that look like they have a broken encoding.",
+            issues.len()
+        ));
+
+        Ok(())
+    }
+}
+
+/// Add a message for every string in `strings` that looks broken.
+fn check<'a, L: std::fmt::Display>(
+    issues: &mut Vec<String>,
+    path: Option<&Path>,
+    strings: impl Iterator<Item = StringEntry<'a, L>>,
+) {
+    for entry in strings {
+        let Some(problem) = encoding_problem(entry.string) else {
+            continue;
+        };
+
+        let file = path
+            .map(|p| format!("{}: ", p.display()))
+            .unwrap_or_default();
+        let preview: String = entry.string.chars().take(60).collect();
+        issues.push(format!(
+            "{file}{}:{} ({}) {problem}: {preview:?}",
+            entry.index, entry.sub, entry.language
+        ));
+    }
+}
+
+/// Returns why `string` looks broken, if it does.
+///
+/// A double encoded string is UTF-8 that was read as Latin-1 or Windows-1252
+/// and encoded again, like `Ã©` for `é` or `â€™` for `’`. So every run of
+/// characters that fit in a single byte of those code pages is turned back
+/// into bytes, and if they make valid multi byte UTF-8 its most likely
+/// double encoded, as real text almost never have such runs.
+fn encoding_problem(string: &str) -> Option<&'static str> {
+    if string.contains(char::REPLACEMENT_CHARACTER) {
+        return Some("have replacement characters");
+    }
+
+    let mut run = Vec::new();
+    for byte in string.chars().map(single_byte).chain([None]) {
+        match byte {
+            Some(byte) => run.push(byte),
+            None => {
+                if run.len() > 1 && std::str::from_utf8(&run).is_ok() {
+                    return Some("looks double encoded");
+                }
+                run.clear();
+            }
+        }
+    }
+
+    None
+}
+
+/// The byte of a non ascii character in Latin-1 or Windows-1252, the later
+/// have some printable characters in place of the `0x80..0xA0` control codes.
+fn single_byte(ch: char) -> Option<u8> {
+    const WINDOWS_1252: [char; 32] = [
+        '€', '\u{81}', '‚', 'ƒ', '„', '…', '†', '‡', 'ˆ', '‰', 'Š', '‹', 'Œ', '\u{8D}', 'Ž',
+        '\u{8F}', '\u{90}', '‘', '’', '“', '”', '•', '–', '—', '˜', '™', 'š', '›', 'œ', '\u{9D}',
+        'ž', 'Ÿ',
+    ];
+
+    match ch as u32 {
+        0x80..=0xFF => Some(ch as u8),
+        _ => WINDOWS_1252
+            .iter()
+            .position(|c| *c == ch)
+            .map(|i| 0x80 + i as u8),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn double_encoded_strings_are_found() {
+        assert_eq!(encoding_problem("cafÃ©"), Some("looks double encoded"));
+        assert_eq!(encoding_problem("donâ€™t"), Some("looks double encoded"));
+        assert_eq!(
+            encoding_problem("broken \u{FFFD} text"),
+            Some("have replacement characters")
+        );
+
+        for fine in [
+            "",
+            "plain ascii",
+            "café",
+            "naïve façade",
+            "Ñandú",
+            "ÀÉ",
+            "✓ Ünïcödé",
+        ] {
+            assert_eq!(encoding_problem(fine), None, "{fine}");
+        }
+    }
+
+    #[test]
+    fn single_byte_follows_windows_1252() {
+        assert_eq!(single_byte('é'), Some(0xE9));
+        assert_eq!(single_byte('\u{A0}'), Some(0xA0));
+        assert_eq!(single_byte('€'), Some(0x80));
+        assert_eq!(single_byte('’'), Some(0x92));
+        assert_eq!(single_byte('Ÿ'), Some(0x9F));
+        assert_eq!(single_byte('\u{81}'), Some(0x81));
+        assert_eq!(single_byte('a'), None);
+        assert_eq!(single_byte('✓'), None);
+    }
+}
@@ -0,0 +1,55 @@
+use std::path::PathBuf;
+
+use anyhow::bail;
+use clap::{Parser, ValueHint};
+use dloc_core::{
+    games::{ds::DSLocal, hzd::HZDLocal},
+    logger::Logger,
+    serialize::{DecimaGroup, SerializeData},
+};
+
+use crate::{logger::CliLogger, Game};
+
+use super::utils;
+
+#[derive(Debug, Parser)]
+#[command(arg_required_else_help = true)]
+pub struct CheckExport {
+    /// Exported txt file, the deinfo file should be next to it
+    #[arg(value_hint = ValueHint::FilePath, value_parser = utils::is_file)]
+    exported_file: PathBuf,
+    /// The file was exported in group mode
+    #[arg(short, long)]
+    group: bool,
+}
+
+impl CheckExport {
+    pub fn command(self, game: Game, mut logger: CliLogger) -> anyhow::Result<()> {
+        logger.info("Checking the exported file against its deinfo.");
+
+        let issues = match (game, self.group) {
+            (Game::Auto, _) => {
+                bail!("Auto can't be used in check-export command, input the game directly.")
+            }
+            (Game::Hzd, false) => HZDLocal::check_txt_export(&self.exported_file)?,
+            (Game::Hzd, true) => {
+                DecimaGroup::<HZDLocal, CliLogger>::check_txt_export(&self.exported_file)?
+            }
+            (Game::Ds, false) => DSLocal::check_txt_export(&self.exported_file)?,
+            (Game::Ds, true) => {
+                DecimaGroup::<DSLocal, CliLogger>::check_txt_export(&self.exported_file)?
+            }
+        };
+
+        if issues.is_empty() {
+            logger.good("No issue found, the export is ready to import.");
+            return Ok(());
+        }
+
+        for issue in issues.iter() {
+            logger.error(issue);
+        }
+
+        bail!("Found {} issue(s) in the export.", issues.len());
+    }
+}
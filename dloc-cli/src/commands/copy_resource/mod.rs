@@ -0,0 +1,81 @@
+use std::{
+    fs::File,
+    io::{BufReader, BufWriter},
+    path::PathBuf,
+};
+
+use anyhow::bail;
+use clap::{Parser, ValueHint};
+use dloc_core::{
+    games::{ds::DSLocal, hzd::HZDLocal},
+    logger::Logger,
+};
+
+use crate::{logger::CliLogger, Game};
+
+use super::{shared::resolve_game, utils};
+
+#[derive(Debug, Parser)]
+#[command(arg_required_else_help = true)]
+pub struct CopyResource {
+    /// Core file to copy the resources from
+    #[arg(value_hint = ValueHint::FilePath, value_parser = utils::is_file)]
+    source_core: PathBuf,
+    /// Core file to copy the resources into
+    #[arg(value_hint = ValueHint::FilePath, value_parser = utils::is_file)]
+    input_core: PathBuf,
+    /// Indices of the resources to copy, the same in both cores, like 3,7
+    #[arg(short, long, required = true, value_delimiter = ',')]
+    indices: Vec<usize>,
+    /// Output core file, by default its next to the input core
+    #[arg(value_hint = ValueHint::FilePath)]
+    output: Option<PathBuf>,
+    /// Don't skip writing the core to disk if nothing changed
+    #[arg(short, long)]
+    dont_skip: bool,
+}
+
+impl CopyResource {
+    pub fn command(self, game: Game, mut logger: CliLogger) -> anyhow::Result<()> {
+        let game = resolve_game(game, &self.input_core, &mut logger)?;
+        logger.info(format!("Selected game: {game:#?}"));
+
+        let output = utils::resolve_output(self.output, &self.input_core, "new.core");
+        let source = BufReader::new(File::open(&self.source_core)?);
+        let reader = BufReader::new(File::open(&self.input_core)?);
+
+        match game {
+            Game::Hzd => {
+                let source = HZDLocal::new(source)?;
+                let mut core = HZDLocal::new(reader)?;
+                for &index in &self.indices {
+                    logger.info(format!("Copying resource {index}."));
+                    core.copy_resource(&source, index)?;
+                }
+
+                if !self.dont_skip && !core.is_modified() {
+                    bail!("Nothing changed, write to disk cancelled.");
+                }
+                core.write(&mut BufWriter::new(File::create(&output)?))?;
+            }
+            Game::Ds => {
+                let source = DSLocal::new(source)?;
+                let mut core = DSLocal::new(reader)?;
+                for &index in &self.indices {
+                    logger.info(format!("Copying resource {index}."));
+                    core.copy_resource(&source, index)?;
+                }
+
+                if !self.dont_skip && !core.is_modified() {
+                    bail!("Nothing changed, write to disk cancelled.");
+                }
+                core.write(&mut BufWriter::new(File::create(&output)?))?;
+            }
+            Game::Auto => unreachable!(),
+        }
+
+        logger.good(format!("New core written to {output:?}."));
+
+        Ok(())
+    }
+}
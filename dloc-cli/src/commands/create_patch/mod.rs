@@ -0,0 +1,61 @@
+use std::{
+    fs::File,
+    io::{BufReader, BufWriter, Write},
+    path::PathBuf,
+};
+
+use anyhow::bail;
+use clap::{Parser, ValueHint};
+use dloc_core::{
+    games::{ds::DSLocal, hzd::HZDLocal},
+    logger::Logger,
+};
+
+use crate::{logger::CliLogger, Game};
+
+use super::{shared::resolve_game, utils};
+
+#[derive(Debug, Parser)]
+#[command(arg_required_else_help = true)]
+pub struct CreatePatch {
+    /// Vanilla core file
+    #[arg(value_hint = ValueHint::FilePath, value_parser = utils::is_file)]
+    reference_core: PathBuf,
+    /// Translated version of the same core
+    #[arg(value_hint = ValueHint::FilePath, value_parser = utils::is_file)]
+    modified_core: PathBuf,
+    /// Output patch file, by default its next to the translated core
+    #[arg(value_hint = ValueHint::FilePath)]
+    output: Option<PathBuf>,
+}
+
+impl CreatePatch {
+    pub fn command(self, game: Game, mut logger: CliLogger) -> anyhow::Result<()> {
+        let game = resolve_game(game, &self.modified_core, &mut logger)?;
+        logger.info(format!("Selected game: {game:#?}"));
+
+        let reference = BufReader::new(File::open(&self.reference_core)?);
+        let modified = BufReader::new(File::open(&self.modified_core)?);
+        let patch = match game {
+            Game::Hzd => HZDLocal::new(modified)?.create_patch(&HZDLocal::new(reference)?)?,
+            Game::Ds => DSLocal::new(modified)?.create_patch(&DSLocal::new(reference)?)?,
+            Game::Auto => unreachable!(),
+        };
+
+        if patch.is_empty() {
+            bail!("The cores have the same strings, there is nothing to patch.");
+        }
+
+        let output = utils::resolve_output(self.output, &self.modified_core, "patch");
+        let mut writer = BufWriter::new(File::create(&output)?);
+        patch.write(&mut writer)?;
+        writer.flush()?;
+
+        logger.good(format!(
+            "Patch with {} strings written to {output:?}.",
+            patch.len()
+        ));
+
+        Ok(())
+    }
+}
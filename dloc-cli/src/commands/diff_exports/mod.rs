@@ -0,0 +1,103 @@
+use std::{fmt::Display, io::Write, path::PathBuf};
+
+use anyhow::bail;
+use clap::{Parser, ValueHint};
+use dloc_core::{
+    games::{ds::DSLocal, hzd::HZDLocal},
+    logger::Logger,
+    serialize::{ExportDifference, SerializeData},
+};
+
+use crate::{logger::CliLogger, Game};
+
+use super::{shared::FormatOptions, utils, SerializeType};
+
+#[derive(Debug, Parser)]
+#[command(arg_required_else_help = true)]
+pub struct DiffExports {
+    /// Old exported file of a single core
+    #[arg(value_hint = ValueHint::FilePath, value_parser = utils::is_file)]
+    old: PathBuf,
+    /// New exported file of the same core in the same format
+    #[arg(value_hint = ValueHint::FilePath, value_parser = utils::is_file)]
+    new: PathBuf,
+}
+
+impl DiffExports {
+    pub fn command(
+        self,
+        game: Game,
+        sert: Option<SerializeType>,
+        mut logger: CliLogger,
+    ) -> anyhow::Result<()> {
+        let sert = SerializeType::resolve_import(sert, &self.old, &mut logger);
+        if sert == SerializeType::Txt {
+            bail!("Txt exports can't be compared without their core, use another format.");
+        }
+        if sert == SerializeType::Sqlite {
+            bail!("SQLite exports can't be compared, query them directly or use another format.");
+        }
+        let serialize_type = sert.to_core(FormatOptions::default());
+
+        let output = match game {
+            Game::Auto => {
+                bail!("Auto can't be used in diff-exports command, input the game directly.")
+            }
+            Game::Hzd => format_differences(HZDLocal::diff_exports(
+                &self.old,
+                &self.new,
+                serialize_type,
+            )?),
+            Game::Ds => {
+                format_differences(DSLocal::diff_exports(&self.old, &self.new, serialize_type)?)
+            }
+        };
+
+        let Some((output, strings, resources)) = output else {
+            logger.good("Both exports have the same strings.");
+            return Ok(());
+        };
+
+        logger.stdout.write_all(output.as_bytes())?;
+        logger.warn(format!(
+            "{strings} strings differ in {resources} resources."
+        ));
+
+        Ok(())
+    }
+}
+
+/// List the differences under a header for each resource, returns the text
+/// with the number of strings and resources that differ.
+fn format_differences<L: Display>(
+    differences: Vec<ExportDifference<L>>,
+) -> Option<(String, usize, usize)> {
+    if differences.is_empty() {
+        return None;
+    }
+
+    let mut output = String::new();
+    let mut resources = 0;
+    let mut last_index = None;
+    for difference in differences.iter() {
+        if last_index != Some(difference.index) {
+            output.push_str(&format!("Resource {}\n", difference.index));
+            last_index = Some(difference.index);
+            resources += 1;
+        }
+
+        let string = |s: &Option<String>| match s {
+            Some(s) => format!("{s:?}"),
+            None => "missing".to_owned(),
+        };
+        output.push_str(&format!(
+            "  {:<24}{:<6}{} -> {}\n",
+            difference.language.to_string(),
+            format!("[{}]", difference.sub),
+            string(&difference.old),
+            string(&difference.new)
+        ));
+    }
+
+    Some((output, differences.len(), resources))
+}
@@ -0,0 +1,93 @@
+use std::{
+    fmt::Display,
+    fs::File,
+    io::{BufReader, BufWriter, Write},
+    path::{Path, PathBuf},
+};
+
+use anyhow::bail;
+use clap::{Parser, ValueHint};
+use dloc_core::{
+    games::{ds::DSLocal, hzd::HZDLocal},
+    logger::Logger,
+    serialize::EofReplacor,
+};
+
+use crate::{logger::CliLogger, Game};
+
+use super::{
+    shared::{parse_ds_languages, parse_hzd_languages, resolve_game},
+    utils,
+};
+
+#[derive(Debug, Parser)]
+#[command(arg_required_else_help = true)]
+pub struct ExtractLanguage {
+    /// Input core file
+    #[arg(value_hint = ValueHint::FilePath, value_parser = utils::is_file)]
+    input_core: PathBuf,
+    /// Language to extract
+    language: String,
+    /// Output text file, by default its next to the input core
+    #[arg(value_hint = ValueHint::FilePath)]
+    output: Option<PathBuf>,
+}
+
+impl ExtractLanguage {
+    pub fn command(self, game: Game, mut logger: CliLogger) -> anyhow::Result<()> {
+        let game = resolve_game(game, &self.input_core, &mut logger)?;
+        logger.info(format!("Selected game: {game:#?}"));
+
+        let reader = BufReader::new(File::open(&self.input_core)?);
+        let (count, output) = match game {
+            Game::Hzd => {
+                let Some(&language) =
+                    parse_hzd_languages(vec![self.language], Vec::new(), &mut logger).first()
+                else {
+                    bail!("Didn't found any valid Language.");
+                };
+                let core = HZDLocal::new(reader)?;
+                let output = output_path(self.output, &self.input_core, language);
+                (
+                    write_language(&output, core.language_strings(language))?,
+                    output,
+                )
+            }
+            Game::Ds => {
+                let Some(&language) =
+                    parse_ds_languages(vec![self.language], Vec::new(), &mut logger).first()
+                else {
+                    bail!("Didn't found any valid Language.");
+                };
+                let core = DSLocal::new(reader)?;
+                let output = output_path(self.output, &self.input_core, language);
+                (
+                    write_language(&output, core.language_strings(language))?,
+                    output,
+                )
+            }
+            Game::Auto => unreachable!(),
+        };
+
+        logger.good(format!("{count} strings written to {output:?}."));
+
+        Ok(())
+    }
+}
+
+fn output_path(output: Option<PathBuf>, input: &Path, language: impl Display) -> PathBuf {
+    utils::resolve_output(output, input, &format!("{language}.txt"))
+}
+
+/// Write every string as a line, line breaks inside the strings are
+/// escaped. Returns the number of written lines.
+fn write_language(output: &Path, strings: Vec<String>) -> std::io::Result<usize> {
+    let mut writer = BufWriter::new(File::create(output)?);
+    let count = strings.len();
+    for string in strings {
+        writeln!(writer, "{}", string.replace_eol())?;
+    }
+    writer.flush()?;
+
+    Ok(count)
+}
@@ -0,0 +1,180 @@
+use std::{
+    collections::BTreeMap,
+    fmt::Display,
+    fs::File,
+    io::{BufReader, BufWriter, Write},
+    path::PathBuf,
+};
+
+use anyhow::bail;
+use clap::{Parser, ValueHint};
+use dloc_core::{
+    games::{ds::DSLocal, hzd::HZDLocal, StringEntry},
+    logger::Logger,
+};
+
+use crate::{logger::CliLogger, Game};
+
+use super::{
+    shared::{parse_ds_languages, parse_hzd_languages, resolve_game},
+    utils,
+};
+
+#[derive(Debug, Parser)]
+#[command(arg_required_else_help = true)]
+pub struct FindIdentical {
+    /// Input core file
+    #[arg(value_hint = ValueHint::FilePath, value_parser = utils::is_file)]
+    input_core: PathBuf,
+    /// Languages to compare, pass 'all' if you want to compare every language
+    #[arg(short, long, num_args = 1.., default_values_t = ["all".to_owned()], value_delimiter = ' ')]
+    languages: Vec<String>,
+    /// Copy the shared string into the languages that are empty and write a new core
+    #[arg(long)]
+    fill_empty: bool,
+    /// Output core file of --fill-empty, by default its next to the input core
+    #[arg(value_hint = ValueHint::FilePath)]
+    output: Option<PathBuf>,
+}
+
+/// A string that is the same in every selected language that isn't empty.
+struct Identical<L> {
+    index: usize,
+    sub: usize,
+    string: String,
+    /// Selected languages that don't have the string yet.
+    empty: Vec<L>,
+}
+
+impl FindIdentical {
+    pub fn command(self, game: Game, mut logger: CliLogger) -> anyhow::Result<()> {
+        let game = resolve_game(game, &self.input_core, &mut logger)?;
+        logger.info(format!("Selected game: {game:#?}"));
+
+        let output = utils::resolve_output(self.output, &self.input_core, "new.core");
+        let reader = BufReader::new(File::open(&self.input_core)?);
+
+        let filled = match game {
+            Game::Hzd => {
+                let languages = parse_hzd_languages(self.languages, Vec::new(), &mut logger);
+                if languages.len() < 2 {
+                    bail!("At least two languages are needed to compare.");
+                }
+
+                let mut core = HZDLocal::new(reader)?;
+                let identical = find(core.strings(), &languages);
+                report(&identical, &mut logger)?;
+
+                if !self.fill_empty {
+                    return Ok(());
+                }
+                let mut filled = 0;
+                for entry in identical {
+                    for language in entry.empty {
+                        core.set_string(entry.index, language, entry.sub, entry.string.clone())?;
+                        filled += 1;
+                    }
+                }
+                if filled != 0 {
+                    core.write(&mut BufWriter::new(File::create(&output)?))?;
+                }
+                filled
+            }
+            Game::Ds => {
+                let languages = parse_ds_languages(self.languages, Vec::new(), &mut logger);
+                if languages.len() < 2 {
+                    bail!("At least two languages are needed to compare.");
+                }
+
+                let mut core = DSLocal::new(reader)?;
+                let identical = find(core.strings(), &languages);
+                report(&identical, &mut logger)?;
+
+                if !self.fill_empty {
+                    return Ok(());
+                }
+                let mut filled = 0;
+                for entry in identical {
+                    for language in entry.empty {
+                        core.set_string(entry.index, language, entry.sub, entry.string.clone())?;
+                        filled += 1;
+                    }
+                }
+                if filled != 0 {
+                    core.write(&mut BufWriter::new(File::create(&output)?))?;
+                }
+                filled
+            }
+            Game::Auto => unreachable!(),
+        };
+
+        if filled == 0 {
+            bail!("No empty string to fill, write to disk cancelled.");
+        }
+        logger.good(format!(
+            "Filled {filled} empty strings, new core written to {output:?}."
+        ));
+
+        Ok(())
+    }
+}
+
+/// Find the strings that at least two of the `languages` have and all of
+/// them are the same, empty strings are taken as not translated yet.
+fn find<'a, L: Copy + PartialEq + 'a>(
+    strings: impl Iterator<Item = StringEntry<'a, L>>,
+    languages: &[L],
+) -> Vec<Identical<L>> {
+    let mut positions: BTreeMap<(usize, usize), Vec<(L, &str)>> = BTreeMap::new();
+    for entry in strings.filter(|e| languages.contains(&e.language)) {
+        positions
+            .entry((entry.index, entry.sub))
+            .or_default()
+            .push((entry.language, entry.string));
+    }
+
+    positions
+        .into_iter()
+        .filter_map(|((index, sub), strings)| {
+            let (filled, empty): (Vec<_>, Vec<_>) =
+                strings.into_iter().partition(|(_, s)| !s.is_empty());
+            let (_, string) = *filled.first()?;
+            if filled.len() < 2 || filled.iter().any(|(_, s)| *s != string) {
+                return None;
+            }
+
+            Some(Identical {
+                index,
+                sub,
+                string: string.to_owned(),
+                empty: empty.into_iter().map(|(l, _)| l).collect(),
+            })
+        })
+        .collect()
+}
+
+/// Print every identical string and how many were found.
+fn report<L: Display>(identical: &[Identical<L>], logger: &mut CliLogger) -> std::io::Result<()> {
+    if identical.is_empty() {
+        logger.good("Didn't found any string that is the same in every language.");
+        return Ok(());
+    }
+
+    let mut output = String::new();
+    for entry in identical {
+        let preview: String = entry.string.chars().take(60).collect();
+        output.push_str(&format!("{}:{} {preview:?}", entry.index, entry.sub));
+        if !entry.empty.is_empty() {
+            let empty: Vec<_> = entry.empty.iter().map(ToString::to_string).collect();
+            output.push_str(&format!(" (empty in {})", empty.join(", ")));
+        }
+        output.push('\n');
+    }
+    logger.stdout.write_all(output.as_bytes())?;
+
+    logger.good(format!(
+        "Found {} strings that are the same in every language.",
+        identical.len()
+    ));
+    Ok(())
+}
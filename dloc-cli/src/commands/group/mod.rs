@@ -1,17 +1,24 @@
-use std::{fs::File, io::BufReader, path::PathBuf};
+use std::path::PathBuf;
 
 use anyhow::bail;
 use clap::{Parser, ValueHint};
 use dloc_core::{
-    games::{detect, ds::DSLocal, hzd::HZDLocal},
+    games::{
+        ds::{self, DSLocal},
+        hzd::{self, HZDLocal},
+    },
     logger::Logger,
-    serialize::DecimaGroup,
+    serialize::{deinfo_path, DecimaGroup, ImportOptions, PathRemap},
 };
 
 use crate::{logger::CliLogger, Game};
 
 use super::{
-    shared::{parse_ds_languages, parse_hzd_languages, Action},
+    shared::{
+        keep_populated, parse_ds_fallback, parse_ds_languages, parse_hzd_fallback,
+        parse_hzd_languages, resolve_game, wants_populated, write_checksums, Action, FormatOptions,
+        LineEnding, ResourceKind,
+    },
     utils, SerializeType,
 };
 
@@ -22,6 +29,17 @@ pub struct Group {
     input_dir: PathBuf,
     /// Output file
     output: Option<PathBuf>,
+    /// Only use the core files listed in this file, one path relative to input folder per line, in that order
+    #[arg(long, value_hint = ValueHint::FilePath, value_parser = utils::is_file)]
+    file_list: Option<PathBuf>,
+    /// Write the SHA-256 of every written file to a .sha256 file next to the output, check it with
+    /// verify-checksums
+    #[arg(long)]
+    checksums: bool,
+    /// Skip the core files bigger than this with a warning, in bytes or with a K, M or G suffix like 512M.
+    /// Only the file sizes are checked so oversized cores are never parsed
+    #[arg(long, value_name = "SIZE", value_parser = utils::parse_size)]
+    max_file_size: Option<u64>,
     #[command(subcommand)]
     action: Action,
 }
@@ -30,34 +48,15 @@ impl Group {
     pub fn command(
         self,
         game: Game,
-        sert: SerializeType,
+        sert: Option<SerializeType>,
         mut logger: CliLogger,
     ) -> anyhow::Result<()> {
-        let game = match game {
-            Game::Auto => {
-                let Some(path) = std::fs::read_dir(&self.input_dir)?.flatten().find(|d| {
-                    let path = d.path();
-                    path.is_file()
-                        .then(|| {
-                            path.extension()
-                                .is_some_and(|e| e.eq_ignore_ascii_case("core"))
-                        })
-                        .is_some()
-                }) else {
-                    bail!("Can't find any core file in input folder to use in auto detect.");
-                };
-
-                let mut reader = BufReader::new(File::open(path.path())?);
-                match detect::detect_game(&mut reader)? {
-                    detect::GameDetection::Hzd => Game::Hzd,
-                    detect::GameDetection::Ds => Game::Ds,
-                    detect::GameDetection::Mixed => bail!("Found mixed magic in input core."),
-                    detect::GameDetection::Unknown => bail!("Failed to detect any supported game."),
-                }
-            }
-            Game::Hzd => Game::Hzd,
-            Game::Ds => Game::Ds,
-        };
+        let game = resolve_game(game, &self.input_dir, &mut logger)?;
+        let files = self
+            .file_list
+            .as_deref()
+            .map(utils::read_file_list)
+            .transpose()?;
 
         logger.info(format!("Selected game: {game:#?}"));
         logger.info(format!("Selected action: {}", self.action.name()));
@@ -66,13 +65,36 @@ impl Group {
             Game::Hzd => match self.action {
                 Action::Export {
                     languages,
+                    exclude,
                     add_language_names,
+                    language_separator,
+                    add_indices,
+                    json_indent,
+                    omit_empty,
+                    normalize_line_endings,
+                    only,
+                    filter,
+                    uuid,
+                    sort_resources,
+                    byte_lengths,
+                    blank,
+                    fill_empty_from,
+                    split,
+                    no_deinfo,
+                    ..
                 } => {
+                    if no_deinfo && !split {
+                        bail!("--no-deinfo can only be used with --split.");
+                    }
+
+                    let sert = sert.unwrap_or_default();
                     let output = self
                         .output
                         .unwrap_or_else(|| self.input_dir.with_extension(sert.extension()));
 
-                    let languages = parse_hzd_languages(languages, &mut logger);
+                    let except_empty = wants_populated(&languages);
+                    let mut languages = parse_hzd_languages(languages, exclude, &mut logger);
+                    let fill_empty_from = parse_hzd_fallback(fill_empty_from)?;
 
                     if languages.is_empty() {
                         bail!("Didn't found any valid Language.");
@@ -80,44 +102,171 @@ impl Group {
 
                     logger.info(format!("Selected languages: {languages:?}"));
 
-                    let serialize_type = sert.to_core(Some(add_language_names));
+                    let serialize_type = sert.to_core(FormatOptions {
+                        add_language_names,
+                        language_separator,
+                        add_indices,
+                        json_indent,
+                        omit_empty,
+                        line_ending: normalize_line_endings.then_some(LineEnding::Lf),
+                    });
 
                     logger.info(format!(
                         "Serializing locals into {:?} format.",
                         serialize_type
                     ));
 
-                    DecimaGroup::<HZDLocal, _>::new(self.input_dir, logger)?.export(
-                        output,
-                        languages,
-                        serialize_type,
-                    )?;
+                    let filters = hzd::ExportFilters {
+                        resources: ResourceKind::to_hzd_filter(only),
+                        content: filter,
+                        uuids: uuid,
+                        sort_by_uuid: sort_resources,
+                        byte_lengths,
+                        fill_empty_from,
+                        blank,
+                    };
+                    let mut group =
+                        DecimaGroup::<HZDLocal, _>::new_with_files(self.input_dir, files, logger)?;
+                    if let Some(max_size) = self.max_file_size {
+                        group.set_max_file_size(max_size)?;
+                    }
+                    if except_empty {
+                        let mut populated = Vec::new();
+                        group.for_each(|_, g| {
+                            for language in g.populated_languages() {
+                                if !populated.contains(&language) {
+                                    populated.push(language);
+                                }
+                            }
+                        })?;
+                        keep_populated(&mut languages, &populated, group.logger_mut());
+                        if languages.is_empty() {
+                            bail!("None of the selected languages have any string.");
+                        }
+                    }
+                    if split {
+                        group.set_skip_deinfo(no_deinfo);
+                        group.export_split(&output, languages, serialize_type, &filters)?;
+                    } else {
+                        group.export(&output, languages, serialize_type, &filters)?;
+                    }
+
+                    if self.checksums {
+                        let mut written = vec![output.clone()];
+                        if sert == SerializeType::Txt && !split {
+                            written.push(deinfo_path(&output));
+                        }
+                        write_checksums(&output, &written, group.logger_mut())?;
+                    }
                 }
                 Action::Import {
                     exported_file,
-                    dont_skip: _,
+                    strip_prefix,
+                    add_prefix,
+                    max_length,
+                    check_placeholders,
+                    verify_after_write,
+                    preserve_mtime,
+                    line_ending,
+                    force,
+                    split,
+                    ..
                 } => {
+                    if utils::is_stdin(&exported_file) {
+                        bail!("Reading the exported file from stdin is only supported in single mode.");
+                    }
+                    if split != exported_file.is_dir() {
+                        bail!(
+                            "The exported file should be a folder if and only if --split is used."
+                        );
+                    }
+
+                    let sert = SerializeType::resolve_import(sert, &exported_file, &mut logger);
                     let output = self
                         .output
                         .unwrap_or_else(|| self.input_dir.with_extension("new"));
 
-                    DecimaGroup::<HZDLocal, _>::new(self.input_dir, logger)?.import(
-                        exported_file,
-                        output,
-                        sert.to_core(None),
-                    )?;
+                    let mut group =
+                        DecimaGroup::<HZDLocal, _>::new_with_files(self.input_dir, files, logger)?;
+
+                    if let Some(max_size) = self.max_file_size {
+                        group.set_max_file_size(max_size)?;
+                    }
+                    group.set_path_remap(PathRemap {
+                        strip_prefix,
+                        add_prefix,
+                    });
+                    group.set_max_length(max_length);
+                    group.set_check_placeholders(check_placeholders);
+                    group.set_verify_after_write(verify_after_write);
+                    group.set_preserve_mtime(preserve_mtime);
+                    let options = ImportOptions {
+                        check_resource_count: !force,
+                    };
+                    if split {
+                        group.import_split(
+                            &exported_file,
+                            &output,
+                            sert.to_core(FormatOptions {
+                                line_ending,
+                                ..Default::default()
+                            }),
+                            options,
+                        )?;
+                    } else {
+                        group.import(
+                            &exported_file,
+                            &output,
+                            sert.to_core(FormatOptions {
+                                line_ending,
+                                ..Default::default()
+                            }),
+                            options,
+                        )?;
+                    }
+
+                    if self.checksums {
+                        write_checksums(
+                            &output,
+                            std::slice::from_ref(&output),
+                            group.logger_mut(),
+                        )?;
+                    }
                 }
             },
             Game::Ds => match self.action {
                 Action::Export {
                     languages,
+                    exclude,
                     add_language_names,
+                    language_separator,
+                    add_indices,
+                    json_indent,
+                    omit_empty,
+                    normalize_line_endings,
+                    only,
+                    filter,
+                    uuid,
+                    sort_resources,
+                    byte_lengths,
+                    blank,
+                    fill_empty_from,
+                    split,
+                    no_deinfo,
+                    ..
                 } => {
+                    if no_deinfo && !split {
+                        bail!("--no-deinfo can only be used with --split.");
+                    }
+
+                    let sert = sert.unwrap_or_default();
                     let output = self
                         .output
                         .unwrap_or_else(|| self.input_dir.with_extension(sert.extension()));
 
-                    let languages = parse_ds_languages(languages, &mut logger);
+                    let except_empty = wants_populated(&languages);
+                    let mut languages = parse_ds_languages(languages, exclude, &mut logger);
+                    let fill_empty_from = parse_ds_fallback(fill_empty_from)?;
 
                     if languages.is_empty() {
                         bail!("Didn't found any valid Language.");
@@ -125,29 +274,138 @@ impl Group {
 
                     logger.info(format!("Selected languages: {languages:?}"));
 
-                    let serialize_type = sert.to_core(Some(add_language_names));
+                    let serialize_type = sert.to_core(FormatOptions {
+                        add_language_names,
+                        language_separator,
+                        add_indices,
+                        json_indent,
+                        omit_empty,
+                        line_ending: normalize_line_endings.then_some(LineEnding::Lf),
+                    });
+
+                    if only.is_some() {
+                        logger.warn("--only is only used with HZD, ignoring it.");
+                    }
 
                     logger.info(format!("Serializing locals into {:?} format.", sert));
 
-                    DecimaGroup::<DSLocal, _>::new(self.input_dir, logger)?.export(
-                        output,
-                        languages,
-                        serialize_type,
-                    )?;
+                    let filters = ds::ExportFilters {
+                        resources: (),
+                        content: filter,
+                        uuids: uuid,
+                        sort_by_uuid: sort_resources,
+                        byte_lengths,
+                        fill_empty_from,
+                        blank,
+                    };
+                    let mut group =
+                        DecimaGroup::<DSLocal, _>::new_with_files(self.input_dir, files, logger)?;
+
+                    if let Some(max_size) = self.max_file_size {
+                        group.set_max_file_size(max_size)?;
+                    }
+                    if except_empty {
+                        let mut populated = Vec::new();
+                        group.for_each(|_, g| {
+                            for language in g.populated_languages() {
+                                if !populated.contains(&language) {
+                                    populated.push(language);
+                                }
+                            }
+                        })?;
+                        keep_populated(&mut languages, &populated, group.logger_mut());
+                        if languages.is_empty() {
+                            bail!("None of the selected languages have any string.");
+                        }
+                    }
+                    if split {
+                        group.set_skip_deinfo(no_deinfo);
+                        group.export_split(&output, languages, serialize_type, &filters)?;
+                    } else {
+                        group.export(&output, languages, serialize_type, &filters)?;
+                    }
+
+                    if self.checksums {
+                        let mut written = vec![output.clone()];
+                        if sert == SerializeType::Txt && !split {
+                            written.push(deinfo_path(&output));
+                        }
+                        write_checksums(&output, &written, group.logger_mut())?;
+                    }
                 }
                 Action::Import {
                     exported_file,
-                    dont_skip: _,
+                    strip_prefix,
+                    add_prefix,
+                    max_length,
+                    check_placeholders,
+                    verify_after_write,
+                    preserve_mtime,
+                    line_ending,
+                    force,
+                    split,
+                    ..
                 } => {
+                    if utils::is_stdin(&exported_file) {
+                        bail!("Reading the exported file from stdin is only supported in single mode.");
+                    }
+                    if split != exported_file.is_dir() {
+                        bail!(
+                            "The exported file should be a folder if and only if --split is used."
+                        );
+                    }
+
+                    let sert = SerializeType::resolve_import(sert, &exported_file, &mut logger);
                     let output = self
                         .output
                         .unwrap_or_else(|| self.input_dir.with_extension("new"));
 
-                    DecimaGroup::<DSLocal, _>::new(self.input_dir, logger)?.import(
-                        exported_file,
-                        output,
-                        sert.to_core(None),
-                    )?;
+                    let mut group =
+                        DecimaGroup::<DSLocal, _>::new_with_files(self.input_dir, files, logger)?;
+
+                    if let Some(max_size) = self.max_file_size {
+                        group.set_max_file_size(max_size)?;
+                    }
+                    group.set_path_remap(PathRemap {
+                        strip_prefix,
+                        add_prefix,
+                    });
+                    group.set_max_length(max_length);
+                    group.set_check_placeholders(check_placeholders);
+                    group.set_verify_after_write(verify_after_write);
+                    group.set_preserve_mtime(preserve_mtime);
+                    let options = ImportOptions {
+                        check_resource_count: !force,
+                    };
+                    if split {
+                        group.import_split(
+                            &exported_file,
+                            &output,
+                            sert.to_core(FormatOptions {
+                                line_ending,
+                                ..Default::default()
+                            }),
+                            options,
+                        )?;
+                    } else {
+                        group.import(
+                            &exported_file,
+                            &output,
+                            sert.to_core(FormatOptions {
+                                line_ending,
+                                ..Default::default()
+                            }),
+                            options,
+                        )?;
+                    }
+
+                    if self.checksums {
+                        write_checksums(
+                            &output,
+                            std::slice::from_ref(&output),
+                            group.logger_mut(),
+                        )?;
+                    }
                 }
             },
             Game::Auto => unreachable!(),
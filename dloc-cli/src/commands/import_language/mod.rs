@@ -0,0 +1,96 @@
+use std::{
+    fs::File,
+    io::{BufRead, BufReader, BufWriter},
+    path::{Path, PathBuf},
+};
+
+use anyhow::bail;
+use clap::{Parser, ValueHint};
+use dloc_core::{
+    games::{ds::DSLocal, hzd::HZDLocal},
+    logger::Logger,
+    serialize::EofReplacor,
+};
+
+use crate::{logger::CliLogger, Game};
+
+use super::{
+    shared::{parse_ds_languages, parse_hzd_languages, resolve_game},
+    utils,
+};
+
+#[derive(Debug, Parser)]
+#[command(arg_required_else_help = true)]
+pub struct ImportLanguage {
+    /// Original core file the text file extracted from
+    #[arg(value_hint = ValueHint::FilePath, value_parser = utils::is_file)]
+    input_core: PathBuf,
+    /// Language of the text file
+    language: String,
+    /// Text file made by extract-language, one string per line
+    #[arg(value_hint = ValueHint::FilePath, value_parser = utils::is_file)]
+    text_file: PathBuf,
+    /// Output core file, by default its next to the input core
+    #[arg(value_hint = ValueHint::FilePath)]
+    output: Option<PathBuf>,
+    /// Don't skip writing the core to disk if nothing changed
+    #[arg(short, long)]
+    dont_skip: bool,
+}
+
+impl ImportLanguage {
+    pub fn command(self, game: Game, mut logger: CliLogger) -> anyhow::Result<()> {
+        let game = resolve_game(game, &self.input_core, &mut logger)?;
+        logger.info(format!("Selected game: {game:#?}"));
+
+        let lines = read_lines(&self.text_file)?;
+        let output = utils::resolve_output(self.output, &self.input_core, "new.core");
+        let reader = BufReader::new(File::open(&self.input_core)?);
+
+        match game {
+            Game::Hzd => {
+                let Some(&language) =
+                    parse_hzd_languages(vec![self.language], Vec::new(), &mut logger).first()
+                else {
+                    bail!("Didn't found any valid Language.");
+                };
+                let mut core = HZDLocal::new(reader)?;
+
+                core.set_language_strings(language, lines)?;
+
+                if !self.dont_skip && !core.is_modified() {
+                    bail!("Nothing changed, write to disk cancelled.");
+                }
+                core.write(&mut BufWriter::new(File::create(&output)?))?;
+            }
+            Game::Ds => {
+                let Some(&language) =
+                    parse_ds_languages(vec![self.language], Vec::new(), &mut logger).first()
+                else {
+                    bail!("Didn't found any valid Language.");
+                };
+                let mut core = DSLocal::new(reader)?;
+
+                core.set_language_strings(language, lines)?;
+
+                if !self.dont_skip && !core.is_modified() {
+                    bail!("Nothing changed, write to disk cancelled.");
+                }
+                core.write(&mut BufWriter::new(File::create(&output)?))?;
+            }
+            Game::Auto => unreachable!(),
+        }
+
+        logger.good(format!("New core written to {output:?}."));
+
+        Ok(())
+    }
+}
+
+/// Read the lines of the text file and put back the escaped line breaks.
+fn read_lines(path: &Path) -> std::io::Result<Vec<String>> {
+    BufReader::new(File::open(path)?)
+        .lines()
+        .map(|l| l.map(|l| l.trim_end_matches('\r').to_owned().replace_eol_back()))
+        .collect()
+}
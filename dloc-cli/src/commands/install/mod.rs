@@ -0,0 +1,183 @@
+use std::{
+    fs::{self, File},
+    path::{self, Path, PathBuf},
+};
+
+use anyhow::bail;
+use clap::{Parser, ValueHint};
+use dloc_core::logger::Logger;
+
+use crate::logger::CliLogger;
+
+use super::utils;
+
+#[derive(Debug, Parser)]
+#[command(arg_required_else_help = true)]
+pub struct Install {
+    /// Output folder of a group import, its layout should match the game folder
+    #[arg(value_hint = ValueHint::DirPath, value_parser = utils::is_dir)]
+    new_dir: PathBuf,
+    /// Game folder that the group was exported from
+    #[arg(value_hint = ValueHint::DirPath, value_parser = utils::is_dir)]
+    game_dir: PathBuf,
+    /// Folder to backup the replaced cores into, defaults to a `<game folder>.backup` folder next to
+    /// the game folder. It can't be the game folder or a folder inside it.
+    /// Cores that already have a backup keep it, so the backup always has the first version
+    #[arg(long, value_hint = ValueHint::DirPath)]
+    backup_dir: Option<PathBuf>,
+    /// Don't backup the replaced cores
+    #[arg(long, conflicts_with = "backup_dir")]
+    no_backup: bool,
+    /// Keep the modification time of the replaced cores, for tools that sync by it
+    #[arg(long)]
+    preserve_mtime: bool,
+}
+
+impl Install {
+    pub fn command(self, mut logger: CliLogger) -> anyhow::Result<()> {
+        let files: Vec<_> = utils::list_files(&self.new_dir)?
+            .into_iter()
+            .filter(|f| {
+                f.extension()
+                    .is_some_and(|e| e.eq_ignore_ascii_case("core"))
+            })
+            .collect();
+
+        if files.is_empty() {
+            bail!("Didn't found any core file in new folder.");
+        }
+
+        let backup_dir = self.backup_dir()?;
+
+        let mut backed_up = 0;
+        for file in files.iter() {
+            let target = self.game_dir.join(file);
+            if !target.is_file() {
+                logger.warn(format!(
+                    "{} doesn't exist in game folder, it will be created.",
+                    file.display()
+                ));
+            } else if let Some(backup_dir) = &backup_dir {
+                let backup = backup_dir.join(file);
+                if backup.is_file() {
+                    logger.info(format!(
+                        "{} already have a backup, keeping it.",
+                        file.display()
+                    ));
+                } else {
+                    if let Some(parent) = backup.parent() {
+                        fs::create_dir_all(parent)?;
+                    }
+                    fs::copy(&target, backup)?;
+                    backed_up += 1;
+                }
+            }
+
+            let modified = match self.preserve_mtime && target.is_file() {
+                true => Some(fs::metadata(&target)?.modified()?),
+                false => None,
+            };
+
+            if let Some(parent) = target.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::copy(self.new_dir.join(file), &target)?;
+
+            if let Some(modified) = modified {
+                File::options()
+                    .write(true)
+                    .open(&target)?
+                    .set_modified(modified)?;
+            }
+        }
+
+        if let Some(backup_dir) = backup_dir {
+            logger.info(format!(
+                "Backed up {backed_up} core(s) into {backup_dir:?}, use restore to put them back."
+            ));
+        }
+        logger.good(format!("Installed {} core(s).", files.len()));
+
+        Ok(())
+    }
+
+    /// Returns the folder to backup the replaced cores into, the default one
+    /// is made from the canonical game folder so `.` or `..` still get a
+    /// folder next to the game folder instead of the game folder itself.
+    fn backup_dir(&self) -> anyhow::Result<Option<PathBuf>> {
+        if self.no_backup {
+            return Ok(None);
+        }
+
+        let game_dir = fs::canonicalize(&self.game_dir)?;
+        let backup_dir = match &self.backup_dir {
+            Some(dir) => dir.clone(),
+            None => {
+                let Some(name) = game_dir.file_name() else {
+                    bail!("Can't put a backup folder next to {game_dir:?}, use --backup-dir.");
+                };
+                let mut name = name.to_owned();
+                name.push(".backup");
+                game_dir.with_file_name(name)
+            }
+        };
+
+        if resolve(&backup_dir)?.starts_with(&game_dir) {
+            bail!("Backup folder {backup_dir:?} is inside the game folder, the backups would be overwritten.");
+        }
+
+        Ok(Some(backup_dir))
+    }
+}
+
+/// Returns the canonical form of `path` even if it doesn't exist yet, by
+/// canonicalizing the part of it that exists.
+fn resolve(path: &Path) -> anyhow::Result<PathBuf> {
+    let path = path::absolute(path)?;
+    let Some(existing) = path.ancestors().find(|p| p.exists()) else {
+        return Ok(path);
+    };
+    let rest = path.strip_prefix(existing)?;
+    Ok(fs::canonicalize(existing)?.join(rest))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn install(game_dir: &str, backup_dir: Option<&str>) -> Install {
+        Install {
+            new_dir: PathBuf::from("new"),
+            game_dir: PathBuf::from(game_dir),
+            backup_dir: backup_dir.map(PathBuf::from),
+            no_backup: false,
+            preserve_mtime: false,
+        }
+    }
+
+    #[test]
+    fn default_backup_dir_is_next_to_the_game_dir() {
+        let current = fs::canonicalize(".").unwrap();
+        let mut name = current.file_name().unwrap().to_owned();
+        name.push(".backup");
+
+        let backup_dir = install(".", None).backup_dir().unwrap().unwrap();
+        assert_eq!(backup_dir, current.with_file_name(name));
+        assert!(!backup_dir.starts_with(&current));
+
+        let parent = current.parent().unwrap();
+        let backup_dir = install("..", None).backup_dir().unwrap().unwrap();
+        assert!(!backup_dir.starts_with(parent));
+    }
+
+    #[test]
+    fn backup_dir_inside_the_game_dir_is_rejected() {
+        for backup_dir in [".", "./src", "backups/new", "src/../backups"] {
+            assert!(
+                install(".", Some(backup_dir)).backup_dir().is_err(),
+                "{backup_dir}"
+            );
+        }
+        assert!(install(".", Some("../elsewhere")).backup_dir().is_ok());
+    }
+}
@@ -1,4 +1,7 @@
-use std::{fmt::Write, io::Write as _};
+use std::{
+    fmt::{Display, Write},
+    io::Write as _,
+};
 
 use dloc_core::games::{ds, hzd};
 
@@ -6,7 +9,7 @@ use crate::logger::CliLogger;
 
 use super::Game;
 
-pub fn print_languages(game: Game, mut logger: CliLogger) -> anyhow::Result<()> {
+pub fn print_languages(game: Game, sort: bool, mut logger: CliLogger) -> anyhow::Result<()> {
     match game {
         Game::Auto => {
             logger.stdout.write_all(
@@ -14,22 +17,18 @@ pub fn print_languages(game: Game, mut logger: CliLogger) -> anyhow::Result<()>
             )?;
         }
         Game::Hzd => {
-            let languages = hzd::Language::ALL_VARIANTS.into_iter().fold(
-                String::from("Horizon Zero Dawn supported languages:\n"),
-                |mut s, v| {
-                    let _ = writeln!(s, "  - {v}");
-                    s
-                },
+            let languages = format_languages(
+                "Horizon Zero Dawn supported languages:\n",
+                hzd::Language::ALL_VARIANTS,
+                sort,
             );
             logger.stdout.write_all(languages.as_bytes())?;
         }
         Game::Ds => {
-            let languages = ds::Language::ALL_VARIANTS.into_iter().fold(
-                String::from("Death Stranding supported languages:\n"),
-                |mut s, v| {
-                    let _ = writeln!(s, "  - {v}");
-                    s
-                },
+            let languages = format_languages(
+                "Death Stranding supported languages:\n",
+                ds::Language::ALL_VARIANTS,
+                sort,
             );
             logger.stdout.write_all(languages.as_bytes())?;
         }
@@ -37,3 +36,20 @@ pub fn print_languages(game: Game, mut logger: CliLogger) -> anyhow::Result<()>
 
     Ok(())
 }
+
+fn format_languages<L: Display>(
+    title: &str,
+    languages: impl IntoIterator<Item = L>,
+    sort: bool,
+) -> String {
+    let mut languages: Vec<String> = languages.into_iter().map(|l| l.to_string()).collect();
+
+    if sort {
+        languages.sort_by_key(|l| l.to_lowercase());
+    }
+
+    languages.into_iter().fold(String::from(title), |mut s, v| {
+        let _ = writeln!(s, "  - {v}");
+        s
+    })
+}
@@ -0,0 +1,124 @@
+use std::{
+    collections::BTreeMap,
+    fmt::{Display, Write},
+    fs::File,
+    io::{BufReader, Write as _},
+    path::PathBuf,
+};
+
+use anyhow::bail;
+use clap::{Parser, ValueHint};
+use dloc_core::{
+    games::{ds::DSLocal, hzd::HZDLocal, StringEntry},
+    logger::Logger,
+    serialize::DecimaGroup,
+};
+
+use crate::{logger::CliLogger, Game};
+
+use super::shared::resolve_game;
+
+#[derive(Debug, Parser)]
+#[command(arg_required_else_help = true)]
+pub struct LengthStats {
+    /// Input core file or a folder that have core files inside it
+    #[arg(value_hint = ValueHint::AnyPath)]
+    input: PathBuf,
+}
+
+/// Length statistics of the strings of a single language.
+#[derive(Debug, Default)]
+struct Stats {
+    count: usize,
+    min: usize,
+    max: usize,
+    total: usize,
+}
+
+impl Stats {
+    fn add(&mut self, len: usize) {
+        self.min = if self.count == 0 {
+            len
+        } else {
+            self.min.min(len)
+        };
+        self.max = self.max.max(len);
+        self.total += len;
+        self.count += 1;
+    }
+}
+
+impl LengthStats {
+    pub fn command(self, game: Game, mut logger: CliLogger) -> anyhow::Result<()> {
+        if !self.input.exists() {
+            bail!("You need to pass a valid file or dir path.");
+        }
+
+        let game = resolve_game(game, &self.input, &mut logger)?;
+        logger.info(format!("Selected game: {game:#?}"));
+
+        let report = match game {
+            Game::Hzd => {
+                let mut stats = BTreeMap::new();
+                if self.input.is_dir() {
+                    DecimaGroup::<HZDLocal, _>::new(&self.input, &mut logger)?
+                        .for_each(|_, core| tally(&mut stats, core.strings()))?;
+                } else {
+                    let core = HZDLocal::new(BufReader::new(File::open(&self.input)?))?;
+                    tally(&mut stats, core.strings());
+                }
+                format_report(stats)
+            }
+            Game::Ds => {
+                let mut stats = BTreeMap::new();
+                if self.input.is_dir() {
+                    DecimaGroup::<DSLocal, _>::new(&self.input, &mut logger)?
+                        .for_each(|_, core| tally(&mut stats, core.strings()))?;
+                } else {
+                    let core = DSLocal::new(BufReader::new(File::open(&self.input)?))?;
+                    tally(&mut stats, core.strings());
+                }
+                format_report(stats)
+            }
+            Game::Auto => unreachable!(),
+        };
+
+        logger.stdout.write_all(report.as_bytes())?;
+
+        Ok(())
+    }
+}
+
+/// Add the length of every non empty string to the stats of its language.
+fn tally<'a, L: Ord>(
+    stats: &mut BTreeMap<L, Stats>,
+    strings: impl Iterator<Item = StringEntry<'a, L>>,
+) {
+    for entry in strings.filter(|e| !e.string.is_empty()) {
+        stats
+            .entry(entry.language)
+            .or_default()
+            .add(entry.string.chars().count());
+    }
+}
+
+fn format_report<L: Display>(stats: BTreeMap<L, Stats>) -> String {
+    let mut report = format!(
+        "{:<24}{:>10}{:>8}{:>8}{:>10}\n",
+        "Language", "Strings", "Min", "Max", "Avg"
+    );
+
+    for (lang, s) in stats {
+        let _ = writeln!(
+            report,
+            "{:<24}{:>10}{:>8}{:>8}{:>10.1}",
+            lang.to_string(),
+            s.count,
+            s.min,
+            s.max,
+            s.total as f64 / s.count as f64
+        );
+    }
+
+    report
+}
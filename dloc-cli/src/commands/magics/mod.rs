@@ -0,0 +1,48 @@
+use std::{fmt::Write, io::Write as _};
+
+use dloc_core::games::detect::KNOWN_MAGICS;
+
+use crate::logger::CliLogger;
+
+use super::Game;
+
+pub fn print_magics(game: Game, mut logger: CliLogger) -> anyhow::Result<()> {
+    let game_name = match game {
+        Game::Auto => None,
+        Game::Hzd => Some("Horizon Zero Dawn"),
+        Game::Ds => Some("Death Stranding"),
+    };
+
+    let mut output = format!(
+        "{:<20}{:<26}{:<20}{}\n",
+        "Magic", "Bytes in file", "Game", "Resource"
+    );
+
+    for known in KNOWN_MAGICS
+        .iter()
+        .filter(|m| game_name.is_none_or(|g| m.game == g))
+    {
+        let _ = writeln!(
+            output,
+            "{:<20}{:<26}{:<20}{}",
+            format!("{:#018X}", known.magic),
+            magic_bytes(known.magic),
+            known.game,
+            known.resource
+        );
+    }
+
+    logger.stdout.write_all(output.as_bytes())?;
+
+    Ok(())
+}
+
+/// The bytes of `magic` as they are in a core file.
+pub(super) fn magic_bytes(magic: u64) -> String {
+    magic
+        .to_le_bytes()
+        .iter()
+        .map(|b| format!("{b:02X}"))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
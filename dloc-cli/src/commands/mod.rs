@@ -1,14 +1,35 @@
+use std::str::FromStr;
+
 use clap::{Subcommand, ValueEnum};
 
 use crate::logger::CliLogger;
 
 pub use shared::SerializeType;
 
+mod apply_patch;
+mod check_encoding;
+mod check_export;
+mod copy_resource;
+mod create_patch;
+mod diff_exports;
+mod extract_language;
+mod find_identical;
 mod group;
+mod import_language;
+mod install;
 mod languages;
+mod length_stats;
+mod magics;
+mod restore;
+mod scan_unknown;
+mod selftest;
 mod shared;
+mod show;
 mod single;
+mod split_export;
+mod swap;
 mod utils;
+mod verify_checksums;
 
 #[derive(Clone, Copy, Debug, Default, ValueEnum, PartialEq, Eq)]
 pub enum Game {
@@ -23,6 +44,14 @@ pub enum Game {
     Ds,
 }
 
+impl FromStr for Game {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        <Self as ValueEnum>::from_str(s, true)
+    }
+}
+
 /// Different program log levels.
 #[derive(Clone, Copy, Debug, Default, ValueEnum, PartialEq, Eq)]
 pub enum LogLevel {
@@ -48,19 +77,85 @@ pub enum LogLevel {
 #[command(arg_required_else_help = true)]
 pub enum Commands {
     /// See supported languages for each game
-    Languages,
+    Languages {
+        /// Sort the languages alphabetically by their name
+        #[arg(short, long)]
+        sort: bool,
+    },
+    /// See the known chunk magic numbers, all of them if game is auto
+    Magics,
+    /// Count the chunk magics dloc doesn't know in every core of a folder, with their total size
+    ScanUnknown(scan_unknown::ScanUnknown),
     /// Extract or import strings from a single core file
     Single(single::Single),
     /// Extract or import strings from a group of core files
     Group(group::Group),
+    /// Show min, max and average length of non empty strings per language
+    LengthStats(length_stats::LengthStats),
+    /// Check an exported txt file against its deinfo without touching any core file
+    CheckExport(check_export::CheckExport),
+    /// Split a txt file exported in group mode into a file for every core, like export --split
+    SplitExport(split_export::SplitExport),
+    /// List the strings that differ between two exports of the same core, no core file is needed
+    DiffExports(diff_exports::DiffExports),
+    /// Report strings that look double encoded or have replacement characters
+    CheckEncoding(check_encoding::CheckEncoding),
+    /// List the strings that are the same in every language, like "OK", and optionally fill the empty ones
+    FindIdentical(find_identical::FindIdentical),
+    /// Copy the cores made by a group import over the game cores, backing up the originals
+    Install(install::Install),
+    /// Copy the backups in a backup folder back over the modified files
+    Restore(restore::Restore),
+    /// Extract the strings of a single language to a text file, one string per line
+    ExtractLanguage(extract_language::ExtractLanguage),
+    /// Import a text file made by extract-language back into its core
+    ImportLanguage(import_language::ImportLanguage),
+    /// Make a small patch of the strings that differ between a vanilla core and its translation
+    CreatePatch(create_patch::CreatePatch),
+    /// Apply a patch made by create-patch to a vanilla core
+    ApplyPatch(apply_patch::ApplyPatch),
+    /// Exchange the strings of two languages in a core, to find out which one the game reads
+    Swap(swap::Swap),
+    /// Replace resources of a core with the ones at the same index of another core, to port a fix
+    CopyResource(copy_resource::CopyResource),
+    /// Print the strings of a single local resource
+    Show(show::Show),
+    /// Round trip synthetic cores through every format to check this build, no game file is needed
+    Selftest(selftest::Selftest),
+    /// Check the files listed in a .sha256 file written with --checksums
+    VerifyChecksums(verify_checksums::VerifyChecksums),
 }
 
 impl Commands {
-    pub fn command(self, game: Game, sert: SerializeType, logger: CliLogger) -> anyhow::Result<()> {
+    pub fn command(
+        self,
+        game: Game,
+        sert: Option<SerializeType>,
+        logger: CliLogger,
+    ) -> anyhow::Result<()> {
         match self {
             Commands::Single(c) => c.command(game, sert, logger),
             Commands::Group(c) => c.command(game, sert, logger),
-            Commands::Languages => languages::print_languages(game, logger),
+            Commands::Languages { sort } => languages::print_languages(game, sort, logger),
+            Commands::Magics => magics::print_magics(game, logger),
+            Commands::ScanUnknown(c) => c.command(logger),
+            Commands::CheckExport(c) => c.command(game, logger),
+            Commands::SplitExport(c) => c.command(game, logger),
+            Commands::DiffExports(c) => c.command(game, sert, logger),
+            Commands::CheckEncoding(c) => c.command(game, logger),
+            Commands::LengthStats(c) => c.command(game, logger),
+            Commands::FindIdentical(c) => c.command(game, logger),
+            Commands::Install(c) => c.command(logger),
+            Commands::Restore(c) => c.command(logger),
+            Commands::ExtractLanguage(c) => c.command(game, logger),
+            Commands::ImportLanguage(c) => c.command(game, logger),
+            Commands::CreatePatch(c) => c.command(game, logger),
+            Commands::ApplyPatch(c) => c.command(game, logger),
+            Commands::Swap(c) => c.command(game, logger),
+            Commands::CopyResource(c) => c.command(game, logger),
+            Commands::Show(c) => c.command(game, logger),
+            Commands::Selftest(c) => c.command(game, sert, logger),
+            Commands::VerifyChecksums(c) => c.command(logger),
         }
     }
 }
@@ -0,0 +1,67 @@
+use std::{fs, path::PathBuf};
+
+use anyhow::bail;
+use clap::{Parser, ValueHint};
+use dloc_core::logger::Logger;
+
+use crate::logger::CliLogger;
+
+use super::utils;
+
+#[derive(Debug, Parser)]
+#[command(arg_required_else_help = true)]
+pub struct Restore {
+    /// Backup folder, its layout should match the target folder
+    #[arg(value_hint = ValueHint::DirPath, value_parser = utils::is_dir)]
+    backup_dir: PathBuf,
+    /// Target folder (or the folder of the target core) to restore the backups into
+    #[arg(value_hint = ValueHint::DirPath, value_parser = utils::is_dir)]
+    target_dir: PathBuf,
+    /// Only restore these files, paths are relative to the target folder
+    #[arg(long, num_args = 1..)]
+    files: Vec<PathBuf>,
+}
+
+impl Restore {
+    pub fn command(self, mut logger: CliLogger) -> anyhow::Result<()> {
+        let files = if self.files.is_empty() {
+            utils::list_files(&self.backup_dir)?
+        } else {
+            self.files
+        };
+
+        if files.is_empty() {
+            bail!("Didn't found any file in backup folder.");
+        }
+
+        let missing: Vec<_> = files
+            .iter()
+            .filter(|f| !self.backup_dir.join(f).is_file())
+            .collect();
+        if !missing.is_empty() {
+            for file in missing.iter() {
+                logger.error(format!("Backup of {} not found.", file.display()));
+            }
+            bail!("{} backup(s) are missing, nothing restored.", missing.len());
+        }
+
+        for file in files.iter() {
+            let target = self.target_dir.join(file);
+            if !target.is_file() {
+                logger.warn(format!(
+                    "{} doesn't exist in target folder, it will be created.",
+                    file.display()
+                ));
+            }
+
+            if let Some(parent) = target.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::copy(self.backup_dir.join(file), target)?;
+        }
+
+        logger.good(format!("Restored {} file(s).", files.len()));
+
+        Ok(())
+    }
+}
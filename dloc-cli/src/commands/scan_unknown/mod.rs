@@ -0,0 +1,85 @@
+use std::{
+    cmp::Reverse,
+    collections::BTreeMap,
+    fmt::Write,
+    fs::File,
+    io::{BufReader, Write as _},
+    path::PathBuf,
+};
+
+use anyhow::bail;
+use clap::{Parser, ValueHint};
+use dloc_core::{games::detect::tally_unknown_magics, logger::Logger};
+
+use crate::logger::CliLogger;
+
+use super::{magics::magic_bytes, utils};
+
+#[derive(Debug, Parser)]
+#[command(arg_required_else_help = true)]
+pub struct ScanUnknown {
+    /// Folder to scan for core files, a single core file also works
+    #[arg(value_hint = ValueHint::AnyPath, value_parser = utils::is_file_or_dir)]
+    input: PathBuf,
+}
+
+impl ScanUnknown {
+    pub fn command(self, mut logger: CliLogger) -> anyhow::Result<()> {
+        let files = if self.input.is_dir() {
+            utils::list_files(&self.input)?
+                .into_iter()
+                .filter(|f| {
+                    f.extension()
+                        .is_some_and(|e| e.eq_ignore_ascii_case("core"))
+                })
+                .map(|f| self.input.join(f))
+                .collect()
+        } else {
+            vec![self.input]
+        };
+
+        if files.is_empty() {
+            bail!("Didn't found any core file in input folder.");
+        }
+
+        let mut tally = BTreeMap::new();
+        let mut scanned = 0;
+        for file in files.iter() {
+            let result = File::open(file)
+                .map_err(Into::into)
+                .and_then(|f| tally_unknown_magics(&mut BufReader::new(f), &mut tally));
+            match result {
+                Ok(()) => scanned += 1,
+                Err(e) => logger.warn(format!("Skipped {}: {e}", file.display())),
+            }
+        }
+
+        logger.info(format!(
+            "Scanned {scanned} core files, found {} unknown magics.",
+            tally.len()
+        ));
+
+        let mut magics: Vec<_> = tally.into_iter().collect();
+        magics.sort_by_key(|(_, unknown)| Reverse(unknown.chunks));
+
+        let mut output = format!(
+            "{:<20}{:<26}{:<10}{:<10}{}\n",
+            "Magic", "Bytes in file", "Chunks", "Cores", "Size"
+        );
+        for (magic, unknown) in magics {
+            let _ = writeln!(
+                output,
+                "{:<20}{:<26}{:<10}{:<10}{}",
+                format!("{magic:#018X}"),
+                magic_bytes(magic),
+                unknown.chunks,
+                unknown.cores,
+                unknown.bytes
+            );
+        }
+
+        logger.stdout.write_all(output.as_bytes())?;
+
+        Ok(())
+    }
+}
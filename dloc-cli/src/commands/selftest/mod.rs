@@ -0,0 +1,144 @@
+use std::{fs, io::Cursor, path::Path};
+
+use anyhow::bail;
+use clap::{Parser, ValueEnum};
+use dloc_core::{
+    games::{
+        ds::{self, DSLocal},
+        hzd::{self, HZDLocal},
+    },
+    logger::Logger,
+    serialize::{SerializeData, SerializeType as CoreSerializeType},
+};
+
+use crate::{logger::CliLogger, Game};
+
+use super::{shared::FormatOptions, SerializeType};
+
+#[derive(Debug, Parser)]
+pub struct Selftest {
+    /// Number of made up resources in each synthetic core
+    #[arg(long, value_name = "N", default_value_t = 20)]
+    resources: usize,
+}
+
+/// Export a synthetic core, import it into a copy with every string emptied,
+/// write the copy and read it back, then check it matches the synthetic core.
+macro_rules! round_trip {
+    ($name:ident, $local:ty, $language:ty) => {
+        fn $name(
+            resources: usize,
+            serialize_type: CoreSerializeType,
+            temp_dir: &Path,
+        ) -> anyhow::Result<()> {
+            let source = <$local>::synthetic(resources)?;
+            let mut target = <$local>::synthetic(resources)?;
+            let entries: Vec<_> = target
+                .strings()
+                .map(|e| (e.index, e.sub, e.language))
+                .collect();
+            for (index, sub, language) in entries {
+                target.set_string(index, language, sub, String::new())?;
+            }
+
+            if let CoreSerializeType::Txt { .. } | CoreSerializeType::Sqlite = serialize_type {
+                let path = temp_dir.join(format!("selftest.{}", serialize_type.extension()));
+                source.serialize(
+                    &path,
+                    <$language>::ALL_VARIANTS,
+                    serialize_type.clone(),
+                    &Default::default(),
+                )?;
+                target.deserialize_and_update(&path, serialize_type, Default::default())?;
+            } else {
+                let mut exported = Vec::new();
+                source.serialize_to_writer(
+                    &mut exported,
+                    <$language>::ALL_VARIANTS,
+                    serialize_type.clone(),
+                    &Default::default(),
+                )?;
+                target.deserialize_and_update_from_reader(
+                    exported.as_slice(),
+                    serialize_type,
+                    Default::default(),
+                )?;
+            }
+
+            let written = target.to_bytes()?;
+            source.verify_written(Cursor::new(&written))?;
+            if written != source.to_bytes()? {
+                bail!("The written core doesn't match the synthetic core byte by byte.");
+            }
+            Ok(())
+        }
+    };
+}
+
+round_trip!(hzd_round_trip, HZDLocal, hzd::Language);
+round_trip!(ds_round_trip, DSLocal, ds::Language);
+
+impl Selftest {
+    pub fn command(
+        self,
+        game: Game,
+        sert: Option<SerializeType>,
+        mut logger: CliLogger,
+    ) -> anyhow::Result<()> {
+        let games = match game {
+            Game::Auto => vec![Game::Hzd, Game::Ds],
+            game => vec![game],
+        };
+
+        let mut formats = Vec::new();
+        for sert in sert.map_or_else(|| SerializeType::value_variants().to_vec(), |s| vec![s]) {
+            formats.push((format!("{sert:?}"), sert.to_core(FormatOptions::default())));
+            if sert == SerializeType::Txt {
+                formats.push((
+                    "Txt with indices".to_owned(),
+                    sert.to_core(FormatOptions {
+                        add_indices: true,
+                        ..Default::default()
+                    }),
+                ));
+                formats.push((
+                    "Txt with language names".to_owned(),
+                    sert.to_core(FormatOptions {
+                        add_language_names: true,
+                        ..Default::default()
+                    }),
+                ));
+            }
+        }
+
+        let temp_dir = std::env::temp_dir().join(format!("dloc-selftest-{}", std::process::id()));
+        fs::create_dir_all(&temp_dir)?;
+
+        let mut failed = 0;
+        for game in games {
+            for (name, serialize_type) in formats.iter().cloned() {
+                let result = match game {
+                    Game::Hzd => hzd_round_trip(self.resources, serialize_type, &temp_dir),
+                    _ => ds_round_trip(self.resources, serialize_type, &temp_dir),
+                };
+
+                match result {
+                    Ok(()) => logger.good(format!("{game:?} {name}: ok")),
+                    Err(e) => {
+                        logger.error(format!("{game:?} {name}: {e}"));
+                        failed += 1;
+                    }
+                }
+            }
+        }
+
+        fs::remove_dir_all(&temp_dir)?;
+
+        if failed != 0 {
+            bail!("{failed} round trips failed, this build can't be trusted with real files.");
+        }
+        logger.good("Every round trip finished without any difference.");
+
+        Ok(())
+    }
+}
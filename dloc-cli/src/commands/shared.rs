@@ -1,13 +1,20 @@
-use std::path::PathBuf;
+use std::{
+    fmt::Display,
+    fs::File,
+    io::{BufReader, Read, Seek},
+    path::{Path, PathBuf},
+    str::FromStr,
+};
 
+use anyhow::bail;
 use clap::{Subcommand, ValueEnum, ValueHint};
 use dloc_core::{
-    games::{ds, hzd},
+    games::{detect, ds, hzd, ContentFilter, Uuid},
     logger::Logger,
-    serialize::SerializeType as CoreSerializeType,
+    serialize::{Compression, LineEnding as CoreLineEnding, SerializeType as CoreSerializeType},
 };
 
-use super::utils;
+use super::{utils, Game};
 
 #[derive(Clone, Copy, Debug, Default, ValueEnum, PartialEq, Eq)]
 pub enum SerializeType {
@@ -15,16 +22,143 @@ pub enum SerializeType {
     Json,
     Yaml,
     Txt,
+    /// Flat `index[:sub]:Language=value` lines
+    KeyValue,
+    /// Tab separated table with a row for each string and a column for each language
+    #[value(alias = "tsv")]
+    Table,
+    /// Newline delimited Json, a Json object for each string
+    #[value(alias = "jsonl")]
+    Ndjson,
+    /// Gettext PO with a message for each string, import reads the msgstr
+    Po,
+    /// SQLite database with a row for each string
+    Sqlite,
+}
+
+/// Settings of the exported file that only some of the formats use, see
+/// [`SerializeType::to_core`].
+#[derive(Debug, Default)]
+pub struct FormatOptions {
+    pub add_language_names: bool,
+    pub language_separator: Option<String>,
+    pub add_indices: bool,
+    pub json_indent: Option<String>,
+    pub omit_empty: bool,
+    pub line_ending: Option<LineEnding>,
 }
 
 impl SerializeType {
-    pub fn to_core(self, add_language_names: Option<bool>) -> CoreSerializeType {
+    pub fn to_core(self, options: FormatOptions) -> CoreSerializeType {
+        let FormatOptions {
+            add_language_names,
+            language_separator,
+            add_indices,
+            json_indent,
+            omit_empty,
+            line_ending,
+        } = options;
+
         match self {
-            Self::Json => CoreSerializeType::Json,
-            Self::Yaml => CoreSerializeType::Yaml,
+            Self::Json => CoreSerializeType::Json {
+                indent: json_indent,
+                omit_empty,
+            },
+            Self::Yaml => CoreSerializeType::Yaml { omit_empty },
             Self::Txt => CoreSerializeType::Txt {
-                add_language_names: add_language_names.unwrap_or_default(),
+                add_language_names,
+                language_separator,
+                add_indices,
+                line_ending: line_ending.map(LineEnding::to_core),
             },
+            Self::KeyValue => CoreSerializeType::KeyValue,
+            Self::Table => CoreSerializeType::Table,
+            Self::Ndjson => CoreSerializeType::Ndjson,
+            Self::Po => CoreSerializeType::Po,
+            Self::Sqlite => CoreSerializeType::Sqlite,
+        }
+    }
+
+    /// Guess the serialize type from the start of an exported `content`,
+    /// anything that isn't Json, Yaml, key-value, a table, Ndjson, PO or SQLite is taken as Txt.
+    pub fn sniff(content: &[u8]) -> Self {
+        let content = content.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(content);
+        let content = content.trim_ascii_start();
+        let after_bracket = content.get(1..).unwrap_or_default().trim_ascii_start();
+
+        match content {
+            _ if content.starts_with(b"SQLite format 3\0") => Self::Sqlite,
+            _ if content.starts_with(b"{\"index\":") || content.starts_with(b"{\"path\":") => {
+                Self::Ndjson
+            }
+            [b'{', ..] => Self::Json,
+            [b'[', ..] if matches!(after_bracket.first(), None | Some(b'{' | b']')) => Self::Json,
+            [b'-', b'-', b'-', ..] | [b'-', b' ' | b'\n' | b'\r', ..] => Self::Yaml,
+            [b'#', ..] => Self::KeyValue,
+            [b'i', b'n', b'd', b'e', b'x', b'\t', ..] => Self::Table,
+            _ if content.starts_with(b"msgid ") || content.starts_with(b"msgctxt ") => Self::Po,
+            _ => {
+                let line = content.split(|b| *b == b'\n').next().unwrap_or_default();
+                let key = line.split(|b| *b == b'=').next().unwrap_or_default();
+                let is_key_value = line.len() != key.len()
+                    && key.first().is_some_and(u8::is_ascii_digit)
+                    && key.contains(&b':')
+                    && key.iter().all(|b| b.is_ascii_alphanumeric() || *b == b':');
+                if is_key_value {
+                    Self::KeyValue
+                } else {
+                    Self::Txt
+                }
+            }
+        }
+    }
+
+    /// Guess the serialize type from the extension of `path`, the extension
+    /// of a compression like `.gz` is skipped.
+    pub fn from_extension(path: &Path) -> Option<Self> {
+        let path = match Compression::from_path(path) {
+            Some(_) => path.with_extension(""),
+            None => path.to_path_buf(),
+        };
+        let ext = path.extension()?.to_str()?.to_lowercase();
+        match ext.as_str() {
+            "json" => Some(Self::Json),
+            "yaml" | "yml" => Some(Self::Yaml),
+            "txt" => Some(Self::Txt),
+            "properties" => Some(Self::KeyValue),
+            "tsv" => Some(Self::Table),
+            "ndjson" | "jsonl" => Some(Self::Ndjson),
+            "po" => Some(Self::Po),
+            "sqlite" | "db" => Some(Self::Sqlite),
+            _ => None,
+        }
+    }
+
+    /// Use `sert` if its given, if not detect it from the extension of the
+    /// `exported_file` and fallback to the default one.
+    pub fn resolve_import(
+        sert: Option<Self>,
+        exported_file: &Path,
+        logger: &mut impl Logger,
+    ) -> Self {
+        if let Some(sert) = sert {
+            return sert;
+        }
+
+        match Self::from_extension(exported_file) {
+            Some(sert) => {
+                logger.info(format!(
+                    "Detected {sert:?} format from exported file extension."
+                ));
+                sert
+            }
+            None => {
+                let sert = Self::default();
+                logger.warn(format!(
+                    "Can't detect the format from exported file extension, using {sert:?}."
+                ));
+                sert
+            }
         }
     }
 
@@ -33,6 +167,44 @@ impl SerializeType {
             Self::Json => "json",
             Self::Yaml => "yaml",
             Self::Txt => "txt",
+            Self::KeyValue => "properties",
+            Self::Table => "tsv",
+            Self::Ndjson => "ndjson",
+            Self::Po => "po",
+            Self::Sqlite => "sqlite",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, ValueEnum, PartialEq, Eq)]
+pub enum ResourceKind {
+    Localized,
+    Cutscene,
+}
+
+impl ResourceKind {
+    pub fn to_hzd_filter(kind: Option<Self>) -> hzd::ResourceFilter {
+        match kind {
+            None => hzd::ResourceFilter::All,
+            Some(Self::Localized) => hzd::ResourceFilter::Localized,
+            Some(Self::Cutscene) => hzd::ResourceFilter::Cutscene,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, ValueEnum, PartialEq, Eq)]
+pub enum LineEnding {
+    Lf,
+    Crlf,
+    Cr,
+}
+
+impl LineEnding {
+    pub const fn to_core(self) -> CoreLineEnding {
+        match self {
+            Self::Lf => CoreLineEnding::Lf,
+            Self::Crlf => CoreLineEnding::CrLf,
+            Self::Cr => CoreLineEnding::Cr,
         }
     }
 }
@@ -41,22 +213,139 @@ impl SerializeType {
 pub enum Action {
     /// Export locals from input
     Export {
-        /// Languages to export, pass 'all' if you want to export everything
+        /// Languages to export, pass 'all' if you want to export everything or 'all-except-empty'
+        /// for the languages that have at least one non empty string in the input
         #[arg(short, long, num_args = 1.., default_values_t = ["all".to_owned()], value_delimiter = ' ')]
         languages: Vec<String>,
+        /// Languages to leave out of the selected ones, like `-l all --exclude Japanese Korean`
+        #[arg(long, num_args = 1.., value_delimiter = ' ', value_name = "LANGUAGES")]
+        exclude: Vec<String>,
         /// This option is only used when serialize-type is Txt
         #[arg(short, long)]
         add_language_names: bool,
+        /// Separator between the language name and the string, defaults to ":: "
+        #[arg(long, value_name = "SEPARATOR", requires = "add_language_names")]
+        language_separator: Option<String>,
+        /// Start each line with the index of its resource as `[index] `, import strips it again.
+        /// This option is only used when serialize-type is Txt
+        #[arg(long)]
+        add_indices: bool,
+        /// Export every kind of line break as <lf> so the ones added by editors on other systems don't show
+        /// up as changes, use --line-ending in import to choose what they become. Only used with Txt
+        #[arg(long)]
+        normalize_line_endings: bool,
+        /// Indentation of the json output, 'tab' or a number of spaces. This option is only used with Json
+        #[arg(long, value_name = "INDENT", value_parser = utils::parse_indent)]
+        json_indent: Option<String>,
+        /// Leave empty strings out of the output and just list their languages, import sets them back to
+        /// empty. This option is only used with Json and Yaml
+        #[arg(long)]
+        omit_empty: bool,
+        /// Reference core file, only export the languages that changed compared to it in each resource,
+        /// this option is only used in single mode with Json or Yaml
+        #[arg(long, value_name = "REFERENCE_CORE", value_hint = ValueHint::FilePath, value_parser = utils::is_file)]
+        only_changed_languages: Option<PathBuf>,
+        /// Previous export of the core, copy the fields other tools added to its resources (like notes or ids) to
+        /// the same resources of the new export. This option is only used in single mode with Json or Yaml
+        #[arg(long, value_name = "PREVIOUS_EXPORT", value_hint = ValueHint::FilePath, value_parser = utils::is_file,
+            conflicts_with = "only_changed_languages")]
+        keep_fields: Option<PathBuf>,
+        /// Only export this kind of resources, this option is only used with HZD
+        #[arg(long, value_enum)]
+        only: Option<ResourceKind>,
+        /// Only export the strings matching this regex, Json, Yaml and Txt keep every string of a resource
+        /// that have a matching string in any language
+        #[arg(long, value_name = "REGEX", value_parser = ContentFilter::new)]
+        filter: Option<ContentFilter>,
+        /// Only export the resources with one of these uuids, as 32 hex digits like the show command prints them
+        #[arg(long, num_args = 1.., value_delimiter = ' ', value_parser = Uuid::from_str)]
+        uuid: Vec<Uuid>,
+        /// Export the resources in the order of their uuid and record the uuids, import then finds each
+        /// resource by its uuid so the export still works when a game patch reorders them.
+        /// This option is only used with Json, Yaml and Txt
+        #[arg(long)]
+        sort_resources: bool,
+        /// Put the text of this language in place of empty strings so translators don't start from blank,
+        /// import skips the filled strings that weren't edited. This option is only used with Json and Yaml
+        #[arg(long, value_name = "LANGUAGE")]
+        fill_empty_from: Option<String>,
+        /// Add the size in bytes each exported string would take in the core as a byte_len field, import ignores it.
+        /// This option is only used with Json and Yaml
+        #[arg(long)]
+        byte_lengths: bool,
+        /// Export every selected string as empty, a template with the resources and cutscene lines of the core
+        /// for translators to fill in without any source text. Import reads it back like any other export
+        #[arg(long, conflicts_with_all = ["fill_empty_from", "only_changed_languages"])]
+        blank: bool,
+        /// Export each core to its own file inside the output folder, this option is only used in group mode
+        #[arg(long)]
+        split: bool,
+        /// Don't write a deserialize info next to each txt file, import makes it again from the cores and the
+        /// ranges come from the file names. Needs all languages without names or indices and every resource in
+        /// the order of the core, only used with --split
+        #[arg(long, conflicts_with_all = ["only", "filter", "uuid", "sort_resources"])]
+        no_deinfo: bool,
+        /// Also write the cutscene timings to a .timings.json file next to the output, import reads it back
+        /// with --timings. This option is only used in single mode with HZD
+        #[arg(long)]
+        timings: bool,
+        /// Also write the unknown bytes of every cutscene as hex to a .raw.json file next to the output, import
+        /// reads it back with --raw-data. Only meant for research, this option is only used in single mode with HZD
+        #[arg(long)]
+        raw_data: bool,
     },
     /// Import locals back to core and create a new file
     #[command(arg_required_else_help = true)]
     Import {
-        /// Exported local file
-        #[arg(value_hint = ValueHint::FilePath, value_parser = utils::is_file)]
+        /// Exported local file, or the exported folder when used with --split. In single mode pass '-' to
+        /// read it from stdin, the format is detected from the content if its not given
+        #[arg(value_hint = ValueHint::AnyPath, value_parser = utils::is_file_dir_or_stdin)]
         exported_file: PathBuf,
         /// Don't skip writing the core to disk if nothing changed, this option don't have any effect in group mode
         #[arg(short, long)]
         dont_skip: bool,
+        /// Prefix to strip from the file paths stored in exported file, this option is only used in group mode
+        #[arg(long, value_hint = ValueHint::DirPath)]
+        strip_prefix: Option<PathBuf>,
+        /// Prefix to add to the file paths stored in exported file, this option is only used in group mode
+        #[arg(long, value_hint = ValueHint::DirPath)]
+        add_prefix: Option<PathBuf>,
+        /// Warn about every imported string that is longer than this many characters
+        #[arg(long, value_name = "N")]
+        max_length: Option<usize>,
+        /// Warn about every imported string that lost or gained a placeholder like %s, {0} or <b>
+        #[arg(long)]
+        check_placeholders: bool,
+        /// Put back every escaped line break as this line ending instead of the one it was exported as.
+        /// This option is only used with Txt
+        #[arg(long, value_enum)]
+        line_ending: Option<LineEnding>,
+        /// Import a Txt, Json or Yaml export even if the core doesn't have as many resources as the one it
+        /// was exported from
+        #[arg(long)]
+        force: bool,
+        /// Allow the output to be the input core, it gets backed up into a `<core>.backup` folder
+        /// next to itself first, that `restore` can put back.
+        /// This option is only used in single mode
+        #[arg(long)]
+        in_place: bool,
+        /// Replace the timing of the cutscene lines with the ones in the .timings.json file next to the
+        /// exported file. This option is only used in single mode with HZD
+        #[arg(long)]
+        timings: bool,
+        /// Replace the unknown bytes of every cutscene with the ones in the .raw.json file next to the exported
+        /// file. Only meant for research, this option is only used in single mode with HZD
+        #[arg(long)]
+        raw_data: bool,
+        /// Read every written core back and check that it parses and have the expected strings
+        #[arg(long)]
+        verify_after_write: bool,
+        /// Give every written core the modification time of its input core, for tools that sync by it
+        #[arg(long)]
+        preserve_mtime: bool,
+        /// Import the folder made by export --split, this option is only used in group mode
+        #[arg(long)]
+        split: bool,
     },
 }
 
@@ -69,31 +358,322 @@ impl Action {
     }
 }
 
-pub fn parse_hzd_languages(languages: Vec<String>, logger: &mut impl Logger) -> Vec<hzd::Language> {
-    if languages.iter().any(|l| l.eq_ignore_ascii_case("all")) {
-        hzd::Language::ALL_VARIANTS.to_vec()
-    } else {
-        parse_languages(languages, logger)
+/// Write the checksums of the written `files` next to `output`, see
+/// [`utils::write_checksums`].
+pub fn write_checksums(
+    output: &Path,
+    files: &[PathBuf],
+    logger: &mut impl Logger,
+) -> anyhow::Result<()> {
+    let checksums = utils::write_checksums(output, files)?;
+    logger.good(format!("Checksums written to {checksums:?}."));
+    Ok(())
+}
+
+/// Resolve [`Game::Auto`] by detecting the game from `input`, if `input`
+/// is a folder its core files are searched (recursively) until one of them
+/// can be detected.
+pub fn resolve_game(game: Game, input: &Path, logger: &mut impl Logger) -> anyhow::Result<Game> {
+    if game != Game::Auto {
+        return Ok(game);
+    }
+
+    if !input.is_dir() {
+        return match detect_file(input, logger)? {
+            detect::GameDetection::Mixed => bail!("Found mixed magic in input core."),
+            detect::GameDetection::Unknown => bail!("Failed to detect any supported game."),
+            detect::GameDetection::Hzd => Ok(Game::Hzd),
+            detect::GameDetection::Ds => Ok(Game::Ds),
+        };
+    }
+
+    let mut found_core = false;
+    let mut dirs = vec![input.to_path_buf()];
+    while let Some(dir) = dirs.pop() {
+        for entry in std::fs::read_dir(dir)?.flatten() {
+            let path = entry.path();
+            if path.is_dir() {
+                dirs.push(path);
+                continue;
+            }
+            if !path
+                .extension()
+                .is_some_and(|e| e.eq_ignore_ascii_case("core"))
+            {
+                continue;
+            }
+
+            found_core = true;
+            match detect_file(&path, logger)? {
+                detect::GameDetection::Mixed => {
+                    bail!("Found mixed magic in core file: {}", path.display())
+                }
+                detect::GameDetection::Unknown => continue,
+                detect::GameDetection::Hzd => return Ok(Game::Hzd),
+                detect::GameDetection::Ds => return Ok(Game::Ds),
+            }
+        }
     }
+
+    if found_core {
+        bail!("Failed to detect any supported game.");
+    }
+    bail!("Can't find any core file in input folder to use in auto detect.");
+}
+
+/// Same as [`resolve_game`] but detect the game from an already opened
+/// `reader`, the reader is rewinded to the start after detection.
+///
+/// Returns [`None`] if `reader` have resources of more then one game.
+pub fn resolve_game_from<R: Read + Seek>(
+    game: Game,
+    reader: &mut R,
+    logger: &mut impl Logger,
+) -> anyhow::Result<Option<Game>> {
+    if game != Game::Auto {
+        return Ok(Some(game));
+    }
+
+    let detection = detect::detect_game_with_progress(reader, logger)?;
+    reader.rewind()?;
+
+    match detection {
+        detect::GameDetection::Mixed => Ok(None),
+        detect::GameDetection::Unknown => bail!("Failed to detect any supported game."),
+        detect::GameDetection::Hzd => Ok(Some(Game::Hzd)),
+        detect::GameDetection::Ds => Ok(Some(Game::Ds)),
+    }
+}
+
+fn detect_file(path: &Path, logger: &mut impl Logger) -> anyhow::Result<detect::GameDetection> {
+    let mut reader = BufReader::new(File::open(path)?);
+    Ok(detect::detect_game_with_progress(&mut reader, logger)?)
+}
+
+/// The languages of the game that isn't selected.
+struct OtherGame {
+    game: &'static str,
+    other: &'static str,
+    names: Vec<String>,
+}
+
+/// Parse the selected languages and remove the `exclude`d ones from them.
+pub fn parse_hzd_languages(
+    languages: Vec<String>,
+    exclude: Vec<String>,
+    logger: &mut impl Logger,
+) -> Vec<hzd::Language> {
+    let other = OtherGame {
+        game: "HZD",
+        other: "DS",
+        names: names(&ds::Language::ALL_VARIANTS),
+    };
+    select_languages(
+        languages,
+        exclude,
+        &hzd::Language::ALL_VARIANTS,
+        &other,
+        logger,
+    )
+}
+
+/// Parse the selected languages and remove the `exclude`d ones from them.
+pub fn parse_ds_languages(
+    languages: Vec<String>,
+    exclude: Vec<String>,
+    logger: &mut impl Logger,
+) -> Vec<ds::Language> {
+    let other = OtherGame {
+        game: "DS",
+        other: "HZD",
+        names: names(&hzd::Language::ALL_VARIANTS),
+    };
+    select_languages(
+        languages,
+        exclude,
+        &ds::Language::ALL_VARIANTS,
+        &other,
+        logger,
+    )
+}
+
+/// Language selector that expands to every language with a non empty string
+/// in the input.
+const ALL_EXCEPT_EMPTY: &str = "all-except-empty";
+
+/// Returns `true` if the `all-except-empty` selector is in `languages`, the
+/// selected languages should then go through [`keep_populated`] once the
+/// input is loaded.
+pub fn wants_populated(languages: &[String]) -> bool {
+    languages
+        .iter()
+        .any(|l| l.eq_ignore_ascii_case(ALL_EXCEPT_EMPTY))
+}
+
+/// Remove the languages that aren't `populated` from the selected ones.
+pub fn keep_populated<T: Display + PartialEq>(
+    languages: &mut Vec<T>,
+    populated: &[T],
+    logger: &mut impl Logger,
+) {
+    let (kept, empty): (Vec<_>, Vec<_>) = languages.drain(..).partition(|l| populated.contains(l));
+    if !empty.is_empty() {
+        logger.info(format!(
+            "Skipping languages without any string: {}",
+            names(&empty).join(", ")
+        ));
+    }
+    *languages = kept;
 }
 
-pub fn parse_ds_languages(languages: Vec<String>, logger: &mut impl Logger) -> Vec<ds::Language> {
-    if languages.iter().any(|l| l.eq_ignore_ascii_case("all")) {
-        ds::Language::ALL_VARIANTS.to_vec()
+fn select_languages<T: FromStr + Display + Copy + PartialEq>(
+    languages: Vec<String>,
+    exclude: Vec<String>,
+    all: &[T],
+    other: &OtherGame,
+    logger: &mut impl Logger,
+) -> Vec<T> {
+    let mut languages = if languages
+        .iter()
+        .any(|l| l.eq_ignore_ascii_case("all") || l.eq_ignore_ascii_case(ALL_EXCEPT_EMPTY))
+    {
+        all.to_vec()
     } else {
-        parse_languages(languages, logger)
+        parse_languages(languages, all, other, logger)
+    };
+
+    let exclude = parse_languages(exclude, all, other, logger);
+    languages.retain(|l| !exclude.contains(l));
+    languages
+}
+
+/// Parse the language given to `--fill-empty-from`.
+pub fn parse_hzd_fallback(language: Option<String>) -> anyhow::Result<Option<hzd::Language>> {
+    language
+        .map(|l| parse_language(l, "fallback", &hzd::Language::ALL_VARIANTS))
+        .transpose()
+}
+
+/// Parse the language given to `--fill-empty-from`.
+pub fn parse_ds_fallback(language: Option<String>) -> anyhow::Result<Option<ds::Language>> {
+    language
+        .map(|l| parse_language(l, "fallback", &ds::Language::ALL_VARIANTS))
+        .transpose()
+}
+
+/// Parse a single language out of `all`, `kind` says what the language is
+/// used for in the error.
+pub fn parse_language<T: FromStr + Display>(
+    language: String,
+    kind: &str,
+    all: &[T],
+) -> anyhow::Result<T> {
+    if let Ok(language) = language.parse() {
+        return Ok(language);
+    }
+
+    let names = names(all);
+    match utils::closest_name(&language, names.iter().map(String::as_str)) {
+        Some(name) => bail!("Invalid {kind} language: {language} (did you mean {name}?)"),
+        None => bail!("Invalid {kind} language: {language}"),
     }
 }
 
-fn parse_languages<T: TryFrom<String>>(languages: Vec<String>, logger: &mut impl Logger) -> Vec<T> {
+/// A language that only exists in the `other` game is reported as such.
+fn parse_languages<T: FromStr + Display>(
+    languages: Vec<String>,
+    all: &[T],
+    other: &OtherGame,
+    logger: &mut impl Logger,
+) -> Vec<T> {
+    let names = names(all);
+
     languages
         .into_iter()
-        .filter_map(|s| match T::try_from(s.clone()) {
+        .filter_map(|s| match s.parse() {
             Ok(r) => Some(r),
             Err(_) => {
-                logger.warn(format!("Invalid language: {s}"));
+                if let Some(name) = other.names.iter().find(|n| n.eq_ignore_ascii_case(&s)) {
+                    logger.warn(format!(
+                        "Invalid language: {name} exists in {} but not in {}",
+                        other.other, other.game
+                    ));
+                    return None;
+                }
+                match utils::closest_name(&s, names.iter().map(String::as_str)) {
+                    Some(name) => {
+                        logger.warn(format!("Invalid language: {s} (did you mean {name}?)"))
+                    }
+                    None => logger.warn(format!("Invalid language: {s}")),
+                }
                 None
             }
         })
         .collect()
 }
+
+fn names<T: Display>(languages: &[T]) -> Vec<String> {
+    languages.iter().map(ToString::to_string).collect()
+}
+
+#[cfg(test)]
+mod test {
+    use dloc_core::logger::NoLogger;
+
+    use super::*;
+
+    #[test]
+    fn auto_detect_searches_sub_folders_and_skips_unknown_cores() {
+        let dir = std::env::temp_dir().join(format!("dloc-resolve-game-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("sub")).unwrap();
+        // a single chunk with a magic that no game has
+        let unknown = [0xAB_u8; 8].into_iter().chain([0; 4]).collect::<Vec<_>>();
+        std::fs::write(dir.join("a.core"), unknown).unwrap();
+        assert!(resolve_game(Game::Auto, &dir, &mut NoLogger).is_err());
+
+        let hzd = hzd::HZDLocal::synthetic(2).unwrap().to_bytes().unwrap();
+        std::fs::write(dir.join("sub/b.core"), hzd).unwrap();
+        let game = resolve_game(Game::Auto, &dir, &mut NoLogger);
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(game.unwrap(), Game::Hzd);
+    }
+
+    #[test]
+    fn sniff_every_format() {
+        let cases: [(&[u8], SerializeType); 21] = [
+            (b"{\n  \"locals\": []\n}", SerializeType::Json),
+            (b"[\n  {\"index\": 1}\n]", SerializeType::Json),
+            (b"[]", SerializeType::Json),
+            (b"\xEF\xBB\xBF  \n{}", SerializeType::Json),
+            (b"---\nlocals: []", SerializeType::Yaml),
+            (b"- index: 1", SerializeType::Yaml),
+            (b"-\n  index: 1", SerializeType::Yaml),
+            (b"Hello there", SerializeType::Txt),
+            (b"[3] Hello there", SerializeType::Txt),
+            (b"English:: Hello", SerializeType::Txt),
+            (b"a = b", SerializeType::Txt),
+            (b"", SerializeType::Txt),
+            (b"# a.core\n1:English=hi", SerializeType::KeyValue),
+            (b"1:English=hi", SerializeType::KeyValue),
+            (b"2:1:French=salut\n", SerializeType::KeyValue),
+            (b"index\tsub\tEnglish", SerializeType::Table),
+            (
+                b"{\"index\":1,\"language\":\"English\"}",
+                SerializeType::Ndjson,
+            ),
+            (b"{\"path\":\"a.core\",\"index\":1}", SerializeType::Ndjson),
+            (b"msgid \"\"\nmsgstr \"\"", SerializeType::Po),
+            (b"msgctxt \"1:0:English\"", SerializeType::Po),
+            (b"SQLite format 3\0\x10\0", SerializeType::Sqlite),
+        ];
+        for (content, expected) in cases {
+            assert_eq!(
+                SerializeType::sniff(content),
+                expected,
+                "{}",
+                String::from_utf8_lossy(content)
+            );
+        }
+    }
+}
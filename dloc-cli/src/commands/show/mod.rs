@@ -0,0 +1,164 @@
+use std::{
+    fmt::{Display, Write},
+    fs::File,
+    io::{BufReader, Write as _},
+    path::PathBuf,
+    str::FromStr,
+};
+
+use anyhow::bail;
+use clap::{Parser, ValueHint};
+use dloc_core::{
+    games::{
+        ds::DSLocal,
+        find_uuid,
+        hzd::{HZDLocal, LocalVariants},
+        ResourceInfo, Uuid,
+    },
+    logger::Logger,
+    serialize::EofReplacor,
+};
+
+use crate::{logger::CliLogger, Game};
+
+use super::{
+    shared::{parse_ds_languages, parse_hzd_languages, resolve_game},
+    utils,
+};
+
+#[derive(Debug, Parser)]
+#[command(arg_required_else_help = true)]
+pub struct Show {
+    /// Input core file
+    #[arg(value_hint = ValueHint::FilePath, value_parser = utils::is_file)]
+    input_core: PathBuf,
+    /// Index of the resource inside the core
+    #[arg(required_unless_present = "uuid")]
+    index: Option<usize>,
+    /// Uuid of the resource as 32 hex digits, shows the resource with it instead of taking an index
+    #[arg(long, conflicts_with = "index", value_parser = Uuid::from_str)]
+    uuid: Option<Uuid>,
+    /// Languages to show, pass 'all' if you want to show everything
+    #[arg(short, long, num_args = 1.., default_values_t = ["all".to_owned()], value_delimiter = ' ')]
+    languages: Vec<String>,
+}
+
+impl Show {
+    pub fn command(self, game: Game, mut logger: CliLogger) -> anyhow::Result<()> {
+        let game = resolve_game(game, &self.input_core, &mut logger)?;
+        logger.info(format!("Selected game: {game:#?}"));
+
+        let reader = BufReader::new(File::open(&self.input_core)?);
+        let (index, indexes, output) = match game {
+            Game::Hzd => {
+                let languages = parse_hzd_languages(self.languages, Vec::new(), &mut logger);
+                let local = HZDLocal::new(reader)?;
+                let overview = local.resource_overview();
+                let index = resolve_index(self.index, self.uuid, &overview)?;
+                let uuid = overview.get(index).and_then(|r| r.uuid);
+                let locals = local.get_locals();
+                let indexes: Vec<_> = locals.iter().map(|l| l.index).collect();
+
+                let output = locals.into_iter().find(|l| l.index == index).map(|l| {
+                    let mut output = String::new();
+                    match l.variant {
+                        LocalVariants::Localized(strings) => {
+                            output.push_str(&header(index, uuid, "Localized"));
+                            for (lang, string) in
+                                strings.iter().filter(|(l, _)| languages.contains(l))
+                            {
+                                let _ =
+                                    writeln!(output, "{:<24}{}", lang.to_string(), escape(string));
+                            }
+                        }
+                        LocalVariants::Cutscene(lines) => {
+                            output.push_str(&header(index, uuid, "Cutscene"));
+                            for (lang, lines) in lines.iter().filter(|(l, _)| languages.contains(l))
+                            {
+                                for (sub, string) in lines.iter().enumerate() {
+                                    let _ = writeln!(
+                                        output,
+                                        "{:<24}{:<6}{}",
+                                        lang.to_string(),
+                                        format!("[{sub}]"),
+                                        escape(string)
+                                    );
+                                }
+                            }
+                        }
+                    }
+                    output
+                });
+
+                (index, indexes, output)
+            }
+            Game::Ds => {
+                let languages = parse_ds_languages(self.languages, Vec::new(), &mut logger);
+                let local = DSLocal::new(reader)?;
+                let overview = local.resource_overview();
+                let index = resolve_index(self.index, self.uuid, &overview)?;
+                let uuid = overview.get(index).and_then(|r| r.uuid);
+                let locals = local.get_locals();
+                let indexes: Vec<_> = locals.iter().map(|l| l.index).collect();
+
+                let output = locals.into_iter().find(|l| l.index == index).map(|l| {
+                    let mut output = header(index, uuid, "Localized");
+                    for (lang, string) in l.strings.iter().filter(|(l, _)| languages.contains(l)) {
+                        let _ = writeln!(output, "{:<24}{}", lang.to_string(), escape(string));
+                    }
+                    output
+                });
+
+                (index, indexes, output)
+            }
+            Game::Auto => unreachable!(),
+        };
+
+        let Some(output) = output else {
+            bail!(
+                "There is no local resource at index {}, local resources are at: {}",
+                index,
+                join(&indexes)
+            );
+        };
+
+        logger.stdout.write_all(output.as_bytes())?;
+
+        Ok(())
+    }
+}
+
+/// The index of the resource to show, from the `uuid` if its given.
+fn resolve_index(
+    index: Option<usize>,
+    uuid: Option<Uuid>,
+    overview: &[ResourceInfo],
+) -> anyhow::Result<usize> {
+    let Some(uuid) = uuid else {
+        return Ok(index.unwrap_or_default());
+    };
+    match find_uuid(overview, uuid) {
+        Some(index) => Ok(index),
+        None => bail!("There is no resource with uuid {uuid} in the core."),
+    }
+}
+
+fn header(index: usize, uuid: Option<Uuid>, kind: &str) -> String {
+    match uuid {
+        Some(uuid) => format!("Resource {index} ({kind}) uuid {uuid}\n"),
+        None => format!("Resource {index} ({kind})\n"),
+    }
+}
+
+/// Escape the line breaks so each string stays in its own row.
+fn escape(string: &str) -> String {
+    string.to_owned().replace_eol()
+}
+
+fn join(values: &[impl Display]) -> String {
+    values
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
@@ -1,34 +1,49 @@
 use std::{
-    collections::hash_map::DefaultHasher,
-    fs::File,
-    hash::{Hash, Hasher},
-    io::{BufReader, BufWriter},
-    path::PathBuf,
+    fs::{self, File},
+    io::{BufReader, BufWriter, Read, Seek, Write},
+    path::{Path, PathBuf},
 };
 
 use anyhow::bail;
 use clap::{Parser, ValueHint};
 use dloc_core::{
-    games::{detect, ds::DSLocal, hzd::HZDLocal},
+    games::{
+        check_lengths, check_placeholders, detect,
+        ds::{self, DSLocal},
+        hzd::{self, HZDLocal},
+        string_snapshot,
+    },
     logger::Logger,
-    serialize::SerializeData,
+    serialize::{deinfo_path, ImportOptions, SerializeData},
 };
 
 use crate::{logger::CliLogger, Game};
 
 use super::{
-    shared::{parse_ds_languages, parse_hzd_languages, Action, SerializeType},
-    utils,
+    shared::{
+        keep_populated, parse_ds_fallback, parse_ds_languages, parse_hzd_fallback,
+        parse_hzd_languages, resolve_game_from, wants_populated, write_checksums, Action,
+        FormatOptions, LineEnding, ResourceKind, SerializeType,
+    },
+    utils::{self, ReadSeek},
 };
 
 #[derive(Debug, Parser)]
 #[command(arg_required_else_help = true)]
 pub struct Single {
-    /// Input core file
-    #[arg(value_hint = ValueHint::FilePath, value_parser = utils::is_file)]
+    /// Input core file, pass '-' to read it from stdin
+    #[arg(value_hint = ValueHint::FilePath, value_parser = utils::is_file_or_stdin)]
     input_core: PathBuf,
-    /// Output file
+    /// Output file, if its a directory the output will be saved inside it with a name derived from input
     output: Option<PathBuf>,
+    /// Write the SHA-256 of every written file to a .sha256 file next to the output, check it with
+    /// verify-checksums
+    #[arg(long)]
+    checksums: bool,
+    /// Best effort parse of a damaged core, resources that fail to parse are skipped up to the next
+    /// local resource that does and kept as raw data. Not used for cores with resources of both games
+    #[arg(long)]
+    repair: bool,
     #[command(subcommand)]
     action: Action,
 }
@@ -37,46 +52,102 @@ impl Single {
     pub fn command(
         self,
         game: Game,
-        sert: SerializeType,
+        sert: Option<SerializeType>,
         mut logger: CliLogger,
     ) -> anyhow::Result<()> {
-        let game = match game {
-            Game::Auto => {
-                let mut reader = BufReader::new(File::open(&self.input_core)?);
-                match detect::detect_game(&mut reader)? {
-                    detect::GameDetection::Hzd => Game::Hzd,
-                    detect::GameDetection::Ds => Game::Ds,
-                    detect::GameDetection::Mixed => bail!("Found mixed magic in input core."),
-                    detect::GameDetection::Unknown => bail!("Failed to detect any supported game."),
-                }
+        if utils::is_stdin(&self.input_core) && self.output.as_ref().is_none_or(|o| o.is_dir()) {
+            bail!("An output file path is required when reading the core from stdin.");
+        }
+        if let Action::Import { exported_file, .. } = &self.action {
+            if utils::is_stdin(&self.input_core) && utils::is_stdin(exported_file) {
+                bail!("The core and the exported file can't both be read from stdin.");
             }
-            Game::Hzd => Game::Hzd,
-            Game::Ds => Game::Ds,
+        }
+
+        logger.info("Opening input core file.");
+        let mut reader = utils::open_input(&self.input_core)?;
+        logger.info("Core file opened.");
+
+        let Some(game) = resolve_game_from(game, &mut reader, &mut logger)? else {
+            return self.export_mixed(sert.unwrap_or_default(), reader, logger);
         };
 
         logger.info(format!("Selected game: {game:#?}"));
         logger.info(format!("Selected action: {}", self.action.name()));
 
-        logger.info("Opening input core file.");
-        let reader = BufReader::new(File::open(&self.input_core)?);
-        logger.info("Core file opened.");
+        if let (Action::Import { .. }, Some(stream)) =
+            (&self.action, detect::stream_companion(&self.input_core))
+        {
+            logger.warn(format!(
+                "Found companion stream file {:?}, dloc doesn't update it so edited cutscene timings may get out of sync.",
+                stream
+            ));
+        }
 
         match game {
             Game::Hzd => {
+                // a damaged core may not get this far, the parser reports it anyway
+                let cutscenes = match detect::hzd_cutscene_languages(&mut reader) {
+                    Err(_) if self.repair => Vec::new(),
+                    cutscenes => cutscenes?,
+                };
+                for cutscene in cutscenes.into_iter().filter(|c| !c.matches()) {
+                    logger.warn(format!(
+                        "Cutscene resource {} declares {} languages but dloc expects {}, it will be kept as is.",
+                        cutscene.index,
+                        cutscene.declared,
+                        detect::CutsceneLanguages::EXPECTED
+                    ));
+                }
+                reader.rewind()?;
+
                 logger.info("Loading the core file with HZD parser.");
-                let mut game = HZDLocal::new(reader)?;
+                let (mut game, warnings) = if self.repair {
+                    HZDLocal::new_repairing(reader)?
+                } else {
+                    HZDLocal::new_with_warnings(reader)?
+                };
                 logger.good("Core file loaded.");
+                for warning in warnings {
+                    logger.warn(format!("Parse warning: {warning}."));
+                }
 
                 match self.action {
                     Action::Export {
                         languages,
+                        exclude,
                         add_language_names,
+                        language_separator,
+                        add_indices,
+                        json_indent,
+                        omit_empty,
+                        normalize_line_endings,
+                        only_changed_languages,
+                        keep_fields,
+                        only,
+                        filter,
+                        uuid,
+                        sort_resources,
+                        byte_lengths,
+                        blank,
+                        fill_empty_from,
+                        timings,
+                        raw_data,
+                        ..
                     } => {
-                        let output = self
-                            .output
-                            .unwrap_or_else(|| self.input_core.with_extension(sert.extension()));
+                        let sert = sert.unwrap_or_default();
+                        let output =
+                            utils::resolve_output(self.output, &self.input_core, sert.extension());
 
-                        let languages = parse_hzd_languages(languages, &mut logger);
+                        let except_empty = wants_populated(&languages);
+                        let mut languages = parse_hzd_languages(languages, exclude, &mut logger);
+                        if except_empty {
+                            keep_populated(
+                                &mut languages,
+                                &game.populated_languages(),
+                                &mut logger,
+                            );
+                        }
 
                         if languages.is_empty() {
                             bail!("Didn't found any valid Language.");
@@ -84,58 +155,237 @@ impl Single {
 
                         logger.info(format!("Selected languages: {languages:?}"));
 
-                        let serialize_type = sert.to_core(Some(add_language_names));
+                        let serialize_type = sert.to_core(FormatOptions {
+                            add_language_names,
+                            language_separator,
+                            add_indices,
+                            json_indent: json_indent.clone(),
+                            omit_empty,
+                            line_ending: normalize_line_endings.then_some(LineEnding::Lf),
+                        });
+                        let filters = hzd::ExportFilters {
+                            resources: ResourceKind::to_hzd_filter(only),
+                            content: filter,
+                            uuids: uuid,
+                            sort_by_uuid: sort_resources,
+                            byte_lengths,
+                            fill_empty_from: parse_hzd_fallback(fill_empty_from)?,
+                            blank,
+                        };
 
                         logger.info(format!("Serializing locals into {:?} format.", sert));
-                        game.serialize(output, languages, serialize_type)?;
-                        logger.good("Serialization finished successfully.")
+                        match only_changed_languages {
+                            Some(reference) => {
+                                logger.info("Loading the reference core file.");
+                                let reference =
+                                    HZDLocal::new(BufReader::new(File::open(reference)?))?;
+                                game.serialize_changed(
+                                    &reference,
+                                    &output,
+                                    languages,
+                                    serialize_type,
+                                    &filters,
+                                )?;
+                            }
+                            None => match keep_fields {
+                                Some(previous) => game.serialize_keeping_fields(
+                                    &previous,
+                                    &output,
+                                    languages,
+                                    serialize_type,
+                                    &filters,
+                                )?,
+                                None => {
+                                    game.serialize(&output, languages, serialize_type, &filters)?
+                                }
+                            },
+                        }
+                        logger.good("Serialization finished successfully.");
+
+                        let mut written = vec![output.clone()];
+                        if sert == SerializeType::Txt {
+                            written.push(deinfo_path(&output));
+                        }
+
+                        if timings {
+                            let timings_output = hzd::timings_path(&output);
+                            game.export_timings(&timings_output, json_indent.as_deref())?;
+                            logger
+                                .good(format!("Cutscene timings exported to {timings_output:?}."));
+                            written.push(timings_output);
+                        }
+
+                        if raw_data {
+                            let raw_output = hzd::raw_data_path(&output);
+                            game.export_raw_data(&raw_output, json_indent.as_deref())?;
+                            logger.good(format!("Cutscene raw data exported to {raw_output:?}."));
+                            written.push(raw_output);
+                        }
+
+                        if self.checksums {
+                            write_checksums(&output, &written, &mut logger)?;
+                        }
                     }
                     Action::Import {
                         exported_file,
                         dont_skip,
+                        max_length,
+                        check_placeholders: placeholders,
+                        verify_after_write,
+                        preserve_mtime,
+                        line_ending,
+                        force,
+                        in_place,
+                        timings,
+                        raw_data,
+                        ..
                     } => {
-                        let output = self
-                            .output
-                            .unwrap_or_else(|| self.input_core.with_extension("new.core"));
+                        let output =
+                            utils::resolve_output(self.output, &self.input_core, "new.core");
+                        guard_in_place(&self.input_core, &output, in_place, &mut logger)?;
+                        let options = ImportOptions {
+                            check_resource_count: !force,
+                        };
 
-                        let mut hasher = DefaultHasher::new();
-                        game.hash(&mut hasher);
-                        let hash_before = hasher.finish();
+                        let before = (max_length.is_some() || placeholders)
+                            .then(|| string_snapshot(game.strings()));
 
                         logger.info("Deserializing and updating local files.");
-                        game.deserialize_and_update(exported_file, sert.to_core(None))?;
+                        let mut summary = if utils::is_stdin(&exported_file) {
+                            let (exported, sert) = read_stdin_export(sert, &mut logger)?;
+                            game.deserialize_and_update_from_reader(
+                                exported.as_slice(),
+                                sert.to_core(FormatOptions {
+                                    line_ending,
+                                    ..Default::default()
+                                }),
+                                options,
+                            )?
+                        } else {
+                            let sert =
+                                SerializeType::resolve_import(sert, &exported_file, &mut logger);
+                            game.deserialize_and_update(
+                                &exported_file,
+                                sert.to_core(FormatOptions {
+                                    line_ending,
+                                    ..Default::default()
+                                }),
+                                options,
+                            )?
+                        };
                         logger.good("Deerialization and update finished.");
 
-                        let mut hasher = DefaultHasher::new();
-                        game.hash(&mut hasher);
-                        let hash_after = hasher.finish();
+                        let timings_input = hzd::timings_path(&exported_file);
+                        if timings {
+                            logger.info(format!(
+                                "Importing cutscene timings from {timings_input:?}."
+                            ));
+                            let changed = game.import_timings(timings_input)?;
+                            logger.good(format!("{changed} cutscene timings changed."));
+                        } else if timings_input.is_file() {
+                            logger.warn(format!(
+                                "Found {timings_input:?} but --timings isn't given, the cutscene timings are kept as is."
+                            ));
+                        }
+
+                        let raw_input = hzd::raw_data_path(&exported_file);
+                        if raw_data {
+                            logger.info(format!("Importing cutscene raw data from {raw_input:?}."));
+                            let changed = game.import_raw_data(raw_input)?;
+                            logger.good(format!("{changed} cutscenes raw data changed."));
+                        } else if raw_input.is_file() {
+                            logger.warn(format!(
+                                "Found {raw_input:?} but --raw-data isn't given, the cutscene raw data is kept as is."
+                            ));
+                        }
+
+                        if let (Some(max_length), Some(before)) = (max_length, &before) {
+                            for warning in check_lengths(before, game.strings(), max_length) {
+                                logger.warn(warning);
+                            }
+                        }
+                        if let (true, Some(before)) = (placeholders, &before) {
+                            for warning in check_placeholders(before, game.strings()) {
+                                logger.warn(warning);
+                            }
+                        }
 
-                        if !dont_skip && hash_before == hash_after {
+                        if !dont_skip && !game.is_modified() {
                             bail!("Nothing changed, write to disk cancelled.");
                         }
 
                         logger.info("Writing the updated core to output file.");
-                        let mut writer = BufWriter::new(File::create(output)?);
+                        let mut writer = BufWriter::new(File::create(&output)?);
                         game.write(&mut writer)?;
+                        writer.flush()?;
+                        if preserve_mtime {
+                            utils::copy_mtime(&self.input_core, writer.get_ref())?;
+                        }
                         logger.good("Write finished.");
+
+                        if verify_after_write {
+                            logger.info("Verifying the written core.");
+                            game.verify_written(BufReader::new(File::open(&output)?))?;
+                            logger.good("Written core verified.");
+                        }
+
+                        if self.checksums {
+                            write_checksums(&output, std::slice::from_ref(&output), &mut logger)?;
+                        }
+
+                        summary.files_written = 1;
+                        logger.info(format!("Import summary: {summary}."));
                     }
                 }
             }
             Game::Ds => {
                 logger.info("Loading the core file with HZD parser.");
-                let mut game = DSLocal::new(reader)?;
+                let (mut game, warnings) = if self.repair {
+                    DSLocal::new_repairing(reader)?
+                } else {
+                    DSLocal::new_with_warnings(reader)?
+                };
                 logger.good("Core file loaded.");
+                for warning in warnings {
+                    logger.warn(format!("Parse warning: {warning}."));
+                }
 
                 match self.action {
                     Action::Export {
                         languages,
+                        exclude,
                         add_language_names,
+                        language_separator,
+                        add_indices,
+                        json_indent,
+                        omit_empty,
+                        normalize_line_endings,
+                        only_changed_languages,
+                        keep_fields,
+                        only,
+                        filter,
+                        uuid,
+                        sort_resources,
+                        byte_lengths,
+                        blank,
+                        fill_empty_from,
+                        timings,
+                        raw_data,
+                        ..
                     } => {
-                        let output = self
-                            .output
-                            .unwrap_or_else(|| self.input_core.with_extension(sert.extension()));
+                        let sert = sert.unwrap_or_default();
+                        let output =
+                            utils::resolve_output(self.output, &self.input_core, sert.extension());
 
-                        let languages = parse_ds_languages(languages, &mut logger);
+                        let except_empty = wants_populated(&languages);
+                        let mut languages = parse_ds_languages(languages, exclude, &mut logger);
+                        if except_empty {
+                            keep_populated(
+                                &mut languages,
+                                &game.populated_languages(),
+                                &mut logger,
+                            );
+                        }
 
                         if languages.is_empty() {
                             bail!("Didn't found any valid Language.");
@@ -143,40 +393,160 @@ impl Single {
 
                         logger.info(format!("Selected languages: {languages:?}"));
 
-                        let serialize_type = sert.to_core(Some(add_language_names));
+                        let serialize_type = sert.to_core(FormatOptions {
+                            add_language_names,
+                            language_separator,
+                            add_indices,
+                            json_indent,
+                            omit_empty,
+                            line_ending: normalize_line_endings.then_some(LineEnding::Lf),
+                        });
+
+                        if only.is_some() {
+                            logger.warn("--only is only used with HZD, ignoring it.");
+                        }
+                        if timings || raw_data {
+                            logger.warn(
+                                "--timings and --raw-data are only used with HZD, ignoring them.",
+                            );
+                        }
+                        let filters = ds::ExportFilters {
+                            resources: (),
+                            content: filter,
+                            uuids: uuid,
+                            sort_by_uuid: sort_resources,
+                            byte_lengths,
+                            fill_empty_from: parse_ds_fallback(fill_empty_from)?,
+                            blank,
+                        };
 
                         logger.info(format!("Serializing locals into {:?} format.", sert));
-                        game.serialize(output, languages, serialize_type)?;
-                        logger.good("Serialization finished successfully.")
+                        match only_changed_languages {
+                            Some(reference) => {
+                                logger.info("Loading the reference core file.");
+                                let reference =
+                                    DSLocal::new(BufReader::new(File::open(reference)?))?;
+                                game.serialize_changed(
+                                    &reference,
+                                    &output,
+                                    languages,
+                                    serialize_type,
+                                    &filters,
+                                )?;
+                            }
+                            None => match keep_fields {
+                                Some(previous) => game.serialize_keeping_fields(
+                                    &previous,
+                                    &output,
+                                    languages,
+                                    serialize_type,
+                                    &filters,
+                                )?,
+                                None => {
+                                    game.serialize(&output, languages, serialize_type, &filters)?
+                                }
+                            },
+                        }
+                        logger.good("Serialization finished successfully.");
+
+                        if self.checksums {
+                            let mut written = vec![output.clone()];
+                            if sert == SerializeType::Txt {
+                                written.push(deinfo_path(&output));
+                            }
+                            write_checksums(&output, &written, &mut logger)?;
+                        }
                     }
                     Action::Import {
                         exported_file,
                         dont_skip,
+                        max_length,
+                        check_placeholders: placeholders,
+                        verify_after_write,
+                        preserve_mtime,
+                        line_ending,
+                        force,
+                        in_place,
+                        timings,
+                        raw_data,
+                        ..
                     } => {
-                        let output = self
-                            .output
-                            .unwrap_or_else(|| self.input_core.with_extension("new.core"));
+                        let output =
+                            utils::resolve_output(self.output, &self.input_core, "new.core");
+                        guard_in_place(&self.input_core, &output, in_place, &mut logger)?;
+                        let options = ImportOptions {
+                            check_resource_count: !force,
+                        };
+                        if timings || raw_data {
+                            logger.warn(
+                                "--timings and --raw-data are only used with HZD, ignoring them.",
+                            );
+                        }
 
-                        let mut hasher = DefaultHasher::new();
-                        game.hash(&mut hasher);
-                        let hash_before = hasher.finish();
+                        let before = (max_length.is_some() || placeholders)
+                            .then(|| string_snapshot(game.strings()));
 
                         logger.info("Deserializing and updating local files.");
-                        game.deserialize_and_update(exported_file, sert.to_core(None))?;
+                        let mut summary = if utils::is_stdin(&exported_file) {
+                            let (exported, sert) = read_stdin_export(sert, &mut logger)?;
+                            game.deserialize_and_update_from_reader(
+                                exported.as_slice(),
+                                sert.to_core(FormatOptions {
+                                    line_ending,
+                                    ..Default::default()
+                                }),
+                                options,
+                            )?
+                        } else {
+                            let sert =
+                                SerializeType::resolve_import(sert, &exported_file, &mut logger);
+                            game.deserialize_and_update(
+                                exported_file,
+                                sert.to_core(FormatOptions {
+                                    line_ending,
+                                    ..Default::default()
+                                }),
+                                options,
+                            )?
+                        };
                         logger.good("Deerialization and update finished.");
 
-                        let mut hasher = DefaultHasher::new();
-                        game.hash(&mut hasher);
-                        let hash_after = hasher.finish();
+                        if let (Some(max_length), Some(before)) = (max_length, &before) {
+                            for warning in check_lengths(before, game.strings(), max_length) {
+                                logger.warn(warning);
+                            }
+                        }
+                        if let (true, Some(before)) = (placeholders, &before) {
+                            for warning in check_placeholders(before, game.strings()) {
+                                logger.warn(warning);
+                            }
+                        }
 
-                        if !dont_skip && hash_before == hash_after {
+                        if !dont_skip && !game.is_modified() {
                             bail!("Nothing changed, write to disk cancelled.");
                         }
 
                         logger.info("Writing the updated core to output file.");
-                        let mut writer = BufWriter::new(File::create(output)?);
+                        let mut writer = BufWriter::new(File::create(&output)?);
                         game.write(&mut writer)?;
+                        writer.flush()?;
+                        if preserve_mtime {
+                            utils::copy_mtime(&self.input_core, writer.get_ref())?;
+                        }
                         logger.good("Write finished.");
+
+                        if verify_after_write {
+                            logger.info("Verifying the written core.");
+                            game.verify_written(BufReader::new(File::open(&output)?))?;
+                            logger.good("Written core verified.");
+                        }
+
+                        if self.checksums {
+                            write_checksums(&output, std::slice::from_ref(&output), &mut logger)?;
+                        }
+
+                        summary.files_written = 1;
+                        logger.info(format!("Import summary: {summary}."));
                     }
                 }
             }
@@ -185,4 +555,206 @@ impl Single {
 
         Ok(())
     }
+
+    /// Export a core that have resources of both games, each parser keep the
+    /// resources of the other game as raw data so every game is exported to
+    /// its own file.
+    fn export_mixed(
+        self,
+        sert: SerializeType,
+        mut reader: Box<dyn ReadSeek>,
+        mut logger: CliLogger,
+    ) -> anyhow::Result<()> {
+        let Action::Export {
+            languages,
+            exclude,
+            add_language_names,
+            language_separator,
+            add_indices,
+            json_indent,
+            omit_empty,
+            normalize_line_endings,
+            only_changed_languages,
+            keep_fields,
+            only,
+            filter,
+            uuid,
+            sort_resources,
+            byte_lengths,
+            blank,
+            fill_empty_from,
+            timings,
+            raw_data,
+            ..
+        } = self.action
+        else {
+            bail!("Input core have both HZD and DS resources, pass the game to import each export separately.");
+        };
+
+        logger.warn("Input core have both HZD and DS resources, exporting each game separately.");
+        if self.repair {
+            logger.warn("--repair isn't used for cores with resources of both games.");
+        }
+        if only_changed_languages.is_some()
+            || keep_fields.is_some()
+            || only.is_some()
+            || timings
+            || raw_data
+        {
+            logger.warn(
+                "--only-changed-languages, --keep-fields, --only, --timings and --raw-data are ignored for mixed cores.",
+            );
+        }
+
+        let output = utils::resolve_output(self.output, &self.input_core, sert.extension());
+        let serialize_type = sert.to_core(FormatOptions {
+            add_language_names,
+            language_separator,
+            add_indices,
+            json_indent,
+            omit_empty,
+            line_ending: normalize_line_endings.then_some(LineEnding::Lf),
+        });
+
+        logger.info("Loading the core file with HZD parser.");
+        let hzd = HZDLocal::new(&mut reader)?;
+        let hzd_filters = hzd::ExportFilters {
+            resources: hzd::ResourceFilter::All,
+            content: filter.clone(),
+            uuids: uuid.clone(),
+            sort_by_uuid: sort_resources,
+            byte_lengths,
+            fill_empty_from: parse_hzd_fallback(fill_empty_from.clone())?,
+            blank,
+        };
+        let except_empty = wants_populated(&languages);
+        let mut hzd_languages =
+            parse_hzd_languages(languages.clone(), exclude.clone(), &mut logger);
+        if except_empty {
+            keep_populated(&mut hzd_languages, &hzd.populated_languages(), &mut logger);
+        }
+        let hzd_output = output.with_extension(format!("hzd.{}", sert.extension()));
+        hzd.serialize(
+            &hzd_output,
+            hzd_languages,
+            serialize_type.clone(),
+            &hzd_filters,
+        )?;
+        logger.good(format!("HZD resources exported to {hzd_output:?}."));
+
+        reader.rewind()?;
+
+        logger.info("Loading the core file with DS parser.");
+        let ds = DSLocal::new(&mut reader)?;
+        let ds_filters = ds::ExportFilters {
+            resources: (),
+            content: filter,
+            uuids: uuid,
+            sort_by_uuid: sort_resources,
+            byte_lengths,
+            fill_empty_from: parse_ds_fallback(fill_empty_from)?,
+            blank,
+        };
+        let mut ds_languages = parse_ds_languages(languages, exclude, &mut logger);
+        if except_empty {
+            keep_populated(&mut ds_languages, &ds.populated_languages(), &mut logger);
+        }
+        let ds_output = output.with_extension(format!("ds.{}", sert.extension()));
+        ds.serialize(&ds_output, ds_languages, serialize_type, &ds_filters)?;
+        logger.good(format!("DS resources exported to {ds_output:?}."));
+
+        if self.checksums {
+            let outputs = [hzd_output, ds_output];
+            let mut written = outputs.to_vec();
+            if sert == SerializeType::Txt {
+                written.extend(outputs.iter().map(|o| deinfo_path(o)));
+            }
+            write_checksums(&output, &written, &mut logger)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Make sure an import doesn't overwrite its input core by mistake, with
+/// `in_place` the input core is backed up into a `<input>.backup` folder
+/// first, the same layout `restore` reads, so it can be put back with
+/// `restore <input>.backup <folder of input>`. Like install an existing
+/// backup is kept, so it always has the first version.
+fn guard_in_place(
+    input: &Path,
+    output: &Path,
+    in_place: bool,
+    logger: &mut CliLogger,
+) -> anyhow::Result<()> {
+    if !utils::is_same_file(input, output) {
+        if in_place {
+            logger.warn("Output isn't the input core, ignoring --in-place.");
+        }
+        return Ok(());
+    }
+
+    if !in_place {
+        bail!("Output is the input core, pass --in-place to overwrite it after backing it up.");
+    }
+
+    let Some(name) = input.file_name() else {
+        bail!("Input core {input:?} doesn't have a file name.");
+    };
+    let mut backup_dir = input.as_os_str().to_owned();
+    backup_dir.push(".backup");
+    let backup_dir = PathBuf::from(backup_dir);
+    if backup_dir.is_file() {
+        bail!("{backup_dir:?} is a file, move it away so the input core can be backed up into a folder there.");
+    }
+
+    let backup = backup_dir.join(name);
+    if backup.exists() {
+        let age = fs::metadata(&backup)?
+            .modified()?
+            .elapsed()
+            .unwrap_or_default();
+        logger.info(format!(
+            "Keeping the existing backup {backup:?} made {} ago, delete it to backup the current core.",
+            utils::format_age(age)
+        ));
+    } else {
+        fs::create_dir_all(&backup_dir)?;
+        fs::copy(input, &backup)?;
+        logger.good(format!(
+            "Input core backed up to {backup:?}, use restore {backup_dir:?} to put it back."
+        ));
+    }
+
+    Ok(())
+}
+
+/// Read a whole export from stdin, the format is detected from the content
+/// if `sert` isn't given. Txt isn't supported as it needs its deinfo file
+/// and SQLite as it needs a file to open.
+fn read_stdin_export(
+    sert: Option<SerializeType>,
+    logger: &mut CliLogger,
+) -> anyhow::Result<(Vec<u8>, SerializeType)> {
+    let mut exported = Vec::new();
+    std::io::stdin().lock().read_to_end(&mut exported)?;
+
+    let sert = match sert {
+        Some(sert) => sert,
+        None => {
+            let sert = SerializeType::sniff(&exported);
+            logger.info(format!("Detected {sert:?} format from stdin content."));
+            sert
+        }
+    };
+
+    match sert {
+        SerializeType::Txt => {
+            bail!("Txt exports can't be read from stdin as they need their deinfo file.")
+        }
+        SerializeType::Sqlite => bail!("SQLite exports can't be read from stdin."),
+        _ => {}
+    }
+
+    Ok((exported, sert))
 }
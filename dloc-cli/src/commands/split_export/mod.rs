@@ -0,0 +1,51 @@
+use std::path::PathBuf;
+
+use anyhow::bail;
+use clap::{Parser, ValueHint};
+use dloc_core::{
+    games::{ds::DSLocal, hzd::HZDLocal},
+    logger::Logger,
+    serialize::DecimaGroup,
+};
+
+use crate::{logger::CliLogger, Game};
+
+use super::utils;
+
+#[derive(Debug, Parser)]
+#[command(arg_required_else_help = true)]
+pub struct SplitExport {
+    /// Txt file exported in group mode, the deinfo file should be next to it
+    #[arg(value_hint = ValueHint::FilePath, value_parser = utils::is_file)]
+    exported_file: PathBuf,
+    /// Folder to write a txt file for every core file to, import it with group import --split
+    #[arg(value_hint = ValueHint::DirPath)]
+    output: PathBuf,
+}
+
+impl SplitExport {
+    pub fn command(self, game: Game, mut logger: CliLogger) -> anyhow::Result<()> {
+        logger.info("Splitting the exported file by its deinfo.");
+
+        let count = match game {
+            Game::Auto => {
+                bail!("Auto can't be used in split-export command, input the game directly.")
+            }
+            Game::Hzd => DecimaGroup::<HZDLocal, CliLogger>::split_txt_export(
+                &self.exported_file,
+                &self.output,
+            )?,
+            Game::Ds => DecimaGroup::<DSLocal, CliLogger>::split_txt_export(
+                &self.exported_file,
+                &self.output,
+            )?,
+        };
+
+        logger.good(format!(
+            "Wrote {count} files to {:?}, one for each core file.",
+            self.output
+        ));
+
+        Ok(())
+    }
+}
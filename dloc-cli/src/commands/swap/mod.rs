@@ -0,0 +1,90 @@
+use std::{
+    fs::File,
+    io::{BufReader, BufWriter},
+    path::PathBuf,
+};
+
+use anyhow::bail;
+use clap::{Parser, ValueHint};
+use dloc_core::{
+    games::{
+        ds::{self, DSLocal},
+        hzd::{self, HZDLocal},
+    },
+    logger::Logger,
+};
+
+use crate::{logger::CliLogger, Game};
+
+use super::{
+    shared::{parse_language, resolve_game},
+    utils,
+};
+
+#[derive(Debug, Parser)]
+#[command(arg_required_else_help = true)]
+pub struct Swap {
+    /// Input core file
+    #[arg(value_hint = ValueHint::FilePath, value_parser = utils::is_file)]
+    input_core: PathBuf,
+    /// The two languages to swap, like English,Arabic
+    #[arg(short, long, required = true, value_delimiter = ',')]
+    languages: Vec<String>,
+    /// Output core file, by default its next to the input core
+    #[arg(value_hint = ValueHint::FilePath)]
+    output: Option<PathBuf>,
+    /// Don't skip writing the core to disk if nothing changed
+    #[arg(short, long)]
+    dont_skip: bool,
+}
+
+impl Swap {
+    pub fn command(self, game: Game, mut logger: CliLogger) -> anyhow::Result<()> {
+        let game = resolve_game(game, &self.input_core, &mut logger)?;
+        logger.info(format!("Selected game: {game:#?}"));
+
+        let Ok([a, b]) = <[String; 2]>::try_from(self.languages) else {
+            bail!("--languages needs exactly two languages.");
+        };
+        if a.eq_ignore_ascii_case(&b) {
+            bail!("Can't swap {a} with itself.");
+        }
+
+        let output = utils::resolve_output(self.output, &self.input_core, "new.core");
+        let reader = BufReader::new(File::open(&self.input_core)?);
+
+        match game {
+            Game::Hzd => {
+                let a = parse_language(a, "swap", &hzd::Language::ALL_VARIANTS)?;
+                let b = parse_language(b, "swap", &hzd::Language::ALL_VARIANTS)?;
+                let mut core = HZDLocal::new(reader)?;
+
+                logger.info(format!("Swapping {a} and {b}."));
+                core.swap_languages(a, b);
+
+                if !self.dont_skip && !core.is_modified() {
+                    bail!("Nothing changed, write to disk cancelled.");
+                }
+                core.write(&mut BufWriter::new(File::create(&output)?))?;
+            }
+            Game::Ds => {
+                let a = parse_language(a, "swap", &ds::Language::ALL_VARIANTS)?;
+                let b = parse_language(b, "swap", &ds::Language::ALL_VARIANTS)?;
+                let mut core = DSLocal::new(reader)?;
+
+                logger.info(format!("Swapping {a} and {b}."));
+                core.swap_languages(a, b);
+
+                if !self.dont_skip && !core.is_modified() {
+                    bail!("Nothing changed, write to disk cancelled.");
+                }
+                core.write(&mut BufWriter::new(File::create(&output)?))?;
+            }
+            Game::Auto => unreachable!(),
+        }
+
+        logger.good(format!("New core written to {output:?}."));
+
+        Ok(())
+    }
+}
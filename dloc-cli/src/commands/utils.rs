@@ -1,4 +1,19 @@
-use std::path::{Path, PathBuf};
+use std::{
+    fs::{self, File},
+    io::{BufReader, BufWriter, Cursor, Read, Seek, Write},
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use sha2::{Digest, Sha256};
+
+/// Path used to read the input from stdin.
+pub const STDIN: &str = "-";
+
+/// A reader that can also seek.
+pub trait ReadSeek: Read + Seek {}
+
+impl<T: Read + Seek> ReadSeek for T {}
 
 pub fn is_file(path: &str) -> Result<PathBuf, String> {
     let path = Path::new(path);
@@ -8,6 +23,48 @@ pub fn is_file(path: &str) -> Result<PathBuf, String> {
     Ok(path.to_path_buf())
 }
 
+/// Same as [`is_file`] but also accept [`STDIN`].
+pub fn is_file_or_stdin(path: &str) -> Result<PathBuf, String> {
+    if path == STDIN {
+        return Ok(PathBuf::from(path));
+    }
+    is_file(path)
+}
+
+/// Same as [`is_file_or_dir`] but also accept [`STDIN`].
+pub fn is_file_dir_or_stdin(path: &str) -> Result<PathBuf, String> {
+    if path == STDIN {
+        return Ok(PathBuf::from(path));
+    }
+    is_file_or_dir(path)
+}
+
+/// Set the modification time of `file` to the one of `source`, stdin
+/// doesn't have one so its skipped.
+pub fn copy_mtime(source: &Path, file: &File) -> std::io::Result<()> {
+    if is_stdin(source) {
+        return Ok(());
+    }
+    file.set_modified(fs::metadata(source)?.modified()?)
+}
+
+/// Returns `true` if `path` means stdin.
+pub fn is_stdin(path: &Path) -> bool {
+    path.as_os_str() == STDIN
+}
+
+/// Open `path` for reading, if its [`STDIN`] the whole stdin is read to
+/// memory first as we need to be able to seek in the input.
+pub fn open_input(path: &Path) -> std::io::Result<Box<dyn ReadSeek>> {
+    if is_stdin(path) {
+        let mut buf = Vec::new();
+        std::io::stdin().lock().read_to_end(&mut buf)?;
+        return Ok(Box::new(Cursor::new(buf)));
+    }
+
+    Ok(Box::new(BufReader::new(File::open(path)?)))
+}
+
 pub fn is_dir(path: &str) -> Result<PathBuf, String> {
     let path = Path::new(path);
     if !path.is_dir() {
@@ -15,3 +72,181 @@ pub fn is_dir(path: &str) -> Result<PathBuf, String> {
     }
     Ok(path.to_path_buf())
 }
+
+/// Same as [`is_file`] but also accept a dir.
+pub fn is_file_or_dir(path: &str) -> Result<PathBuf, String> {
+    let path = Path::new(path);
+    if !path.exists() {
+        return Err("You need to pass a valid file or dir path.".to_owned());
+    }
+    Ok(path.to_path_buf())
+}
+
+/// Parse a json indentation, `tab` or a number of spaces.
+pub fn parse_indent(value: &str) -> Result<String, String> {
+    if value.eq_ignore_ascii_case("tab") {
+        return Ok("\t".to_owned());
+    }
+
+    match value.parse::<usize>() {
+        Ok(spaces) => Ok(" ".repeat(spaces)),
+        Err(_) => Err("Indent should be 'tab' or a number of spaces.".to_owned()),
+    }
+}
+
+/// Parse a size in bytes, with an optional `K`, `M` or `G` suffix for
+/// kibibytes, mebibytes and gibibytes, like `512M`.
+pub fn parse_size(value: &str) -> Result<u64, String> {
+    let value = value.trim();
+    let (number, unit) = match value.char_indices().last() {
+        Some((i, 'k' | 'K')) => (&value[..i], 1 << 10),
+        Some((i, 'm' | 'M')) => (&value[..i], 1 << 20),
+        Some((i, 'g' | 'G')) => (&value[..i], 1 << 30),
+        _ => (value, 1),
+    };
+
+    number
+        .trim()
+        .parse::<u64>()
+        .ok()
+        .and_then(|n| n.checked_mul(unit))
+        .ok_or_else(|| {
+            "Size should be a number of bytes, optionally followed by K, M or G.".to_owned()
+        })
+}
+
+/// Format a duration with its biggest unit, like `3 day(s)`.
+pub fn format_age(age: Duration) -> String {
+    let secs = age.as_secs();
+    match secs {
+        0..60 => format!("{secs} second(s)"),
+        60..3600 => format!("{} minute(s)", secs / 60),
+        3600..86400 => format!("{} hour(s)", secs / 3600),
+        _ => format!("{} day(s)", secs / 86400),
+    }
+}
+
+/// Resolve the output path, if `output` is a dir the file name of `input`
+/// with the new `extension` is used inside it, if its `None` the output
+/// will be next to `input`.
+pub fn resolve_output(output: Option<PathBuf>, input: &Path, extension: &str) -> PathBuf {
+    match output {
+        Some(output) if output.is_dir() => match input.file_name() {
+            Some(name) => output.join(Path::new(name).with_extension(extension)),
+            None => output,
+        },
+        Some(output) => output,
+        None => input.with_extension(extension),
+    }
+}
+
+/// Returns `true` if `a` and `b` are paths of the same existing file.
+pub fn is_same_file(a: &Path, b: &Path) -> bool {
+    match (fs::canonicalize(a), fs::canonicalize(b)) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => false,
+    }
+}
+
+/// Levenshtein distance between two strings, case insensitive.
+pub fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.to_lowercase().chars().collect();
+    let b: Vec<char> = b.to_lowercase().chars().collect();
+
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    let mut current = vec![0; b.len() + 1];
+
+    for (i, ca) in a.iter().enumerate() {
+        current[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let cost = usize::from(ca != cb);
+            current[j + 1] = (prev[j] + cost).min(prev[j + 1] + 1).min(current[j] + 1);
+        }
+        std::mem::swap(&mut prev, &mut current);
+    }
+
+    prev[b.len()]
+}
+
+/// Find the closest name to `input` in `names`, if its close enough to be a typo.
+pub fn closest_name<'a>(input: &str, names: impl IntoIterator<Item = &'a str>) -> Option<&'a str> {
+    let max_distance = (input.chars().count() / 3).max(2);
+
+    names
+        .into_iter()
+        .map(|n| (edit_distance(input, n), n))
+        .filter(|(d, _)| *d <= max_distance)
+        .min_by_key(|(d, _)| *d)
+        .map(|(_, n)| n)
+}
+
+/// Read a file list for `--file-list`, one path per line. Empty lines and
+/// lines starting with `#` are skipped.
+pub fn read_file_list(path: &Path) -> std::io::Result<Vec<PathBuf>> {
+    Ok(fs::read_to_string(path)?
+        .lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty() && !l.starts_with('#'))
+        .map(PathBuf::from)
+        .collect())
+}
+
+/// List all the files inside `dir` and its sub folders, relative to `dir`.
+pub fn list_files(dir: &Path) -> std::io::Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    let mut dirs = vec![dir.to_path_buf()];
+
+    while let Some(current) = dirs.pop() {
+        for entry in std::fs::read_dir(current)? {
+            let path = entry?.path();
+            if path.is_dir() {
+                dirs.push(path);
+            } else if let Ok(relative) = path.strip_prefix(dir) {
+                files.push(relative.to_path_buf());
+            }
+        }
+    }
+
+    files.sort();
+    Ok(files)
+}
+
+/// Extension of the checksum file written next to an output.
+pub const CHECKSUMS_EXT: &str = "sha256";
+
+/// Hash the content of the file at `path` with SHA-256, as lowercase hex.
+pub fn sha256(path: &Path) -> std::io::Result<String> {
+    let mut hasher = Sha256::new();
+    std::io::copy(&mut BufReader::new(File::open(path)?), &mut hasher)?;
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+/// Write the SHA-256 of `files` to `<output>.sha256`, in the format of
+/// `sha256sum` so it can check it too. Every file inside a folder of `files`
+/// is hashed and the files that don't exist are skipped. The paths are
+/// relative to the folder of the checksum file, returns its path.
+pub fn write_checksums(output: &Path, files: &[PathBuf]) -> std::io::Result<PathBuf> {
+    let mut hashed = Vec::new();
+    for file in files {
+        if file.is_dir() {
+            hashed.extend(list_files(file)?.into_iter().map(|f| file.join(f)));
+        } else if file.is_file() {
+            hashed.push(file.clone());
+        }
+    }
+
+    let mut checksums = output.as_os_str().to_owned();
+    checksums.push(".");
+    checksums.push(CHECKSUMS_EXT);
+    let checksums = PathBuf::from(checksums);
+
+    let base = checksums.parent().unwrap_or(Path::new(""));
+    let mut writer = BufWriter::new(File::create(&checksums)?);
+    for file in hashed {
+        let name = file.strip_prefix(base).unwrap_or(&file);
+        writeln!(writer, "{}  {}", sha256(&file)?, name.display())?;
+    }
+    writer.flush()?;
+
+    Ok(checksums)
+}
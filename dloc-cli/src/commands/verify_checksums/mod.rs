@@ -0,0 +1,54 @@
+use std::{fs, path::PathBuf};
+
+use anyhow::bail;
+use clap::{Parser, ValueHint};
+use dloc_core::logger::Logger;
+
+use crate::logger::CliLogger;
+
+use super::utils;
+
+#[derive(Debug, Parser)]
+#[command(arg_required_else_help = true)]
+pub struct VerifyChecksums {
+    /// Checksum file written with --checksums, or any file in the format of sha256sum
+    #[arg(value_hint = ValueHint::FilePath, value_parser = utils::is_file)]
+    checksum_file: PathBuf,
+}
+
+impl VerifyChecksums {
+    pub fn command(self, mut logger: CliLogger) -> anyhow::Result<()> {
+        let base = self.checksum_file.parent().unwrap_or(&self.checksum_file);
+        let content = fs::read_to_string(&self.checksum_file)?;
+
+        let mut checked = 0;
+        let mut failed = 0;
+        for (i, line) in content.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            // sha256sum marks files hashed in binary mode with a '*'
+            let Some((hash, name)) = line.split_once("  ").or_else(|| line.split_once(" *")) else {
+                bail!("Line {} of the checksum file isn't valid: {line}", i + 1);
+            };
+
+            checked += 1;
+            let file = base.join(name);
+            if !file.is_file() {
+                logger.error(format!("{name} not found."));
+                failed += 1;
+            } else if !utils::sha256(&file)?.eq_ignore_ascii_case(hash) {
+                logger.error(format!("{name} doesn't match its checksum."));
+                failed += 1;
+            }
+        }
+
+        if failed != 0 {
+            bail!("{failed} of {checked} file(s) don't match their checksum.");
+        }
+
+        logger.good(format!("All {checked} file(s) match their checksum."));
+
+        Ok(())
+    }
+}
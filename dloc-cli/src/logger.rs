@@ -1,13 +1,26 @@
-use std::io::{stderr, stdout, BufWriter, StderrLock, StdoutLock, Write};
+use std::{
+    io::{stderr, stdout, BufWriter, StderrLock, StdoutLock, Write},
+    sync::atomic::{AtomicUsize, Ordering},
+};
 
 use dloc_core::logger::*;
 
 use crate::LogLevel;
 
+/// Number of warnings logged by every [`CliLogger`], even the hidden ones.
+static WARNINGS: AtomicUsize = AtomicUsize::new(0);
+
+/// Returns the number of warnings logged since the program started.
+pub fn warning_count() -> usize {
+    WARNINGS.load(Ordering::Relaxed)
+}
+
 pub struct CliLogger {
     pub stdout: BufWriter<StdoutLock<'static>>,
     stderr: BufWriter<StderrLock<'static>>,
     log_level: LogLevel,
+    /// Log warnings as errors
+    strict: bool,
 }
 
 pub struct CliProgress<'a> {
@@ -20,11 +33,12 @@ pub struct CliProgress<'a> {
 }
 
 impl CliLogger {
-    pub fn new(log_level: LogLevel) -> Self {
+    pub fn new(log_level: LogLevel, strict: bool) -> Self {
         Self {
             stdout: BufWriter::with_capacity(5, stdout().lock()),
             stderr: BufWriter::with_capacity(5, stderr().lock()),
             log_level,
+            strict,
         }
     }
 }
@@ -34,14 +48,19 @@ impl Logger for CliLogger {
 
     fn create_progress(&mut self, title: String, len: usize) -> Self::Progress<'_> {
         write!(&mut self.stdout, "\u{001B}[?25l").expect("Can't write into stdout"); // hide console cursor
-        Self::Progress {
+        let mut progress = Self::Progress {
             stdout: &mut self.stdout,
             log_level: self.log_level,
             finished: (self.log_level == LogLevel::A).then_some(false),
             len,
             current: 0,
             title,
+        };
+        // nothing will be added to an empty progress, so show it as done right away
+        if len == 0 && progress.log_level == LogLevel::A {
+            progress.print_progress();
         }
+        progress
     }
 
     fn info(&mut self, str: impl AsRef<str>) {
@@ -63,6 +82,11 @@ impl Logger for CliLogger {
     }
 
     fn warn(&mut self, str: impl AsRef<str>) {
+        WARNINGS.fetch_add(1, Ordering::Relaxed);
+        if self.strict {
+            return self.error(str);
+        }
+
         if matches!(self.log_level, LogLevel::E | LogLevel::N) {
             return;
         }
@@ -106,8 +130,13 @@ impl<'a> CliProgress<'a> {
     const BAR_LEN: usize = 50;
 
     fn print_progress(&mut self) {
-        let filled_up_length = Self::BAR_LEN * self.current / self.len;
-        let percentage = 100 * self.current / self.len;
+        let (filled_up_length, percentage) = match self.len {
+            0 => (Self::BAR_LEN, 100),
+            len => {
+                let current = self.current.min(len);
+                (Self::BAR_LEN * current / len, 100 * current / len)
+            }
+        };
 
         write!(
             &mut self.stdout,
@@ -1,7 +1,14 @@
+use std::process::ExitCode;
+
+use anyhow::bail;
 use clap::Parser;
+use dloc_core::{
+    games::{ds::DSError, hzd::HZDError, InvalidLanguage},
+    Error,
+};
 
 use commands::{Commands, Game, LogLevel, SerializeType};
-use logger::CliLogger;
+use logger::{warning_count, CliLogger};
 
 mod commands;
 mod logger;
@@ -14,21 +21,183 @@ pub struct Cli {
     /// Input game
     #[arg(value_enum, default_value_t = Game::default())]
     game: Game,
-    /// What format to use to export/import strings
-    #[arg(short, long, value_enum, global = true, default_value_t = SerializeType::default())]
-    format: SerializeType,
+    /// What format to use to export/import strings [default: json], on import
+    /// if its not given the format is detected from the exported file extension
+    #[arg(short, long, value_enum, global = true)]
+    format: Option<SerializeType>,
     /// Global program log level
     #[arg(short = 'L', long, name = "LEVEL", value_enum, global = true, default_value_t = LogLevel::default())]
     log_level: LogLevel,
+    /// Log warnings as errors and fail at the end if there was any of them
+    #[arg(long, visible_alias = "werror", global = true)]
+    strict: bool,
 }
 
 impl Cli {
     fn run(self) -> anyhow::Result<()> {
-        let logger = CliLogger::new(self.log_level);
-        self.command.command(self.game, self.format, logger)
+        let logger = CliLogger::new(self.log_level, self.strict);
+        self.command.command(self.game, self.format, logger)?;
+
+        let warnings = warning_count();
+        if self.strict && warnings != 0 {
+            bail!("{warnings} warnings were treated as errors because of --strict.");
+        }
+
+        Ok(())
+    }
+}
+
+/// Exit code when no localization was found in the input.
+const EXIT_NO_LOCALIZATION: u8 = 2;
+/// Exit code when the languages or their strings don't match the core.
+const EXIT_LANGUAGE_MISMATCH: u8 = 3;
+/// Exit code when a core or an exported file can't be parsed.
+const EXIT_PARSE_ERROR: u8 = 4;
+
+/// Map the error to an exit code so scripts can tell failures apart, every
+/// error that doesn't fit in a category exits with `1`.
+fn exit_code(error: &anyhow::Error) -> u8 {
+    if let Some(error) = error.downcast_ref::<Error>() {
+        return match error {
+            Error::NoLocalResource | Error::NoFileFound(_) => EXIT_NO_LOCALIZATION,
+            Error::LanguageCountMismatch { .. } => EXIT_LANGUAGE_MISMATCH,
+            Error::GameDeserialize(e) if is_mismatch(e.as_ref()) => EXIT_LANGUAGE_MISMATCH,
+            Error::BinRw(_)
+            | Error::InvalidStringEncoding { .. }
+            | Error::BigEndian
+            | Error::SerdeJson(_)
+            | Error::SerdeYaml(_)
+            | Error::DeserializeError(_)
+            | Error::GameDeserialize(_)
+            | Error::InvalidPatch(_) => EXIT_PARSE_ERROR,
+            _ => 1,
+        };
+    }
+
+    if error.is::<InvalidLanguage>() || is_mismatch(error.as_ref()) {
+        return EXIT_LANGUAGE_MISMATCH;
     }
+
+    1
+}
+
+/// Returns `true` if `error` is a game error about the strings of a language
+/// not matching the ones of the core.
+fn is_mismatch(error: &(dyn std::error::Error + 'static)) -> bool {
+    matches!(
+        error.downcast_ref(),
+        Some(
+            HZDError::CutsceneLinesDoesntMatch { .. }
+                | HZDError::LanguageStringCountDoesntMatch { .. }
+        )
+    ) || matches!(
+        error.downcast_ref(),
+        Some(DSError::LanguageStringCountDoesntMatch { .. })
+    )
 }
 
-fn main() -> anyhow::Result<()> {
-    Cli::parse().run()
+fn main() -> ExitCode {
+    match Cli::parse().run() {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("Error: {e:?}");
+            if matches!(e.downcast_ref(), Some(Error::NoLocalResource)) {
+                eprintln!(
+                    "Hint: scan-unknown lists the resources of the core that dloc doesn't support."
+                );
+            }
+            ExitCode::from(exit_code(&e))
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn parse(args: &str) -> Result<Cli, clap::Error> {
+        let dir = std::env::temp_dir();
+        let args = args.replace("DIR", &dir.to_string_lossy());
+        Cli::try_parse_from(["dloc"].into_iter().chain(args.split(' ')))
+    }
+
+    #[test]
+    fn exit_code_of_each_error_class() {
+        use dloc_core::games::{ds, hzd};
+
+        let mismatch = hzd::HZDError::CutsceneLinesDoesntMatch {
+            lang: hzd::Language::English,
+            expected: 2,
+            got: 1,
+        };
+        let count = || ds::DSError::LanguageStringCountDoesntMatch {
+            lang: ds::Language::English,
+            expected: 2,
+            got: 1,
+        };
+        let cases = [
+            (
+                anyhow::Error::from(Error::NoLocalResource),
+                EXIT_NO_LOCALIZATION,
+            ),
+            (Error::NoFileFound("json").into(), EXIT_NO_LOCALIZATION),
+            (
+                Error::GameDeserialize(Box::new(mismatch)).into(),
+                EXIT_LANGUAGE_MISMATCH,
+            ),
+            (
+                Error::GameDeserialize(Box::new(count())).into(),
+                EXIT_LANGUAGE_MISMATCH,
+            ),
+            (count().into(), EXIT_LANGUAGE_MISMATCH),
+            (
+                InvalidLanguage("Klingon".to_owned()).into(),
+                EXIT_LANGUAGE_MISMATCH,
+            ),
+            (
+                Error::GameDeserialize(Box::new(hzd::HZDError::InvalidLocalResourceIdx {
+                    max: 1,
+                    got: 2,
+                }))
+                .into(),
+                EXIT_PARSE_ERROR,
+            ),
+            (
+                Error::DeserializeError("bad".to_owned()).into(),
+                EXIT_PARSE_ERROR,
+            ),
+            (Error::BigEndian.into(), EXIT_PARSE_ERROR),
+            (anyhow::anyhow!("anything else"), 1),
+        ];
+        for (error, code) in cases {
+            assert_eq!(exit_code(&error), code, "{error}");
+        }
+
+        // an import that doesn't match the core keeps the error of the game
+        let mut local = hzd::HZDLocal::synthetic(2).unwrap();
+        let result = local.set_language_strings(hzd::Language::English, Vec::new());
+        assert_eq!(
+            exit_code(&result.unwrap_err().into()),
+            EXIT_LANGUAGE_MISMATCH
+        );
+    }
+
+    #[test]
+    fn no_deinfo_rejects_the_resource_filters() {
+        assert!(parse("hzd group DIR -f txt export --split --no-deinfo").is_ok());
+        for filter in [
+            "--only cutscene",
+            "--filter a",
+            "--uuid 000102030405060708090a0b0c0d0e0f",
+            "--sort-resources",
+        ] {
+            let args = format!("hzd group DIR -f txt export --split --no-deinfo {filter}");
+            let error = parse(&args).unwrap_err();
+            assert_eq!(
+                error.kind(),
+                clap::error::ErrorKind::ArgumentConflict,
+                "{filter}"
+            );
+        }
+    }
 }
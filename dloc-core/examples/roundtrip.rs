@@ -0,0 +1,86 @@
+//! Load a core file, export every string to json in memory, import the json
+//! back into a fresh copy of the core and check both write the same bytes.
+//!
+//! ```sh
+//! cargo run --example roundtrip -- path/to/file.core
+//! ```
+
+use std::{env, fs, io::Cursor};
+
+use dloc_core::{
+    games::{
+        detect::{self, GameDetection},
+        ds::{self, DSLocal},
+        hzd::{self, HZDLocal},
+    },
+    serialize::{ImportOptions, SerializeData, SerializeType},
+    DResult,
+};
+
+fn main() -> DResult<()> {
+    let Some(path) = env::args().nth(1) else {
+        eprintln!("Usage: roundtrip <core file>");
+        return Ok(());
+    };
+
+    let data = fs::read(path)?;
+    let json = SerializeType::Json {
+        indent: None,
+        omit_empty: false,
+    };
+
+    let (exported, written, original) = match detect::detect_game(&mut Cursor::new(&data))? {
+        GameDetection::Hzd => {
+            let core = HZDLocal::new(Cursor::new(&data))?;
+            let mut exported = Vec::new();
+            core.serialize_to_writer(
+                &mut exported,
+                hzd::Language::ALL_VARIANTS,
+                json.clone(),
+                &hzd::ExportFilters::default(),
+            )?;
+
+            let mut copy = HZDLocal::new(Cursor::new(&data))?;
+            let summary = copy.deserialize_and_update_from_reader(
+                exported.as_slice(),
+                json,
+                ImportOptions::default(),
+            )?;
+            println!("Import summary: {summary}.");
+
+            (exported.len(), copy.to_bytes()?, core.to_bytes()?)
+        }
+        GameDetection::Ds => {
+            let core = DSLocal::new(Cursor::new(&data))?;
+            let mut exported = Vec::new();
+            core.serialize_to_writer(
+                &mut exported,
+                ds::Language::ALL_VARIANTS,
+                json.clone(),
+                &ds::ExportFilters::default(),
+            )?;
+
+            let mut copy = DSLocal::new(Cursor::new(&data))?;
+            let summary = copy.deserialize_and_update_from_reader(
+                exported.as_slice(),
+                json,
+                ImportOptions::default(),
+            )?;
+            println!("Import summary: {summary}.");
+
+            (exported.len(), copy.to_bytes()?, core.to_bytes()?)
+        }
+        detection => {
+            eprintln!("Can't round trip the core, detected game: {detection:?}");
+            return Ok(());
+        }
+    };
+
+    assert_eq!(written, original, "the round trip changed the core");
+    println!(
+        "Round trip finished, {exported} bytes of json and {} bytes of core.",
+        written.len()
+    );
+
+    Ok(())
+}
@@ -0,0 +1,18 @@
+//! Feed random bytes to both game parsers, they should always return
+//! an error on invalid input and never panic or try to allocate absurd
+//! amount of memory.
+//!
+//! Run with `cargo +nightly fuzz run parse_core -- -rss_limit_mb=512`
+//! from inside the `dloc-core` folder.
+
+#![no_main]
+
+use std::io::Cursor;
+
+use dloc_core::games::{ds::DSLocal, hzd::HZDLocal};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let _ = HZDLocal::new(Cursor::new(data));
+    let _ = DSLocal::new(Cursor::new(data));
+});
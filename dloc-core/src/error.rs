@@ -13,7 +13,10 @@ pub enum Error {
     StripPrefix(#[from] std::path::StripPrefixError),
 
     #[error(transparent)]
-    BinRw(#[from] binrw::Error),
+    BinRw(binrw::Error),
+
+    #[error("Invalid {kind} string at byte {position:#x} of the core, it may be from another region or corrupted")]
+    InvalidStringEncoding { position: u64, kind: StringEncoding },
 
     #[error(transparent)]
     SerdeJson(#[from] serde_json::Error),
@@ -22,13 +25,108 @@ pub enum Error {
     #[error(transparent)]
     SerdeYaml(#[from] serde_yaml::Error),
 
+    #[cfg(feature = "serialize")]
+    #[error("Serialize error: {0}")]
+    SerializeError(String),
+
+    #[cfg(feature = "serialize")]
+    #[error("Serialize type {0:?} isn't supported for this operation")]
+    UnsupportedSerializeType(crate::serialize::SerializeType),
+
     #[cfg(feature = "serialize")]
     #[error("Deserialize error: {0}")]
     DeserializeError(String),
 
+    /// The error of a game when an import doesn't match its core, downcast
+    /// it to [`HZDError`](crate::games::hzd::HZDError) or
+    /// [`DSError`](crate::games::ds::DSError) to tell what didn't match.
+    #[cfg(feature = "serialize")]
+    #[error("Deserialize error: {0}")]
+    GameDeserialize(Box<dyn std::error::Error + Send + Sync>),
+
+    #[cfg(feature = "serialize")]
+    #[error(".{} exports need dloc to be built with the compression feature", .0.extension())]
+    CompressionDisabled(crate::serialize::Compression),
+
+    #[cfg(feature = "serialize")]
+    #[error("The path {0:?} isn't valid UTF-8 so it can't be written inside a single export, rename it or export each core to its own file")]
+    NonUtf8Path(std::path::PathBuf),
+
+    #[cfg(feature = "sqlite")]
+    #[error(transparent)]
+    Sqlite(#[from] rusqlite::Error),
+
+    #[cfg(feature = "regex")]
+    #[error(transparent)]
+    Regex(#[from] regex::Error),
+
     #[error("No \"{0}\" file found")]
     NoFileFound(&'static str),
 
+    #[error("{0:?} from the file list isn't a file inside the input folder")]
+    ListedFileNotFound(std::path::PathBuf),
+
     #[error("No valid local resource found inside the input")]
     NoLocalResource,
+
+    #[error("Cutscene resource {index} declares {declared} languages but dloc expects {expected}, the core may be from an unsupported game version")]
+    LanguageCountMismatch {
+        index: usize,
+        declared: u32,
+        expected: u32,
+    },
+
+    #[error("Verifying the written core failed, {0}")]
+    VerifyFailed(String),
+
+    #[error("Invalid patch, {0}")]
+    InvalidPatch(String),
+
+    #[error("The input looks like a big-endian core (chunk magics are byte-swapped), only little-endian cores are supported")]
+    BigEndian,
+}
+
+/// Encoding of the strings inside a core.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StringEncoding {
+    Utf8,
+    Utf16,
+}
+
+impl std::fmt::Display for StringEncoding {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Utf8 => f.write_str("UTF-8"),
+            Self::Utf16 => f.write_str("UTF-16"),
+        }
+    }
+}
+
+/// A string that failed to decode, kept inside a [`binrw::Error::Custom`]
+/// until the error is turned into [`Error::InvalidStringEncoding`].
+#[derive(Debug, Error)]
+#[error("Invalid {kind} string at byte {position:#x}")]
+pub(crate) struct InvalidString {
+    pub position: u64,
+    pub kind: StringEncoding,
+}
+
+impl From<binrw::Error> for Error {
+    fn from(error: binrw::Error) -> Self {
+        match find_invalid_string(&error) {
+            Some(&InvalidString { position, kind }) => {
+                Self::InvalidStringEncoding { position, kind }
+            }
+            None => Self::BinRw(error),
+        }
+    }
+}
+
+/// Look for an [`InvalidString`] in `error` and the errors it wraps.
+fn find_invalid_string(error: &binrw::Error) -> Option<&InvalidString> {
+    match error {
+        binrw::Error::Custom { err, .. } => err.downcast_ref(),
+        binrw::Error::Backtrace(backtrace) => find_invalid_string(&backtrace.error),
+        _ => None,
+    }
 }
@@ -1,20 +1,82 @@
-use binrw::{binrw, BinRead, BinWrite};
+use std::{
+    io::{Cursor, Read, Seek, SeekFrom},
+    mem::size_of,
+};
+
+use binrw::{binrw, BinRead, BinResult, BinWrite};
+
+use super::ParseWarning;
 
 /// Represents a chunk of binary data with a magic number, size, and variant
 /// payload. Used for serialized game data.
+///
+/// The chunks of a core are written back to back without any alignment or
+/// padding between them, the same as the game files. The `size` of each chunk
+/// is the exact size of its payload, so padding in the game files would be
+/// read as the magic of the next chunk and those files couldn't be parsed.
 #[binrw]
 #[brw(little)]
-#[derive(Debug, Hash)]
+#[derive(Debug, Clone, Hash)]
 pub struct Chunk<V>
 where
     for<'a> V: RuntimeSize + BinRead<Args<'a> = (u64, u32)> + BinWrite<Args<'a> = ()>,
 {
+    /// The position of the chunk in the core it was read from.
+    #[br(parse_with = current_position)]
+    #[bw(ignore)]
+    offset: u64,
     magic: u64,
-    #[br(temp)]
+    #[br(temp, parse_with = read_checked_count, args(1))]
     #[bw(calc = variant.rt_size())]
     size: u32,
     #[br(args(magic, size))]
     pub variant: V,
+    /// The size that was read from the core, written chunks always use the
+    /// size of their variant instead.
+    #[br(calc = size)]
+    #[bw(ignore)]
+    declared_size: u32,
+}
+
+impl<V> Chunk<V>
+where
+    for<'a> V: RuntimeSize + BinRead<Args<'a> = (u64, u32)> + BinWrite<Args<'a> = ()>,
+{
+    pub(crate) const fn magic(&self) -> u64 {
+        self.magic
+    }
+
+    /// Byte offset of the chunk in the core it was read from, it can differ
+    /// from where the chunk will be written after an import changed the size
+    /// of the chunks before it.
+    pub const fn offset(&self) -> u64 {
+        self.offset
+    }
+
+    /// Replace the variant of this chunk with a copy of the one of `other`,
+    /// the offset and declared size are kept so they still describe the
+    /// core this chunk was read from.
+    pub(crate) fn copy_from(&mut self, other: &Self)
+    where
+        V: Clone,
+    {
+        self.variant = other.variant.clone();
+    }
+
+    /// Size of the chunk payload as declared in the core it was read from,
+    /// it can differ from [`RuntimeSize::rt_size`] of the variant after an
+    /// import changed its strings.
+    pub const fn declared_size(&self) -> u32 {
+        self.declared_size
+    }
+}
+
+/// Returns the byte offset of each chunk in the core it was read from.
+pub(crate) fn chunk_offsets<V>(chunks: &[Chunk<V>]) -> Vec<u64>
+where
+    for<'a> V: RuntimeSize + BinRead<Args<'a> = (u64, u32)> + BinWrite<Args<'a> = ()>,
+{
+    chunks.iter().map(Chunk::offset).collect()
 }
 
 /// A helper trait to get the size of a object in runtime
@@ -22,3 +84,147 @@ pub trait RuntimeSize {
     /// size of the whole variant
     fn rt_size(&self) -> u32;
 }
+
+/// Read a `u32` count and make sure the reader still has at least
+/// `count * item_size` bytes left, so a malformed count can't make us
+/// allocate a huge buffer for a tiny file.
+#[binrw::parser(reader, endian)]
+pub(crate) fn read_checked_count(item_size: u64) -> BinResult<u32> {
+    let count = u32::read_options(reader, endian, ())?;
+
+    let pos = reader.stream_position()?;
+    let end = reader.seek(SeekFrom::End(0))?;
+    reader.seek(SeekFrom::Start(pos))?;
+
+    let remaining = end.saturating_sub(pos);
+    let needed = count as u64 * item_size;
+    if needed > remaining {
+        return Err(binrw::Error::AssertFail {
+            pos: pos.saturating_sub(4),
+            message: format!(
+                "Invalid core file, count {count} needs at least {needed} bytes but only {remaining} bytes are left in input"
+            ),
+        });
+    }
+
+    Ok(count)
+}
+
+/// Returns the current position of the reader without reading anything,
+/// used to remember where a chunk or its data starts.
+#[binrw::parser(reader)]
+pub(crate) fn current_position() -> BinResult<u64> {
+    Ok(reader.stream_position()?)
+}
+
+/// Read the bytes left between the current position and the end of a chunk
+/// with `size` bytes of data starting at `start`.
+///
+/// A resource may have more data than dloc knows about, they are kept so
+/// the chunk is written back with the same size.
+#[binrw::parser(reader)]
+pub(crate) fn read_trailing(start: u64, size: u32) -> BinResult<Vec<u8>> {
+    let pos = reader.stream_position()?;
+    let end = start + size as u64;
+    let Some(len) = end.checked_sub(pos) else {
+        return Err(binrw::Error::AssertFail {
+            pos,
+            message: format!(
+                "Invalid core file, resource should be {size} bytes but {} bytes were read",
+                pos - start
+            ),
+        });
+    };
+
+    let mut trailing = Vec::new();
+    reader.take(len).read_to_end(&mut trailing)?;
+    if trailing.len() as u64 != len {
+        return Err(binrw::Error::Io(std::io::ErrorKind::UnexpectedEof.into()));
+    }
+    Ok(trailing)
+}
+
+/// Size of the magic and size fields at the start of every chunk.
+const HEADER_SIZE: usize = size_of::<u64>() + size_of::<u32>();
+
+/// Read the chunks of a damaged core in `data`, when a chunk fails to parse
+/// the next chunk header with one of the `known_magics` is searched byte by
+/// byte and everything in between is kept as a single raw chunk made by
+/// `raw`, with the magic and size it starts with.
+///
+/// Returns the chunks and a [`ParseWarning::Damaged`] for every skipped
+/// region, a region smaller than a chunk header can't be kept and is dropped.
+pub(crate) fn read_chunks_repairing<V, L>(
+    data: &[u8],
+    known_magics: &[u64],
+    raw: impl Fn(Vec<u8>) -> V,
+) -> (Vec<Chunk<V>>, Vec<ParseWarning<L>>)
+where
+    for<'a> V: RuntimeSize + BinRead<Args<'a> = (u64, u32)> + BinWrite<Args<'a> = ()>,
+{
+    let mut reader = Cursor::new(data);
+    let mut chunks = Vec::new();
+    let mut warnings = Vec::new();
+
+    let mut pos = 0;
+    while pos < data.len() {
+        let error = match read_chunk_at(&mut reader, pos) {
+            Ok((chunk, end)) => {
+                chunks.push(chunk);
+                pos = end;
+                continue;
+            }
+            Err(e) => e,
+        };
+
+        let next = (pos + 1..data.len())
+            .find(|&p| is_chunk_header(data, p, known_magics))
+            .unwrap_or(data.len());
+
+        let region = &data[pos..next];
+        let kept = region.len() >= HEADER_SIZE;
+        if kept {
+            let (header, payload) = region.split_at(HEADER_SIZE);
+            chunks.push(Chunk {
+                offset: pos as u64,
+                magic: u64::from_le_bytes(header[..8].try_into().unwrap_or_default()),
+                variant: raw(payload.to_vec()),
+                declared_size: u32::from_le_bytes(header[8..].try_into().unwrap_or_default()),
+            });
+        }
+        warnings.push(ParseWarning::Damaged {
+            offset: pos as u64,
+            len: region.len() as u64,
+            kept,
+            reason: error.root_cause().to_string(),
+        });
+        pos = next;
+    }
+
+    (chunks, warnings)
+}
+
+/// Returns `true` if `data` has the header of a chunk at `pos`, with one of
+/// the `known_magics` and a size that fits in the rest of `data`. The chunk
+/// itself isn't parsed, a header that is only there by chance fails to parse
+/// in the next round of [`read_chunks_repairing`] and is skipped again.
+fn is_chunk_header(data: &[u8], pos: usize, known_magics: &[u64]) -> bool {
+    let Some(header) = data.get(pos..pos + HEADER_SIZE) else {
+        return false;
+    };
+    let (magic, size) = header.split_at(size_of::<u64>());
+    let magic = u64::from_le_bytes(magic.try_into().unwrap_or_default());
+    let size = u32::from_le_bytes(size.try_into().unwrap_or_default());
+    known_magics.contains(&magic) && pos + HEADER_SIZE + size as usize <= data.len()
+}
+
+/// Read a single chunk starting at `pos` and return it with the position
+/// right after it.
+fn read_chunk_at<V>(reader: &mut Cursor<&[u8]>, pos: usize) -> BinResult<(Chunk<V>, usize)>
+where
+    for<'a> V: RuntimeSize + BinRead<Args<'a> = (u64, u32)> + BinWrite<Args<'a> = ()>,
+{
+    reader.seek(SeekFrom::Start(pos as u64))?;
+    let chunk = Chunk::read(reader)?;
+    Ok((chunk, reader.stream_position()? as usize))
+}
@@ -1,14 +1,23 @@
 //! Functions to try to detect what game is the input core file from
 
-use std::io::{Read, Seek, SeekFrom};
+use std::{
+    collections::BTreeMap,
+    io::{Read, Seek, SeekFrom},
+    path::{Path, PathBuf},
+};
 
-use binrw::BinRead;
+use binrw::{BinRead, Endian};
 
 use crate::{
     games::{ds, hzd},
+    logger::{Logger, Progress},
+    utils::EnumKey,
     DResult, Error,
 };
 
+/// Bytes scanned for each step of the detection progress.
+const PROGRESS_STEP: u64 = 1 << 20;
+
 /// An enum representing the different games that can be detected.
 ///
 /// Variants:
@@ -29,6 +38,36 @@ pub enum GameDetection {
     Unknown,
 }
 
+/// A chunk magic number that dloc knows about.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KnownMagic {
+    pub magic: u64,
+    /// Name of the game that use this magic.
+    pub game: &'static str,
+    /// What kind of resource the chunk holds.
+    pub resource: &'static str,
+}
+
+/// All the chunk magic numbers used in game detection, they are the only
+/// resources of each game with translatable strings inside them.
+pub const KNOWN_MAGICS: [KnownMagic; 3] = [
+    KnownMagic {
+        magic: hzd::LOCALIZED_MAGIC,
+        game: "Horizon Zero Dawn",
+        resource: "Localized",
+    },
+    KnownMagic {
+        magic: hzd::CUTSCENE_MAGIC,
+        game: "Horizon Zero Dawn",
+        resource: "Cutscene",
+    },
+    KnownMagic {
+        magic: ds::LOCALIZED_MAGIC,
+        game: "Death Stranding",
+        resource: "Localized",
+    },
+];
+
 /// Detects which game a core file is from by checking magic numbers.
 ///
 /// Reads magic numbers from the core file. Increments counters when known magic numbers are found.
@@ -36,12 +75,111 @@ pub enum GameDetection {
 ///
 /// Returns a [`GameDetection`] enum variant indicating which game was detected,
 /// [`GameDetection::Mixed`] if multiple games were detected, or [`GameDetection::Unknown`] if no known games were detected.
+/// If no known magic is found but the core [looks big-endian](is_big_endian) [`Error::BigEndian`] is returned.
 pub fn detect_game<R: Read + Seek>(reader: &mut R) -> DResult<GameDetection> {
+    detect(reader, |_| ())
+}
+
+/// Same as [`detect_game`] but report the scanned bytes to `logger` as a
+/// progress with a step for each MiB, cores smaller than that don't make
+/// any progress as they are scanned instantly.
+pub fn detect_game_with_progress<R: Read + Seek, L: Logger>(
+    reader: &mut R,
+    logger: &mut L,
+) -> DResult<GameDetection> {
+    let start = reader.stream_position()?;
+    let end = reader.seek(SeekFrom::End(0))?;
+    reader.seek(SeekFrom::Start(start))?;
+
+    let steps = end.saturating_sub(start) / PROGRESS_STEP;
+    if steps == 0 {
+        return detect_game(reader);
+    }
+
+    let mut progress = logger.create_progress("Detecting game".to_owned(), steps as usize);
+    let mut done = 0;
+    let detection = detect(reader, |position| {
+        while done < steps && (position - start) / PROGRESS_STEP > done {
+            progress.add_progress();
+            done += 1;
+        }
+    });
+    progress.end_progress();
+
+    detection
+}
+
+/// Detect the game, calling `on_chunk` with the position after each chunk.
+fn detect<R: Read + Seek>(reader: &mut R, on_chunk: impl FnMut(u64)) -> DResult<GameDetection> {
+    let start = reader.stream_position()?;
+    let (hzd, ds) = count_magics(reader, Endian::Little, on_chunk)?;
+
+    if hzd == 0 && ds == 0 {
+        reader.seek(SeekFrom::Start(start))?;
+        if is_big_endian(reader)? {
+            return Err(Error::BigEndian);
+        }
+    }
+
+    Ok(match (hzd.eq(&0), ds.eq(&0)) {
+        (true, true) => GameDetection::Unknown,
+        (false, true) => GameDetection::Hzd,
+        (true, false) => GameDetection::Ds,
+        (false, false) => GameDetection::Mixed,
+    })
+}
+
+/// Returns `true` if reading the chunks of the core as big-endian finds any
+/// known magic, this usually means the core is from a big-endian platform.
+pub fn is_big_endian<R: Read + Seek>(reader: &mut R) -> DResult<bool> {
+    let (hzd, ds) = count_magics(reader, Endian::Big, |_| ())?;
+    Ok(hzd != 0 || ds != 0)
+}
+
+/// Turn `error` into [`Error::BigEndian`] if the core starting at `start`
+/// looks big-endian, used when parsing fails to give a clearer error.
+pub(crate) fn explain_parse_error<R: Read + Seek>(
+    reader: &mut R,
+    start: u64,
+    error: Error,
+) -> Error {
+    if reader.seek(SeekFrom::Start(start)).is_ok() && matches!(is_big_endian(reader), Ok(true)) {
+        return Error::BigEndian;
+    }
+    error
+}
+
+/// Count the HZD and DS magics of the chunks, reading them with `endian`.
+fn count_magics<R: Read + Seek>(
+    reader: &mut R,
+    endian: Endian,
+    mut on_chunk: impl FnMut(u64),
+) -> DResult<(usize, usize)> {
     let mut hzd = 0_usize;
     let mut ds = 0_usize;
 
+    walk_chunks(reader, endian, |magic, _, position| {
+        match magic {
+            hzd::LOCALIZED_MAGIC | hzd::CUTSCENE_MAGIC => hzd += 1,
+            ds::LOCALIZED_MAGIC => ds += 1,
+            _ => (),
+        }
+        on_chunk(position);
+    })?;
+
+    Ok((hzd, ds))
+}
+
+/// Read just the header of each chunk and call `on_chunk` with its magic,
+/// its size and the position after it. A header cut by the end of the
+/// core ends the walk.
+fn walk_chunks<R: Read + Seek>(
+    reader: &mut R,
+    endian: Endian,
+    mut on_chunk: impl FnMut(u64, u32, u64),
+) -> DResult<()> {
     loop {
-        let magic = match u64::read_le(reader) {
+        let magic = match u64::read_options(reader, endian, ()) {
             Ok(m) => m,
             Err(e) => {
                 if e.is_eof() {
@@ -52,21 +190,134 @@ pub fn detect_game<R: Read + Seek>(reader: &mut R) -> DResult<GameDetection> {
             }
         };
 
-        let buf_size = u32::read_le(reader)?;
+        let buf_size = match u32::read_options(reader, endian, ()) {
+            Ok(s) => s,
+            Err(e) if e.is_eof() => break,
+            Err(e) => return Err(Error::BinRw(e)),
+        };
 
-        match magic {
-            hzd::LOCALIZED_MAGIC | hzd::CUTSCENE_MAGIC => hzd += 1,
-            ds::LOCALIZED_MAGIC => ds += 1,
-            _ => (),
+        let position = reader.seek(SeekFrom::Current(buf_size as i64))?;
+        on_chunk(magic, buf_size, position);
+    }
+
+    Ok(())
+}
+
+/// How often a chunk magic that isn't in [`KNOWN_MAGICS`] shows up.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct UnknownMagic {
+    /// Number of chunks with the magic.
+    pub chunks: usize,
+    /// Number of cores with at least one chunk with the magic.
+    pub cores: usize,
+    /// Size of all the chunks with the magic, without their headers.
+    pub bytes: u64,
+}
+
+/// Add every chunk of the core that isn't in [`KNOWN_MAGICS`] to `tally`,
+/// keyed by its magic. Only the chunk headers are read so any core can be
+/// scanned, even the ones without a resource dloc supports.
+pub fn tally_unknown_magics<R: Read + Seek>(
+    reader: &mut R,
+    tally: &mut BTreeMap<u64, UnknownMagic>,
+) -> DResult<()> {
+    let mut found = BTreeMap::<u64, UnknownMagic>::new();
+    walk_chunks(reader, Endian::Little, |magic, size, _| {
+        if KNOWN_MAGICS.iter().all(|known| known.magic != magic) {
+            let unknown = found.entry(magic).or_default();
+            unknown.chunks += 1;
+            unknown.bytes += u64::from(size);
         }
+    })?;
 
-        reader.seek(SeekFrom::Current(buf_size as i64))?;
+    // the core is only added once its fully read, so a broken core doesn't
+    // leave half of its chunks in the tally
+    for (magic, unknown) in found {
+        let total = tally.entry(magic).or_default();
+        total.chunks += unknown.chunks;
+        total.cores += 1;
+        total.bytes += unknown.bytes;
     }
 
-    Ok(match (hzd.eq(&0), ds.eq(&0)) {
-        (true, true) => GameDetection::Unknown,
-        (false, true) => GameDetection::Hzd,
-        (true, false) => GameDetection::Ds,
-        (false, false) => GameDetection::Mixed,
-    })
+    Ok(())
+}
+
+/// Language count declared by a HZD cutscene chunk.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CutsceneLanguages {
+    /// Index of the chunk inside the core.
+    pub index: usize,
+    /// Language count declared in the chunk.
+    pub declared: u32,
+}
+
+impl CutsceneLanguages {
+    /// Language count dloc expects every HZD cutscene to have.
+    pub const EXPECTED: u32 = hzd::Language::LEN as u32;
+
+    /// Returns `true` if the declared count is the one dloc expects.
+    pub const fn matches(&self) -> bool {
+        self.declared == Self::EXPECTED
+    }
+}
+
+/// Reads the language count declared by every HZD cutscene chunk without
+/// parsing the rest of it.
+///
+/// Cutscenes that declare a different count than [`CutsceneLanguages::EXPECTED`]
+/// can't be parsed and are kept as raw data, this is a way to find them.
+pub fn hzd_cutscene_languages<R: Read + Seek>(reader: &mut R) -> DResult<Vec<CutsceneLanguages>> {
+    let mut cutscenes = Vec::new();
+
+    for index in 0.. {
+        let magic = match u64::read_le(reader) {
+            Ok(m) => m,
+            Err(e) if e.is_eof() => break,
+            Err(e) => return Err(Error::BinRw(e)),
+        };
+        let size = u32::read_le(reader)?;
+        let start = reader.stream_position()?;
+
+        if magic == hzd::CUTSCENE_MAGIC {
+            if let Some(declared) = read_declared_languages(reader, size)? {
+                cutscenes.push(CutsceneLanguages { index, declared });
+            }
+        }
+
+        reader.seek(SeekFrom::Start(start + size as u64))?;
+    }
+
+    Ok(cutscenes)
+}
+
+/// A cutscene starts with an uuid, a block with its length and then the
+/// language count. [`None`] if the chunk is too small to have them.
+fn read_declared_languages<R: Read + Seek>(reader: &mut R, size: u32) -> DResult<Option<u32>> {
+    const UUID_SIZE: u64 = 16;
+
+    if (size as u64) < UUID_SIZE + 4 {
+        return Ok(None);
+    }
+
+    reader.seek(SeekFrom::Current(UUID_SIZE as i64))?;
+    let block_len = u32::read_le(reader)? as u64 + 4;
+    if UUID_SIZE + 4 + block_len + 4 > size as u64 {
+        return Ok(None);
+    }
+
+    reader.seek(SeekFrom::Current(block_len as i64))?;
+    Ok(Some(u32::read_le(reader)?))
+}
+
+/// Returns the path of the `.core.stream` companion of the given core file if it exists.
+///
+/// Decima keep some of the streamed data (like audio) of a core in this file,
+/// dloc never touch it, so editing things like cutscene timings in the core
+/// may need corresponding edits in the stream.
+pub fn stream_companion<P: AsRef<Path>>(core_path: P) -> Option<PathBuf> {
+    let mut path = core_path.as_ref().as_os_str().to_owned();
+    path.push(".stream");
+
+    let path = PathBuf::from(path);
+    path.is_file().then_some(path)
 }
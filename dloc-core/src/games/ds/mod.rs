@@ -2,20 +2,32 @@
 
 use std::{
     fmt::{Debug, Display},
-    io::{Read, Seek, Write},
+    hash::{Hash, Hasher},
+    io::{Cursor, Read, Seek, Write},
 };
 
 use binrw::{helpers::until_eof, BinWrite, Endian};
 use serde::{Deserialize, Serialize};
 
-use crate::{utils::enum_map, DResult, Error};
+use crate::{
+    games::{
+        self,
+        chunks::{self, chunk_offsets, RuntimeSize},
+        compare_strings,
+        detect::explain_parse_error,
+        synthetic, ParseWarning, ResourceInfo, StringEntry,
+    },
+    patch::{self, Patch},
+    utils::{enum_map, hash_of, types::U8String},
+    DResult, Error,
+};
 
 use structures::Chunk;
 
 pub(crate) use structures::LOCALIZED_MAGIC;
 
-use error::DSError;
-use structures::ChunkVariants;
+pub use error::DSError;
+use structures::{ChunkVariants, LocalGroup, Localized};
 
 mod error;
 #[cfg(feature = "serialize")]
@@ -25,7 +37,7 @@ mod structures;
 enum_map! {
     /// DS supported languages
     #[derive(Serialize, Deserialize)]
-    Language;
+    Language, games::InvalidLanguage;
 
     English = 0,
     French = 1,
@@ -68,11 +80,53 @@ pub struct LocalResource {
     pub strings: FixedMap<String>,
 }
 
+/// Iterate over the strings of a single chunk, see [`DSLocal::strings`].
+fn chunk_strings(
+    index: usize,
+    variant: &ChunkVariants,
+) -> impl Iterator<Item = StringEntry<'_, Language>> {
+    chunk_groups(index, variant, |g| &g.text)
+}
+
+/// Iterate over the `field` of every group of a single chunk as strings.
+fn chunk_groups(
+    index: usize,
+    variant: &ChunkVariants,
+    field: fn(&LocalGroup) -> &U8String,
+) -> impl Iterator<Item = StringEntry<'_, Language>> {
+    let groups = match variant {
+        ChunkVariants::Localized(loc) => Some(&loc.string_groups),
+        ChunkVariants::Others { .. } => None,
+    };
+
+    groups
+        .into_iter()
+        .flat_map(|g| g.iter())
+        .map(move |(language, g)| StringEntry {
+            index,
+            language,
+            sub: 0,
+            string: field(g).as_str(),
+        })
+}
+
+/// The [`games::ExportFilters`] of Death Stranding, it only has one kind of
+/// resource so there is no resource filter.
+pub type ExportFilters = games::ExportFilters<Language>;
+
 /// DSLocal is used to load localization resources from Death Stranding
 /// and store them.
-#[derive(Debug, Hash)]
+#[derive(Debug)]
 pub struct DSLocal {
     chunks: Vec<Chunk>,
+    /// hash of the chunks at load time, used to track modifications
+    load_hash: u64,
+}
+
+impl Hash for DSLocal {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.chunks.hash(state);
+    }
 }
 
 impl DSLocal {
@@ -86,16 +140,127 @@ impl DSLocal {
     /// A [`DResult`] with a [DSLocal] containing the loaded chunks in case of success.
     /// and a [`Error`] on case of failure.
     pub fn new<R: Read + Seek>(mut reader: R) -> DResult<Self> {
-        let chunks: Vec<Chunk> = until_eof(&mut reader, Endian::Little, ())?;
+        let start = reader.stream_position()?;
+        let chunks: Vec<Chunk> = until_eof(&mut reader, Endian::Little, ())
+            .map_err(|e| explain_parse_error(&mut reader, start, e.into()))?;
+
+        Self::from_chunks(chunks, &mut reader, start)
+    }
+
+    /// Creates a new [DSLocal] from a damaged core, when a resource fails
+    /// to parse the parser skips ahead to the next local resource that does
+    /// and keeps the skipped bytes as a raw resource. Use it when [`Self::new`]
+    /// fails, a core that isn't damaged is read the same by both.
+    ///
+    /// ## Returns:
+    /// A [`DResult`] with the DSLocal and its [`warnings`](Self::warnings),
+    /// with a [`ParseWarning::Damaged`] for every skipped region first.
+    pub fn new_repairing<R: Read + Seek>(
+        mut reader: R,
+    ) -> DResult<(Self, Vec<ParseWarning<Language>>)> {
+        let mut data = Vec::new();
+        reader.read_to_end(&mut data)?;
 
+        let (chunks, mut warnings) =
+            chunks::read_chunks_repairing(&data, &[LOCALIZED_MAGIC], |data| {
+                ChunkVariants::Others { data }
+            });
+        let local = Self::from_chunks(chunks, &mut Cursor::new(&data), 0)?;
+        warnings.extend(local.warnings());
+        Ok((local, warnings))
+    }
+
+    /// Check that the `chunks` read from the core starting at `start` of
+    /// `reader` have a local resource and create the [DSLocal].
+    fn from_chunks<R: Read + Seek>(
+        chunks: Vec<Chunk>,
+        reader: &mut R,
+        start: u64,
+    ) -> DResult<Self> {
         if !chunks
             .iter()
             .any(|c| matches!(c.variant, ChunkVariants::Localized(_)))
         {
-            return Err(Error::NoLocalResource);
+            return Err(explain_parse_error(reader, start, Error::NoLocalResource));
+        }
+
+        Ok(Self {
+            load_hash: hash_of(&chunks),
+            chunks,
+        })
+    }
+
+    /// Creates a [`DSLocal`] with an unknown chunk followed by
+    /// `resource_count` made up resources with strings in every language.
+    /// Used to check dloc without any game file.
+    pub fn synthetic(resource_count: usize) -> DResult<Self> {
+        let mut input = synthetic::chunk(0xD10C, vec![0; 8]);
+        for resource in 0..resource_count {
+            let mut data = synthetic::uuid(resource).to_vec();
+            for language in 0..Language::ALL_VARIANTS.len() {
+                synthetic::push_u8_string(&mut data, &synthetic::string(resource, language, 0));
+                synthetic::push_u8_string(&mut data, &format!("note {resource}"));
+                data.push(language as u8);
+            }
+            input.extend(synthetic::chunk(LOCALIZED_MAGIC, data));
+        }
+
+        Self::new(Cursor::new(input))
+    }
+
+    /// Same as [`Self::new`] but also returns the [`warnings`](Self::warnings)
+    /// of the loaded core.
+    pub fn new_with_warnings<R: Read + Seek>(
+        reader: R,
+    ) -> DResult<(Self, Vec<ParseWarning<Language>>)> {
+        let local = Self::new(reader)?;
+        let warnings = local.warnings();
+        Ok((local, warnings))
+    }
+
+    /// Returns the non fatal oddities of the core, like resources that are
+    /// kept as raw data or strings close to their length limit.
+    pub fn warnings(&self) -> Vec<ParseWarning<Language>> {
+        let offsets = self.chunk_offsets();
+        let mut warnings = Vec::new();
+        for (index, chunk) in self.chunks.iter().enumerate() {
+            match &chunk.variant {
+                ChunkVariants::Localized(_) => {
+                    warnings.extend(games::length_warnings(chunk_strings(index, &chunk.variant)))
+                }
+                ChunkVariants::Others { data } => {
+                    if chunk.magic() == LOCALIZED_MAGIC {
+                        warnings.push(ParseWarning::KeptAsRaw { index });
+                        warnings.extend(games::invalid_string_warning::<Localized, _, _>(
+                            index,
+                            offsets[index] + 12,
+                            data,
+                            (),
+                        ));
+                    }
+                }
+            }
         }
+        warnings
+    }
+
+    /// Returns `true` if the content was modified since it was loaded.
+    pub fn is_modified(&self) -> bool {
+        hash_of(&self.chunks) != self.load_hash
+    }
+
+    /// Returns `true` if the chunk at `index` pass the uuid and content `filters`.
+    fn is_serialized(&self, index: usize, filters: &ExportFilters) -> bool {
+        self.chunks
+            .get(index)
+            .is_some_and(|c| filters.allows(c.variant.uuid(), chunk_strings(index, &c.variant)))
+    }
 
-        Ok(Self { chunks })
+    /// Returns `true` if the chunk of `entry` is serialized and the string
+    /// itself pass the content filter.
+    #[cfg(feature = "serialize")]
+    fn is_serialized_string(&self, entry: &StringEntry<Language>, filters: &ExportFilters) -> bool {
+        self.is_serialized(entry.index, filters) && filters.allows_string(entry.string)
     }
 
     // Get all Local resources inside the file.
@@ -114,6 +279,258 @@ impl DSLocal {
             .collect()
     }
 
+    /// Iterate over every string of every language inside the file without cloning them.
+    ///
+    /// The strings come in chunk order, then language order.
+    pub fn strings(&self) -> impl Iterator<Item = StringEntry<'_, Language>> {
+        self.chunks
+            .iter()
+            .enumerate()
+            .flat_map(|(index, c)| chunk_strings(index, &c.variant))
+    }
+
+    /// Iterate over the note of every string, notes are only meant for the
+    /// translators and aren't shown in game, only the Json, Yaml, table, PO
+    /// and SQLite formats export them.
+    ///
+    /// The notes are in the same order as [`strings`](Self::strings).
+    pub fn notes(&self) -> impl Iterator<Item = StringEntry<'_, Language>> {
+        self.chunks
+            .iter()
+            .enumerate()
+            .flat_map(|(index, c)| chunk_groups(index, &c.variant, |g| &g.note))
+    }
+
+    /// Replace the note of every string with `note`, like stamping the
+    /// version of a translation before distributing the core.
+    pub fn set_all_notes(&mut self, note: &str) {
+        for chunk in &mut self.chunks {
+            if let ChunkVariants::Localized(loc) = &mut chunk.variant {
+                for group in loc.string_groups.inner.iter_mut() {
+                    group.note = note.to_owned().into();
+                }
+            }
+        }
+    }
+
+    /// Remove the note of every string, this makes the core smaller.
+    pub fn clear_all_notes(&mut self) {
+        self.set_all_notes("");
+    }
+
+    /// Exchange the strings of languages `a` and `b` in every resource,
+    /// useful to find out which language the game actually reads. The
+    /// notes are swapped with their strings.
+    pub fn swap_languages(&mut self, a: Language, b: Language) {
+        for chunk in &mut self.chunks {
+            if let ChunkVariants::Localized(loc) = &mut chunk.variant {
+                loc.string_groups.swap(a, b);
+            }
+        }
+    }
+
+    /// Replace the resource at `index` with the one at the same index of
+    /// `from`, with every language and note, to port a fix between two cores.
+    /// Both resources should be of the same kind.
+    ///
+    /// ## Arguments:
+    /// * `from` - The core to copy the resource from.
+    /// * `index` - Index of the resource in both cores.
+    ///
+    /// ## Returns:
+    /// Result with [`DSError`] on failure.
+    pub fn copy_resource(&mut self, from: &Self, index: usize) -> Result<(), DSError> {
+        let Some(source) = from.chunks.get(index) else {
+            return Err(DSError::InvalidLocalResourceIdx {
+                max: from.chunks.len(),
+                got: index,
+            });
+        };
+        let max = self.chunks.len();
+        let Some(chunk) = self.chunks.get_mut(index) else {
+            return Err(DSError::InvalidLocalResourceIdx { max, got: index });
+        };
+
+        if source.magic() != chunk.magic() {
+            return Err(DSError::ResourceNotMatchAtIdx {
+                input: source.variant.name(),
+                original: chunk.variant.name(),
+            });
+        }
+        chunk.copy_from(source);
+
+        Ok(())
+    }
+
+    /// Clone every string of `language` into a [`Vec`].
+    ///
+    /// The strings are in the same order as [`strings`](Self::strings), so
+    /// by resource index, DS only has localized resources with one string
+    /// each. This is the order of the lines of the `extract-language` and
+    /// `import-language` commands, and the order
+    /// [`set_language_strings`](Self::set_language_strings) expects.
+    pub fn language_strings(&self, language: Language) -> Vec<String> {
+        self.strings()
+            .filter(|e| e.language == language)
+            .map(|e| e.string.to_owned())
+            .collect()
+    }
+
+    /// Replace every string of `language` with `strings`, the counterpart of
+    /// [`language_strings`](Self::language_strings) with the same order.
+    ///
+    /// ## Returns:
+    /// Result with [`DSError`] on failure, nothing is changed if `strings`
+    /// doesn't have exactly one string for each of the localized strings of `language`.
+    pub fn set_language_strings(
+        &mut self,
+        language: Language,
+        strings: Vec<String>,
+    ) -> Result<(), DSError> {
+        let positions: Vec<_> = self
+            .strings()
+            .filter(|e| e.language == language)
+            .map(|e| (e.index, e.sub))
+            .collect();
+
+        if positions.len() != strings.len() {
+            return Err(DSError::LanguageStringCountDoesntMatch {
+                lang: language,
+                expected: positions.len(),
+                got: strings.len(),
+            });
+        }
+
+        for ((index, sub), string) in positions.into_iter().zip(strings) {
+            self.set_string(index, language, sub, string)?;
+        }
+
+        Ok(())
+    }
+
+    /// Get the languages that have at least one non empty string in any
+    /// resource, languages that were never localized in this core are left out.
+    pub fn populated_languages(&self) -> Vec<Language> {
+        games::populated_languages(&Language::ALL_VARIANTS, self.strings())
+    }
+
+    /// Count the strings of the given `languages` in the resources that pass
+    /// the uuid and content `filters`. This is the number of
+    /// strings a Json, Yaml or txt export with the same languages will have,
+    /// the formats with a line or row for each string can have less as they
+    /// also leave out the strings that don't match the content filter
+    /// themselves.
+    pub fn count_strings(&self, languages: &[Language], filters: &ExportFilters) -> usize {
+        self.strings()
+            .filter(|e| self.is_serialized(e.index, filters) && languages.contains(&e.language))
+            .count()
+    }
+
+    /// Get the index, variant name, size, declared size, uuid and string count of every resource
+    /// inside the file, including the non local ones.
+    pub fn resource_overview(&self) -> Vec<ResourceInfo> {
+        games::resource_overview(
+            self.chunks.iter().map(|c| {
+                (
+                    c.variant.name(),
+                    c.variant.rt_size(),
+                    c.declared_size(),
+                    c.variant.uuid(),
+                )
+            }),
+            self.strings(),
+        )
+    }
+
+    /// Get a single string of a local resource.
+    ///
+    /// ## Arguments:
+    /// * `index` - Index of the resource.
+    /// * `language` - Language of the string.
+    /// * `sub` - Line index, always `0` as there is no cutscene in DS.
+    ///
+    /// ## Returns:
+    /// Result with the string or [`DSError`] if there is no such string.
+    pub fn get_string(
+        &self,
+        index: usize,
+        language: Language,
+        sub: usize,
+    ) -> Result<&str, DSError> {
+        let Some(chunk) = self.chunks.get(index) else {
+            return Err(DSError::InvalidLocalResourceIdx {
+                max: self.chunks.len(),
+                got: index,
+            });
+        };
+
+        match &chunk.variant {
+            ChunkVariants::Localized(_) if sub != 0 => Err(DSError::InvalidLineIdx {
+                index,
+                max: 1,
+                got: sub,
+            }),
+            ChunkVariants::Localized(loc) => Ok(loc.string_groups[language].text.as_str()),
+            ChunkVariants::Others { .. } => Err(DSError::NoStrings { index }),
+        }
+    }
+
+    /// Replace a single string of a local resource.
+    ///
+    /// ## Arguments:
+    /// * `index` - Index of the resource.
+    /// * `language` - Language of the string.
+    /// * `sub` - Line index, always `0` as there is no cutscene in DS.
+    /// * `string` - The new string.
+    ///
+    /// ## Returns:
+    /// Result with [`DSError`] on failure.
+    pub fn set_string(
+        &mut self,
+        index: usize,
+        language: Language,
+        sub: usize,
+        string: String,
+    ) -> Result<(), DSError> {
+        self.group_mut(index, sub, language)?.text = string.into();
+        Ok(())
+    }
+
+    /// Replace the translator note of a single string, the arguments are
+    /// the same as [`set_string`](Self::set_string).
+    pub fn set_note(
+        &mut self,
+        index: usize,
+        language: Language,
+        sub: usize,
+        note: String,
+    ) -> Result<(), DSError> {
+        self.group_mut(index, sub, language)?.note = note.into();
+        Ok(())
+    }
+
+    fn group_mut(
+        &mut self,
+        index: usize,
+        sub: usize,
+        language: Language,
+    ) -> Result<&mut LocalGroup, DSError> {
+        let max = self.chunks.len();
+        let Some(chunk) = self.chunks.get_mut(index) else {
+            return Err(DSError::InvalidLocalResourceIdx { max, got: index });
+        };
+
+        match &mut chunk.variant {
+            ChunkVariants::Localized(_) if sub != 0 => Err(DSError::InvalidLineIdx {
+                index,
+                max: 1,
+                got: sub,
+            }),
+            ChunkVariants::Localized(loc) => Ok(&mut loc.string_groups[language]),
+            ChunkVariants::Others { .. } => Err(DSError::NoStrings { index }),
+        }
+    }
+
     /// Updates the local resources in this [`DSLocal`] with the provided locals.
     ///
     /// ## Arguments:
@@ -148,6 +565,44 @@ impl DSLocal {
         Ok(())
     }
 
+    /// Make a [`Patch`] of the strings that differ from `reference`, which
+    /// should be the vanilla version of this core.
+    pub fn create_patch(&self, reference: &Self) -> DResult<Patch> {
+        Patch::diff(
+            patch::GAME_DS,
+            reference.chunks.len(),
+            reference.strings(),
+            self.strings(),
+        )
+    }
+
+    /// Apply a [`Patch`] made by [`Self::create_patch`] and returns the
+    /// number of patched strings. Nothing is changed if the patch is for
+    /// another game, was made from another version of this core or have
+    /// strings that don't exist in this core.
+    pub fn apply_patch(&mut self, patch: &Patch) -> DResult<usize> {
+        let strings = patch.strings(patch::GAME_DS, self.chunks.len(), self.strings())?;
+        let count = strings.len();
+        // every string was checked to exist, so this can't fail half way
+        for (index, sub, language, string) in strings {
+            self.set_string(index, language, sub, string)
+                .map_err(|e| Error::InvalidPatch(e.to_string()))?;
+        }
+        Ok(count)
+    }
+
+    /// Returns the byte offset of each chunk (resource index) in the core it
+    /// was read from, chunks that are written after an import can end up at
+    /// other offsets.
+    ///
+    /// Core files don't have any header or boundary marker, so when several
+    /// cores are concatenated in one file there is no way to tell where each
+    /// one ends, but these offsets together with the size of the original
+    /// cores can be used to know which core each chunk came from.
+    pub fn chunk_offsets(&self) -> Vec<u64> {
+        chunk_offsets(&self.chunks)
+    }
+
     /// Writes the chunks in this [`DSLocal`] to the given writer.
     ///
     /// ## Arguments:
@@ -159,4 +614,711 @@ impl DSLocal {
         self.chunks.write(writer)?;
         Ok(())
     }
+
+    /// Writes the chunks in this [`DSLocal`] to a new buffer and return it.
+    ///
+    /// ## Returns:
+    /// Return the written bytes or a [`Error`] on failure.
+    pub fn to_bytes(&self) -> DResult<Vec<u8>> {
+        let mut writer = Cursor::new(Vec::new());
+        self.write(&mut writer)?;
+        Ok(writer.into_inner())
+    }
+
+    /// Read back a core written from this [`DSLocal`] and check that it
+    /// parses and have the same strings, to catch invalid written files.
+    ///
+    /// ## Arguments:
+    /// * `reader` - The reader of the written core.
+    ///
+    /// ## Returns:
+    /// Return [`Error::VerifyFailed`] if the strings doesn't match.
+    pub fn verify_written<R: Read + Seek>(&self, reader: R) -> DResult<()> {
+        let written = Self::new(reader)?;
+        compare_strings(self.strings(), written.strings()).map_err(Error::VerifyFailed)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    #[cfg(feature = "regex")]
+    use crate::games::ContentFilter;
+    #[cfg(feature = "serialize")]
+    use crate::serialize::ImportOptions;
+
+    fn localized_chunk(group_count: usize) -> Vec<u8> {
+        let mut data = vec![0_u8; 16];
+        for i in 0..group_count {
+            synthetic::push_u8_string(&mut data, &format!("text {i}"));
+            synthetic::push_u8_string(&mut data, &format!("note {i}"));
+            data.push(i as u8);
+        }
+        synthetic::chunk(LOCALIZED_MAGIC, data)
+    }
+
+    #[test]
+    fn string_groups_follow_language_order() {
+        assert!(Language::ALL_VARIANTS
+            .iter()
+            .enumerate()
+            .all(|(i, l)| usize::from(*l) == i));
+
+        let input = localized_chunk(Language::ALL_VARIANTS.len());
+        let local = DSLocal::new(Cursor::new(&input)).unwrap();
+
+        let mut count = 0;
+        for entry in local.strings() {
+            assert_eq!(
+                entry.string,
+                format!("text {}", usize::from(entry.language))
+            );
+            count += 1;
+        }
+        assert_eq!(count, Language::ALL_VARIANTS.len());
+        assert_eq!(
+            local.count_strings(&Language::ALL_VARIANTS, &ExportFilters::default()),
+            count
+        );
+        assert_eq!(
+            local.count_strings(&[Language::English], &ExportFilters::default()),
+            1
+        );
+        assert_eq!(local.to_bytes().unwrap(), input);
+    }
+
+    #[test]
+    fn get_string_reads_what_set_string_wrote() {
+        let mut local = DSLocal::synthetic(1).unwrap();
+        local
+            .set_string(1, Language::Greek, 0, "γεια".to_owned())
+            .unwrap();
+        assert_eq!(local.get_string(1, Language::Greek, 0).unwrap(), "γεια");
+        assert!(matches!(
+            local.get_string(1, Language::Greek, 1),
+            Err(DSError::InvalidLineIdx { got: 1, .. })
+        ));
+        assert!(matches!(
+            local.get_string(0, Language::Greek, 0),
+            Err(DSError::NoStrings { index: 0 })
+        ));
+        assert!(matches!(
+            local.get_string(2, Language::Greek, 0),
+            Err(DSError::InvalidLocalResourceIdx { max: 2, got: 2 })
+        ));
+    }
+
+    #[test]
+    fn set_language_strings_round_trip() {
+        let source = DSLocal::synthetic(3).unwrap();
+        let strings = source.language_strings(Language::Greek);
+        assert_eq!(strings.len(), 3);
+
+        let mut target = DSLocal::synthetic(3).unwrap();
+        target
+            .set_language_strings(Language::Greek, vec![String::new(); 3])
+            .unwrap();
+        assert!(target
+            .language_strings(Language::Greek)
+            .iter()
+            .all(String::is_empty));
+
+        assert!(matches!(
+            target.set_language_strings(Language::Greek, vec![String::new(); 4]),
+            Err(DSError::LanguageStringCountDoesntMatch {
+                expected: 3,
+                got: 4,
+                ..
+            })
+        ));
+
+        target
+            .set_language_strings(Language::Greek, strings)
+            .unwrap();
+        assert_eq!(target.to_bytes().unwrap(), source.to_bytes().unwrap());
+    }
+
+    #[test]
+    fn all_notes_can_be_replaced() {
+        let input = localized_chunk(Language::ALL_VARIANTS.len());
+        let mut local = DSLocal::new(Cursor::new(&input)).unwrap();
+        assert!(local
+            .notes()
+            .all(|e| e.string == format!("note {}", usize::from(e.language))));
+
+        local.set_all_notes("v1.2");
+        assert!(local.notes().all(|e| e.string == "v1.2"));
+        assert!(local.is_modified());
+
+        local.clear_all_notes();
+        assert!(local.notes().all(|e| e.string.is_empty()));
+        assert_eq!(local.notes().count(), Language::ALL_VARIANTS.len());
+        let size = local.to_bytes().unwrap().len();
+        assert!(size < input.len());
+        assert!(DSLocal::new(Cursor::new(local.to_bytes().unwrap())).is_ok());
+    }
+
+    #[test]
+    fn wrong_group_count_is_kept_as_raw_data() {
+        let mut input = localized_chunk(Language::ALL_VARIANTS.len() + 1);
+        input.extend(localized_chunk(Language::ALL_VARIANTS.len()));
+        let local = DSLocal::new(Cursor::new(&input)).unwrap();
+
+        assert!(matches!(
+            local.chunks[0].variant,
+            ChunkVariants::Others { .. }
+        ));
+        assert!(local.strings().all(|e| e.index == 1));
+        assert_eq!(local.warnings(), [ParseWarning::KeptAsRaw { index: 0 }]);
+
+        let overview = local.resource_overview();
+        assert_eq!(overview.len(), 2);
+        assert_eq!(overview[0].variant, "Others");
+        assert_eq!(overview[0].string_count, 0);
+        assert_eq!(overview[1].variant, "Localized");
+        assert_eq!(overview[1].string_count, Language::ALL_VARIANTS.len());
+        assert_eq!(
+            overview[1].size as usize,
+            localized_chunk(Language::ALL_VARIANTS.len()).len() - 12
+        );
+        assert_eq!(local.to_bytes().unwrap(), input);
+    }
+
+    #[cfg(feature = "regex")]
+    #[test]
+    fn content_filter_keeps_resource_indices() {
+        let mut input = localized_chunk(Language::ALL_VARIANTS.len());
+        input.extend(localized_chunk(Language::ALL_VARIANTS.len()));
+        let mut local = DSLocal::new(Cursor::new(&input)).unwrap();
+        local
+            .set_string(1, Language::French, 0, "has {0}".to_owned())
+            .unwrap();
+
+        let filters = ExportFilters {
+            content: Some(ContentFilter::new(r"\{0\}").unwrap()),
+            ..Default::default()
+        };
+        assert_eq!(
+            local.count_strings(&Language::ALL_VARIANTS, &filters),
+            Language::ALL_VARIANTS.len()
+        );
+        assert!((0..2).all(|i| local.is_serialized(i, &filters) == (i == 1)));
+    }
+
+    #[cfg(all(feature = "regex", feature = "serialize"))]
+    #[test]
+    fn content_filter_keeps_only_matching_strings() {
+        use crate::serialize::private::InternalStringAccess;
+
+        let mut input = localized_chunk(Language::ALL_VARIANTS.len());
+        input.extend(localized_chunk(Language::ALL_VARIANTS.len()));
+        let mut local = DSLocal::new(Cursor::new(&input)).unwrap();
+        local
+            .set_string(1, Language::French, 0, "has {0}".to_owned())
+            .unwrap();
+        let all = local.strings().count();
+
+        let filters = ExportFilters {
+            content: Some(ContentFilter::new(r"\{0\}").unwrap()),
+            ..Default::default()
+        };
+        let exported: Vec<_> = local.internal_exported_strings(&filters).collect();
+        assert_eq!(exported.len(), 1);
+        assert_eq!(
+            (exported[0].index, exported[0].language),
+            (1, Language::French)
+        );
+        assert_eq!(local.internal_strings().count(), all);
+    }
+
+    #[cfg(feature = "serialize")]
+    #[test]
+    fn serde_import_checks_resource_count() {
+        use crate::serialize::private::InternalDataSerializer;
+
+        let data = DSLocal::synthetic(3).unwrap().internal_serialize(
+            &[Language::English],
+            false,
+            &ExportFilters::default(),
+        );
+        assert_eq!(data[0].resource_count, Some(4));
+
+        let mut target = DSLocal::synthetic(2).unwrap();
+        assert!(matches!(
+            target.internal_deserialize_and_update(data.clone(), ImportOptions::default()),
+            Err(DSError::ResourceCountDoesntMatch {
+                expected: 4,
+                got: 3
+            })
+        ));
+
+        // the last resource doesn't exist in the target
+        let options = ImportOptions {
+            check_resource_count: false,
+        };
+        assert!(target
+            .internal_deserialize_and_update(data[..2].to_vec(), options)
+            .is_ok());
+    }
+
+    #[cfg(feature = "serialize")]
+    #[test]
+    fn multi_language_txt_round_trip() {
+        use crate::serialize::private::InternalPlainTextDataSerializer;
+
+        let source = DSLocal::synthetic(3).unwrap();
+        let languages = [Language::English, Language::French, Language::German];
+        let (mut lines, deinfo) =
+            source.internal_serialize_to_lines(&languages, &ExportFilters::default(), None, false);
+        assert_eq!(lines.len(), 3 * languages.len());
+        assert!(DSLocal::internal_check_lines(lines.len(), &deinfo).is_empty());
+
+        // lines are in resource then language order
+        lines[4] = "changed".to_owned();
+        let mut target = DSLocal::synthetic(3).unwrap();
+        target
+            .internal_deserialize_and_update_from_lines(&lines, deinfo, ImportOptions::default())
+            .unwrap();
+
+        let mut expected = DSLocal::synthetic(3).unwrap();
+        expected
+            .set_string(2, Language::French, 0, "changed".to_owned())
+            .unwrap();
+        assert!(target.strings().eq(expected.strings()));
+    }
+
+    #[cfg(feature = "serialize")]
+    #[test]
+    fn blank_export_keeps_the_structure() {
+        use crate::serialize::private::{
+            InternalDataSerializer, InternalPlainTextDataSerializer, InternalStringAccess,
+        };
+
+        let source = DSLocal::synthetic(3).unwrap();
+        let languages = [Language::English, Language::French];
+        let (lines, _) =
+            source.internal_serialize_to_lines(&languages, &ExportFilters::default(), None, false);
+
+        let blank = ExportFilters {
+            blank: true,
+            ..Default::default()
+        };
+        let (blank_lines, _) = source.internal_serialize_to_lines(&languages, &blank, None, false);
+        assert_eq!(blank_lines.len(), lines.len());
+        assert!(blank_lines.iter().all(String::is_empty));
+        assert!(source
+            .internal_exported_strings(&blank)
+            .all(|e| e.string.is_empty()));
+
+        // the next export without the filters isn't blank
+        let (after, _) =
+            source.internal_serialize_to_lines(&languages, &ExportFilters::default(), None, false);
+        assert_eq!(after, lines);
+
+        let export = source.internal_serialize(&languages, false, &blank);
+        assert_eq!(export.len(), 3);
+        assert!(export.iter().all(
+            |l| l.strings.len() == languages.len() && l.strings.values().all(String::is_empty)
+        ));
+    }
+
+    #[cfg(feature = "serialize")]
+    #[test]
+    fn sorted_export_imports_by_uuid() {
+        use crate::serialize::private::InternalDataSerializer;
+
+        let chunk = |uuid| {
+            let mut chunk = localized_chunk(Language::ALL_VARIANTS.len());
+            chunk[12] = uuid;
+            chunk
+        };
+
+        let mut local = DSLocal::new(Cursor::new([chunk(2), chunk(1)].concat())).unwrap();
+        local
+            .set_string(0, Language::English, 0, "two".to_owned())
+            .unwrap();
+        let sorted = ExportFilters {
+            sort_by_uuid: true,
+            ..Default::default()
+        };
+        let export = local.internal_serialize(&[Language::English], false, &sorted);
+        assert!(export.iter().map(|l| l.index).eq([1, 0]));
+        // sorting only applies to the export it was passed to
+        let unsorted =
+            local.internal_serialize(&[Language::English], false, &ExportFilters::default());
+        assert!(unsorted.iter().all(|l| l.uuid.is_none()));
+
+        // a game patch swapped the resources
+        let mut patched = DSLocal::new(Cursor::new([chunk(1), chunk(2)].concat())).unwrap();
+        patched
+            .internal_deserialize_and_update(export, ImportOptions::default())
+            .unwrap();
+        assert!(patched
+            .strings()
+            .any(|e| e.index == 1 && e.language == Language::English && e.string == "two"));
+    }
+
+    #[test]
+    fn mismatched_input_changes_nothing() {
+        use std::collections::{BTreeMap, BTreeSet};
+
+        use crate::serialize::private::InternalDataSerializer;
+        use serialize::LocalResource;
+
+        let input = localized_chunk(Language::ALL_VARIANTS.len());
+        let mut local = DSLocal::new(Cursor::new(&input)).unwrap();
+        let resource = |index| LocalResource {
+            index,
+            uuid: None,
+            resource_count: None,
+            strings: BTreeMap::from([(Language::English, "changed".to_owned())]),
+            empty: BTreeSet::new(),
+            notes: BTreeMap::new(),
+            filled: BTreeMap::new(),
+            byte_len: BTreeMap::new(),
+            extra: BTreeMap::new(),
+        };
+
+        let result = local.internal_deserialize_and_update(
+            vec![resource(0), resource(3), resource(5)],
+            ImportOptions::default(),
+        );
+        assert!(matches!(
+            result,
+            Err(DSError::InputDoesntMatch { errors }) if errors.len() == 2
+        ));
+        assert_eq!(local.to_bytes().unwrap(), input);
+    }
+
+    #[test]
+    fn byte_swapped_magic_is_reported() {
+        let mut input = localized_chunk(Language::ALL_VARIANTS.len());
+        input[..8].reverse();
+        input[8..12].reverse();
+
+        assert!(matches!(
+            DSLocal::new(Cursor::new(&input)),
+            Err(Error::BigEndian)
+        ));
+        assert!(matches!(
+            crate::games::detect::detect_game(&mut Cursor::new(&input)),
+            Err(Error::BigEndian)
+        ));
+    }
+
+    #[test]
+    fn verify_written_finds_changed_strings() {
+        let input = localized_chunk(Language::ALL_VARIANTS.len());
+        let mut local = DSLocal::new(Cursor::new(&input)).unwrap();
+        local.verify_written(Cursor::new(&input)).unwrap();
+
+        local
+            .set_string(0, Language::English, 0, "changed".to_owned())
+            .unwrap();
+        assert!(matches!(
+            local.verify_written(Cursor::new(&input)),
+            Err(Error::VerifyFailed(_))
+        ));
+        local
+            .verify_written(Cursor::new(local.to_bytes().unwrap()))
+            .unwrap();
+    }
+
+    #[cfg(feature = "serialize")]
+    #[test]
+    fn serde_export_keeps_the_note_of_each_language() {
+        use crate::serialize::private::InternalDataSerializer;
+
+        let input = localized_chunk(Language::ALL_VARIANTS.len());
+        let local = DSLocal::new(Cursor::new(&input)).unwrap();
+        let mut data = local.internal_serialize(
+            &[Language::English, Language::French],
+            false,
+            &ExportFilters::default(),
+        );
+        assert_eq!(data[0].notes[&Language::English], "note 0");
+        assert_eq!(data[0].notes[&Language::French], "note 1");
+        assert_eq!(data[0].notes.len(), 2);
+
+        let mut target = DSLocal::new(Cursor::new(&input)).unwrap();
+        target
+            .internal_deserialize_and_update(data.clone(), ImportOptions::default())
+            .unwrap();
+        assert_eq!(target.to_bytes().unwrap(), input);
+
+        data[0].notes.insert(Language::French, "changed".to_owned());
+        data[0].notes.insert(Language::English, String::new());
+        target
+            .internal_deserialize_and_update(data, ImportOptions::default())
+            .unwrap();
+        let ChunkVariants::Localized(loc) = &target.chunks[0].variant else {
+            panic!("the chunk should be localized");
+        };
+        assert_eq!(loc.string_groups[Language::English].note.as_str(), "note 0");
+        assert_eq!(loc.string_groups[Language::French].note.as_str(), "changed");
+        assert_eq!(loc.string_groups[Language::German].note.as_str(), "note 3");
+    }
+
+    #[cfg(feature = "serialize")]
+    #[test]
+    fn po_comments_are_the_notes() {
+        use crate::serialize::{SerializeData, SerializeType};
+
+        let input = localized_chunk(Language::ALL_VARIANTS.len());
+        let mut local = DSLocal::new(Cursor::new(&input)).unwrap();
+        let ChunkVariants::Localized(loc) = &mut local.chunks[0].variant else {
+            panic!("the chunk should be localized");
+        };
+        loc.string_groups[Language::French].note = String::new().into();
+        let mut export = Vec::new();
+        local
+            .serialize_to_writer(
+                &mut export,
+                [Language::English, Language::French],
+                SerializeType::Po,
+                &ExportFilters::default(),
+            )
+            .unwrap();
+        let export = String::from_utf8(export).unwrap();
+        assert!(export.contains("\n\n#. note 0\nmsgctxt \"0:0:English\"\n"));
+        assert!(export.contains("\n\nmsgctxt \"0:0:French\"\n"));
+
+        // the header and the first message
+        let export = export.replacen("msgstr \"", "msgstr \"new ", 2);
+        let mut target = DSLocal::new(Cursor::new(&input)).unwrap();
+        let summary = target
+            .deserialize_and_update_from_reader(
+                export.as_bytes(),
+                SerializeType::Po,
+                ImportOptions::default(),
+            )
+            .unwrap();
+        assert_eq!(summary.strings_changed, 1);
+        assert!(target
+            .strings()
+            .any(|e| e.language == Language::English && e.string == "new text 0"));
+    }
+
+    #[cfg(feature = "serialize")]
+    #[test]
+    fn table_keeps_the_note_of_each_language() {
+        use crate::serialize::{SerializeData, SerializeType};
+
+        let mut source = DSLocal::synthetic(2).unwrap();
+        for (index, language, note) in [
+            (1, Language::English, "english note"),
+            (1, Language::French, "note\tfrançaise"),
+            (2, Language::French, "another"),
+        ] {
+            source
+                .set_note(index, language, 0, note.to_owned())
+                .unwrap();
+        }
+        let mut export = Vec::new();
+        source
+            .serialize_to_writer(
+                &mut export,
+                [Language::English, Language::French],
+                SerializeType::Table,
+                &ExportFilters::default(),
+            )
+            .unwrap();
+        let export = String::from_utf8(export).unwrap();
+        let mut rows = export.lines();
+        assert_eq!(
+            rows.next(),
+            Some("index\tsub\tEnglish\tFrench\tnote:English\tnote:French")
+        );
+        assert!(rows
+            .next()
+            .unwrap()
+            .ends_with("\tenglish note\tnote\\tfrançaise"));
+
+        let mut target = DSLocal::synthetic(2).unwrap();
+        target
+            .deserialize_and_update_from_reader(
+                export.as_bytes(),
+                SerializeType::Table,
+                ImportOptions::default(),
+            )
+            .unwrap();
+        assert_eq!(target.to_bytes().unwrap(), source.to_bytes().unwrap());
+
+        // a blank note keeps the note of the core
+        let export = export.replace("\tnote\\tfrançaise", "\t");
+        let mut target = DSLocal::synthetic(2).unwrap();
+        target
+            .deserialize_and_update_from_reader(
+                export.as_bytes(),
+                SerializeType::Table,
+                ImportOptions::default(),
+            )
+            .unwrap();
+        let notes: Vec<_> = target
+            .notes()
+            .filter(|e| e.index == 1 && matches!(e.language, Language::English | Language::French))
+            .map(|e| (e.language, e.string))
+            .collect();
+        assert_eq!(
+            notes,
+            [
+                (Language::English, "english note"),
+                (Language::French, "note 0")
+            ]
+        );
+    }
+
+    #[cfg(feature = "serialize")]
+    #[test]
+    fn omitted_empty_strings_round_trip() {
+        use crate::serialize::{private::InternalDataSerializer, SerializeData, SerializeType};
+
+        let source = DSLocal::synthetic(3).unwrap();
+        let formats = [
+            SerializeType::Json {
+                indent: None,
+                omit_empty: true,
+            },
+            #[cfg(feature = "serialize_yaml")]
+            SerializeType::Yaml { omit_empty: true },
+        ];
+        for serialize_type in formats {
+            let mut export = Vec::new();
+            source
+                .serialize_to_writer(
+                    &mut export,
+                    Language::ALL_VARIANTS,
+                    serialize_type.clone(),
+                    &ExportFilters::default(),
+                )
+                .unwrap();
+
+            let mut target = DSLocal::synthetic(3).unwrap();
+            for (index, language) in [(1, Language::English), (2, Language::French)] {
+                target
+                    .set_string(index, language, 0, "changed".to_owned())
+                    .unwrap();
+            }
+            target
+                .deserialize_and_update_from_reader(
+                    export.as_slice(),
+                    serialize_type,
+                    ImportOptions::default(),
+                )
+                .unwrap();
+            assert_eq!(target.to_bytes().unwrap(), source.to_bytes().unwrap());
+        }
+
+        // a language listed as empty and with a string keeps the string
+        let mut data =
+            source.internal_serialize(&Language::ALL_VARIANTS, true, &ExportFilters::default());
+        let local = data.iter_mut().find(|l| !l.empty.is_empty()).unwrap();
+        let language = *local.empty.first().unwrap();
+        local.strings.insert(language, "both".to_owned());
+        let index = local.index;
+        assert!(DSLocal::internal_flatten(data.clone())
+            .into_iter()
+            .rev()
+            .find(|(i, _, l, _)| (*i, *l) == (index, language))
+            .is_some_and(|(.., s)| s == "both"));
+        let mut target = DSLocal::synthetic(3).unwrap();
+        target
+            .internal_deserialize_and_update(data, ImportOptions::default())
+            .unwrap();
+        assert_eq!(target.get_string(index, language, 0).unwrap(), "both");
+    }
+
+    #[cfg(feature = "serialize")]
+    #[test]
+    fn filled_strings_are_only_imported_when_edited() {
+        use crate::serialize::{private::InternalDataSerializer, SerializeData, SerializeType};
+
+        let core = || {
+            let mut local = DSLocal::synthetic(2).unwrap();
+            for index in [1, 2] {
+                local
+                    .set_string(index, Language::English, 0, "english".to_owned())
+                    .unwrap();
+                local
+                    .set_string(index, Language::French, 0, String::new())
+                    .unwrap();
+            }
+            local
+        };
+        let source = core();
+        let filters = ExportFilters {
+            fill_empty_from: Some(Language::English),
+            ..Default::default()
+        };
+
+        let mut data = source.internal_serialize(&Language::ALL_VARIANTS, false, &filters);
+        assert_eq!(data[0].strings[&Language::French], "english");
+        assert_eq!(data[0].filled[&Language::French], Language::English);
+
+        let serialize_type = SerializeType::Json {
+            indent: None,
+            omit_empty: false,
+        };
+        let mut export = Vec::new();
+        source
+            .serialize_to_writer(
+                &mut export,
+                Language::ALL_VARIANTS,
+                serialize_type.clone(),
+                &filters,
+            )
+            .unwrap();
+        let mut target = core();
+        target
+            .deserialize_and_update_from_reader(
+                export.as_slice(),
+                serialize_type,
+                ImportOptions::default(),
+            )
+            .unwrap();
+        assert_eq!(target.to_bytes().unwrap(), core().to_bytes().unwrap());
+
+        data[1]
+            .strings
+            .insert(Language::French, "edited".to_owned());
+        target
+            .internal_deserialize_and_update(data, ImportOptions::default())
+            .unwrap();
+        assert_eq!(target.get_string(1, Language::French, 0).unwrap(), "");
+        assert_eq!(target.get_string(2, Language::French, 0).unwrap(), "edited");
+    }
+
+    #[cfg(feature = "serialize")]
+    #[test]
+    fn byte_lengths_are_of_the_exported_text() {
+        use crate::serialize::private::InternalDataSerializer;
+
+        let mut local = DSLocal::synthetic(1).unwrap();
+        local
+            .set_string(1, Language::English, 0, "ünï".to_owned())
+            .unwrap();
+        local
+            .set_string(1, Language::French, 0, String::new())
+            .unwrap();
+        let languages = [Language::English, Language::French];
+
+        let filters = ExportFilters {
+            byte_lengths: true,
+            ..Default::default()
+        };
+        let data = local.internal_serialize(&languages, false, &filters);
+        assert_eq!(data[0].byte_len[&Language::English], 2 + 5);
+        assert_eq!(data[0].byte_len[&Language::French], 2);
+
+        let data = local.internal_serialize(&languages, false, &ExportFilters::default());
+        assert!(data[0].byte_len.is_empty());
+
+        let filters = ExportFilters {
+            byte_lengths: true,
+            fill_empty_from: Some(Language::English),
+            ..Default::default()
+        };
+        let data = local.internal_serialize(&languages, false, &filters);
+        assert_eq!(data[0].byte_len[&Language::French], 2 + 5);
+    }
 }
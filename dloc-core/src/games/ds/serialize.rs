@@ -1,7 +1,6 @@
 use std::{
     collections::{BTreeMap, BTreeSet},
-    fs::File,
-    io::{BufReader, BufWriter},
+    io::{Read, Seek, Write},
     ops::Range,
 };
 
@@ -9,27 +8,126 @@ use serde::{Deserialize, Serialize};
 
 use crate::{
     error::DResult,
-    serialize::private::{
-        InternalDataSerializer, InternalGroupExtractor, InternalPlainTextDataSerializer,
-        InternalSerializerBase,
+    games::{self, StringEntry, Uuid},
+    serialize::{
+        copy_extra_fields, default_language_separator,
+        private::{
+            InternalDataSerializer, InternalGroupExtractor, InternalPlainTextDataSerializer,
+            InternalSerializerBase, InternalStringAccess,
+        },
+        strip_txt_line, txt_line, ExtraFields, ImportOptions,
     },
+    utils::{deserialize_index, types::U8String},
 };
 
-use super::{error::DSError, structures::ChunkVariants, DSLocal, Language};
+use super::{error::DSError, structures::ChunkVariants, DSLocal, ExportFilters, Language};
 
 impl InternalGroupExtractor for DSLocal {
-    fn internal_new(reader: BufReader<File>) -> DResult<Self> {
+    fn internal_new<R: Read + Seek>(reader: R) -> DResult<Self> {
         Self::new(reader)
     }
 
-    fn internal_write(&self, mut writer: BufWriter<File>) -> DResult<()> {
-        self.write(&mut writer)
+    fn internal_write<W: Write + Seek>(&self, writer: &mut W) -> DResult<()> {
+        self.write(writer)
+    }
+}
+
+impl InternalStringAccess for DSLocal {
+    const NOTES: bool = true;
+
+    fn internal_strings(&self) -> Box<dyn Iterator<Item = StringEntry<'_, Self::Language>> + '_> {
+        Box::new(self.strings())
+    }
+
+    fn internal_exported_strings<'a>(
+        &'a self,
+        filters: &'a ExportFilters,
+    ) -> Box<dyn Iterator<Item = StringEntry<'a, Self::Language>> + 'a> {
+        Box::new(
+            self.strings()
+                .filter(|e| self.is_serialized_string(e, filters))
+                .map(|e| StringEntry {
+                    string: filters.exported(e.string),
+                    ..e
+                }),
+        )
+    }
+
+    fn internal_set_string(
+        &mut self,
+        index: usize,
+        sub: usize,
+        language: Self::Language,
+        string: String,
+    ) -> Result<(), Self::Error> {
+        self.set_string(index, language, sub, string)
+    }
+
+    fn internal_string_extras(
+        &self,
+        index: usize,
+        _sub: usize,
+        language: Self::Language,
+    ) -> (Option<&str>, Option<u64>) {
+        match self.chunks.get(index).map(|c| &c.variant) {
+            Some(ChunkVariants::Localized(loc)) => {
+                (Some(loc.string_groups[language].note.as_str()), None)
+            }
+            _ => (None, None),
+        }
+    }
+
+    fn internal_set_note(
+        &mut self,
+        index: usize,
+        sub: usize,
+        language: Self::Language,
+        note: String,
+    ) -> Result<(), Self::Error> {
+        self.set_note(index, language, sub, note)
+    }
+}
+
+impl DSLocal {
+    /// Check every resource of `data` against the core before anything is
+    /// changed, so a mismatched input can't leave the core half imported.
+    fn validate_input(&self, data: &[LocalResource]) -> Result<(), DSError> {
+        let mut errors: Vec<_> = data
+            .iter()
+            .filter_map(|local| {
+                let index = match games::resolve_index(&self.chunks, local.index, local.uuid) {
+                    Ok(index) => index,
+                    Err(e) => return Some((local.index, e.into())),
+                };
+                let error = match self.chunks.get(index).map(|c| &c.variant) {
+                    Some(ChunkVariants::Localized(_)) => return None,
+                    Some(ChunkVariants::Others { .. }) => DSError::ResourceNotMatchAtIdx {
+                        input: "Localized",
+                        original: "Others",
+                    },
+                    None => DSError::InvalidLocalResourceIdx {
+                        max: self.chunks.len(),
+                        got: index,
+                    },
+                };
+                Some((index, error))
+            })
+            .collect();
+
+        match errors.len() {
+            0 => Ok(()),
+            1 => Err(errors.remove(0).1),
+            _ => Err(DSError::InputDoesntMatch { errors }),
+        }
     }
 }
 
 impl InternalSerializerBase for DSLocal {
     type Language = Language;
+    const ALL_LANGUAGES: &'static [Language] = &Language::ALL_VARIANTS;
+    const GAME: &'static str = "DS";
     type Error = DSError;
+    type ExportFilters = ExportFilters;
 }
 
 // --> serde serializer
@@ -37,46 +135,234 @@ impl InternalSerializerBase for DSLocal {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LocalResource {
     /// Original index of resource
+    #[serde(deserialize_with = "deserialize_index")]
     pub index: usize,
+    /// Only recorded when sorting by uuid, see [`ExportFilters::sort_by_uuid`](crate::games::ExportFilters::sort_by_uuid).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub uuid: Option<Uuid>,
+    /// Number of resources in the exported core, only recorded on the first
+    /// resource of an export and missing in exports written before it was.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resource_count: Option<usize>,
     pub strings: BTreeMap<Language, String>,
+    /// Languages left out of `strings` because they are empty, see
+    /// [`SerializeType::Json`](crate::serialize::SerializeType::Json).
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    pub empty: BTreeSet<Language>,
+    /// Translator notes of the exported languages, blank notes are left out.
+    /// A note missing on import keeps the note of the core.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub notes: BTreeMap<Language, String>,
+    /// Languages that got the text of another language because they were
+    /// empty, see [`ExportFilters::fill_empty_from`](crate::games::ExportFilters::fill_empty_from).
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub filled: BTreeMap<Language, Language>,
+    /// Size of each string in the core, see [`ExportFilters::byte_lengths`](crate::games::ExportFilters::byte_lengths).
+    /// Ignored on import.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub byte_len: BTreeMap<Language, usize>,
+    /// Fields dloc doesn't know, like notes added by other tools. Ignored on
+    /// import, see [`SerializeData::serialize_keeping_fields`](crate::serialize::SerializeData::serialize_keeping_fields).
+    #[serde(flatten)]
+    pub extra: ExtraFields,
 }
 
 impl InternalDataSerializer for DSLocal {
     type Output = Vec<LocalResource>;
 
-    fn internal_serialize(&self, languages: &[Self::Language]) -> Self::Output {
-        self.chunks
-            .iter()
-            .enumerate()
-            .filter_map(|(index, chunk)| match &chunk.variant {
-                ChunkVariants::Localized(loc) => Some(LocalResource {
-                    index,
-                    strings: loc
+    fn internal_serialize(
+        &self,
+        languages: &[Self::Language],
+        omit_empty: bool,
+        filters: &ExportFilters,
+    ) -> Self::Output {
+        let mut result: Self::Output =
+            games::serialize_order(&self.chunks, filters.sort_by_uuid, |i| {
+                self.is_serialized(i, filters)
+            })
+            .into_iter()
+            .filter_map(|index| match &self.chunks[index].variant {
+                ChunkVariants::Localized(loc) => {
+                    let source = filters
+                        .fill_empty_from
+                        .map(|l| (l, loc.string_groups[l].text.as_str()))
+                        .filter(|(_, s)| !s.is_empty());
+                    let mut filled = BTreeMap::new();
+
+                    let (empty, strings): (Vec<_>, Vec<_>) = loc
                         .string_groups
                         .iter()
                         .filter(|(l, _)| languages.contains(l))
-                        .map(|(l, s)| (l, s.text.to_string()))
-                        .collect(),
-                }),
+                        .map(|(l, s)| match source {
+                            Some((source, text)) if s.text.as_str().is_empty() => {
+                                filled.insert(l, source);
+                                (l, text.to_owned())
+                            }
+                            _ => (l, filters.exported(&s.text).to_owned()),
+                        })
+                        .partition(|(_, s)| omit_empty && s.is_empty());
+                    let byte_len = match filters.byte_lengths {
+                        true => strings
+                            .iter()
+                            .map(|(l, s)| (*l, U8String::from(s.clone()).full_size()))
+                            .collect(),
+                        false => BTreeMap::new(),
+                    };
+
+                    Some(LocalResource {
+                        index,
+                        uuid: games::recorded_uuid(&self.chunks, filters.sort_by_uuid, index),
+                        resource_count: None,
+                        strings: strings.into_iter().collect(),
+                        empty: empty.into_iter().map(|(l, _)| l).collect(),
+                        notes: loc
+                            .string_groups
+                            .iter()
+                            .filter(|(l, s)| languages.contains(l) && !s.note.as_str().is_empty())
+                            .map(|(l, s)| (l, s.note.to_string()))
+                            .collect(),
+                        filled,
+                        byte_len,
+                        extra: ExtraFields::new(),
+                    })
+                }
                 ChunkVariants::Others { .. } => None,
             })
+            .collect();
+        if let Some(first) = result.first_mut() {
+            first.resource_count = Some(self.chunks.len());
+        }
+
+        result
+    }
+
+    fn internal_serialize_changed(
+        &self,
+        reference: &Self,
+        languages: &[Self::Language],
+        filters: &ExportFilters,
+    ) -> Result<Self::Output, Self::Error> {
+        if self.chunks.len() != reference.chunks.len() {
+            return Err(DSError::ReferenceDoesntMatch {
+                expected: self.chunks.len(),
+                got: reference.chunks.len(),
+            });
+        }
+
+        let mut result = Vec::new();
+
+        for (index, (chunk, rchunk)) in self.chunks.iter().zip(&reference.chunks).enumerate() {
+            if !self.is_serialized(index, filters) {
+                continue;
+            }
+
+            match (&chunk.variant, &rchunk.variant) {
+                (ChunkVariants::Localized(loc), ChunkVariants::Localized(rloc)) => {
+                    let strings: BTreeMap<_, _> = loc
+                        .string_groups
+                        .iter()
+                        .filter(|(l, s)| {
+                            languages.contains(l)
+                                && s.text.as_str() != rloc.string_groups[*l].text.as_str()
+                        })
+                        .map(|(l, s)| (l, s.text.to_string()))
+                        .collect();
+
+                    if !strings.is_empty() {
+                        result.push(LocalResource {
+                            index,
+                            uuid: None,
+                            resource_count: None,
+                            strings,
+                            empty: BTreeSet::new(),
+                            notes: BTreeMap::new(),
+                            filled: BTreeMap::new(),
+                            byte_len: BTreeMap::new(),
+                            extra: ExtraFields::new(),
+                        });
+                    }
+                }
+                (ChunkVariants::Others { .. }, ChunkVariants::Others { .. }) => (),
+                (c, r) => {
+                    return Err(DSError::ResourceNotMatchAtIdx {
+                        input: c.name(),
+                        original: r.name(),
+                    })
+                }
+            }
+        }
+
+        if let Some(first) = result.first_mut() {
+            first.resource_count = Some(self.chunks.len());
+        }
+
+        Ok(result)
+    }
+
+    fn internal_flatten(data: Self::Output) -> Vec<(usize, usize, Self::Language, String)> {
+        data.into_iter()
+            .flat_map(|local| {
+                let index = local.index;
+                let empty = local.empty.into_iter().map(|l| (l, String::new()));
+                empty
+                    .chain(local.strings)
+                    .map(move |(l, s)| (index, 0, l, s))
+            })
             .collect()
     }
 
-    fn internal_deserialize_and_update(&mut self, data: Self::Output) -> Result<(), Self::Error> {
+    fn internal_copy_extra_fields(data: &mut Self::Output, previous: Self::Output) {
+        copy_extra_fields(data, previous, |l| (l.uuid, l.index, &mut l.extra));
+    }
+
+    fn internal_deserialize_and_update(
+        &mut self,
+        data: Self::Output,
+        options: ImportOptions,
+    ) -> Result<(), Self::Error> {
+        match data.iter().find_map(|l| l.resource_count) {
+            Some(expected) if options.check_resource_count && expected != self.chunks.len() => {
+                return Err(DSError::ResourceCountDoesntMatch {
+                    expected,
+                    got: self.chunks.len(),
+                });
+            }
+            _ => (),
+        }
+        self.validate_input(&data)?;
+
         for local in data {
-            let Some(chunk) = self.chunks.get_mut(local.index) else {
+            let index = games::resolve_index(&self.chunks, local.index, local.uuid)?;
+            let Some(chunk) = self.chunks.get_mut(index) else {
                 return Err(DSError::InvalidLocalResourceIdx {
                     max: self.chunks.len(),
-                    got: local.index,
+                    got: index,
                 });
             };
 
             match &mut chunk.variant {
                 ChunkVariants::Localized(oloc) => {
+                    // the fallback text of filled languages is only written if it was edited
+                    let fallbacks: BTreeMap<_, _> = local
+                        .filled
+                        .iter()
+                        .map(|(l, source)| (*l, oloc.string_groups[*source].text.to_string()))
+                        .collect();
+                    // a language that is also in `strings` keeps its string
+                    for lang in local.empty {
+                        oloc.string_groups[lang].text = String::new().into();
+                    }
                     for (lang, str) in local.strings {
+                        if fallbacks.get(&lang) == Some(&str) {
+                            continue;
+                        }
                         oloc.string_groups[lang].text = str.into();
                     }
+                    for (lang, note) in local.notes {
+                        if !note.is_empty() {
+                            oloc.string_groups[lang].note = note.into();
+                        }
+                    }
                 }
                 ChunkVariants::Others { .. } => {
                     return Err(DSError::ResourceNotMatchAtIdx {
@@ -97,64 +383,98 @@ impl InternalDataSerializer for DSLocal {
 pub struct TxtLocalInfo {
     index: usize,
     range: Range<usize>,
+    /// Only recorded when sorting by uuid, see [`ExportFilters::sort_by_uuid`](crate::games::ExportFilters::sort_by_uuid).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    uuid: Option<Uuid>,
 }
 
 #[derive(Serialize, Deserialize)]
 pub struct TxtDeInfo {
     languages: BTreeSet<Language>,
     add_language_names: bool,
+    #[serde(default = "default_language_separator")]
+    language_separator: String,
+    #[serde(default)]
+    add_indices: bool,
     count: usize,
+    /// Number of resources in the exported core, missing in deinfo files
+    /// written before it was recorded.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    resource_count: Option<usize>,
     info: Vec<TxtLocalInfo>,
 }
 
 impl InternalPlainTextDataSerializer for DSLocal {
     type DeserializeInfo = TxtDeInfo;
 
-    fn internal_serialize_to_lines(
+    fn internal_serialize_lines_with<E, F>(
         &self,
         languages: &[Self::Language],
-        add_language_names: bool,
-    ) -> (Vec<String>, Self::DeserializeInfo) {
+        filters: &ExportFilters,
+        language_separator: Option<&str>,
+        add_indices: bool,
+        mut sink: F,
+    ) -> Result<Self::DeserializeInfo, E>
+    where
+        F: FnMut(String) -> Result<(), E>,
+    {
         let languages = BTreeSet::from_iter(languages.iter().copied());
 
         let mut count = 0;
-        let mut lines = Vec::new();
         let mut info = Vec::new();
 
-        for (index, chunk) in self.chunks.iter().enumerate() {
-            if let ChunkVariants::Localized(loc) = &chunk.variant {
+        for index in games::serialize_order(&self.chunks, filters.sort_by_uuid, |i| {
+            self.is_serialized(i, filters)
+        }) {
+            if let ChunkVariants::Localized(loc) = &self.chunks[index].variant {
                 for lang in languages.iter() {
-                    if add_language_names {
-                        lines.push(format!("{lang}:: {}", loc.string_groups[*lang].text));
-                    } else {
-                        lines.push(loc.string_groups[*lang].text.to_string());
-                    }
-
-                    info.push(TxtLocalInfo {
+                    sink(txt_line(
                         index,
-                        range: count..count + languages.len(),
-                    });
-
-                    count += languages.len();
+                        lang,
+                        filters.exported(&loc.string_groups[*lang].text),
+                        language_separator,
+                        add_indices,
+                    ))?;
                 }
+
+                info.push(TxtLocalInfo {
+                    index,
+                    range: count..count + languages.len(),
+                    uuid: games::recorded_uuid(&self.chunks, filters.sort_by_uuid, index),
+                });
+
+                count += languages.len();
             }
         }
 
-        let info = TxtDeInfo {
+        Ok(TxtDeInfo {
             languages,
-            add_language_names,
+            add_language_names: language_separator.is_some(),
+            language_separator: language_separator
+                .map_or_else(default_language_separator, str::to_owned),
+            add_indices,
             count,
+            resource_count: Some(self.chunks.len()),
             info,
-        };
-
-        (lines, info)
+        })
     }
 
     fn internal_deserialize_and_update_from_lines(
         &mut self,
         lines: &[String],
         deinfo: Self::DeserializeInfo,
+        options: ImportOptions,
     ) -> Result<(), Self::Error> {
+        match deinfo.resource_count {
+            Some(expected) if options.check_resource_count && expected != self.chunks.len() => {
+                return Err(DSError::ResourceCountDoesntMatch {
+                    expected,
+                    got: self.chunks.len(),
+                });
+            }
+            _ => (),
+        }
+
         if lines.len() != deinfo.count {
             return Err(DSError::LineCountDoesntMatchWithInput {
                 expected: deinfo.count,
@@ -162,11 +482,16 @@ impl InternalPlainTextDataSerializer for DSLocal {
             });
         }
 
+        let separator = deinfo
+            .add_language_names
+            .then_some(deinfo.language_separator.as_str());
+
         for info in deinfo.info {
-            let Some(chunk) = self.chunks.get_mut(info.index) else {
+            let index = games::resolve_index(&self.chunks, info.index, info.uuid)?;
+            let Some(chunk) = self.chunks.get_mut(index) else {
                 return Err(DSError::InvalidLocalResourceIdx {
                     max: self.chunks.len(),
-                    got: info.index,
+                    got: index,
                 });
             };
 
@@ -185,15 +510,10 @@ impl InternalPlainTextDataSerializer for DSLocal {
                     );
 
                     for (lang, line) in deinfo.languages.iter().zip(lines) {
-                        oloc.string_groups[*lang].text = if deinfo.add_language_names {
-                            line.strip_prefix((lang.to_string() + "::").as_str())
-                                .unwrap_or_else(|| line)
-                                .trim_start()
-                        } else {
-                            line
-                        }
-                        .to_owned()
-                        .into();
+                        oloc.string_groups[*lang].text =
+                            strip_txt_line(line, info.index, lang, separator, deinfo.add_indices)
+                                .to_owned()
+                                .into();
                     }
                 }
                 ChunkVariants::Others { .. } => {
@@ -207,4 +527,33 @@ impl InternalPlainTextDataSerializer for DSLocal {
 
         Ok(())
     }
+
+    fn internal_check_lines(line_count: usize, deinfo: &Self::DeserializeInfo) -> Vec<String> {
+        let mut issues = Vec::new();
+
+        if line_count != deinfo.count {
+            issues.push(format!(
+                "Line count doesn't match with deinfo, expected {} but got {line_count}",
+                deinfo.count
+            ));
+        }
+
+        for info in deinfo.info.iter() {
+            if info.range.start > info.range.end || info.range.end > line_count {
+                issues.push(format!(
+                    "Range {:?} of resource {} is out of bounds, input have {line_count} lines",
+                    info.range, info.index
+                ));
+            } else if info.range.len() != deinfo.languages.len() {
+                issues.push(format!(
+                    "Resource {} should have {} lines but have {}",
+                    info.index,
+                    deinfo.languages.len(),
+                    info.range.len()
+                ));
+            }
+        }
+
+        issues
+    }
 }
@@ -3,7 +3,10 @@ use std::{fmt::Debug, mem::size_of};
 use binrw::binrw;
 
 use super::FixedMap;
-use crate::{games::chunks::RuntimeSize, utils::types::U8String};
+use crate::{
+    games::{chunks::RuntimeSize, ResourceUuid, Uuid},
+    utils::types::U8String,
+};
 
 pub const LOCALIZED_MAGIC: u64 = 0x31BE502435317445;
 
@@ -12,9 +15,15 @@ pub type Chunk = crate::games::chunks::Chunk<ChunkVariants>;
 #[binrw]
 #[brw(little)]
 #[br(import(magic: u64, size: u32))]
-#[derive(Hash)]
+#[derive(Clone, Hash)]
 pub enum ChunkVariants {
+    // the groups don't have any language code, so at least make sure we
+    // read exactly one group for each language, if not the chunk is kept
+    // as raw data instead of mapping its strings to wrong languages. Unlike
+    // HZD trailing bytes can't be kept here as they can't be told apart from
+    // extra groups, but as raw data they are still written back unchanged
     #[br(pre_assert(magic == LOCALIZED_MAGIC))]
+    #[br(assert(self_0.rt_size() == size, "Invalid core file, localized resource size is {size} but {} bytes were read, the string groups doesn't match the language count", self_0.rt_size()))]
     Localized(Box<Localized>),
     /// Data variant for unknown chunk data.
     /// Stores raw binary data.
@@ -24,6 +33,28 @@ pub enum ChunkVariants {
     },
 }
 
+impl ChunkVariants {
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Self::Localized(_) => "Localized",
+            Self::Others { .. } => "Others",
+        }
+    }
+
+    pub const fn uuid(&self) -> Option<Uuid> {
+        match self {
+            Self::Localized(loc) => Some(Uuid(loc.uuid)),
+            Self::Others { .. } => None,
+        }
+    }
+}
+
+impl ResourceUuid for ChunkVariants {
+    fn resource_uuid(&self) -> Option<Uuid> {
+        self.uuid()
+    }
+}
+
 impl RuntimeSize for ChunkVariants {
     fn rt_size(&self) -> u32 {
         match self {
@@ -47,9 +78,12 @@ impl Debug for ChunkVariants {
 
 #[binrw]
 #[brw(little)]
-#[derive(Hash)]
+#[derive(Clone, Hash)]
 pub struct Localized {
     uuid: [u8; 16],
+    /// Unlike HZD cutscenes, groups don't store a language code, there is
+    /// one group for each language and they are stored in the same order
+    /// as [`Language`](super::Language) variants.
     pub string_groups: FixedMap<LocalGroup>,
 }
 
@@ -79,7 +113,9 @@ impl Debug for Localized {
 #[derive(Clone, Debug, Hash)]
 pub struct LocalGroup {
     pub text: U8String,
-    note: U8String,
+    /// A note for the translators, not shown in game and only exported by
+    /// the Json, Yaml, table, PO and SQLite formats.
+    pub(crate) note: U8String,
     mode: u8,
 }
 
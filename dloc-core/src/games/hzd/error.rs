@@ -1,6 +1,7 @@
 use thiserror::Error;
 
 use super::Language;
+use crate::games::{list_resource_errors, Uuid, UuidNotFound};
 
 #[derive(Debug, Error)]
 pub enum HZDError {
@@ -12,13 +13,48 @@ pub enum HZDError {
         expected: usize,
         got: usize,
     },
+    #[error("Resource at index {index} have {max} lines, but found line {got}")]
+    InvalidLineIdx {
+        index: usize,
+        max: usize,
+        got: usize,
+    },
+    #[error("Resource at index {index} have no strings")]
+    NoStrings { index: usize },
     #[error("Resource not match at index, input is {input} but original is {original}")]
     ResourceNotMatchAtIdx {
         input: &'static str,
         original: &'static str,
     },
+    #[error("Core have {expected} strings in language {lang} but got {got}")]
+    LanguageStringCountDoesntMatch {
+        lang: Language,
+        expected: usize,
+        got: usize,
+    },
+    #[error("Reference resource count doesn't match, expected {expected} but got {got}")]
+    ReferenceDoesntMatch { expected: usize, got: usize },
+    #[error("Export was made from a core with {expected} resources but this core have {got}, it may be from another version of the game")]
+    ResourceCountDoesntMatch { expected: usize, got: usize },
     #[error("Input line count doesn't match with with the deserialize info says, expected {expected} but got {got}")]
     LineCountDoesntMatchWithInput { expected: usize, got: usize },
+    #[error("Lines of resource {index} doesn't match with the languages in deserialize info, did you changed something in deinfo file?")]
+    InvalidLineRanges { index: usize },
     #[error("Found invalid index when tried to read strings from input. max index: {max}, found in index {invalid_index}. are you sure you didn't modifed the data?")]
     InvalidIndex { max: usize, invalid_index: usize },
+    #[error("Useless block of cutscene {index} should have at least 4 bytes, but found {len}")]
+    InvalidUselessBlock { index: usize, len: usize },
+    #[error("Can't find the resource with uuid {uuid} in the core")]
+    UuidNotFound { uuid: Uuid },
+    /// Every resource of the input that doesn't match, by the index of the
+    /// resource in the core. A resource whose uuid isn't in the core keeps
+    /// the index written in the input.
+    #[error("Input doesn't match the core, nothing was imported:{}", list_resource_errors(.errors))]
+    InputDoesntMatch { errors: Vec<(usize, HZDError)> },
+}
+
+impl From<UuidNotFound> for HZDError {
+    fn from(e: UuidNotFound) -> Self {
+        Self::UuidNotFound { uuid: e.0 }
+    }
 }
@@ -1,8 +1,11 @@
 //! Horizon Zero Dawn
 
 use std::{
+    collections::BTreeMap,
     fmt::{Debug, Display},
-    io::{Read, Seek, Write},
+    hash::{Hash, Hasher},
+    io::{Cursor, Read, Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
 };
 
 use binrw::{helpers::until_eof, BinWrite, Endian};
@@ -10,11 +13,19 @@ use serde::{Deserialize, Serialize};
 
 use crate::{
     error::{DResult, Error},
-    utils::{enum_map, types::U8String},
+    games::{
+        self,
+        chunks::{self, chunk_offsets, RuntimeSize},
+        compare_strings,
+        detect::{self, explain_parse_error},
+        synthetic, ParseWarning, ResourceInfo, StringEntry,
+    },
+    patch::{self, Patch},
+    utils::{enum_map, hash_of, types::U8String},
 };
 
-use error::HZDError;
-use structures::{Chunk, ChunkVariants, CutsceneStringGroup};
+pub use error::HZDError;
+use structures::{Chunk, ChunkVariants, Cutscene, CutsceneStringGroup, Localized};
 
 pub(crate) use structures::{CUTSCENE_MAGIC, LOCALIZED_MAGIC};
 
@@ -26,7 +37,7 @@ mod structures;
 enum_map! {
     /// HZD availible languages
     #[derive(Serialize, Deserialize)]
-    Language;
+    Language, games::InvalidLanguage;
 
     English = 0,
     French = 1,
@@ -57,6 +68,31 @@ impl Display for Language {
     }
 }
 
+/// The [`games::ExportFilters`] of Horizon Zero Dawn, with a [`ResourceFilter`].
+pub type ExportFilters = games::ExportFilters<Language, ResourceFilter>;
+
+/// Which kind of local resources get serialized.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ResourceFilter {
+    #[default]
+    All,
+    /// Just the localized resources (UI strings and such).
+    Localized,
+    /// Just the cutscene resources.
+    Cutscene,
+}
+
+impl ResourceFilter {
+    fn allows(self, variant: &ChunkVariants) -> bool {
+        matches!(
+            (self, variant),
+            (Self::All, _)
+                | (Self::Localized, ChunkVariants::Localized(_))
+                | (Self::Cutscene, ChunkVariants::Cutscene(_))
+        )
+    }
+}
+
 /// LocalResource represents a resource loaded from the game files.
 #[derive(Debug, Clone)]
 pub struct LocalResource {
@@ -84,11 +120,104 @@ impl LocalVariants {
     }
 }
 
+/// Cutscenes with a language count that dloc doesn't support are kept as raw
+/// data, so if nothing else is found tell that instead of only saying there
+/// is no local resource.
+fn language_count_error<R: Read + Seek>(reader: &mut R, start: u64) -> Option<Error> {
+    reader.seek(SeekFrom::Start(start)).ok()?;
+    let cutscene = detect::hzd_cutscene_languages(reader)
+        .ok()?
+        .into_iter()
+        .find(|c| !c.matches())?;
+
+    Some(Error::LanguageCountMismatch {
+        index: cutscene.index,
+        declared: cutscene.declared,
+        expected: detect::CutsceneLanguages::EXPECTED,
+    })
+}
+
+/// Iterate over the strings of a single chunk, see [`HZDLocal::strings`].
+fn chunk_strings(
+    index: usize,
+    variant: &ChunkVariants,
+) -> Box<dyn Iterator<Item = StringEntry<'_, Language>> + '_> {
+    match variant {
+        ChunkVariants::Localized(loc) => {
+            Box::new(loc.strings.iter().map(move |(language, s)| StringEntry {
+                index,
+                language,
+                sub: 0,
+                string: s.as_str(),
+            }))
+        }
+        ChunkVariants::Cutscene(cut) => Box::new(cut.list.iter().flat_map(move |(language, g)| {
+            g.strings_data
+                .iter()
+                .enumerate()
+                .map(move |(sub, s)| StringEntry {
+                    index,
+                    language,
+                    sub,
+                    string: s.string.as_str(),
+                })
+        })),
+        ChunkVariants::Others { .. } => Box::new(std::iter::empty()),
+    }
+}
+
+/// Timing of every cutscene line, keyed by resource index, line index and language.
+pub type CutsceneTimings = BTreeMap<usize, BTreeMap<usize, BTreeMap<Language, u64>>>;
+
+/// Path of the timings sidecar of an export, `out.json` gives `out.timings.json`
+/// and `out.json.gz` gives `out.timings.json.gz`.
+#[cfg(feature = "serialize")]
+pub fn timings_path(export: &Path) -> PathBuf {
+    sidecar_path(export, "timings.json")
+}
+
+/// Bytes of a cutscene that dloc doesn't know the meaning of, they are
+/// only exposed for research and kept as is by normal imports.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CutsceneRawData {
+    /// The block after the uuid, including the 4 bytes after it.
+    pub useless_block: Vec<u8>,
+    /// The bytes at the end of the cutscene.
+    pub unk: [u8; 5],
+}
+
+/// Path of the raw data sidecar of an export, `out.json` gives `out.raw.json`
+/// and `out.json.gz` gives `out.raw.json.gz`.
+#[cfg(feature = "serialize")]
+pub fn raw_data_path(export: &Path) -> PathBuf {
+    sidecar_path(export, "raw.json")
+}
+
+/// Replace the extension of `export` with `extension`, keeping the
+/// compression of the export so the sidecar is compressed the same way.
+#[cfg(feature = "serialize")]
+fn sidecar_path(export: &Path, extension: &str) -> PathBuf {
+    match crate::serialize::Compression::from_path(export) {
+        Some(compression) => export
+            .with_extension("")
+            .with_extension(format!("{extension}.{}", compression.extension())),
+        None => export.with_extension(extension),
+    }
+}
+
 /// HZDLocal is used to load localization resources from Horizon Zero Dawn
 /// and store them.
-#[derive(Debug, Hash)]
+#[derive(Debug)]
 pub struct HZDLocal {
     chunks: Vec<Chunk>,
+    /// hash of the chunks at load time, used to track modifications
+    load_hash: u64,
+}
+
+impl Hash for HZDLocal {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.chunks.hash(state);
+    }
 }
 
 impl HZDLocal {
@@ -102,18 +231,190 @@ impl HZDLocal {
     /// A [`DResult`] with a HZDLocal containing the loaded chunks in case of success.
     /// and a [`Error`] on case of failure.
     pub fn new<R: Read + Seek>(mut reader: R) -> DResult<Self> {
-        let chunks: Vec<Chunk> = until_eof(&mut reader, Endian::Little, ())?;
+        let start = reader.stream_position()?;
+        let chunks: Vec<Chunk> = until_eof(&mut reader, Endian::Little, ())
+            .map_err(|e| explain_parse_error(&mut reader, start, e.into()))?;
 
+        Self::from_chunks(chunks, &mut reader, start)
+    }
+
+    /// Creates a new [HZDLocal] from a damaged core, when a resource fails
+    /// to parse the parser skips ahead to the next local resource that does
+    /// and keeps the skipped bytes as a raw resource. Use it when [`Self::new`]
+    /// fails, a core that isn't damaged is read the same by both.
+    ///
+    /// ## Returns:
+    /// A [`DResult`] with the HZDLocal and its [`warnings`](Self::warnings),
+    /// with a [`ParseWarning::Damaged`] for every skipped region first.
+    pub fn new_repairing<R: Read + Seek>(
+        mut reader: R,
+    ) -> DResult<(Self, Vec<ParseWarning<Language>>)> {
+        let mut data = Vec::new();
+        reader.read_to_end(&mut data)?;
+
+        let (chunks, mut warnings) =
+            chunks::read_chunks_repairing(&data, &[LOCALIZED_MAGIC, CUTSCENE_MAGIC], |data| {
+                ChunkVariants::Others { data }
+            });
+        let local = Self::from_chunks(chunks, &mut Cursor::new(&data), 0)?;
+        warnings.extend(local.warnings());
+        Ok((local, warnings))
+    }
+
+    /// Check that the `chunks` read from the core starting at `start` of
+    /// `reader` have a local resource and create the [HZDLocal].
+    fn from_chunks<R: Read + Seek>(
+        chunks: Vec<Chunk>,
+        reader: &mut R,
+        start: u64,
+    ) -> DResult<Self> {
         if !chunks.iter().any(|c| {
             matches!(
                 c.variant,
                 ChunkVariants::Cutscene(_) | ChunkVariants::Localized(_)
             )
         }) {
-            return Err(Error::NoLocalResource);
+            if let Some(error) = language_count_error(reader, start) {
+                return Err(error);
+            }
+            return Err(explain_parse_error(reader, start, Error::NoLocalResource));
+        }
+
+        Ok(Self {
+            load_hash: hash_of(&chunks),
+            chunks,
+        })
+    }
+
+    /// Creates a [`HZDLocal`] with an unknown chunk followed by
+    /// `resource_count` made up resources, alternating between localized
+    /// and cutscene ones with strings in every language. Used to check dloc
+    /// without any game file.
+    pub fn synthetic(resource_count: usize) -> DResult<Self> {
+        let mut input = synthetic::chunk(0xD10C, vec![0; 8]);
+        for resource in 0..resource_count {
+            let mut data = synthetic::uuid(resource).to_vec();
+            if resource % 2 == 0 {
+                for language in 0..Language::ALL_VARIANTS.len() {
+                    synthetic::push_u8_string(&mut data, &synthetic::string(resource, language, 0));
+                }
+                input.extend(synthetic::chunk(LOCALIZED_MAGIC, data));
+                continue;
+            }
+
+            let lines = 1 + resource % 3;
+            data.extend(0_u32.to_le_bytes());
+            data.extend([0_u8; 4]);
+            data.extend((Language::ALL_VARIANTS.len() as u32).to_le_bytes());
+            for language in 0..Language::ALL_VARIANTS.len() {
+                data.extend((language as u32 + 1).to_le_bytes());
+                data.extend((lines as u32).to_le_bytes());
+                for line in 0..lines {
+                    let string = synthetic::string(resource, language, line);
+                    data.extend((string.encode_utf16().count() as u32).to_le_bytes());
+                    data.extend(string.encode_utf16().flat_map(u16::to_le_bytes));
+                    data.extend((line as u64 * 1000).to_le_bytes());
+                }
+            }
+            data.extend([0_u8; 5]);
+            input.extend(synthetic::chunk(CUTSCENE_MAGIC, data));
+        }
+
+        Self::new(Cursor::new(input))
+    }
+
+    /// Same as [`Self::new`] but also returns the [`warnings`](Self::warnings)
+    /// of the loaded core.
+    pub fn new_with_warnings<R: Read + Seek>(
+        reader: R,
+    ) -> DResult<(Self, Vec<ParseWarning<Language>>)> {
+        let local = Self::new(reader)?;
+        let warnings = local.warnings();
+        Ok((local, warnings))
+    }
+
+    /// Returns the non fatal oddities of the core, like cutscenes that are
+    /// kept as raw data or strings close to their length limit.
+    pub fn warnings(&self) -> Vec<ParseWarning<Language>> {
+        let offsets = self.chunk_offsets();
+        let mut warnings = Vec::new();
+        for (index, chunk) in self.chunks.iter().enumerate() {
+            let trailing = match &chunk.variant {
+                ChunkVariants::Localized(loc) => loc.trailing_len(),
+                ChunkVariants::Cutscene(cut) => cut.trailing_len(),
+                ChunkVariants::Others { .. } => 0,
+            };
+            if trailing != 0 {
+                warnings.push(ParseWarning::TrailingBytes {
+                    index,
+                    len: trailing,
+                });
+            }
+
+            match &chunk.variant {
+                ChunkVariants::Localized(_) => {
+                    warnings.extend(games::length_warnings(chunk_strings(index, &chunk.variant)))
+                }
+                ChunkVariants::Cutscene(cut) => {
+                    let codes: Vec<_> = cut.list.iter().map(|(_, g)| g.lang_code).collect();
+                    warnings.extend(codes.windows(2).filter(|w| w[0] == w[1]).map(|w| {
+                        ParseWarning::DuplicateLangCode {
+                            index,
+                            lang_code: w[0],
+                        }
+                    }));
+
+                    if cut.list.iter().any(|(_, g)| !g.strings_data.is_empty()) {
+                        warnings.extend(
+                            cut.list
+                                .iter()
+                                .filter(|(_, g)| g.strings_data.is_empty())
+                                .map(|(language, _)| ParseWarning::EmptyCutsceneGroup {
+                                    index,
+                                    language,
+                                }),
+                        );
+                    }
+                }
+                ChunkVariants::Others { data } => {
+                    let offset = offsets[index] + 12;
+                    let size = (data.len() as u32,);
+                    let invalid = match chunk.magic() {
+                        LOCALIZED_MAGIC => games::invalid_string_warning::<Localized, _, _>(
+                            index, offset, data, size,
+                        ),
+                        CUTSCENE_MAGIC => games::invalid_string_warning::<Cutscene, _, _>(
+                            index, offset, data, size,
+                        ),
+                        _ => continue,
+                    };
+                    warnings.push(ParseWarning::KeptAsRaw { index });
+                    warnings.extend(invalid);
+                }
+            }
         }
+        warnings
+    }
 
-        Ok(Self { chunks })
+    /// Returns `true` if the content was modified since it was loaded.
+    pub fn is_modified(&self) -> bool {
+        hash_of(&self.chunks) != self.load_hash
+    }
+
+    /// Returns `true` if the chunk at `index` pass the resource, uuid and
+    /// content `filters`.
+    fn is_serialized(&self, index: usize, filters: &ExportFilters) -> bool {
+        self.chunks.get(index).is_some_and(|c| {
+            filters.resources.allows(&c.variant)
+                && filters.allows(c.variant.uuid(), chunk_strings(index, &c.variant))
+        })
+    }
+
+    /// Returns `true` if the chunk of `entry` is serialized and the string
+    /// itself pass the content filter.
+    #[cfg(feature = "serialize")]
+    fn is_serialized_string(&self, entry: &StringEntry<Language>, filters: &ExportFilters) -> bool {
+        self.is_serialized(entry.index, filters) && filters.allows_string(entry.string)
     }
 
     /// Get all Local resources inside the file.
@@ -136,6 +437,393 @@ impl HZDLocal {
             .collect()
     }
 
+    /// Iterate over every string of every language inside the file without cloning them.
+    ///
+    /// The strings come in chunk order, then language order and for cutscenes
+    /// line order inside each language.
+    pub fn strings(&self) -> impl Iterator<Item = StringEntry<'_, Language>> {
+        self.chunks
+            .iter()
+            .enumerate()
+            .flat_map(|(index, c)| chunk_strings(index, &c.variant))
+    }
+
+    /// Clone every string of `language` into a [`Vec`].
+    ///
+    /// The strings are in the same order as [`strings`](Self::strings), so
+    /// by resource index with localized and cutscene resources mixed and
+    /// their lines are in order.
+    /// This is the order of the lines of the `extract-language` and
+    /// `import-language` commands, and the order
+    /// [`set_language_strings`](Self::set_language_strings) expects.
+    pub fn language_strings(&self, language: Language) -> Vec<String> {
+        self.strings()
+            .filter(|e| e.language == language)
+            .map(|e| e.string.to_owned())
+            .collect()
+    }
+
+    /// Replace every string of `language` with `strings`, the counterpart of
+    /// [`language_strings`](Self::language_strings) with the same order.
+    ///
+    /// ## Returns:
+    /// Result with [`HZDError`] on failure, nothing is changed if `strings`
+    /// doesn't have exactly one string for each of the localized strings and cutscene lines of `language`.
+    pub fn set_language_strings(
+        &mut self,
+        language: Language,
+        strings: Vec<String>,
+    ) -> Result<(), HZDError> {
+        let positions: Vec<_> = self
+            .strings()
+            .filter(|e| e.language == language)
+            .map(|e| (e.index, e.sub))
+            .collect();
+
+        if positions.len() != strings.len() {
+            return Err(HZDError::LanguageStringCountDoesntMatch {
+                lang: language,
+                expected: positions.len(),
+                got: strings.len(),
+            });
+        }
+
+        for ((index, sub), string) in positions.into_iter().zip(strings) {
+            self.set_string(index, language, sub, string)?;
+        }
+
+        Ok(())
+    }
+
+    /// Get the languages that have at least one non empty string in any
+    /// resource, languages that were never localized in this core are left out.
+    pub fn populated_languages(&self) -> Vec<Language> {
+        games::populated_languages(&Language::ALL_VARIANTS, self.strings())
+    }
+
+    /// Count the strings of the given `languages` in the resources that pass
+    /// the resource, uuid and content `filters`. This is the number of
+    /// strings a Json, Yaml or txt export with the same languages will have,
+    /// the formats with a line or row for each string can have less as they
+    /// also leave out the strings that don't match the content filter
+    /// themselves.
+    pub fn count_strings(&self, languages: &[Language], filters: &ExportFilters) -> usize {
+        self.strings()
+            .filter(|e| self.is_serialized(e.index, filters) && languages.contains(&e.language))
+            .count()
+    }
+
+    /// Get the index, variant name, size, declared size, uuid and string count of every resource
+    /// inside the file, including the non local ones.
+    pub fn resource_overview(&self) -> Vec<ResourceInfo> {
+        games::resource_overview(
+            self.chunks.iter().map(|c| {
+                (
+                    c.variant.name(),
+                    c.variant.rt_size(),
+                    c.declared_size(),
+                    c.variant.uuid(),
+                )
+            }),
+            self.strings(),
+        )
+    }
+
+    /// Get a single string of a local resource.
+    ///
+    /// ## Arguments:
+    /// * `index` - Index of the resource.
+    /// * `language` - Language of the string.
+    /// * `sub` - Line index inside a cutscene, should be `0` for other resources.
+    ///
+    /// ## Returns:
+    /// Result with the string or [`HZDError`] if there is no such string.
+    pub fn get_string(
+        &self,
+        index: usize,
+        language: Language,
+        sub: usize,
+    ) -> Result<&str, HZDError> {
+        let Some(chunk) = self.chunks.get(index) else {
+            return Err(HZDError::InvalidLocalResourceIdx {
+                max: self.chunks.len(),
+                got: index,
+            });
+        };
+
+        match &chunk.variant {
+            ChunkVariants::Localized(loc) if sub == 0 => Ok(loc.strings[language].as_str()),
+            ChunkVariants::Localized(_) => Err(HZDError::InvalidLineIdx {
+                index,
+                max: 1,
+                got: sub,
+            }),
+            ChunkVariants::Cutscene(cut) => {
+                let lines = &cut.list[language].strings_data;
+                lines
+                    .get(sub)
+                    .map(|line| line.string.as_str())
+                    .ok_or(HZDError::InvalidLineIdx {
+                        index,
+                        max: lines.len(),
+                        got: sub,
+                    })
+            }
+            ChunkVariants::Others { .. } => Err(HZDError::NoStrings { index }),
+        }
+    }
+
+    /// Replace a single string of a local resource.
+    ///
+    /// ## Arguments:
+    /// * `index` - Index of the resource.
+    /// * `language` - Language of the string.
+    /// * `sub` - Line index inside a cutscene, should be `0` for other resources.
+    /// * `string` - The new string.
+    ///
+    /// ## Returns:
+    /// Result with [`HZDError`] on failure.
+    pub fn set_string(
+        &mut self,
+        index: usize,
+        language: Language,
+        sub: usize,
+        string: String,
+    ) -> Result<(), HZDError> {
+        let max = self.chunks.len();
+        let Some(chunk) = self.chunks.get_mut(index) else {
+            return Err(HZDError::InvalidLocalResourceIdx { max, got: index });
+        };
+
+        match &mut chunk.variant {
+            ChunkVariants::Localized(loc) if sub == 0 => loc.strings[language] = string.into(),
+            ChunkVariants::Localized(_) => {
+                return Err(HZDError::InvalidLineIdx {
+                    index,
+                    max: 1,
+                    got: sub,
+                })
+            }
+            ChunkVariants::Cutscene(cut) => {
+                let lines = &mut cut.list[language].strings_data;
+                let max = lines.len();
+                let Some(line) = lines.get_mut(sub) else {
+                    return Err(HZDError::InvalidLineIdx {
+                        index,
+                        max,
+                        got: sub,
+                    });
+                };
+                line.string = string.into();
+            }
+            ChunkVariants::Others { .. } => return Err(HZDError::NoStrings { index }),
+        }
+
+        Ok(())
+    }
+
+    /// Get the timing of every cutscene line, keyed by resource index, line
+    /// index and language.
+    pub fn cutscene_timings(&self) -> CutsceneTimings {
+        let mut timings = CutsceneTimings::new();
+        for (index, c) in self.chunks.iter().enumerate() {
+            let ChunkVariants::Cutscene(cut) = &c.variant else {
+                continue;
+            };
+            let lines = timings.entry(index).or_default();
+            for (language, g) in cut.list.iter() {
+                for (sub, data) in g.strings_data.iter().enumerate() {
+                    lines.entry(sub).or_default().insert(language, data.timing);
+                }
+            }
+        }
+        timings
+    }
+
+    /// Replace the timing of a single cutscene line.
+    ///
+    /// ## Arguments:
+    /// * `index` - Index of the cutscene resource.
+    /// * `sub` - Line index inside the cutscene.
+    /// * `language` - Language of the line.
+    /// * `timing` - The new timing.
+    ///
+    /// ## Returns:
+    /// Result with [`HZDError`] on failure.
+    pub fn set_timing(
+        &mut self,
+        index: usize,
+        sub: usize,
+        language: Language,
+        timing: u64,
+    ) -> Result<(), HZDError> {
+        self.check_cutscene_line(index, sub, language)?;
+        if let ChunkVariants::Cutscene(cut) = &mut self.chunks[index].variant {
+            cut.list[language].strings_data[sub].timing = timing;
+        }
+
+        Ok(())
+    }
+
+    /// Check that the resource at `index` is a cutscene with a line `sub`
+    /// in `language`, without changing anything.
+    fn check_cutscene_line(
+        &self,
+        index: usize,
+        sub: usize,
+        language: Language,
+    ) -> Result<(), HZDError> {
+        let Some(chunk) = self.chunks.get(index) else {
+            return Err(HZDError::InvalidLocalResourceIdx {
+                max: self.chunks.len(),
+                got: index,
+            });
+        };
+
+        let ChunkVariants::Cutscene(cut) = &chunk.variant else {
+            return Err(HZDError::ResourceNotMatchAtIdx {
+                input: "Cutscene",
+                original: chunk.variant.name(),
+            });
+        };
+
+        let max = cut.list[language].strings_data.len();
+        if sub >= max {
+            return Err(HZDError::InvalidLineIdx {
+                index,
+                max,
+                got: sub,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Get the unknown bytes of every cutscene, keyed by resource index.
+    pub fn cutscene_raw_data(&self) -> BTreeMap<usize, CutsceneRawData> {
+        self.chunks
+            .iter()
+            .enumerate()
+            .filter_map(|(index, c)| match &c.variant {
+                ChunkVariants::Cutscene(cut) => Some((index, cut.raw_data())),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Replace the unknown bytes of a cutscene, see [`CutsceneRawData`].
+    ///
+    /// ## Arguments:
+    /// * `index` - Index of the cutscene resource.
+    /// * `raw` - The new bytes, the useless block should have at least 4 bytes.
+    ///
+    /// ## Returns:
+    /// Result with [`HZDError`] on failure.
+    pub fn set_cutscene_raw_data(
+        &mut self,
+        index: usize,
+        raw: CutsceneRawData,
+    ) -> Result<(), HZDError> {
+        let max = self.chunks.len();
+        let Some(chunk) = self.chunks.get_mut(index) else {
+            return Err(HZDError::InvalidLocalResourceIdx { max, got: index });
+        };
+
+        let ChunkVariants::Cutscene(cut) = &mut chunk.variant else {
+            return Err(HZDError::ResourceNotMatchAtIdx {
+                input: "Cutscene",
+                original: chunk.variant.name(),
+            });
+        };
+
+        let len = raw.useless_block.len();
+        if !cut.set_raw_data(raw) {
+            return Err(HZDError::InvalidUselessBlock { index, len });
+        }
+
+        Ok(())
+    }
+
+    /// Write the language groups of every cutscene in the order of `languages`,
+    /// the languages that aren't in it come after in their current order.
+    ///
+    /// Groups are always sorted by their language code when read, but unless
+    /// this is called they are written back in the order they had in the
+    /// core. Use it for game builds that expect another order.
+    pub fn set_cutscene_group_order(&mut self, languages: &[Language]) {
+        for chunk in self.chunks.iter_mut() {
+            let ChunkVariants::Cutscene(cut) = &mut chunk.variant else {
+                continue;
+            };
+
+            let mut order: Vec<usize> = Vec::with_capacity(cut.write_order.len());
+            for index in languages.iter().copied().map(usize::from) {
+                if !order.contains(&index) {
+                    order.push(index);
+                }
+            }
+            let rest: Vec<usize> = (cut.write_order.iter().copied())
+                .filter(|i| !order.contains(i))
+                .collect();
+            order.extend(rest);
+            cut.write_order = order;
+        }
+    }
+
+    /// Exchange the strings of languages `a` and `b` in every resource,
+    /// useful to find out which language the game actually reads.
+    ///
+    /// Cutscene groups keep their language code and their place in the
+    /// write order, only their lines are swapped.
+    pub fn swap_languages(&mut self, a: Language, b: Language) {
+        for chunk in self.chunks.iter_mut() {
+            match &mut chunk.variant {
+                ChunkVariants::Localized(loc) => loc.strings.swap(a, b),
+                ChunkVariants::Cutscene(cut) => {
+                    cut.list.swap(a, b);
+                    let code = cut.list[a].lang_code;
+                    cut.list[a].lang_code = cut.list[b].lang_code;
+                    cut.list[b].lang_code = code;
+                }
+                ChunkVariants::Others { .. } => {}
+            }
+        }
+    }
+
+    /// Replace the resource at `index` with the one at the same index of
+    /// `from`, with every language and line, to port a fix between two cores.
+    /// Both resources should be of the same kind, unlike an import the line
+    /// count of a cutscene may change.
+    ///
+    /// ## Arguments:
+    /// * `from` - The core to copy the resource from.
+    /// * `index` - Index of the resource in both cores.
+    ///
+    /// ## Returns:
+    /// Result with [`HZDError`] on failure.
+    pub fn copy_resource(&mut self, from: &Self, index: usize) -> Result<(), HZDError> {
+        let Some(source) = from.chunks.get(index) else {
+            return Err(HZDError::InvalidLocalResourceIdx {
+                max: from.chunks.len(),
+                got: index,
+            });
+        };
+        let max = self.chunks.len();
+        let Some(chunk) = self.chunks.get_mut(index) else {
+            return Err(HZDError::InvalidLocalResourceIdx { max, got: index });
+        };
+
+        if source.magic() != chunk.magic() {
+            return Err(HZDError::ResourceNotMatchAtIdx {
+                input: source.variant.name(),
+                original: chunk.variant.name(),
+            });
+        }
+        chunk.copy_from(source);
+
+        Ok(())
+    }
+
     /// Updates the local resources in this [`HZDLocal`] with the provided locals.
     ///
     /// ## Arguments:
@@ -185,6 +873,44 @@ impl HZDLocal {
         Ok(())
     }
 
+    /// Make a [`Patch`] of the strings that differ from `reference`, which
+    /// should be the vanilla version of this core.
+    pub fn create_patch(&self, reference: &Self) -> DResult<Patch> {
+        Patch::diff(
+            patch::GAME_HZD,
+            reference.chunks.len(),
+            reference.strings(),
+            self.strings(),
+        )
+    }
+
+    /// Apply a [`Patch`] made by [`Self::create_patch`] and returns the
+    /// number of patched strings. Nothing is changed if the patch is for
+    /// another game, was made from another version of this core or have
+    /// strings that don't exist in this core.
+    pub fn apply_patch(&mut self, patch: &Patch) -> DResult<usize> {
+        let strings = patch.strings(patch::GAME_HZD, self.chunks.len(), self.strings())?;
+        let count = strings.len();
+        // every string was checked to exist, so this can't fail half way
+        for (index, sub, language, string) in strings {
+            self.set_string(index, language, sub, string)
+                .map_err(|e| Error::InvalidPatch(e.to_string()))?;
+        }
+        Ok(count)
+    }
+
+    /// Returns the byte offset of each chunk (resource index) in the core it
+    /// was read from, chunks that are written after an import can end up at
+    /// other offsets.
+    ///
+    /// Core files don't have any header or boundary marker, so when several
+    /// cores are concatenated in one file there is no way to tell where each
+    /// one ends, but these offsets together with the size of the original
+    /// cores can be used to know which core each chunk came from.
+    pub fn chunk_offsets(&self) -> Vec<u64> {
+        chunk_offsets(&self.chunks)
+    }
+
     /// Writes the chunks in this [`HZDLocal`] to the given writer.
     ///
     /// ## Arguments:
@@ -196,6 +922,29 @@ impl HZDLocal {
         self.chunks.write(writer)?;
         Ok(())
     }
+
+    /// Writes the chunks in this [`HZDLocal`] to a new buffer and return it.
+    ///
+    /// ## Returns:
+    /// Return the written bytes or a [`Error`] on failure.
+    pub fn to_bytes(&self) -> DResult<Vec<u8>> {
+        let mut writer = Cursor::new(Vec::new());
+        self.write(&mut writer)?;
+        Ok(writer.into_inner())
+    }
+
+    /// Read back a core written from this [`HZDLocal`] and check that it
+    /// parses and have the same strings, to catch invalid written files.
+    ///
+    /// ## Arguments:
+    /// * `reader` - The reader of the written core.
+    ///
+    /// ## Returns:
+    /// Return [`Error::VerifyFailed`] if the strings doesn't match.
+    pub fn verify_written<R: Read + Seek>(&self, reader: R) -> DResult<()> {
+        let written = Self::new(reader)?;
+        compare_strings(self.strings(), written.strings()).map_err(Error::VerifyFailed)
+    }
 }
 
 impl From<FixedMap<U8String>> for FixedMap<String> {
@@ -214,3 +963,545 @@ impl From<FixedMap<CutsceneStringGroup>> for FixedMap<Vec<String>> {
         })
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::{error::StringEncoding, games::Uuid};
+
+    use super::*;
+
+    fn cutscene_chunk(lang_count: u32) -> Vec<u8> {
+        let mut data = synthetic::uuid(0).to_vec();
+        data.extend(0_u32.to_le_bytes());
+        data.extend([0_u8; 4]);
+        data.extend(lang_count.to_le_bytes());
+        synthetic::chunk(CUTSCENE_MAGIC, data)
+    }
+
+    #[test]
+    fn cutscene_groups_keep_their_order() {
+        // a cutscene with its groups stored in reverse language order
+        let mut data = synthetic::uuid(0).to_vec();
+        data.extend(0_u32.to_le_bytes());
+        data.extend([0_u8; 4]);
+        data.extend((Language::ALL_VARIANTS.len() as u32).to_le_bytes());
+        for language in (0..Language::ALL_VARIANTS.len()).rev() {
+            let string: Vec<u16> = format!("line {language}").encode_utf16().collect();
+            data.extend((language as u32 + 1).to_le_bytes());
+            data.extend(1_u32.to_le_bytes());
+            data.extend((string.len() as u32).to_le_bytes());
+            data.extend(string.iter().flat_map(|c| c.to_le_bytes()));
+            data.extend(0_u64.to_le_bytes());
+        }
+        data.extend([0_u8; 5]);
+        let input = synthetic::chunk(CUTSCENE_MAGIC, data);
+
+        let mut local = HZDLocal::new(Cursor::new(&input)).unwrap();
+        assert_eq!(local.language_strings(Language::English), ["line 0"]);
+        assert_eq!(local.to_bytes().unwrap(), input);
+
+        local.set_cutscene_group_order(&[]);
+        assert_eq!(local.to_bytes().unwrap(), input);
+
+        local.set_cutscene_group_order(&[Language::English]);
+        let output = local.to_bytes().unwrap();
+        assert_ne!(output, input);
+        let written = HZDLocal::new(Cursor::new(&output)).unwrap();
+        assert!(written.strings().eq(local.strings()));
+        assert_eq!(written.to_bytes().unwrap(), output);
+
+        // english is written first, then the rest in the order of the core
+        let english: Vec<u8> = "line 0".encode_utf16().flat_map(u16::to_le_bytes).collect();
+        let position = output.windows(english.len()).position(|w| w == english);
+        assert_eq!(position, Some(8 + 4 + 16 + 4 + 4 + 4 + 4 + 4 + 4));
+    }
+
+    #[test]
+    fn swapping_languages_twice_changes_nothing() {
+        let source = HZDLocal::synthetic(4).unwrap();
+        let english = source.language_strings(Language::English);
+        let arabic = source.language_strings(Language::Arabic);
+
+        let mut swapped = HZDLocal::synthetic(4).unwrap();
+        swapped.swap_languages(Language::English, Language::Arabic);
+        assert_eq!(swapped.language_strings(Language::English), arabic);
+        assert_eq!(swapped.language_strings(Language::Arabic), english);
+        assert!(swapped.is_modified());
+
+        let written = HZDLocal::new(Cursor::new(swapped.to_bytes().unwrap())).unwrap();
+        assert!(written.strings().eq(swapped.strings()));
+
+        swapped.swap_languages(Language::Arabic, Language::English);
+        assert_eq!(swapped.to_bytes().unwrap(), source.to_bytes().unwrap());
+    }
+
+    #[test]
+    fn copy_resource_replaces_the_whole_resource() {
+        let mut source = HZDLocal::synthetic(4).unwrap();
+        source
+            .set_string(2, Language::German, 1, "fixed".to_owned())
+            .unwrap();
+        source
+            .set_string(1, Language::French, 0, "also fixed".to_owned())
+            .unwrap();
+
+        // read back so the declared size of the source differs
+        let source = HZDLocal::new(Cursor::new(source.to_bytes().unwrap())).unwrap();
+
+        let mut target = HZDLocal::synthetic(4).unwrap();
+        let declared = target.resource_overview()[2].declared_size;
+        target.copy_resource(&source, 2).unwrap();
+        assert_eq!(
+            target.get_string(2, Language::German, 1).ok(),
+            Some("fixed")
+        );
+        let copied = &target.resource_overview()[2];
+        assert_eq!(copied.declared_size, declared);
+        assert_ne!(copied.size, declared);
+        assert_ne!(
+            target.get_string(1, Language::French, 0).ok(),
+            Some("also fixed")
+        );
+        assert!(matches!(
+            target.copy_resource(&source, 9),
+            Err(HZDError::InvalidLocalResourceIdx { max: 5, got: 9 })
+        ));
+
+        // without the unknown first chunk every resource moves down by one
+        let bytes = source.to_bytes().unwrap();
+        let first = 12 + u32::from_le_bytes(bytes[8..12].try_into().unwrap()) as usize;
+        let shifted = HZDLocal::new(Cursor::new(&bytes[first..])).unwrap();
+        assert!(matches!(
+            target.copy_resource(&shifted, 1),
+            Err(HZDError::ResourceNotMatchAtIdx { .. })
+        ));
+    }
+
+    #[test]
+    fn uuid_filter_keeps_resource_indices() {
+        let local = HZDLocal::synthetic(4).unwrap();
+        let uuids: Vec<_> = local.resource_overview().iter().map(|r| r.uuid).collect();
+        assert_eq!(uuids[3], Some(Uuid(synthetic::uuid(2))));
+
+        let filters = ExportFilters {
+            uuids: vec![Uuid(synthetic::uuid(2))],
+            ..Default::default()
+        };
+        assert!((0..5).all(|i| local.is_serialized(i, &filters) == (i == 3)));
+
+        // the filter stays with the call, the core itself isn't filtered
+        assert!((1..5).all(|i| local.is_serialized(i, &ExportFilters::default())));
+    }
+
+    #[test]
+    fn empty_languages_are_not_populated() {
+        let mut local = HZDLocal::synthetic(4).unwrap();
+        assert_eq!(local.populated_languages(), Language::ALL_VARIANTS);
+
+        let korean: Vec<_> = local
+            .strings()
+            .filter(|e| e.language == Language::Korean)
+            .map(|e| (e.index, e.sub))
+            .collect();
+        for (index, sub) in korean {
+            local
+                .set_string(index, Language::Korean, sub, String::new())
+                .unwrap();
+        }
+        let populated = local.populated_languages();
+        assert!(!populated.contains(&Language::Korean));
+        assert_eq!(populated.len(), Language::ALL_VARIANTS.len() - 1);
+    }
+
+    #[test]
+    fn language_strings_compose_with_set_string() {
+        let source = HZDLocal::synthetic(4).unwrap();
+        let strings = source.language_strings(Language::German);
+        assert!(!strings.is_empty());
+
+        let mut target = HZDLocal::synthetic(4).unwrap();
+        let positions: Vec<_> = target
+            .strings()
+            .filter(|e| e.language == Language::German)
+            .map(|e| (e.index, e.sub))
+            .collect();
+        for (index, sub) in positions.iter().copied() {
+            target
+                .set_string(index, Language::German, sub, String::new())
+                .unwrap();
+        }
+        for ((index, sub), string) in positions.into_iter().zip(strings) {
+            target
+                .set_string(index, Language::German, sub, string)
+                .unwrap();
+        }
+
+        assert_eq!(target.to_bytes().unwrap(), source.to_bytes().unwrap());
+    }
+
+    #[test]
+    fn set_language_strings_round_trip() {
+        let source = HZDLocal::synthetic(4).unwrap();
+        let strings = source.language_strings(Language::German);
+
+        let mut target = HZDLocal::synthetic(4).unwrap();
+        let empty = vec![String::new(); strings.len()];
+        target
+            .set_language_strings(Language::German, empty)
+            .unwrap();
+        assert_ne!(target.to_bytes().unwrap(), source.to_bytes().unwrap());
+
+        assert!(matches!(
+            target.set_language_strings(Language::German, Vec::new()),
+            Err(HZDError::LanguageStringCountDoesntMatch { got: 0, .. })
+        ));
+
+        target
+            .set_language_strings(Language::German, strings)
+            .unwrap();
+        assert_eq!(target.to_bytes().unwrap(), source.to_bytes().unwrap());
+    }
+
+    #[test]
+    fn chunks_are_written_without_padding() {
+        let mut local = HZDLocal::synthetic(5).unwrap();
+        local
+            .set_string(1, Language::English, 0, "odd".to_owned())
+            .unwrap();
+
+        let written = local.to_bytes().unwrap();
+        let offsets = HZDLocal::new(Cursor::new(&written))
+            .unwrap()
+            .chunk_offsets();
+        let ends = offsets
+            .iter()
+            .skip(1)
+            .copied()
+            .chain([written.len() as u64]);
+        for (offset, end) in offsets.iter().zip(ends) {
+            let start = *offset as usize;
+            let size = u32::from_le_bytes(written[start + 8..start + 12].try_into().unwrap());
+            assert_eq!(*offset + 12 + size as u64, end);
+        }
+    }
+
+    #[test]
+    fn chunk_offsets_are_the_read_offsets() {
+        let mut local = HZDLocal::synthetic(3).unwrap();
+        let before = local.chunk_offsets();
+        local
+            .set_string(1, Language::English, 0, "long".repeat(32))
+            .unwrap();
+        assert_eq!(local.chunk_offsets(), before);
+
+        let written = local.to_bytes().unwrap();
+        let after = HZDLocal::new(Cursor::new(&written))
+            .unwrap()
+            .chunk_offsets();
+        assert_eq!(after[..2], before[..2]);
+        assert!(after[2] > before[2]);
+    }
+
+    #[test]
+    fn trailing_bytes_are_kept() {
+        let local = HZDLocal::synthetic(3).unwrap();
+        let mut input = local.to_bytes().unwrap();
+        let offsets = local.chunk_offsets();
+        // resource 1 is localized and 2 is a cutscene
+        for index in [2, 1] {
+            let start = offsets[index] as usize;
+            let size = u32::from_le_bytes(input[start + 8..start + 12].try_into().unwrap());
+            let end = start + 12 + size as usize;
+            input.splice(end..end, [0xAB; 3]);
+            input[start + 8..start + 12].copy_from_slice(&(size + 3).to_le_bytes());
+        }
+
+        let mut local = HZDLocal::new(Cursor::new(&input)).unwrap();
+        assert_eq!(
+            local
+                .warnings()
+                .into_iter()
+                .filter(|w| matches!(w, ParseWarning::TrailingBytes { len: 3, .. }))
+                .count(),
+            2
+        );
+        assert_eq!(local.to_bytes().unwrap(), input);
+
+        local
+            .set_string(1, Language::English, 0, "longer than before".to_owned())
+            .unwrap();
+        let written = local.to_bytes().unwrap();
+        let reread = HZDLocal::new(Cursor::new(&written)).unwrap();
+        assert_eq!(reread.to_bytes().unwrap(), written);
+        assert_eq!(reread.warnings().len(), local.warnings().len());
+    }
+
+    #[test]
+    fn invalid_string_is_located() {
+        let mut input = HZDLocal::synthetic(2).unwrap().to_bytes().unwrap();
+        let text = input
+            .windows(12)
+            .position(|w| w == b"First line 0")
+            .unwrap();
+        input[text] = 0xFF;
+
+        let local = HZDLocal::new(Cursor::new(&input)).unwrap();
+        assert_eq!(
+            local.warnings()[..2],
+            [
+                ParseWarning::KeptAsRaw { index: 1 },
+                ParseWarning::InvalidString {
+                    index: 1,
+                    position: text as u64 - 2,
+                    kind: StringEncoding::Utf8,
+                }
+            ]
+        );
+    }
+
+    #[test]
+    fn strings_come_in_chunk_then_language_order() {
+        let local = HZDLocal::synthetic(2).unwrap();
+        let entries: Vec<_> = local.strings().collect();
+
+        // one localized string and a cutscene with two lines per language
+        let languages = Language::ALL_VARIANTS.len();
+        assert_eq!(entries.len(), languages * 3);
+        assert!(entries
+            .windows(2)
+            .all(|w| (w[0].index, w[0].language) <= (w[1].index, w[1].language)));
+        assert_eq!(
+            entries[0],
+            StringEntry {
+                index: 1,
+                language: Language::ALL_VARIANTS[0],
+                sub: 0,
+                string: &synthetic::string(0, 0, 0),
+            }
+        );
+        assert!(entries[languages..]
+            .chunks(2)
+            .all(|lines| lines[0].sub == 0 && lines[1].sub == 1));
+    }
+
+    #[test]
+    fn synthetic_core_has_every_resource() {
+        let local = HZDLocal::synthetic(4).unwrap();
+        let overview = local.resource_overview();
+        assert!(overview.iter().map(|r| r.variant).eq([
+            "Others",
+            "Localized",
+            "Cutscene",
+            "Localized",
+            "Cutscene"
+        ]));
+
+        let input = local.to_bytes().unwrap();
+        assert_eq!(
+            HZDLocal::new(Cursor::new(&input))
+                .unwrap()
+                .to_bytes()
+                .unwrap(),
+            input
+        );
+    }
+
+    #[test]
+    fn get_string_reads_what_set_string_wrote() {
+        let mut local = HZDLocal::synthetic(2).unwrap();
+        local
+            .set_string(2, Language::French, 1, "bonjour".to_owned())
+            .unwrap();
+        assert_eq!(local.get_string(2, Language::French, 1).unwrap(), "bonjour");
+        assert!(local.strings().any(|e| e.index == 1
+            && e.language == Language::German
+            && local.get_string(1, Language::German, 0).ok() == Some(e.string)));
+
+        assert!(matches!(
+            local.get_string(2, Language::French, 2),
+            Err(HZDError::InvalidLineIdx { max: 2, got: 2, .. })
+        ));
+        assert!(matches!(
+            local.get_string(1, Language::French, 1),
+            Err(HZDError::InvalidLineIdx { max: 1, .. })
+        ));
+        assert!(matches!(
+            local.get_string(0, Language::French, 0),
+            Err(HZDError::NoStrings { index: 0 })
+        ));
+        assert!(matches!(
+            local.get_string(3, Language::French, 0),
+            Err(HZDError::InvalidLocalResourceIdx { max: 3, got: 3 })
+        ));
+    }
+
+    #[test]
+    fn repairing_skips_a_damaged_resource() {
+        let input = HZDLocal::synthetic(4).unwrap().to_bytes().unwrap();
+        let mut damaged = input.clone();
+        // size of the first localized resource, right after the 20 bytes of the unknown chunk
+        damaged[28..32].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert!(HZDLocal::new(Cursor::new(&damaged)).is_err());
+
+        let (local, warnings) = HZDLocal::new_repairing(Cursor::new(&damaged)).unwrap();
+        assert!(matches!(
+            warnings[..2],
+            [
+                ParseWarning::Damaged {
+                    offset: 20,
+                    kept: true,
+                    ..
+                },
+                ParseWarning::KeptAsRaw { index: 1 }
+            ]
+        ));
+        assert_eq!(local.strings().map(|e| e.index).min(), Some(2));
+        assert_eq!(local.resource_overview()[1].declared_size, u32::MAX);
+        assert_eq!(local.to_bytes().unwrap(), input);
+
+        let (_, warnings) = HZDLocal::new_repairing(Cursor::new(&input)).unwrap();
+        assert!(warnings.is_empty());
+    }
+
+    #[test]
+    fn declared_size_stays_after_import() {
+        let input = HZDLocal::synthetic(2).unwrap().to_bytes().unwrap();
+        let mut local = HZDLocal::new(Cursor::new(&input)).unwrap();
+        assert!(local
+            .resource_overview()
+            .iter()
+            .all(|r| r.size == r.declared_size));
+
+        let before = local.resource_overview()[1];
+        local
+            .set_string(1, Language::English, 0, "a longer string".repeat(4))
+            .unwrap();
+        let after = local.resource_overview()[1];
+        assert_eq!(after.declared_size, before.declared_size);
+        assert!(after.size > after.declared_size);
+    }
+
+    #[test]
+    fn unknown_magics_are_tallied_per_core() {
+        let mut first = synthetic::chunk(0xD10C, vec![0; 8]);
+        first.extend(cutscene_chunk(3));
+        first.extend(synthetic::chunk(0xD10C, vec![0; 4]));
+        let second = synthetic::chunk(0xBEEF, vec![]);
+
+        let mut tally = std::collections::BTreeMap::new();
+        detect::tally_unknown_magics(&mut Cursor::new(&first), &mut tally).unwrap();
+        detect::tally_unknown_magics(&mut Cursor::new(&second), &mut tally).unwrap();
+
+        assert_eq!(
+            tally.into_iter().collect::<Vec<_>>(),
+            [
+                (
+                    0xBEEF,
+                    detect::UnknownMagic {
+                        chunks: 1,
+                        cores: 1,
+                        bytes: 0
+                    }
+                ),
+                (
+                    0xD10C,
+                    detect::UnknownMagic {
+                        chunks: 2,
+                        cores: 1,
+                        bytes: 12
+                    }
+                )
+            ]
+        );
+    }
+
+    #[test]
+    fn unsupported_language_count_is_reported() {
+        let mut input = synthetic::chunk(0, vec![]);
+        input.extend(cutscene_chunk(3));
+
+        let cutscenes = detect::hzd_cutscene_languages(&mut Cursor::new(&input)).unwrap();
+        assert_eq!(
+            cutscenes,
+            [detect::CutsceneLanguages {
+                index: 1,
+                declared: 3
+            }]
+        );
+        assert!(!cutscenes[0].matches());
+
+        assert!(matches!(
+            HZDLocal::new(Cursor::new(&input)),
+            Err(Error::LanguageCountMismatch {
+                index: 1,
+                declared: 3,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn cutscene_raw_data_is_replaced() {
+        let mut local = HZDLocal::synthetic(2).unwrap();
+        assert_eq!(
+            local.cutscene_raw_data()[&2],
+            CutsceneRawData {
+                useless_block: vec![0; 4],
+                unk: [0; 5],
+            }
+        );
+
+        let raw = CutsceneRawData {
+            useless_block: vec![1, 2, 3, 4, 5, 6],
+            unk: [7, 8, 9, 10, 11],
+        };
+        local.set_cutscene_raw_data(2, raw.clone()).unwrap();
+        let written = HZDLocal::new(Cursor::new(local.to_bytes().unwrap())).unwrap();
+        assert_eq!(written.cutscene_raw_data()[&2], raw);
+        assert_eq!(
+            written.get_string(2, Language::English, 1).unwrap(),
+            local.get_string(2, Language::English, 1).unwrap()
+        );
+
+        let short = CutsceneRawData {
+            useless_block: vec![1, 2, 3],
+            unk: [0; 5],
+        };
+        assert!(matches!(
+            local.set_cutscene_raw_data(2, short.clone()),
+            Err(HZDError::InvalidUselessBlock { index: 2, len: 3 })
+        ));
+        assert!(matches!(
+            local.set_cutscene_raw_data(1, short),
+            Err(HZDError::ResourceNotMatchAtIdx { .. })
+        ));
+        assert_eq!(local.cutscene_raw_data()[&2], raw);
+    }
+
+    #[cfg(feature = "serialize")]
+    #[test]
+    fn po_comments_name_the_resource() {
+        use crate::serialize::private::InternalStringAccess;
+
+        let mut data = vec![0_u8; 16];
+        for language in Language::ALL_VARIANTS {
+            let text = language.to_string();
+            data.extend((text.len() as u16).to_le_bytes());
+            data.extend(text.as_bytes());
+        }
+        let mut input = vec![0_u8; 8];
+        input.extend(0_u32.to_le_bytes());
+        input.extend(LOCALIZED_MAGIC.to_le_bytes());
+        input.extend((data.len() as u32).to_le_bytes());
+        input.extend(data);
+
+        let local = HZDLocal::new(Cursor::new(&input)).unwrap();
+        assert_eq!(
+            local
+                .internal_string_comment(1, 0, Language::English)
+                .as_deref(),
+            Some("Localized 1")
+        );
+        assert_eq!(local.internal_string_comment(2, 0, Language::English), None);
+    }
+}
@@ -1,35 +1,108 @@
 use std::{
     collections::{BTreeMap, BTreeSet},
-    fs::File,
-    io::{BufReader, BufWriter},
+    io::{Read, Seek, Write},
     ops::Range,
+    path::Path,
 };
 
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    error::DResult,
-    serialize::private::{
-        InternalDataSerializer, InternalGroupExtractor, InternalPlainTextDataSerializer,
-        InternalSerializerBase,
+    error::{DResult, Error},
+    games::{self, StringEntry, Uuid},
+    serialize::{
+        copy_extra_fields, default_language_separator, open_export,
+        private::{
+            InternalDataSerializer, InternalGroupExtractor, InternalPlainTextDataSerializer,
+            InternalSerializerBase, InternalStringAccess,
+        },
+        strip_txt_line, txt_line, write_json, ExportWriter, ExtraFields, ImportOptions,
+    },
+    utils::{
+        deserialize_index,
+        types::{U16String, U8String},
     },
 };
 
-use super::{error::HZDError, structures::ChunkVariants, HZDLocal, Language};
+use super::{
+    error::HZDError, structures::ChunkVariants, CutsceneRawData, CutsceneTimings, ExportFilters,
+    HZDLocal, Language,
+};
 
 impl InternalGroupExtractor for HZDLocal {
-    fn internal_new(reader: BufReader<File>) -> DResult<Self> {
+    fn internal_new<R: Read + Seek>(reader: R) -> DResult<Self> {
         Self::new(reader)
     }
 
-    fn internal_write(&self, mut writer: BufWriter<File>) -> DResult<()> {
-        self.write(&mut writer)
+    fn internal_write<W: Write + Seek>(&self, writer: &mut W) -> DResult<()> {
+        self.write(writer)
+    }
+}
+
+impl InternalStringAccess for HZDLocal {
+    fn internal_strings(&self) -> Box<dyn Iterator<Item = StringEntry<'_, Self::Language>> + '_> {
+        Box::new(self.strings())
+    }
+
+    fn internal_exported_strings<'a>(
+        &'a self,
+        filters: &'a ExportFilters,
+    ) -> Box<dyn Iterator<Item = StringEntry<'a, Self::Language>> + 'a> {
+        Box::new(
+            self.strings()
+                .filter(|e| self.is_serialized_string(e, filters))
+                .map(|e| StringEntry {
+                    string: filters.exported(e.string),
+                    ..e
+                }),
+        )
+    }
+
+    fn internal_set_string(
+        &mut self,
+        index: usize,
+        sub: usize,
+        language: Self::Language,
+        string: String,
+    ) -> Result<(), Self::Error> {
+        self.set_string(index, language, sub, string)
+    }
+
+    fn internal_string_extras(
+        &self,
+        index: usize,
+        sub: usize,
+        language: Self::Language,
+    ) -> (Option<&str>, Option<u64>) {
+        let timing = match self.chunks.get(index).map(|c| &c.variant) {
+            Some(ChunkVariants::Cutscene(cut)) => {
+                cut.list[language].strings_data.get(sub).map(|s| s.timing)
+            }
+            _ => None,
+        };
+        (None, timing)
+    }
+
+    fn internal_string_comment(
+        &self,
+        index: usize,
+        sub: usize,
+        _language: Self::Language,
+    ) -> Option<String> {
+        match self.chunks.get(index).map(|c| &c.variant) {
+            Some(ChunkVariants::Cutscene(_)) => Some(format!("Cutscene {index}, line {sub}")),
+            Some(variant) => Some(format!("{} {index}", variant.name())),
+            None => None,
+        }
     }
 }
 
 impl InternalSerializerBase for HZDLocal {
     type Language = Language;
+    const ALL_LANGUAGES: &'static [Language] = &Language::ALL_VARIANTS;
+    const GAME: &'static str = "HZD";
     type Error = HZDError;
+    type ExportFilters = ExportFilters;
 }
 
 // --> serde serializer
@@ -37,7 +110,28 @@ impl InternalSerializerBase for HZDLocal {
 #[derive(Serialize, Deserialize)]
 pub enum SerdeLocalVariants {
     Localized(BTreeMap<Language, String>),
-    Cutscene(BTreeMap<Language, Vec<String>>),
+    Cutscene(BTreeMap<Language, SerdeCutsceneLines>),
+}
+
+/// Lines of a cutscene for a single language, either all of them or
+/// just the ones that should change by their line index.
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum SerdeCutsceneLines {
+    All(Vec<String>),
+    Sparse(BTreeMap<SerdeLineIndex, String>),
+}
+
+/// Line index inside a cutscene, map keys are always strings in JSON so
+/// both numbers and numeric strings are accepted.
+#[derive(Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(transparent)]
+pub struct SerdeLineIndex(#[serde(deserialize_with = "deserialize_index")] usize);
+
+impl From<Vec<String>> for SerdeCutsceneLines {
+    fn from(value: Vec<String>) -> Self {
+        Self::All(value)
+    }
 }
 
 impl SerdeLocalVariants {
@@ -51,72 +145,378 @@ impl SerdeLocalVariants {
 
 #[derive(Serialize, Deserialize)]
 pub struct SerdeLocal {
+    #[serde(deserialize_with = "deserialize_index")]
     index: usize,
+    /// Only recorded when sorting by uuid, see [`ExportFilters::sort_by_uuid`](crate::games::ExportFilters::sort_by_uuid).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    uuid: Option<Uuid>,
+    /// Number of resources in the exported core, only recorded on the first
+    /// resource of an export and missing in exports written before it was.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    resource_count: Option<usize>,
     #[serde(flatten)]
     variant: SerdeLocalVariants,
+    /// Languages left out of `variant` because all of their strings are empty.
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    empty: BTreeSet<Language>,
+    /// Languages that got the text of another language because they were
+    /// empty, see [`ExportFilters::fill_empty_from`](crate::games::ExportFilters::fill_empty_from).
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    filled: BTreeMap<Language, Language>,
+    /// Size of each string in the core, localized strings are UTF-8 with a
+    /// 2 byte prefix and cutscene lines are UTF-16 with a 4 byte prefix, see
+    /// [`ExportFilters::byte_lengths`](crate::games::ExportFilters::byte_lengths).
+    /// Ignored on import.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    byte_len: BTreeMap<Language, SerdeByteLen>,
+    /// Fields dloc doesn't know, like notes added by other tools. Ignored on
+    /// import, see [`SerializeData::serialize_keeping_fields`](crate::serialize::SerializeData::serialize_keeping_fields).
+    #[serde(flatten)]
+    extra: ExtraFields,
+}
+
+/// Size of a localized string or of each line of a cutscene.
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum SerdeByteLen {
+    String(usize),
+    Lines(Vec<usize>),
 }
 
 impl InternalDataSerializer for HZDLocal {
     type Output = Vec<SerdeLocal>;
 
-    fn internal_serialize(&self, languages: &[Self::Language]) -> Self::Output {
+    fn internal_serialize(
+        &self,
+        languages: &[Self::Language],
+        omit_empty: bool,
+        filters: &ExportFilters,
+    ) -> Self::Output {
         let locals = self.get_locals();
         let mut result = Vec::with_capacity(locals.len());
 
         macro_rules! add_just_langs {
-            ($index:expr, $loc:expr, $variant:ident) => {
+            ($index:expr, $loc:expr, $variant:ident, $is_empty:expr, $fill:expr, $clear:expr, $size:expr) => {{
+                let mut loc = $loc;
+                if filters.blank {
+                    languages.iter().for_each(|l| $clear(&mut loc[*l]));
+                }
+                let mut filled = BTreeMap::new();
+                if let Some(source) = filters.fill_empty_from {
+                    let source_strings = loc[source].clone();
+                    for &lang in languages.iter().filter(|l| **l != source) {
+                        if $fill(&mut loc[lang], &source_strings) {
+                            filled.insert(lang, source);
+                        }
+                    }
+                }
+
+                let (empty, strings): (Vec<_>, Vec<_>) = loc
+                    .into_iter()
+                    .filter(|(l, _)| languages.contains(l))
+                    .partition(|(_, s)| omit_empty && $is_empty(s));
+                let byte_len = match filters.byte_lengths {
+                    true => strings.iter().map(|(l, s)| (*l, $size(s))).collect(),
+                    false => BTreeMap::new(),
+                };
+
                 result.push(SerdeLocal {
                     index: $index,
+                    uuid: games::recorded_uuid(&self.chunks, filters.sort_by_uuid, $index),
+                    resource_count: None,
                     variant: SerdeLocalVariants::$variant(
-                        $loc.into_iter()
-                            .filter(|(l, _)| languages.contains(l))
-                            .collect(),
+                        strings.into_iter().map(|(l, s)| (l, s.into())).collect(),
                     ),
+                    empty: empty.into_iter().map(|(l, _)| l).collect(),
+                    filled,
+                    byte_len,
+                    extra: ExtraFields::new(),
                 })
-            };
+            }};
         }
 
-        for local in locals {
+        for local in locals
+            .into_iter()
+            .filter(|l| self.is_serialized(l.index, filters))
+        {
             match local.variant {
                 super::LocalVariants::Localized(loc) => {
-                    add_just_langs!(local.index, loc, Localized);
+                    add_just_langs!(
+                        local.index,
+                        loc,
+                        Localized,
+                        |s: &String| s.is_empty(),
+                        |s: &mut String, source: &String| fill_empty(s, source),
+                        String::clear,
+                        |s: &String| SerdeByteLen::String(U8String::from(s.clone()).full_size())
+                    );
                 }
                 super::LocalVariants::Cutscene(cut) => {
-                    add_just_langs!(local.index, cut, Cutscene);
+                    add_just_langs!(
+                        local.index,
+                        cut,
+                        Cutscene,
+                        |s: &Vec<String>| s.iter().all(String::is_empty),
+                        |lines: &mut Vec<String>, source: &Vec<String>| lines
+                            .iter_mut()
+                            .zip(source)
+                            .fold(false, |filled, (s, source)| fill_empty(s, source) | filled),
+                        |lines: &mut Vec<String>| lines.iter_mut().for_each(String::clear),
+                        |lines: &Vec<String>| SerdeByteLen::Lines(
+                            lines
+                                .iter()
+                                .map(|s| U16String::from(s.clone()).full_size())
+                                .collect()
+                        )
+                    );
                 }
             }
         }
 
+        if filters.sort_by_uuid {
+            result.sort_by_key(|l| l.uuid);
+        }
+        if let Some(first) = result.first_mut() {
+            first.resource_count = Some(self.chunks.len());
+        }
+
         result
     }
 
-    fn internal_deserialize_and_update(&mut self, data: Self::Output) -> Result<(), Self::Error> {
+    fn internal_serialize_changed(
+        &self,
+        reference: &Self,
+        languages: &[Self::Language],
+        filters: &ExportFilters,
+    ) -> Result<Self::Output, Self::Error> {
+        if self.chunks.len() != reference.chunks.len() {
+            return Err(HZDError::ReferenceDoesntMatch {
+                expected: self.chunks.len(),
+                got: reference.chunks.len(),
+            });
+        }
+
+        let mut result = Vec::new();
+
+        for (index, (chunk, rchunk)) in self.chunks.iter().zip(&reference.chunks).enumerate() {
+            if !self.is_serialized(index, filters) {
+                continue;
+            }
+
+            match (&chunk.variant, &rchunk.variant) {
+                (ChunkVariants::Localized(loc), ChunkVariants::Localized(rloc)) => {
+                    let strings: BTreeMap<_, _> = loc
+                        .strings
+                        .iter()
+                        .filter(|(l, s)| {
+                            languages.contains(l) && s.as_str() != rloc.strings[*l].as_str()
+                        })
+                        .map(|(l, s)| (l, s.to_string()))
+                        .collect();
+
+                    if !strings.is_empty() {
+                        result.push(SerdeLocal {
+                            index,
+                            uuid: None,
+                            resource_count: None,
+                            variant: SerdeLocalVariants::Localized(strings),
+                            empty: BTreeSet::new(),
+                            filled: BTreeMap::new(),
+                            byte_len: BTreeMap::new(),
+                            extra: ExtraFields::new(),
+                        });
+                    }
+                }
+                (ChunkVariants::Cutscene(cut), ChunkVariants::Cutscene(rcut)) => {
+                    let list: BTreeMap<_, _> =
+                        cut.list
+                            .iter()
+                            .filter(|(l, g)| {
+                                languages.contains(l)
+                                    && !g.strings_data.iter().map(|s| s.string.as_str()).eq(rcut
+                                        .list[*l]
+                                        .strings_data
+                                        .iter()
+                                        .map(|s| s.string.as_str()))
+                            })
+                            .map(|(l, g)| {
+                                (
+                                    l,
+                                    g.strings_data
+                                        .iter()
+                                        .map(|s| s.string.to_string())
+                                        .collect::<Vec<_>>()
+                                        .into(),
+                                )
+                            })
+                            .collect();
+
+                    if !list.is_empty() {
+                        result.push(SerdeLocal {
+                            index,
+                            uuid: None,
+                            resource_count: None,
+                            variant: SerdeLocalVariants::Cutscene(list),
+                            empty: BTreeSet::new(),
+                            filled: BTreeMap::new(),
+                            byte_len: BTreeMap::new(),
+                            extra: ExtraFields::new(),
+                        });
+                    }
+                }
+                (ChunkVariants::Others { .. }, ChunkVariants::Others { .. }) => (),
+                (c, r) => {
+                    return Err(HZDError::ResourceNotMatchAtIdx {
+                        input: c.name(),
+                        original: r.name(),
+                    })
+                }
+            }
+        }
+
+        if let Some(first) = result.first_mut() {
+            first.resource_count = Some(self.chunks.len());
+        }
+
+        Ok(result)
+    }
+
+    fn internal_flatten(data: Self::Output) -> Vec<(usize, usize, Self::Language, String)> {
+        let mut strings = Vec::new();
+        for local in data {
+            let index = local.index;
+            match local.variant {
+                SerdeLocalVariants::Localized(map) => {
+                    strings.extend(
+                        local
+                            .empty
+                            .into_iter()
+                            .map(|l| (index, 0, l, String::new())),
+                    );
+                    strings.extend(map.into_iter().map(|(l, s)| (index, 0, l, s)));
+                }
+                // the line count of an omitted cutscene language isn't known,
+                // so they are left out
+                SerdeLocalVariants::Cutscene(map) => {
+                    for (language, lines) in map {
+                        match lines {
+                            SerdeCutsceneLines::All(lines) => strings.extend(
+                                lines
+                                    .into_iter()
+                                    .enumerate()
+                                    .map(|(sub, s)| (index, sub, language, s)),
+                            ),
+                            SerdeCutsceneLines::Sparse(lines) => strings.extend(
+                                lines
+                                    .into_iter()
+                                    .map(|(sub, s)| (index, sub.0, language, s)),
+                            ),
+                        }
+                    }
+                }
+            }
+        }
+        strings
+    }
+
+    fn internal_copy_extra_fields(data: &mut Self::Output, previous: Self::Output) {
+        copy_extra_fields(data, previous, |l| (l.uuid, l.index, &mut l.extra));
+    }
+
+    fn internal_deserialize_and_update(
+        &mut self,
+        data: Self::Output,
+        options: ImportOptions,
+    ) -> Result<(), Self::Error> {
+        match data.iter().find_map(|l| l.resource_count) {
+            Some(expected) if options.check_resource_count && expected != self.chunks.len() => {
+                return Err(HZDError::ResourceCountDoesntMatch {
+                    expected,
+                    got: self.chunks.len(),
+                });
+            }
+            _ => (),
+        }
+        self.validate_input(&data)?;
+
         for local in data {
-            let Some(chunk) = self.chunks.get_mut(local.index) else {
+            let index = games::resolve_index(&self.chunks, local.index, local.uuid)?;
+            let Some(chunk) = self.chunks.get_mut(index) else {
                 return Err(HZDError::InvalidLocalResourceIdx {
                     max: self.chunks.len(),
-                    got: local.index,
+                    got: index,
                 });
             };
 
             match (local.variant, &mut chunk.variant) {
                 (SerdeLocalVariants::Localized(loc), ChunkVariants::Localized(oloc)) => {
+                    // the fallback text of filled languages is only written if it was edited
+                    let fallbacks: BTreeMap<_, _> = local
+                        .filled
+                        .iter()
+                        .map(|(l, source)| (*l, oloc.strings[*source].to_string()))
+                        .collect();
+                    // a language that is also in the strings keeps its string
+                    for lang in local.empty {
+                        oloc.strings[lang] = String::new().into();
+                    }
                     for (lang, str) in loc.into_iter() {
+                        if fallbacks.get(&lang) == Some(&str) {
+                            continue;
+                        }
                         oloc.strings[lang] = str.into();
                     }
                 }
                 (SerdeLocalVariants::Cutscene(cut), ChunkVariants::Cutscene(oloc)) => {
-                    for (lang, list) in cut.into_iter() {
-                        if list.len() != oloc.list[lang].strings_data.len() {
-                            return Err(HZDError::CutsceneLinesDoesntMatch {
-                                lang,
-                                expected: oloc.list[lang].strings_data.len(),
-                                got: list.len(),
-                            });
+                    let fallbacks: BTreeMap<_, Vec<_>> = local
+                        .filled
+                        .iter()
+                        .map(|(l, source)| {
+                            let lines = &oloc.list[*source].strings_data;
+                            (*l, lines.iter().map(|s| s.string.to_string()).collect())
+                        })
+                        .collect();
+                    let is_fallback = |lang, sub: usize, str: &String| {
+                        fallbacks.get(&lang).and_then(|l| l.get(sub)) == Some(str)
+                    };
+                    for lang in local.empty {
+                        for csd in oloc.list[lang].strings_data.iter_mut() {
+                            csd.string = String::new().into();
                         }
+                    }
+                    for (lang, lines) in cut.into_iter() {
+                        let str_data = &mut oloc.list[lang].strings_data;
+                        match lines {
+                            SerdeCutsceneLines::All(list) => {
+                                if list.len() != str_data.len() {
+                                    return Err(HZDError::CutsceneLinesDoesntMatch {
+                                        lang,
+                                        expected: str_data.len(),
+                                        got: list.len(),
+                                    });
+                                }
 
-                        for (csd, str) in oloc.list[lang].strings_data.iter_mut().zip(list) {
-                            csd.string = str.into();
+                                for (sub, (csd, str)) in str_data.iter_mut().zip(list).enumerate() {
+                                    if !is_fallback(lang, sub, &str) {
+                                        csd.string = str.into();
+                                    }
+                                }
+                            }
+                            SerdeCutsceneLines::Sparse(lines) => {
+                                let max = str_data.len();
+                                for (SerdeLineIndex(sub), str) in lines {
+                                    let Some(csd) = str_data.get_mut(sub) else {
+                                        return Err(HZDError::InvalidLineIdx {
+                                            index,
+                                            max,
+                                            got: sub,
+                                        });
+                                    };
+                                    if !is_fallback(lang, sub, &str) {
+                                        csd.string = str.into();
+                                    }
+                                }
+                            }
                         }
                     }
                 }
@@ -133,6 +533,15 @@ impl InternalDataSerializer for HZDLocal {
     }
 }
 
+/// Put `source` in `string` if it's empty, returns `true` if it was filled.
+fn fill_empty(string: &mut String, source: &str) -> bool {
+    if !string.is_empty() || source.is_empty() {
+        return false;
+    }
+    string.push_str(source);
+    true
+}
+
 // --> txt serializer
 
 #[derive(Serialize, Deserialize)]
@@ -155,59 +564,108 @@ pub struct TxtLocalInfo {
     index: usize,
     range: Range<usize>,
     variant: TxtLocalVariants,
+    /// Only recorded when sorting by uuid, see [`ExportFilters::sort_by_uuid`](crate::games::ExportFilters::sort_by_uuid).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    uuid: Option<Uuid>,
+    /// Number of lines of each language in a cutscene, empty for other
+    /// resources and in deinfo files written before it existed.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    line_counts: Vec<usize>,
+}
+
+/// Number of lines of each language in a cutscene with `total` lines, if
+/// the deinfo doesn't have them the lines are divided equally between
+/// the languages. [`None`] if the lines can't belong to the languages.
+fn cutscene_line_counts(
+    line_counts: &[usize],
+    total: usize,
+    languages: usize,
+) -> Option<Vec<usize>> {
+    if line_counts.is_empty() {
+        if languages == 0 {
+            return (total == 0).then(Vec::new);
+        }
+        return (total % languages == 0).then(|| vec![total / languages; languages]);
+    }
+
+    (line_counts.len() == languages && line_counts.iter().sum::<usize>() == total)
+        .then(|| line_counts.to_vec())
 }
 
 #[derive(Serialize, Deserialize)]
 pub struct TxtDeInfo {
     languages: BTreeSet<Language>,
     add_language_names: bool,
+    #[serde(default = "default_language_separator")]
+    language_separator: String,
+    #[serde(default)]
+    add_indices: bool,
     count: usize,
+    /// Number of resources in the exported core, missing in deinfo files
+    /// written before it was recorded.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    resource_count: Option<usize>,
     info: Vec<TxtLocalInfo>,
 }
 
 impl InternalPlainTextDataSerializer for HZDLocal {
     type DeserializeInfo = TxtDeInfo;
 
-    fn internal_serialize_to_lines(
+    fn internal_serialize_lines_with<E, F>(
         &self,
         languages: &[Self::Language],
-        add_language_names: bool,
-    ) -> (Vec<String>, Self::DeserializeInfo) {
+        filters: &ExportFilters,
+        language_separator: Option<&str>,
+        add_indices: bool,
+        mut sink: F,
+    ) -> Result<Self::DeserializeInfo, E>
+    where
+        F: FnMut(String) -> Result<(), E>,
+    {
         let languages = BTreeSet::from_iter(languages.iter().copied());
 
         let mut count = 0;
-        let mut lines = Vec::new();
         let mut info = Vec::new();
 
-        for (index, chunk) in self.chunks.iter().enumerate() {
-            match &chunk.variant {
+        for index in games::serialize_order(&self.chunks, filters.sort_by_uuid, |i| {
+            self.is_serialized(i, filters)
+        }) {
+            match &self.chunks[index].variant {
                 ChunkVariants::Localized(loc) => {
                     for lang in languages.iter() {
-                        if add_language_names {
-                            lines.push(format!("{lang}:: {}", loc.strings[*lang]));
-                        } else {
-                            lines.push(loc.strings[*lang].to_string());
-                        }
+                        sink(txt_line(
+                            index,
+                            lang,
+                            filters.exported(&loc.strings[*lang]),
+                            language_separator,
+                            add_indices,
+                        ))?;
                     }
 
                     info.push(TxtLocalInfo {
                         index,
                         range: count..count + languages.len(),
                         variant: TxtLocalVariants::Localized,
+                        uuid: games::recorded_uuid(&self.chunks, filters.sort_by_uuid, index),
+                        line_counts: Vec::new(),
                     });
 
                     count += languages.len();
                 }
                 ChunkVariants::Cutscene(cut) => {
                     let mut t_count = 0;
+                    let mut line_counts = Vec::with_capacity(languages.len());
                     for lang in languages.iter() {
+                        line_counts.push(cut.list[*lang].strings_data.len());
                         for str_data in cut.list[*lang].strings_data.iter() {
                             t_count += 1;
-                            lines.push(if add_language_names {
-                                format!("{lang}:: {}", str_data.string)
-                            } else {
-                                str_data.string.to_string()
-                            })
+                            sink(txt_line(
+                                index,
+                                lang,
+                                filters.exported(&str_data.string),
+                                language_separator,
+                                add_indices,
+                            ))?;
                         }
                     }
 
@@ -215,6 +673,8 @@ impl InternalPlainTextDataSerializer for HZDLocal {
                         index,
                         range: count..count + t_count,
                         variant: TxtLocalVariants::Cutscene,
+                        uuid: games::recorded_uuid(&self.chunks, filters.sort_by_uuid, index),
+                        line_counts,
                     });
 
                     count += t_count;
@@ -223,21 +683,34 @@ impl InternalPlainTextDataSerializer for HZDLocal {
             }
         }
 
-        let info = TxtDeInfo {
+        Ok(TxtDeInfo {
             languages,
-            add_language_names,
+            add_language_names: language_separator.is_some(),
+            language_separator: language_separator
+                .map_or_else(default_language_separator, str::to_owned),
+            add_indices,
             count,
+            resource_count: Some(self.chunks.len()),
             info,
-        };
-
-        (lines, info)
+        })
     }
 
     fn internal_deserialize_and_update_from_lines(
         &mut self,
         lines: &[String],
         deinfo: Self::DeserializeInfo,
+        options: ImportOptions,
     ) -> Result<(), Self::Error> {
+        match deinfo.resource_count {
+            Some(expected) if options.check_resource_count && expected != self.chunks.len() => {
+                return Err(HZDError::ResourceCountDoesntMatch {
+                    expected,
+                    got: self.chunks.len(),
+                });
+            }
+            _ => (),
+        }
+
         if lines.len() != deinfo.count {
             return Err(HZDError::LineCountDoesntMatchWithInput {
                 expected: deinfo.count,
@@ -245,11 +718,18 @@ impl InternalPlainTextDataSerializer for HZDLocal {
             });
         }
 
+        let separator = deinfo
+            .add_language_names
+            .then_some(deinfo.language_separator.as_str());
+        let strip =
+            |line, index, lang| strip_txt_line(line, index, lang, separator, deinfo.add_indices);
+
         for info in deinfo.info {
-            let Some(chunk) = self.chunks.get_mut(info.index) else {
+            let index = games::resolve_index(&self.chunks, info.index, info.uuid)?;
+            let Some(chunk) = self.chunks.get_mut(index) else {
                 return Err(HZDError::InvalidLocalResourceIdx {
                     max: self.chunks.len(),
-                    got: info.index,
+                    got: index,
                 });
             };
 
@@ -260,50 +740,42 @@ impl InternalPlainTextDataSerializer for HZDLocal {
                 });
             };
 
-            assert_eq!(
-                lines.len(), deinfo.languages.len() * lines.len().saturating_div(deinfo.languages.len()),
-                "range of lines doesn't match with languages count, did you changed something in deinfo file?"
-            );
-
             match (info.variant, &mut chunk.variant) {
                 (TxtLocalVariants::Localized, ChunkVariants::Localized(oloc)) => {
+                    if lines.len() != deinfo.languages.len() {
+                        return Err(HZDError::InvalidLineRanges { index: info.index });
+                    }
+
                     for (lang, line) in deinfo.languages.iter().zip(lines) {
-                        oloc.strings[*lang] = if deinfo.add_language_names {
-                            line.strip_prefix((lang.to_string() + "::").as_str())
-                                .unwrap_or_else(|| line)
-                                .trim_start()
-                        } else {
-                            line
-                        }
-                        .to_owned()
-                        .into();
+                        oloc.strings[*lang] = strip(line, info.index, lang).to_owned().into();
                     }
                 }
                 (TxtLocalVariants::Cutscene, ChunkVariants::Cutscene(oloc)) => {
-                    for (lang, lines) in deinfo
-                        .languages
-                        .iter()
-                        .zip(lines.chunks_exact(lines.len() / deinfo.languages.len()))
-                    {
+                    let Some(line_counts) = cutscene_line_counts(
+                        &info.line_counts,
+                        lines.len(),
+                        deinfo.languages.len(),
+                    ) else {
+                        return Err(HZDError::InvalidLineRanges { index: info.index });
+                    };
+
+                    let mut start = 0;
+                    for (lang, line_count) in deinfo.languages.iter().zip(line_counts) {
+                        let lines = &lines[start..start + line_count];
+                        start += line_count;
+
                         let str_data = &mut oloc.list[*lang].strings_data;
 
                         if lines.len() != str_data.len() {
                             return Err(HZDError::CutsceneLinesDoesntMatch {
                                 lang: *lang,
                                 expected: str_data.len(),
-                                got: lines.len() / deinfo.languages.len(),
+                                got: lines.len(),
                             });
                         }
 
                         for (line, sdata) in lines.iter().zip(str_data) {
-                            sdata.string = if deinfo.add_language_names {
-                                line.strip_prefix((lang.to_string() + ":: ").as_str())
-                                    .unwrap_or_else(|| line)
-                            } else {
-                                line
-                            }
-                            .to_owned()
-                            .into();
+                            sdata.string = strip(line, info.index, lang).to_owned().into();
                         }
                     }
                 }
@@ -318,4 +790,953 @@ impl InternalPlainTextDataSerializer for HZDLocal {
 
         Ok(())
     }
+
+    fn internal_check_lines(line_count: usize, deinfo: &Self::DeserializeInfo) -> Vec<String> {
+        let mut issues = Vec::new();
+
+        if line_count != deinfo.count {
+            issues.push(format!(
+                "Line count doesn't match with deinfo, expected {} but got {line_count}",
+                deinfo.count
+            ));
+        }
+
+        for info in deinfo.info.iter() {
+            if info.range.start > info.range.end || info.range.end > line_count {
+                issues.push(format!(
+                    "Range {:?} of resource {} is out of bounds, input have {line_count} lines",
+                    info.range, info.index
+                ));
+                continue;
+            }
+
+            match info.variant {
+                TxtLocalVariants::Localized if info.range.len() != deinfo.languages.len() => {
+                    issues.push(format!(
+                        "Localized resource {} should have {} lines but have {}",
+                        info.index,
+                        deinfo.languages.len(),
+                        info.range.len()
+                    ));
+                }
+                TxtLocalVariants::Cutscene
+                    if cutscene_line_counts(
+                        &info.line_counts,
+                        info.range.len(),
+                        deinfo.languages.len(),
+                    )
+                    .is_none() =>
+                {
+                    issues.push(format!(
+                        "Cutscene resource {} have {} lines that doesn't match with the lines of {} languages",
+                        info.index,
+                        info.range.len(),
+                        deinfo.languages.len()
+                    ));
+                }
+                _ => (),
+            }
+        }
+
+        issues
+    }
+}
+
+impl HZDLocal {
+    /// Check every resource of `data` against the core before anything is
+    /// changed, so a mismatched input can't leave the core half imported.
+    fn validate_input(&self, data: &[SerdeLocal]) -> Result<(), HZDError> {
+        let mut errors = Vec::new();
+
+        for local in data {
+            let index = match games::resolve_index(&self.chunks, local.index, local.uuid) {
+                Ok(index) => index,
+                Err(e) => {
+                    errors.push((local.index, e.into()));
+                    continue;
+                }
+            };
+            let Some(chunk) = self.chunks.get(index) else {
+                errors.push((
+                    index,
+                    HZDError::InvalidLocalResourceIdx {
+                        max: self.chunks.len(),
+                        got: index,
+                    },
+                ));
+                continue;
+            };
+
+            match (&local.variant, &chunk.variant) {
+                (SerdeLocalVariants::Localized(_), ChunkVariants::Localized(_)) => (),
+                (SerdeLocalVariants::Cutscene(cut), ChunkVariants::Cutscene(oloc)) => {
+                    for (lang, lines) in cut {
+                        let max = oloc.list[*lang].strings_data.len();
+                        match lines {
+                            SerdeCutsceneLines::All(list) if list.len() != max => {
+                                errors.push((
+                                    index,
+                                    HZDError::CutsceneLinesDoesntMatch {
+                                        lang: *lang,
+                                        expected: max,
+                                        got: list.len(),
+                                    },
+                                ));
+                            }
+                            SerdeCutsceneLines::All(_) => (),
+                            SerdeCutsceneLines::Sparse(lines) => errors.extend(
+                                lines.keys().filter(|SerdeLineIndex(sub)| *sub >= max).map(
+                                    |SerdeLineIndex(sub)| {
+                                        (
+                                            index,
+                                            HZDError::InvalidLineIdx {
+                                                index,
+                                                max,
+                                                got: *sub,
+                                            },
+                                        )
+                                    },
+                                ),
+                            ),
+                        }
+                    }
+                }
+                (l, c) => errors.push((
+                    index,
+                    HZDError::ResourceNotMatchAtIdx {
+                        input: l.name(),
+                        original: c.name(),
+                    },
+                )),
+            }
+        }
+
+        match errors.len() {
+            0 => Ok(()),
+            1 => Err(errors.remove(0).1),
+            _ => Err(HZDError::InputDoesntMatch { errors }),
+        }
+    }
+
+    /// Write the timing of every cutscene line to a json sidecar, so the
+    /// timings can be edited without touching the exported text.
+    ///
+    /// ## Arguments:
+    /// * `output`: path of the sidecar, see [`super::timings_path`]. It is
+    ///   compressed if the path has the extension of a [`Compression`](crate::serialize::Compression).
+    /// * `indent`: indentation of the json, like [`SerializeType::Json`](crate::serialize::SerializeType::Json).
+    pub fn export_timings<P: AsRef<Path>>(&self, output: P, indent: Option<&str>) -> DResult<()> {
+        let mut writer = ExportWriter::create(output.as_ref())?;
+        write_json(&mut writer, &self.cutscene_timings(), indent)?;
+        Ok(writer.finish()?)
+    }
+
+    /// Read a timings sidecar made by [`Self::export_timings`] and update the
+    /// timing of every line in it. Every changed line is checked before any
+    /// timing is set, so nothing is imported if one of them doesn't match.
+    ///
+    /// ## Arguments:
+    /// * `input`: path of the sidecar.
+    ///
+    /// ## Return:
+    /// Returns a [`DResult`] with the number of changed timings.
+    pub fn import_timings<P: AsRef<Path>>(&mut self, input: P) -> DResult<usize> {
+        let timings: CutsceneTimings = serde_json::from_reader(open_export(input.as_ref())?)?;
+        let current = self.cutscene_timings();
+
+        let mut changed = Vec::new();
+        let mut errors = Vec::new();
+        for (index, lines) in timings {
+            for (sub, languages) in lines {
+                for (language, timing) in languages {
+                    let old = current
+                        .get(&index)
+                        .and_then(|l| l.get(&sub))
+                        .and_then(|l| l.get(&language));
+                    if old == Some(&timing) {
+                        continue;
+                    }
+                    match self.check_cutscene_line(index, sub, language) {
+                        Ok(()) => changed.push((index, sub, language, timing)),
+                        Err(e) => errors.push((index, e)),
+                    }
+                }
+            }
+        }
+
+        let error = match errors.len() {
+            0 => None,
+            1 => Some(errors.remove(0).1),
+            _ => Some(HZDError::InputDoesntMatch { errors }),
+        };
+        if let Some(error) = error {
+            return Err(Error::GameDeserialize(error.into()));
+        }
+
+        for &(index, sub, language, timing) in &changed {
+            self.set_timing(index, sub, language, timing)
+                .map_err(|e| Error::GameDeserialize(e.into()))?;
+        }
+
+        Ok(changed.len())
+    }
+
+    /// Write the unknown bytes of every cutscene as hex to a json sidecar,
+    /// this is only meant for research on the cutscene format.
+    ///
+    /// ## Arguments:
+    /// * `output`: path of the sidecar, see [`super::raw_data_path`]. It is
+    ///   compressed if the path has the extension of a [`Compression`](crate::serialize::Compression).
+    /// * `indent`: indentation of the json, like [`SerializeType::Json`](crate::serialize::SerializeType::Json).
+    pub fn export_raw_data<P: AsRef<Path>>(&self, output: P, indent: Option<&str>) -> DResult<()> {
+        let raw: BTreeMap<_, _> = self
+            .cutscene_raw_data()
+            .into_iter()
+            .map(|(index, raw)| (index, SerdeCutsceneRaw::from(raw)))
+            .collect();
+
+        let mut writer = ExportWriter::create(output.as_ref())?;
+        write_json(&mut writer, &raw, indent)?;
+        Ok(writer.finish()?)
+    }
+
+    /// Read a raw data sidecar made by [`Self::export_raw_data`] and replace
+    /// the unknown bytes of every cutscene in it.
+    ///
+    /// ## Arguments:
+    /// * `input`: path of the sidecar.
+    ///
+    /// ## Return:
+    /// Returns a [`DResult`] with the number of changed cutscenes.
+    pub fn import_raw_data<P: AsRef<Path>>(&mut self, input: P) -> DResult<usize> {
+        let raw: BTreeMap<usize, SerdeCutsceneRaw> =
+            serde_json::from_reader(open_export(input.as_ref())?)?;
+        let current = self.cutscene_raw_data();
+
+        let mut changed = 0;
+        for (index, raw) in raw {
+            let raw = CutsceneRawData::try_from(raw)
+                .map_err(|e| Error::DeserializeError(format!("cutscene {index}: {e}")))?;
+            if current.get(&index) == Some(&raw) {
+                continue;
+            }
+            self.set_cutscene_raw_data(index, raw)
+                .map_err(|e| Error::GameDeserialize(e.into()))?;
+            changed += 1;
+        }
+
+        Ok(changed)
+    }
+}
+
+/// [`CutsceneRawData`] with the bytes written as hex.
+#[derive(Serialize, Deserialize)]
+struct SerdeCutsceneRaw {
+    useless_block: String,
+    unk: String,
+}
+
+impl From<CutsceneRawData> for SerdeCutsceneRaw {
+    fn from(value: CutsceneRawData) -> Self {
+        let hex = |bytes: &[u8]| bytes.iter().map(|b| format!("{b:02x}")).collect();
+        Self {
+            useless_block: hex(&value.useless_block),
+            unk: hex(&value.unk),
+        }
+    }
+}
+
+impl TryFrom<SerdeCutsceneRaw> for CutsceneRawData {
+    type Error = String;
+
+    fn try_from(value: SerdeCutsceneRaw) -> Result<Self, Self::Error> {
+        let bytes = |hex: &str| {
+            if hex.len() % 2 != 0 || !hex.is_ascii() {
+                return Err(format!("invalid hex bytes {hex}"));
+            }
+            (0..hex.len())
+                .step_by(2)
+                .map(|i| u8::from_str_radix(&hex[i..i + 2], 16))
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|_| format!("invalid hex bytes {hex}"))
+        };
+
+        let unk = bytes(&value.unk)?;
+        Ok(Self {
+            useless_block: bytes(&value.useless_block)?,
+            unk: unk
+                .try_into()
+                .map_err(|_| "unk should have 5 bytes".to_owned())?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn cutscene_line_counts_from_deinfo() {
+        assert_eq!(cutscene_line_counts(&[2, 0, 3], 5, 3), Some(vec![2, 0, 3]));
+        assert_eq!(cutscene_line_counts(&[2, 0, 3], 6, 3), None);
+        assert_eq!(cutscene_line_counts(&[2, 3], 5, 3), None);
+        // older deinfo files don't have the line counts
+        assert_eq!(cutscene_line_counts(&[], 6, 3), Some(vec![2, 2, 2]));
+        assert_eq!(cutscene_line_counts(&[], 5, 3), None);
+        assert_eq!(cutscene_line_counts(&[], 0, 0), Some(vec![]));
+    }
+
+    #[test]
+    fn txt_import_checks_resource_count() {
+        let source = HZDLocal::synthetic(4).unwrap();
+        let export = || {
+            source.internal_serialize_to_lines(
+                &[Language::English],
+                &ExportFilters::default(),
+                None,
+                false,
+            )
+        };
+        let (lines, deinfo) = export();
+        assert_eq!(deinfo.resource_count, Some(5));
+
+        let mut target = HZDLocal::synthetic(5).unwrap();
+        assert!(matches!(
+            target.internal_deserialize_and_update_from_lines(
+                &lines,
+                deinfo,
+                ImportOptions::default()
+            ),
+            Err(HZDError::ResourceCountDoesntMatch {
+                expected: 5,
+                got: 6
+            })
+        ));
+
+        let options = ImportOptions {
+            check_resource_count: false,
+        };
+        assert!(target
+            .internal_deserialize_and_update_from_lines(&lines, export().1, options)
+            .is_ok());
+
+        // older deinfo files don't have the resource count
+        let mut target = HZDLocal::synthetic(5).unwrap();
+        let (_, mut deinfo) = export();
+        deinfo.resource_count = None;
+        assert!(target
+            .internal_deserialize_and_update_from_lines(&lines, deinfo, ImportOptions::default())
+            .is_ok());
+    }
+
+    #[test]
+    fn json_import_checks_resource_count() {
+        use crate::serialize::{SerializeData, SerializeType};
+
+        let json = SerializeType::Json {
+            indent: None,
+            omit_empty: false,
+        };
+        let source = HZDLocal::synthetic(4).unwrap();
+        let mut export = Vec::new();
+        source
+            .serialize_to_writer(
+                &mut export,
+                [Language::English],
+                json.clone(),
+                &ExportFilters::default(),
+            )
+            .unwrap();
+        let data: Vec<SerdeLocal> = serde_json::from_slice(&export).unwrap();
+        assert_eq!(data[0].resource_count, Some(5));
+        assert!(data[1..].iter().all(|l| l.resource_count.is_none()));
+
+        let mut target = HZDLocal::synthetic(5).unwrap();
+        let before = target.to_bytes().unwrap();
+        let result = target.deserialize_and_update_from_reader(
+            export.as_slice(),
+            json.clone(),
+            ImportOptions::default(),
+        );
+        assert!(matches!(
+            result,
+            Err(Error::GameDeserialize(e)) if matches!(
+                e.downcast_ref(),
+                Some(HZDError::ResourceCountDoesntMatch { expected: 5, got: 6 })
+            )
+        ));
+        assert_eq!(target.to_bytes().unwrap(), before);
+
+        let options = ImportOptions {
+            check_resource_count: false,
+        };
+        assert!(target
+            .deserialize_and_update_from_reader(export.as_slice(), json, options)
+            .is_ok());
+
+        // older exports don't have the resource count
+        let mut target = HZDLocal::synthetic(5).unwrap();
+        let mut data =
+            source.internal_serialize(&[Language::English], false, &ExportFilters::default());
+        data[0].resource_count = None;
+        assert!(target
+            .internal_deserialize_and_update(data, ImportOptions::default())
+            .is_ok());
+    }
+
+    #[test]
+    fn blank_export_keeps_the_structure() {
+        let source = HZDLocal::synthetic(4).unwrap();
+        let languages = [Language::English, Language::French];
+        let (lines, deinfo) =
+            source.internal_serialize_to_lines(&languages, &ExportFilters::default(), None, false);
+
+        let blank = ExportFilters {
+            blank: true,
+            ..Default::default()
+        };
+        let (blank_lines, blank_deinfo) =
+            source.internal_serialize_to_lines(&languages, &blank, None, false);
+        assert_eq!(blank_lines.len(), lines.len());
+        assert!(blank_lines.iter().all(String::is_empty));
+        assert!(source
+            .internal_exported_strings(&blank)
+            .all(|e| e.string.is_empty()));
+        assert!(source
+            .internal_serialize(&languages, true, &blank)
+            .iter()
+            .all(|l| l.empty.len() == languages.len()));
+
+        // the template imports back and filling it gives the source again
+        let mut target = HZDLocal::synthetic(4).unwrap();
+        target
+            .internal_deserialize_and_update_from_lines(
+                &blank_lines,
+                blank_deinfo,
+                ImportOptions::default(),
+            )
+            .unwrap();
+        assert!(target
+            .strings()
+            .filter(|e| languages.contains(&e.language))
+            .all(|e| e.string.is_empty()));
+        target
+            .internal_deserialize_and_update_from_lines(&lines, deinfo, ImportOptions::default())
+            .unwrap();
+        assert_eq!(target.to_bytes().unwrap(), source.to_bytes().unwrap());
+    }
+
+    #[test]
+    fn unknown_fields_are_kept_for_a_new_export() {
+        let source = HZDLocal::synthetic(2).unwrap();
+        let languages = [Language::English];
+        let mut previous = serde_json::to_value(source.internal_serialize(
+            &languages,
+            false,
+            &ExportFilters::default(),
+        ))
+        .unwrap();
+        previous[1]["note"] = "checked by QA".into();
+        previous[1]["tool"] = serde_json::json!({ "id": 7 });
+
+        let previous: Vec<SerdeLocal> = serde_json::from_value(previous).unwrap();
+        assert!(previous[0].extra.is_empty());
+        assert_eq!(previous[1].extra.len(), 2);
+        assert_eq!(previous[1].variant.name(), "Cutscene");
+
+        // the extra fields don't change what gets imported
+        let mut target = HZDLocal::synthetic(2).unwrap();
+        let summary = target.internal_deserialize_and_update(previous, ImportOptions::default());
+        assert!(summary.is_ok());
+        assert_eq!(target.to_bytes().unwrap(), source.to_bytes().unwrap());
+
+        let mut export = source.internal_serialize(&languages, false, &ExportFilters::default());
+        let mut previous: Vec<SerdeLocal> = serde_json::from_value(
+            serde_json::to_value(source.internal_serialize(
+                &languages,
+                false,
+                &ExportFilters::default(),
+            ))
+            .unwrap(),
+        )
+        .unwrap();
+        previous[1]
+            .extra
+            .insert("note".to_owned(), "checked by QA".into());
+        HZDLocal::internal_copy_extra_fields(&mut export, previous);
+        let export = serde_json::to_value(export).unwrap();
+        assert_eq!(export[1]["note"], "checked by QA");
+        assert!(export[0].get("note").is_none());
+    }
+
+    #[test]
+    fn resource_filter_only_changes_the_export() {
+        use crate::serialize::{SerializeData, SerializeType};
+
+        let mut source = HZDLocal::synthetic(4).unwrap();
+        source
+            .set_string(1, Language::English, 0, "changed".to_owned())
+            .unwrap();
+        source
+            .set_string(2, Language::English, 0, "changed line".to_owned())
+            .unwrap();
+        let mut export = Vec::new();
+        source
+            .serialize_to_writer(
+                &mut export,
+                [Language::English],
+                SerializeType::KeyValue,
+                &ExportFilters::default(),
+            )
+            .unwrap();
+
+        let mut target = HZDLocal::synthetic(4).unwrap();
+        let summary = target
+            .deserialize_and_update_from_reader(
+                export.as_slice(),
+                SerializeType::KeyValue,
+                ImportOptions::default(),
+            )
+            .unwrap();
+        assert_eq!(summary.strings_changed, 2);
+        assert_eq!(summary.resources_updated, 2);
+        assert_eq!(target.internal_strings().count(), source.strings().count());
+        let localized = ExportFilters {
+            resources: crate::games::hzd::ResourceFilter::Localized,
+            ..Default::default()
+        };
+        assert!(target
+            .internal_exported_strings(&localized)
+            .all(|e| matches!(target.chunks[e.index].variant, ChunkVariants::Localized(_))));
+        assert_eq!(
+            target
+                .internal_exported_strings(&ExportFilters::default())
+                .count(),
+            source.strings().count()
+        );
+    }
+
+    #[test]
+    fn timings_sidecar_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = super::super::timings_path(&dir.path().join("out.json"));
+        assert_eq!(path, dir.path().join("out.timings.json"));
+        assert_eq!(
+            super::super::timings_path(&dir.path().join("out.json.gz")),
+            dir.path().join("out.timings.json.gz")
+        );
+
+        let source = HZDLocal::synthetic(4).unwrap();
+        source.export_timings(&path, Some("\t")).unwrap();
+        assert!(std::fs::read_to_string(&path)
+            .unwrap()
+            .starts_with("{\n\t\"2\""));
+        let mut timings: CutsceneTimings =
+            serde_json::from_reader(std::fs::File::open(&path).unwrap()).unwrap();
+        assert_eq!(timings.keys().copied().collect::<Vec<_>>(), [2, 4]);
+        assert_eq!(timings[&2][&1][&Language::English], 1000);
+
+        *timings
+            .get_mut(&2)
+            .and_then(|l| l.get_mut(&1))
+            .and_then(|l| l.get_mut(&Language::French))
+            .unwrap() = 1234;
+        serde_json::to_writer(std::fs::File::create(&path).unwrap(), &timings).unwrap();
+
+        let mut target = HZDLocal::synthetic(4).unwrap();
+        assert_eq!(target.import_timings(&path).unwrap(), 1);
+        assert_eq!(target.cutscene_timings(), timings);
+        assert_eq!(target.strings().count(), source.strings().count());
+    }
+
+    #[test]
+    fn timings_of_other_resources_are_rejected() {
+        let mut local = HZDLocal::synthetic(4).unwrap();
+        assert!(matches!(
+            local.set_timing(1, 0, Language::English, 1),
+            Err(HZDError::ResourceNotMatchAtIdx { .. })
+        ));
+        assert!(matches!(
+            local.set_timing(4, 1, Language::English, 1),
+            Err(HZDError::InvalidLineIdx {
+                index: 4,
+                max: 1,
+                got: 1
+            })
+        ));
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("out.timings.json");
+        std::fs::write(&path, r#"{"1":{"0":{"English":5}}}"#).unwrap();
+        let Err(Error::GameDeserialize(error)) = local.import_timings(&path) else {
+            panic!("the timing of a localized resource should be rejected");
+        };
+        assert!(matches!(
+            error.downcast_ref(),
+            Some(HZDError::ResourceNotMatchAtIdx { .. })
+        ));
+
+        // the valid timing of cutscene 2 isn't set either
+        let before = local.cutscene_timings();
+        std::fs::write(
+            &path,
+            r#"{"1":{"0":{"English":5}},"2":{"0":{"English":5}},"4":{"1":{"English":5}}}"#,
+        )
+        .unwrap();
+        let Err(Error::GameDeserialize(error)) = local.import_timings(&path) else {
+            panic!("the timings should be rejected");
+        };
+        assert!(matches!(
+            error.downcast_ref(),
+            Some(HZDError::InputDoesntMatch { errors }) if errors.len() == 2
+        ));
+        assert_eq!(local.cutscene_timings(), before);
+    }
+
+    #[test]
+    fn omitted_empty_strings_round_trip() {
+        use crate::serialize::{SerializeData, SerializeType};
+
+        let source = HZDLocal::synthetic(4).unwrap();
+        let formats = [
+            SerializeType::Json {
+                indent: None,
+                omit_empty: true,
+            },
+            #[cfg(feature = "serialize_yaml")]
+            SerializeType::Yaml { omit_empty: true },
+        ];
+        for serialize_type in formats {
+            let mut export = Vec::new();
+            source
+                .serialize_to_writer(
+                    &mut export,
+                    Language::ALL_VARIANTS,
+                    serialize_type.clone(),
+                    &ExportFilters::default(),
+                )
+                .unwrap();
+
+            let mut target = HZDLocal::synthetic(4).unwrap();
+            for (index, language) in [(1, Language::English), (3, Language::French)] {
+                target
+                    .set_string(index, language, 0, "changed".to_owned())
+                    .unwrap();
+            }
+            target
+                .deserialize_and_update_from_reader(
+                    export.as_slice(),
+                    serialize_type,
+                    ImportOptions::default(),
+                )
+                .unwrap();
+            assert_eq!(target.to_bytes().unwrap(), source.to_bytes().unwrap());
+        }
+
+        // a language listed as empty and with a string keeps the string
+        let mut data =
+            source.internal_serialize(&Language::ALL_VARIANTS, true, &ExportFilters::default());
+        let local = data
+            .iter_mut()
+            .find(|l| matches!(l.variant, SerdeLocalVariants::Localized(_)) && !l.empty.is_empty())
+            .unwrap();
+        let language = *local.empty.first().unwrap();
+        if let SerdeLocalVariants::Localized(map) = &mut local.variant {
+            map.insert(language, "both".to_owned());
+        }
+        let index = local.index;
+        let mut target = HZDLocal::synthetic(4).unwrap();
+        target
+            .internal_deserialize_and_update(data, ImportOptions::default())
+            .unwrap();
+        assert_eq!(target.get_string(index, language, 0).unwrap(), "both");
+    }
+
+    #[test]
+    fn raw_data_hex_round_trip() {
+        let raw = CutsceneRawData {
+            useless_block: vec![0x00, 0x0f, 0xa0, 0xff],
+            unk: [1, 2, 3, 4, 0xab],
+        };
+        let serde = SerdeCutsceneRaw::from(raw.clone());
+        assert_eq!(serde.useless_block, "000fa0ff");
+        assert_eq!(serde.unk, "01020304ab");
+        assert_eq!(CutsceneRawData::try_from(serde).unwrap(), raw);
+
+        let upper = SerdeCutsceneRaw {
+            useless_block: "000FA0FF".to_owned(),
+            unk: "01020304AB".to_owned(),
+        };
+        assert_eq!(CutsceneRawData::try_from(upper).unwrap(), raw);
+
+        for (useless_block, unk) in [
+            ("000", "0102030405"),
+            ("zz00", "0102030405"),
+            ("é0", "0102030405"),
+            ("0000", "01020304"),
+            ("0000", "010203040506"),
+        ] {
+            let serde = SerdeCutsceneRaw {
+                useless_block: useless_block.to_owned(),
+                unk: unk.to_owned(),
+            };
+            assert!(
+                CutsceneRawData::try_from(serde).is_err(),
+                "{useless_block} {unk}"
+            );
+        }
+    }
+
+    #[test]
+    fn byte_lengths_are_of_the_exported_text() {
+        let mut local = HZDLocal::synthetic(2).unwrap();
+        local
+            .set_string(1, Language::English, 0, "ünï".to_owned())
+            .unwrap();
+        for (sub, string) in ["one", ""].into_iter().enumerate() {
+            local
+                .set_string(2, Language::English, sub, "line".to_owned())
+                .unwrap();
+            local
+                .set_string(2, Language::French, sub, string.to_owned())
+                .unwrap();
+        }
+
+        let languages = [Language::English, Language::French];
+        let sizes = |filters: &ExportFilters| {
+            let data = local.internal_serialize(&languages, false, filters);
+            let localized = match &data[0].byte_len[&Language::English] {
+                SerdeByteLen::String(size) => *size,
+                SerdeByteLen::Lines(_) => panic!("a localized resource has a single size"),
+            };
+            let lines = match &data[1].byte_len[&Language::French] {
+                SerdeByteLen::Lines(sizes) => sizes.clone(),
+                SerdeByteLen::String(_) => panic!("a cutscene has the size of each line"),
+            };
+            (localized, lines)
+        };
+        let with_sizes = ExportFilters {
+            byte_lengths: true,
+            ..Default::default()
+        };
+        assert_eq!(sizes(&with_sizes), (2 + 5, vec![4 + 6, 4]));
+
+        let filled = ExportFilters {
+            fill_empty_from: Some(Language::English),
+            ..with_sizes.clone()
+        };
+        assert_eq!(sizes(&filled).1, vec![4 + 6, 4 + 8]);
+        let blank = ExportFilters {
+            blank: true,
+            ..with_sizes
+        };
+        assert_eq!(sizes(&blank), (2, vec![4, 4]));
+    }
+
+    #[test]
+    fn flatten_lists_every_string() {
+        let mut local = HZDLocal::synthetic(2).unwrap();
+        local
+            .set_string(1, Language::French, 0, String::new())
+            .unwrap();
+        let languages = [Language::English, Language::French];
+        let data = local.internal_serialize(&languages, true, &ExportFilters::default());
+        assert!(data[0].empty.contains(&Language::French));
+        let lines = local.language_strings(Language::English);
+
+        let flat = HZDLocal::internal_flatten(data);
+        let english = |index, sub| {
+            flat.iter()
+                .find(|(i, s, l, _)| (*i, *s, *l) == (index, sub, Language::English))
+                .map(|(.., string)| string.as_str())
+        };
+        assert!(flat.contains(&(1, 0, Language::French, String::new())));
+        assert_eq!(english(1, 0), Some(lines[0].as_str()));
+        assert_eq!(english(2, 1), Some(lines[2].as_str()));
+
+        // sparse cutscene lines keep their line index
+        let mut data = local.internal_serialize(&languages, true, &ExportFilters::default());
+        let SerdeLocalVariants::Cutscene(map) = &mut data[1].variant else {
+            panic!("the second resource is a cutscene");
+        };
+        let sparse = BTreeMap::from([(SerdeLineIndex(1), "only".to_owned())]);
+        map.insert(Language::English, SerdeCutsceneLines::Sparse(sparse));
+        let flat = HZDLocal::internal_flatten(data);
+        let cutscene: Vec<_> = flat
+            .iter()
+            .filter(|(i, _, l, _)| (*i, *l) == (2, Language::English))
+            .collect();
+        assert_eq!(cutscene, [&(2, 1, Language::English, "only".to_owned())]);
+    }
+
+    #[test]
+    fn diff_exports_lists_changed_strings() {
+        use crate::serialize::{ExportDifference, SerializeData, SerializeType};
+
+        let dir = tempfile::tempdir().unwrap();
+        let old = dir.path().join("old.json");
+        let new = dir.path().join("new.properties");
+        let json = SerializeType::Json {
+            indent: None,
+            omit_empty: false,
+        };
+
+        let mut local = HZDLocal::synthetic(2).unwrap();
+        local
+            .serialize(
+                &old,
+                [Language::English, Language::French],
+                json.clone(),
+                &ExportFilters::default(),
+            )
+            .unwrap();
+        assert!(HZDLocal::diff_exports(&old, &old, json.clone())
+            .unwrap()
+            .is_empty());
+
+        let before = local
+            .get_string(2, Language::English, 1)
+            .unwrap()
+            .to_owned();
+        local
+            .set_string(2, Language::English, 1, "changed".to_owned())
+            .unwrap();
+        local
+            .serialize(
+                &new,
+                [Language::English],
+                SerializeType::KeyValue,
+                &ExportFilters::default(),
+            )
+            .unwrap();
+        let new_json = dir.path().join("new.json");
+        local
+            .serialize(
+                &new_json,
+                [Language::English],
+                json.clone(),
+                &ExportFilters::default(),
+            )
+            .unwrap();
+
+        let differences = HZDLocal::diff_exports(&old, &new_json, json).unwrap();
+        assert_eq!(
+            differences[0],
+            ExportDifference {
+                index: 1,
+                sub: 0,
+                language: Language::French,
+                old: Some(local.get_string(1, Language::French, 0).unwrap().to_owned()),
+                new: None,
+            }
+        );
+        assert!(differences.contains(&ExportDifference {
+            index: 2,
+            sub: 1,
+            language: Language::English,
+            old: Some(before),
+            new: Some("changed".to_owned()),
+        }));
+        assert!(differences
+            .iter()
+            .all(|d| d.language == Language::French || d.sub == 1));
+
+        // other formats than Json work the same, but not txt as it needs the core
+        assert!(HZDLocal::diff_exports(&new, &new, SerializeType::KeyValue)
+            .unwrap()
+            .is_empty());
+        let txt = SerializeType::Txt {
+            add_language_names: false,
+            language_separator: None,
+            add_indices: false,
+            line_ending: None,
+        };
+        assert!(HZDLocal::diff_exports(&old, &old, txt).is_err());
+    }
+
+    #[test]
+    fn filled_strings_are_only_imported_when_edited() {
+        let core = || {
+            let mut local = HZDLocal::synthetic(2).unwrap();
+            for (index, sub) in [(1, 0), (2, 0), (2, 1)] {
+                local
+                    .set_string(index, Language::English, sub, format!("english {sub}"))
+                    .unwrap();
+                local
+                    .set_string(index, Language::French, sub, String::new())
+                    .unwrap();
+            }
+            local
+        };
+        let source = core();
+        let filters = ExportFilters {
+            fill_empty_from: Some(Language::English),
+            ..Default::default()
+        };
+
+        let data = source.internal_serialize(&Language::ALL_VARIANTS, false, &filters);
+        assert!(data
+            .iter()
+            .all(|l| l.filled.get(&Language::French) == Some(&Language::English)));
+        let mut target = core();
+        target
+            .internal_deserialize_and_update(data, ImportOptions::default())
+            .unwrap();
+        assert_eq!(target.to_bytes().unwrap(), core().to_bytes().unwrap());
+
+        let mut data = source.internal_serialize(&Language::ALL_VARIANTS, false, &filters);
+        if let SerdeLocalVariants::Cutscene(map) = &mut data[1].variant {
+            if let Some(SerdeCutsceneLines::All(lines)) = map.get_mut(&Language::French) {
+                assert_eq!(lines[1], "english 1");
+                lines[1] = "edited".to_owned();
+            }
+        }
+        target
+            .internal_deserialize_and_update(data, ImportOptions::default())
+            .unwrap();
+        assert_eq!(target.get_string(1, Language::French, 0).unwrap(), "");
+        assert_eq!(target.get_string(2, Language::French, 0).unwrap(), "");
+        assert_eq!(target.get_string(2, Language::French, 1).unwrap(), "edited");
+    }
+
+    #[test]
+    fn mismatches_are_reported_by_core_index() {
+        use crate::games::synthetic;
+
+        let source = HZDLocal::synthetic(4).unwrap();
+        let sorted = ExportFilters {
+            sort_by_uuid: true,
+            ..Default::default()
+        };
+        let mut data = source.internal_serialize(&[Language::English], false, &sorted);
+        for local in data.iter_mut() {
+            match local.index {
+                // a stale index, the uuid points to the cutscene at 2
+                1 => {
+                    local.index = 9;
+                    local.uuid = Some(Uuid(synthetic::uuid(1)));
+                }
+                3 => local.uuid = Some(Uuid([0xFF; 16])),
+                _ => (),
+            }
+        }
+
+        let mut target = HZDLocal::synthetic(4).unwrap();
+        let Err(HZDError::InputDoesntMatch { errors }) =
+            target.internal_deserialize_and_update(data, ImportOptions::default())
+        else {
+            panic!("the input should not match");
+        };
+        let mut errors: Vec<_> = errors
+            .into_iter()
+            .map(|(index, e)| (index, e.to_string()))
+            .collect();
+        errors.sort();
+        assert_eq!(errors.len(), 2);
+        assert_eq!(errors[0].0, 2);
+        assert!(errors[0].1.starts_with("Resource not match"));
+        assert_eq!(errors[1].0, 3);
+        assert!(errors[1].1.contains("uuid"));
+        assert!(!target.is_modified());
+    }
 }
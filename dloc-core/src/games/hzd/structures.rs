@@ -1,18 +1,26 @@
 use std::{fmt::Debug, mem::size_of};
 
-use binrw::binrw;
+use binrw::{binrw, BinResult, BinWrite};
 
 use crate::{
-    games::chunks::RuntimeSize,
+    games::{
+        chunks::{current_position, read_checked_count, read_trailing, RuntimeSize},
+        ResourceUuid, Uuid,
+    },
     utils::{
         types::{U16String, U8String},
         EnumKey,
     },
 };
 
-use super::{FixedMap, Language};
+use super::{CutsceneRawData, FixedMap, Language};
 
+/// Magic of the localized text resources, they hold every string that
+/// isn't a cutscene subtitle. The sentence resources of the dialogues don't
+/// have any text of their own, they refer to a localized text resource by
+/// its uuid (usually in the same core) so their strings are exported from it.
 pub const LOCALIZED_MAGIC: u64 = 0xB89A596B420BB2E2;
+/// Magic of the cutscene subtitles, a list of timed lines for each language.
 pub const CUTSCENE_MAGIC: u64 = 0x5A3ECD4ADA693D7F;
 
 pub type Chunk = crate::games::chunks::Chunk<ChunkVariants>;
@@ -24,12 +32,12 @@ pub type Chunk = crate::games::chunks::Chunk<ChunkVariants>;
 #[binrw]
 #[brw(little)]
 #[br(import(magic: u64, size: u32))]
-#[derive(Hash)]
+#[derive(Clone, Hash)]
 pub enum ChunkVariants {
     #[br(pre_assert(magic == LOCALIZED_MAGIC))]
-    Localized(Box<Localized>),
+    Localized(#[br(args(size))] Box<Localized>),
     #[br(pre_assert(magic == CUTSCENE_MAGIC))]
-    Cutscene(Box<Cutscene>),
+    Cutscene(#[br(args(size))] Box<Cutscene>),
     /// Data variant for unknown chunk data.
     /// Stores raw binary data.
     Others {
@@ -46,6 +54,20 @@ impl ChunkVariants {
             Self::Others { .. } => "Others",
         }
     }
+
+    pub const fn uuid(&self) -> Option<Uuid> {
+        match self {
+            Self::Localized(loc) => Some(Uuid(loc.uuid)),
+            Self::Cutscene(cut) => Some(Uuid(cut.uuid)),
+            Self::Others { .. } => None,
+        }
+    }
+}
+
+impl ResourceUuid for ChunkVariants {
+    fn resource_uuid(&self) -> Option<Uuid> {
+        self.uuid()
+    }
 }
 
 impl RuntimeSize for ChunkVariants {
@@ -73,10 +95,24 @@ impl Debug for ChunkVariants {
 
 #[binrw]
 #[brw(little)]
-#[derive(Hash)]
+#[br(import(size: u32))]
+#[derive(Clone, Hash)]
 pub struct Localized {
+    #[br(temp, parse_with = current_position)]
+    #[bw(ignore)]
+    start: u64,
     uuid: [u8; 16],
     pub strings: FixedMap<U8String>,
+    /// Bytes after the strings that dloc doesn't know about.
+    #[br(parse_with = read_trailing, args(start, size))]
+    trailing: Vec<u8>,
+}
+
+impl Localized {
+    /// Number of unknown bytes after the strings.
+    pub(crate) fn trailing_len(&self) -> usize {
+        self.trailing.len()
+    }
 }
 
 impl RuntimeSize for Localized {
@@ -86,7 +122,8 @@ impl RuntimeSize for Localized {
                 .strings
                 .iter()
                 .map(|(_, s)| s.full_size())
-                .sum::<usize>()) as u32
+                .sum::<usize>()
+            + self.trailing.len()) as u32
     }
 }
 
@@ -95,23 +132,72 @@ impl Debug for Localized {
         f.debug_struct("Localized")
             .field("uuid", &format!("{:#x?}", self.uuid))
             .field("strings", &self.strings)
+            .field("trailing_bytes", &self.trailing.len())
             .finish()
     }
 }
 
 #[binrw]
 #[brw(little)]
-#[derive(Hash)]
+#[br(import(size: u32))]
+#[derive(Clone, Hash)]
 pub struct Cutscene {
+    #[br(temp, parse_with = current_position)]
+    #[bw(ignore)]
+    start: u64,
     uuid: [u8; 16],
+    #[br(parse_with = read_checked_count, args(1))]
     useless_block_len: u32,
-    #[br(count = useless_block_len + 4)]
+    /// Block of unknown meaning plus the 4 bytes after it, the name is only
+    /// historical. It's kept as is and can be read and replaced through
+    /// [`CutsceneRawData`].
+    #[br(count = useless_block_len as usize + 4)]
     useless_block: Vec<u8>,
     #[br(assert(lang_count == Language::LEN as u32, "Language count doesn't match with what dloc expect HZD to have {lang_count} != {}.", Language::LEN))]
     lang_count: u32,
-    #[br(map = sort_cutscene_group)]
+    #[br(temp)]
+    #[bw(ignore)]
+    groups: FixedMap<CutsceneStringGroup>,
+    /// Index in `list` of every group in the order they are written, by
+    /// default the order they had in the core.
+    #[br(calc = read_order(&groups))]
+    #[bw(ignore)]
+    pub(crate) write_order: Vec<usize>,
+    #[br(calc = sort_cutscene_group(groups))]
+    #[bw(write_with = write_in_order, args(write_order))]
     pub list: FixedMap<CutsceneStringGroup>,
+    /// Bytes of unknown meaning at the end, see [`CutsceneRawData`].
     unk: [u8; 5],
+    /// Bytes after the cutscene data that dloc doesn't know about.
+    #[br(parse_with = read_trailing, args(start, size))]
+    trailing: Vec<u8>,
+}
+
+impl Cutscene {
+    /// Number of unknown bytes after the cutscene data.
+    pub(crate) fn trailing_len(&self) -> usize {
+        self.trailing.len()
+    }
+
+    /// The bytes of the cutscene that dloc doesn't know the meaning of.
+    pub(crate) fn raw_data(&self) -> CutsceneRawData {
+        CutsceneRawData {
+            useless_block: self.useless_block.clone(),
+            unk: self.unk,
+        }
+    }
+
+    /// Replace the unknown bytes, returns `false` if the useless block is
+    /// too short to be written back.
+    pub(crate) fn set_raw_data(&mut self, raw: CutsceneRawData) -> bool {
+        let Some(len) = raw.useless_block.len().checked_sub(4) else {
+            return false;
+        };
+        self.useless_block_len = len as u32;
+        self.useless_block = raw.useless_block;
+        self.unk = raw.unk;
+        true
+    }
 }
 
 impl RuntimeSize for Cutscene {
@@ -119,7 +205,8 @@ impl RuntimeSize for Cutscene {
         let other_sizes = self.uuid.len()
             + (self.useless_block_len as usize + 4) // u32 + usless_block
             + (size_of::<u32>() * 2) // 4 extra bytes after useless block + lang_count
-            + self.unk.len();
+            + self.unk.len()
+            + self.trailing.len();
 
         let string_sizes: usize = self
             .list
@@ -144,7 +231,9 @@ impl Debug for Cutscene {
             .field("useless_block", &self.useless_block)
             .field("lang_count", &self.lang_count)
             .field("list", &self.list)
+            .field("write_order", &self.write_order)
             .field("unk", &format!("{:#x?}", self.unk))
+            .field("trailing_bytes", &self.trailing.len())
             .finish()
     }
 }
@@ -154,7 +243,9 @@ impl Debug for Cutscene {
 #[derive(Debug, Clone, Hash)]
 pub struct CutsceneStringGroup {
     #[br(assert(lang_code <= Language::LEN as _, "Invalid core file, lang code was \"{lang_code}\". it shouldn't be bigger then {}", Language::LEN))]
-    lang_code: u32,
+    pub(crate) lang_code: u32,
+    // every string data is at least 12 bytes (empty string + timing)
+    #[br(parse_with = read_checked_count, args(12))]
     count: u32,
     #[br(count = count)]
     pub strings_data: Vec<CutsceneStringData>,
@@ -165,7 +256,7 @@ pub struct CutsceneStringGroup {
 #[derive(Clone, Hash)]
 pub struct CutsceneStringData {
     pub string: U16String,
-    timing: u64,
+    pub(crate) timing: u64,
 }
 
 impl Debug for CutsceneStringData {
@@ -180,6 +271,29 @@ impl Debug for CutsceneStringData {
 /// Sorts the cutscene string groups in the given map by their language code.
 /// Returns the sorted map.
 fn sort_cutscene_group(mut map: FixedMap<CutsceneStringGroup>) -> FixedMap<CutsceneStringGroup> {
-    map.inner.sort_by(|e1, e2| e1.lang_code.cmp(&e2.lang_code));
+    map.inner.sort_by_key(|e| e.lang_code);
     map
 }
+
+/// Index of every group of `map` after [`sort_cutscene_group`], in the order
+/// they are stored in the core.
+fn read_order(map: &FixedMap<CutsceneStringGroup>) -> Vec<usize> {
+    // same stable sort as `sort_cutscene_group`
+    let mut sorted: Vec<usize> = (0..map.len()).collect();
+    sorted.sort_by_key(|&i| map.inner[i].lang_code);
+
+    let mut order = vec![0; map.len()];
+    for (index, &position) in sorted.iter().enumerate() {
+        order[position] = index;
+    }
+    order
+}
+
+/// Write the groups of `list` in `order`, see [`read_order`].
+#[binrw::writer(writer, endian)]
+fn write_in_order(list: &FixedMap<CutsceneStringGroup>, order: &[usize]) -> BinResult<()> {
+    for &index in order {
+        list.inner[index].write_options(writer, endian, ())?;
+    }
+    Ok(())
+}
@@ -1,6 +1,755 @@
 //! Supported games modules
 
-mod chunks;
+use std::{collections::BTreeMap, fmt::Display, io::Cursor, str::FromStr};
+
+use binrw::BinRead;
+
+use crate::{error::StringEncoding, Error};
+
+pub(crate) mod chunks;
 pub mod detect;
 pub mod ds;
 pub mod hzd;
+pub(crate) mod synthetic;
+
+/// A single string inside a core file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StringEntry<'a, L> {
+    /// Index of the resource that hold the string.
+    pub index: usize,
+    pub language: L,
+    /// Line index inside a cutscene, always `0` for other resources.
+    pub sub: usize,
+    pub string: &'a str,
+}
+
+/// Error of parsing the name of a language that the game doesn't have.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("Invalid language: {0}")]
+pub struct InvalidLanguage(pub String);
+
+/// Uuid of a resource, unlike the index it stays the same when a game patch
+/// reorders the resources of a core. Shown as 32 hex digits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Uuid(pub [u8; 16]);
+
+impl Display for Uuid {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.iter().try_for_each(|b| write!(f, "{b:02x}"))
+    }
+}
+
+impl FromStr for Uuid {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let error = || format!("Invalid uuid {s}, expected 32 hex digits");
+        if s.len() != 32 || !s.is_ascii() {
+            return Err(error());
+        }
+
+        let mut uuid = [0; 16];
+        for (i, b) in uuid.iter_mut().enumerate() {
+            *b = u8::from_str_radix(&s[i * 2..i * 2 + 2], 16).map_err(|_| error())?;
+        }
+        Ok(Self(uuid))
+    }
+}
+
+#[cfg(feature = "serialize")]
+impl serde::Serialize for Uuid {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+#[cfg(feature = "serialize")]
+impl<'de> serde::Deserialize<'de> for Uuid {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+/// A resource variant of a game that may have a uuid.
+pub(crate) trait ResourceUuid {
+    fn resource_uuid(&self) -> Option<Uuid>;
+}
+
+/// The resource with a uuid recorded in an export isn't in the core, see
+/// [`resolve_index`].
+pub(crate) struct UuidNotFound(pub Uuid);
+
+/// Indices of the resources that pass `is_serialized` in the order they get
+/// serialized, sorted by their uuid if `sort_by_uuid` is set.
+#[cfg(feature = "serialize")]
+pub(crate) fn serialize_order<V>(
+    chunks: &[chunks::Chunk<V>],
+    sort_by_uuid: bool,
+    is_serialized: impl Fn(usize) -> bool,
+) -> Vec<usize>
+where
+    for<'a> V: chunks::RuntimeSize
+        + BinRead<Args<'a> = (u64, u32)>
+        + binrw::BinWrite<Args<'a> = ()>
+        + ResourceUuid,
+{
+    let mut order: Vec<usize> = (0..chunks.len()).filter(|i| is_serialized(*i)).collect();
+    if sort_by_uuid {
+        order.sort_by_key(|i| chunks[*i].variant.resource_uuid());
+    }
+    order
+}
+
+/// Uuid of the resource at `index` to record in an export, uuids are only
+/// recorded when sorting by them.
+#[cfg(feature = "serialize")]
+pub(crate) fn recorded_uuid<V>(
+    chunks: &[chunks::Chunk<V>],
+    sort_by_uuid: bool,
+    index: usize,
+) -> Option<Uuid>
+where
+    for<'a> V: chunks::RuntimeSize
+        + BinRead<Args<'a> = (u64, u32)>
+        + binrw::BinWrite<Args<'a> = ()>
+        + ResourceUuid,
+{
+    if !sort_by_uuid {
+        return None;
+    }
+    chunks.get(index).and_then(|c| c.variant.resource_uuid())
+}
+
+/// Index of the resource to import to, if the export recorded the `uuid`
+/// the resource is found by it instead of the stored `index`.
+#[cfg(feature = "serialize")]
+pub(crate) fn resolve_index<V>(
+    chunks: &[chunks::Chunk<V>],
+    index: usize,
+    uuid: Option<Uuid>,
+) -> Result<usize, UuidNotFound>
+where
+    for<'a> V: chunks::RuntimeSize
+        + BinRead<Args<'a> = (u64, u32)>
+        + binrw::BinWrite<Args<'a> = ()>
+        + ResourceUuid,
+{
+    let Some(uuid) = uuid else {
+        return Ok(index);
+    };
+
+    chunks
+        .iter()
+        .position(|c| c.variant.resource_uuid() == Some(uuid))
+        .ok_or(UuidNotFound(uuid))
+}
+
+/// Returns `true` if a resource with `uuid` pass the uuid `filter`, an
+/// empty filter allows every resource and resources without a uuid only
+/// pass an empty filter.
+pub(crate) fn uuid_allowed(filter: &[Uuid], uuid: Option<Uuid>) -> bool {
+    filter.is_empty() || uuid.is_some_and(|u| filter.contains(&u))
+}
+
+/// Filter the serialized strings by their content. Json, Yaml and Txt
+/// exports write whole resources, so they keep a resource if any of its
+/// strings in any language match the regex, the other formats write each
+/// string on its own and keep just the matching ones. Resources keep their
+/// index so the export can be imported like a full one.
+#[derive(Debug, Clone)]
+pub struct ContentFilter {
+    #[cfg(feature = "regex")]
+    regex: regex::Regex,
+    // can't be built without the regex feature
+    #[cfg(not(feature = "regex"))]
+    never: std::convert::Infallible,
+}
+
+impl ContentFilter {
+    /// Creates a new [`ContentFilter`] from a regex `pattern`.
+    #[cfg(feature = "regex")]
+    pub fn new(pattern: &str) -> crate::DResult<Self> {
+        Ok(Self {
+            regex: regex::Regex::new(pattern)?,
+        })
+    }
+
+    /// Returns `true` if any of the `strings` match the filter.
+    pub(crate) fn matches<'a, L: 'a>(
+        &self,
+        mut strings: impl Iterator<Item = StringEntry<'a, L>>,
+    ) -> bool {
+        strings.any(|e| self.is_match(e.string))
+    }
+
+    /// Returns `true` if `string` match the filter.
+    #[cfg(feature = "regex")]
+    pub(crate) fn is_match(&self, string: &str) -> bool {
+        self.regex.is_match(string)
+    }
+
+    #[cfg(not(feature = "regex"))]
+    pub(crate) fn is_match(&self, _string: &str) -> bool {
+        match self.never {}
+    }
+}
+
+/// What an export of a core writes, passed to each serialize call so
+/// exporting with filters doesn't change the core or later exports of it.
+/// The default exports every string as is. Filtered resources keep their
+/// index so import isn't affected.
+#[derive(Debug, Clone)]
+pub struct ExportFilters<L, R = ()> {
+    /// What kind of resources get serialized, for the games that have more
+    /// than one kind.
+    pub resources: R,
+    /// Only serialize the strings matching it, see [`ContentFilter`] for
+    /// how each format applies it.
+    pub content: Option<ContentFilter>,
+    /// Only serialize the resources with one of these uuids, empty to
+    /// serialize every resource.
+    pub uuids: Vec<Uuid>,
+    /// Serialize the resources in the order of their uuid instead of their
+    /// index and record the uuids, import then finds each resource by its
+    /// uuid so the export still works after a game patch reorders them.
+    /// Only used with Json, Yaml and Txt.
+    pub sort_by_uuid: bool,
+    /// Add the size each serialized string takes once its written to the
+    /// core, including its length prefix, so translators can see how much
+    /// room a string takes. Filled and blank strings get the size of their
+    /// exported text. The sizes are ignored on import. Only used with Json
+    /// and Yaml.
+    pub byte_lengths: bool,
+    /// Serialize the text of this language in place of every empty string of
+    /// the other languages and record which languages got it, import then
+    /// skips the filled strings that weren't edited. Only used with Json and Yaml.
+    pub fill_empty_from: Option<L>,
+    /// Serialize every selected string as empty, keeping the resources and
+    /// the line count of each cutscene, to make a template that translators
+    /// fill in without any source text. Import reads it like any other export.
+    pub blank: bool,
+}
+
+impl<L, R: Default> Default for ExportFilters<L, R> {
+    fn default() -> Self {
+        Self {
+            resources: R::default(),
+            content: None,
+            uuids: Vec::new(),
+            sort_by_uuid: false,
+            byte_lengths: false,
+            fill_empty_from: None,
+            blank: false,
+        }
+    }
+}
+
+impl<L, R> ExportFilters<L, R> {
+    /// The text of `string` to serialize, nothing if `blank` is on.
+    #[cfg(feature = "serialize")]
+    pub(crate) fn exported<'a>(&self, string: &'a str) -> &'a str {
+        match self.blank {
+            true => "",
+            false => string,
+        }
+    }
+
+    /// Returns `true` if a resource with `uuid` and `strings` pass the uuid
+    /// and content filters.
+    pub(crate) fn allows<'a>(
+        &self,
+        uuid: Option<Uuid>,
+        strings: impl Iterator<Item = StringEntry<'a, L>>,
+    ) -> bool
+    where
+        L: 'a,
+    {
+        uuid_allowed(&self.uuids, uuid) && self.content.as_ref().is_none_or(|f| f.matches(strings))
+    }
+
+    /// Returns `true` if `string` pass the content filter.
+    #[cfg(feature = "serialize")]
+    pub(crate) fn allows_string(&self, string: &str) -> bool {
+        self.content.as_ref().is_none_or(|f| f.is_match(string))
+    }
+}
+
+/// A non fatal oddity found while parsing a core, the core can still be
+/// exported and imported but some of its strings may not be what you expect.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseWarning<L> {
+    /// The resource have the magic of a local resource but didn't parse as
+    /// one, so its kept as raw data and none of its strings are exported.
+    KeptAsRaw { index: usize },
+    /// More than one cutscene group have this language code, groups are
+    /// matched to languages in the order of their codes so some of them
+    /// belong to another language.
+    DuplicateLangCode { index: usize, lang_code: u32 },
+    /// The cutscene doesn't have any line in this language while it have
+    /// lines in others.
+    EmptyCutsceneGroup { index: usize, language: L },
+    /// The resource have bytes after its known data, they are kept as is.
+    TrailingBytes { index: usize, len: usize },
+    /// A string of a resource that is kept as raw data isn't valid, `position`
+    /// is the offset of the string inside the core.
+    InvalidString {
+        index: usize,
+        position: u64,
+        kind: StringEncoding,
+    },
+    /// The string is close to the longest string its length prefix can hold.
+    NearLengthLimit {
+        index: usize,
+        language: L,
+        len: usize,
+        max: usize,
+    },
+    /// A repairing parse skipped `len` bytes at `offset` of the core that
+    /// couldn't be parsed. If `kept` they are written back as a raw resource,
+    /// if not the region was too small for one and is dropped.
+    Damaged {
+        offset: u64,
+        len: u64,
+        kept: bool,
+        reason: String,
+    },
+}
+
+impl<L: Display> Display for ParseWarning<L> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::KeptAsRaw { index } => write!(
+                f,
+                "resource {index} looks like a local resource but is kept as raw data"
+            ),
+            Self::DuplicateLangCode { index, lang_code } => write!(
+                f,
+                "cutscene {index} have more than one group with language code {lang_code}"
+            ),
+            Self::EmptyCutsceneGroup { index, language } => {
+                write!(f, "cutscene {index} doesn't have any line in {language}")
+            }
+            Self::TrailingBytes { index, len } => write!(
+                f,
+                "resource {index} have {len} unknown bytes after its data, they are kept as is"
+            ),
+            Self::InvalidString {
+                index,
+                position,
+                kind,
+            } => write!(
+                f,
+                "resource {index} have an invalid {kind} string at byte {position:#x} of the core"
+            ),
+            Self::NearLengthLimit {
+                index,
+                language,
+                len,
+                max,
+            } => write!(
+                f,
+                "string {index} ({language}) is {len} bytes, close to the limit of {max} bytes"
+            ),
+            Self::Damaged {
+                offset,
+                len,
+                kept,
+                reason,
+            } => write!(
+                f,
+                "skipped {len} damaged bytes at {offset:#x} of the core ({reason}), they are {}",
+                if *kept {
+                    "kept as a raw resource"
+                } else {
+                    "dropped"
+                }
+            ),
+        }
+    }
+}
+
+/// Parse the raw `data` of the resource at `index` as `T` again, and if it
+/// fails because of an invalid string warn about it. `offset` is where the
+/// data of the resource starts inside the core.
+pub(crate) fn invalid_string_warning<T, A, L>(
+    index: usize,
+    offset: u64,
+    data: &[u8],
+    args: A,
+) -> Option<ParseWarning<L>>
+where
+    for<'a> T: BinRead<Args<'a> = A>,
+{
+    let error = T::read_le_args(&mut Cursor::new(data), args).err()?;
+    match Error::from(error) {
+        Error::InvalidStringEncoding { position, kind } => Some(ParseWarning::InvalidString {
+            index,
+            position: offset + position,
+            kind,
+        }),
+        _ => None,
+    }
+}
+
+/// Warn about the [`U8String`](crate::utils::types::U8String)s that are
+/// within 10% of the limit of their `u16` length prefix, there isn't much
+/// room left for a longer translation. The prefix counts UTF-8 bytes, so the
+/// UTF-16 cutscene lines of HZD don't belong here.
+pub(crate) fn length_warnings<'a, L: 'a>(
+    strings: impl Iterator<Item = StringEntry<'a, L>>,
+) -> Vec<ParseWarning<L>> {
+    const MAX: usize = u16::MAX as usize;
+
+    strings
+        .filter(|e| e.string.len() > MAX / 10 * 9)
+        .map(|e| ParseWarning::NearLengthLimit {
+            index: e.index,
+            language: e.language,
+            len: e.string.len(),
+            max: MAX,
+        })
+        .collect()
+}
+
+/// The languages of `all` that have at least one non empty string, in the
+/// same order as `all`.
+pub(crate) fn populated_languages<'a, L: Copy + PartialEq + 'a>(
+    all: &[L],
+    strings: impl Iterator<Item = StringEntry<'a, L>>,
+) -> Vec<L> {
+    let mut found = Vec::new();
+    for entry in strings.filter(|e| !e.string.is_empty()) {
+        if !found.contains(&entry.language) {
+            found.push(entry.language);
+        }
+    }
+    all.iter().copied().filter(|l| found.contains(l)).collect()
+}
+
+/// Overview of a single resource inside a core file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ResourceInfo {
+    /// Index of the resource.
+    pub index: usize,
+    /// Name of the resource variant, like `Localized`.
+    pub variant: &'static str,
+    /// Size of the resource data in bytes, as it will be written.
+    pub size: u32,
+    /// Size of the resource data declared in the core it was read from.
+    pub declared_size: u32,
+    /// Uuid of the resource, [`None`] for non local resources.
+    pub uuid: Option<Uuid>,
+    /// Number of strings in every language, `0` for non local resources.
+    pub string_count: usize,
+}
+
+/// Build the [`ResourceInfo`] of every resource from its variant name, size,
+/// declared size, uuid and the strings of the core.
+pub(crate) fn resource_overview<'a, L: 'a>(
+    resources: impl Iterator<Item = (&'static str, u32, u32, Option<Uuid>)>,
+    strings: impl Iterator<Item = StringEntry<'a, L>>,
+) -> Vec<ResourceInfo> {
+    let mut overview: Vec<_> = resources
+        .enumerate()
+        .map(
+            |(index, (variant, size, declared_size, uuid))| ResourceInfo {
+                index,
+                variant,
+                size,
+                declared_size,
+                uuid,
+                string_count: 0,
+            },
+        )
+        .collect();
+
+    for entry in strings {
+        if let Some(info) = overview.get_mut(entry.index) {
+            info.string_count += 1;
+        }
+    }
+
+    overview
+}
+
+/// Index of the resource with `uuid` in the [`ResourceInfo`] list of a core.
+pub fn find_uuid(overview: &[ResourceInfo], uuid: Uuid) -> Option<usize> {
+    overview
+        .iter()
+        .find(|r| r.uuid == Some(uuid))
+        .map(|r| r.index)
+}
+
+/// Format the errors found while validating an input, one resource per line.
+pub(crate) fn list_resource_errors<E: Display>(errors: &[(usize, E)]) -> String {
+    errors
+        .iter()
+        .map(|(index, error)| format!("\n  resource {index}: {error}"))
+        .collect()
+}
+
+/// Compare the strings of a core with the strings read back from the written
+/// core, returns a description of the first difference.
+pub(crate) fn compare_strings<'a, 'b, L: PartialEq + Display>(
+    expected: impl Iterator<Item = StringEntry<'a, L>>,
+    mut written: impl Iterator<Item = StringEntry<'b, L>>,
+) -> Result<(), String> {
+    for e in expected {
+        match written.next() {
+            Some(w)
+                if w.index == e.index
+                    && w.sub == e.sub
+                    && w.language == e.language
+                    && w.string == e.string => {}
+            Some(_) => {
+                return Err(format!(
+                    "string {}:{} ({}) is different",
+                    e.index, e.sub, e.language
+                ))
+            }
+            None => {
+                return Err(format!(
+                    "string {}:{} ({}) is missing",
+                    e.index, e.sub, e.language
+                ))
+            }
+        }
+    }
+
+    if written.next().is_some() {
+        return Err("it have more strings than expected".to_owned());
+    }
+
+    Ok(())
+}
+
+/// Strings of a core keyed by their resource index, line index inside a
+/// cutscene and language, taken before an import to find what it changed.
+pub type StringSnapshot<L> = BTreeMap<(usize, usize, L), String>;
+
+/// Take a [`StringSnapshot`] of `strings`.
+pub fn string_snapshot<'a, L: Ord + 'a>(
+    strings: impl Iterator<Item = StringEntry<'a, L>>,
+) -> StringSnapshot<L> {
+    strings
+        .map(|e| ((e.index, e.sub, e.language), e.string.to_owned()))
+        .collect()
+}
+
+/// The strings of `after` that are different from the same string in
+/// `before` or didn't exist in it, together with the string in `before`.
+pub(crate) fn changed_strings<'a, 'b, L: Ord + Copy + 'a>(
+    before: &'b StringSnapshot<L>,
+    after: impl Iterator<Item = StringEntry<'a, L>>,
+) -> impl Iterator<Item = (Option<&'b str>, StringEntry<'a, L>)> {
+    after.filter_map(move |a| {
+        let b = before
+            .get(&(a.index, a.sub, a.language))
+            .map(String::as_str);
+        (b != Some(a.string)).then_some((b, a))
+    })
+}
+
+/// Compare the strings of a core before and after an import and return a
+/// warning for every changed string that is longer than `max_length`
+/// characters.
+///
+/// ## Arguments:
+/// * `before`: the [`string_snapshot`] of the core before import.
+/// * `after`: the strings of the core after import.
+/// * `max_length`: the max number of characters allowed in a string.
+pub fn check_lengths<'a, L: Display + Ord + Copy + 'a>(
+    before: &StringSnapshot<L>,
+    after: impl Iterator<Item = StringEntry<'a, L>>,
+    max_length: usize,
+) -> Vec<String> {
+    changed_strings(before, after)
+        .filter_map(|(_, a)| {
+            let len = a.string.chars().count();
+            (len > max_length).then(|| {
+                format!(
+                    "String {}:{} ({}) have {len} characters, more than max length {max_length}",
+                    a.index, a.sub, a.language
+                )
+            })
+        })
+        .collect()
+}
+
+/// Every format placeholder inside `string` in the order they are found,
+/// like `%s`, `%1$d`, `{0}` or a markup tag like `<b>` and `</b>`. `%%`
+/// is an escaped percent sign and isn't a placeholder.
+pub fn placeholders(string: &str) -> Vec<&str> {
+    const CONVERSIONS: &[u8] = b"diuoxXfFeEgGaAcspn@";
+
+    let bytes = string.as_bytes();
+    let mut found = Vec::new();
+    let mut i = 0;
+
+    while i < bytes.len() {
+        let end = match bytes[i] {
+            b'%' if bytes.get(i + 1) == Some(&b'%') => {
+                i += 2;
+                continue;
+            }
+            b'%' => {
+                let mut end = i + 1;
+                while end < bytes.len() && b"0123456789$.+-#".contains(&bytes[end]) {
+                    end += 1;
+                }
+                while end < bytes.len() && b"hlLqjzt".contains(&bytes[end]) {
+                    end += 1;
+                }
+                bytes
+                    .get(end)
+                    .is_some_and(|c| CONVERSIONS.contains(c))
+                    .then_some(end)
+            }
+            b'{' => string[i + 1..]
+                .find(['{', '}', '\n'])
+                .map(|len| i + 1 + len)
+                .filter(|&end| end > i + 1 && bytes[end] == b'}'),
+            b'<' => string[i + 1..]
+                .find(['<', '>', '\n'])
+                .map(|len| i + 1 + len)
+                .filter(|&end| {
+                    bytes[end] == b'>'
+                        && bytes
+                            .get(i + 1)
+                            .is_some_and(|c| c.is_ascii_alphabetic() || *c == b'/')
+                }),
+            _ => None,
+        };
+
+        match end {
+            Some(end) => {
+                found.push(&string[i..=end]);
+                i = end + 1;
+            }
+            None => i += 1,
+        }
+    }
+
+    found
+}
+
+/// Compare the strings of a core before and after an import and return a
+/// warning for every changed string that doesn't have the same
+/// [`placeholders`] as before, in any order. Strings imported as empty are
+/// left out as they are usually just not translated yet, and so are the
+/// strings that didn't exist before as there is nothing to compare them to.
+///
+/// ## Arguments:
+/// * `before`: the [`string_snapshot`] of the core before import.
+/// * `after`: the strings of the core after import.
+pub fn check_placeholders<'a, L: Display + Ord + Copy + 'a>(
+    before: &StringSnapshot<L>,
+    after: impl Iterator<Item = StringEntry<'a, L>>,
+) -> Vec<String> {
+    changed_strings(before, after)
+        .filter(|(_, a)| !a.string.is_empty())
+        .filter_map(|(b, a)| {
+            let b = b?;
+            let mut extra = placeholders(a.string);
+            let missing: Vec<_> = placeholders(b)
+                .into_iter()
+                .filter(|p| match extra.iter().position(|e| e == p) {
+                    Some(position) => {
+                        extra.remove(position);
+                        false
+                    }
+                    None => true,
+                })
+                .collect();
+
+            let mut problems = Vec::new();
+            if !missing.is_empty() {
+                problems.push(format!("is missing {}", missing.join(" ")));
+            }
+            if !extra.is_empty() {
+                problems.push(format!("have new {}", extra.join(" ")));
+            }
+            (!problems.is_empty()).then(|| {
+                format!(
+                    "String {}:{} ({}) {} placeholders",
+                    a.index,
+                    a.sub,
+                    a.language,
+                    problems.join(" and ")
+                )
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn changed_strings_are_matched_by_key() {
+        let entry = |index, sub, string| StringEntry {
+            index,
+            sub,
+            language: "English",
+            string,
+        };
+        let before =
+            string_snapshot([entry(0, 0, "a"), entry(0, 1, "b"), entry(1, 0, "c")].into_iter());
+        // a new line shifts every string after it
+        let after = [
+            entry(0, 0, "a"),
+            entry(0, 1, "b"),
+            entry(0, 2, "a much longer line"),
+            entry(1, 0, "c"),
+        ];
+
+        assert_eq!(
+            check_lengths(&before, after.into_iter(), 5),
+            ["String 0:2 (English) have 18 characters, more than max length 5"]
+        );
+    }
+
+    #[test]
+    fn resource_found_by_uuid() {
+        let local = hzd::HZDLocal::synthetic(4).unwrap();
+        let overview = local.resource_overview();
+
+        // index 0 is a chunk without uuid
+        assert_eq!(find_uuid(&overview, Uuid(synthetic::uuid(0))), Some(1));
+        assert_eq!(find_uuid(&overview, Uuid(synthetic::uuid(3))), Some(4));
+        assert_eq!(find_uuid(&overview, Uuid([0xff; 16])), None);
+    }
+
+    #[test]
+    fn placeholders_of_a_string() {
+        assert_eq!(
+            placeholders("%s took %1$d {0} <b>arrows</b> for 100%% {{}} a < b > c 5% %l"),
+            ["%s", "%1$d", "{0}", "<b>", "</b>"]
+        );
+        assert!(placeholders("Ünïcödé ✓ = \\n [3]").is_empty());
+    }
+
+    #[test]
+    fn changed_placeholders_are_reported() {
+        let entries = |strings: &'static [&'static str]| {
+            strings
+                .iter()
+                .enumerate()
+                .map(|(index, string)| StringEntry {
+                    index,
+                    sub: 0,
+                    language: "English",
+                    string,
+                })
+        };
+        let before = string_snapshot(entries(&["%s and {0}", "<b>%d</b>", "keep %s", "no text"]));
+        let after = entries(&["{0} et %s", "<b>%d", "keep %s", "", "new %s"]);
+
+        assert_eq!(
+            check_placeholders(&before, after),
+            ["String 1:0 (English) is missing </b> placeholders"]
+        );
+    }
+}
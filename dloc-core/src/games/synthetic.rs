@@ -0,0 +1,35 @@
+//! Pieces of made up core files, used to check dloc without any game file.
+
+/// A made up string for the `line` of `resource` in the language with the
+/// index of `language`. Some of them are empty and others have line breaks,
+/// non ascii characters and the symbols used by the export formats.
+pub(crate) fn string(resource: usize, language: usize, line: usize) -> String {
+    match (resource + language + line) % 5 {
+        0 => format!("Resource {resource} line {line} in language {language}"),
+        1 => String::new(),
+        2 => format!("First line {resource}\nsecond line\r\nthird line {language}"),
+        3 => format!("Ünïcödé ✓ {resource}:{language} = \\n [{line}] {{0}}"),
+        _ => format!("  surrounded by spaces {resource}  "),
+    }
+}
+
+/// A unique uuid for each resource.
+pub(crate) fn uuid(resource: usize) -> [u8; 16] {
+    let mut uuid = [0xd1; 16];
+    uuid[..8].copy_from_slice(&(resource as u64).to_le_bytes());
+    uuid
+}
+
+/// Wrap `data` in a chunk with the given `magic`.
+pub(crate) fn chunk(magic: u64, data: Vec<u8>) -> Vec<u8> {
+    let mut chunk = magic.to_le_bytes().to_vec();
+    chunk.extend((data.len() as u32).to_le_bytes());
+    chunk.extend(data);
+    chunk
+}
+
+/// Push `str` with a `u16` length prefix.
+pub(crate) fn push_u8_string(buf: &mut Vec<u8>, str: &str) {
+    buf.extend((str.len() as u16).to_le_bytes());
+    buf.extend(str.as_bytes());
+}
@@ -3,6 +3,7 @@ pub use error::*;
 pub mod error;
 pub mod games;
 pub mod logger;
+pub mod patch;
 #[cfg(feature = "serialize")]
 pub mod serialize;
 mod utils;
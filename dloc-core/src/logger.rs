@@ -19,6 +19,60 @@ pub trait Logger {
     fn error(&mut self, str: impl AsRef<str>);
 }
 
+impl<L: Logger> Logger for &mut L {
+    type Progress<'a>
+        = L::Progress<'a>
+    where
+        Self: 'a;
+
+    fn create_progress(&mut self, title: String, len: usize) -> Self::Progress<'_> {
+        (**self).create_progress(title, len)
+    }
+
+    fn info(&mut self, str: impl AsRef<str>) {
+        (**self).info(str)
+    }
+
+    fn good(&mut self, str: impl AsRef<str>) {
+        (**self).good(str)
+    }
+
+    fn warn(&mut self, str: impl AsRef<str>) {
+        (**self).warn(str)
+    }
+
+    fn error(&mut self, str: impl AsRef<str>) {
+        (**self).error(str)
+    }
+}
+
+/// A [`Logger`] that ignore every message and progress, useful when
+/// using the library without any UI.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoLogger;
+
+impl Logger for NoLogger {
+    type Progress<'a> = NoLogger;
+
+    fn create_progress(&mut self, _title: String, _len: usize) -> Self::Progress<'_> {
+        NoLogger
+    }
+
+    fn info(&mut self, _str: impl AsRef<str>) {}
+
+    fn good(&mut self, _str: impl AsRef<str>) {}
+
+    fn warn(&mut self, _str: impl AsRef<str>) {}
+
+    fn error(&mut self, _str: impl AsRef<str>) {}
+}
+
+impl Progress<'_> for NoLogger {
+    fn add_progress(&mut self) {}
+
+    fn end_progress(&mut self) {}
+}
+
 /// The [`Progress`] trait defines methods for reporting progress
 /// during long running operations. This can be implemented to provide
 /// visual feedback in a UI or log output.
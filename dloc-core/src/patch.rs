@@ -0,0 +1,282 @@
+//! A compact binary patch that only have the strings that differ between a
+//! vanilla core and a translated one, made with `create_patch` and applied
+//! with `apply_patch` of each game.
+//!
+//! The patch is little-endian, it starts with `DLOCPTCH`, a version byte, the
+//! game byte, the fingerprint of the vanilla core and the number of strings,
+//! then for every string its resource index, line index, language index and
+//! the UTF-8 bytes with a `u32` length.
+//!
+//! The fingerprint is the number of resources of the vanilla core and a
+//! FNV-1a hash of all of its strings, a patch is only applied to a core with
+//! the same fingerprint so it can't write strings into the wrong resources of
+//! another version of the core that happen to have the same layout.
+
+use std::{
+    collections::HashSet,
+    fmt::Display,
+    io::{Read, Seek, Write},
+};
+
+use binrw::{binrw, BinRead, BinWrite};
+
+use crate::{
+    games::{chunks::read_checked_count, StringEntry},
+    DResult, Error,
+};
+
+const VERSION: u8 = 2;
+
+/// Game byte of Horizon Zero Dawn patches.
+pub(crate) const GAME_HZD: u8 = 0;
+/// Game byte of Death Stranding patches.
+pub(crate) const GAME_DS: u8 = 1;
+
+/// The changed strings of a core, see the [module](self) documentation.
+#[binrw]
+#[brw(little, magic = b"DLOCPTCH")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Patch {
+    #[br(assert(version == VERSION, "Unsupported patch version {version}"))]
+    version: u8,
+    game: u8,
+    resource_count: u32,
+    strings_hash: u64,
+    #[bw(calc = entries.len() as u32)]
+    // every entry is at least 13 bytes
+    #[br(temp, parse_with = read_checked_count, args(13))]
+    count: u32,
+    #[br(count = count)]
+    entries: Vec<PatchEntry>,
+}
+
+#[binrw]
+#[brw(little)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct PatchEntry {
+    index: u32,
+    sub: u32,
+    language: u8,
+    #[bw(calc = string.len() as u32)]
+    #[br(temp, parse_with = read_checked_count, args(1))]
+    len: u32,
+    #[br(count = len, try_map = String::from_utf8)]
+    #[bw(map = String::as_bytes)]
+    string: String,
+}
+
+impl Patch {
+    /// Make a patch of every string of `modified` that differ from the same
+    /// string of `reference`, both should have the same resources and lines.
+    /// `resource_count` is the number of resources of `reference`.
+    pub(crate) fn diff<'a, L: Into<usize> + Copy + PartialEq + Display + 'a>(
+        game: u8,
+        resource_count: usize,
+        reference: impl Iterator<Item = StringEntry<'a, L>>,
+        mut modified: impl Iterator<Item = StringEntry<'a, L>>,
+    ) -> DResult<Self> {
+        let mut hasher = Fnv1a::new();
+        let mut entries = Vec::new();
+        for old in reference {
+            hasher.entry(&old);
+            let Some(new) = modified.next() else {
+                return Err(mismatch(&old));
+            };
+            if (old.index, old.sub) != (new.index, new.sub) || old.language != new.language {
+                return Err(mismatch(&old));
+            }
+            if old.string != new.string {
+                entries.push(PatchEntry {
+                    index: new.index as u32,
+                    sub: new.sub as u32,
+                    language: new.language.into() as u8,
+                    string: new.string.to_owned(),
+                });
+            }
+        }
+
+        if let Some(new) = modified.next() {
+            return Err(mismatch(&new));
+        }
+
+        Ok(Self {
+            version: VERSION,
+            game,
+            resource_count: resource_count as u32,
+            strings_hash: hasher.finish(),
+            entries,
+        })
+    }
+
+    /// Check the patch is for `game`, was made from a core with the same
+    /// fingerprint as the one that have `resource_count` resources and
+    /// `strings`, and every string of it exists in `strings`, then returns
+    /// them with the language index turned back into a language.
+    pub(crate) fn strings<'a, L: From<usize> + Into<usize> + Copy + 'a>(
+        &self,
+        game: u8,
+        resource_count: usize,
+        strings: impl Iterator<Item = StringEntry<'a, L>>,
+    ) -> DResult<Vec<(usize, usize, L, String)>> {
+        if self.game != game {
+            return Err(Error::InvalidPatch(
+                "the patch is for another game".to_owned(),
+            ));
+        }
+        if self.resource_count as usize != resource_count {
+            return Err(Error::InvalidPatch(format!(
+                "the patch is for a core with {} resources but this core have {resource_count}",
+                self.resource_count
+            )));
+        }
+
+        let mut hasher = Fnv1a::new();
+        let existing: HashSet<_> = strings
+            .inspect(|e| hasher.entry(e))
+            .map(|e| (e.index, e.sub, e.language.into()))
+            .collect();
+        if hasher.finish() != self.strings_hash {
+            return Err(Error::InvalidPatch(
+                "the patch is for another version of the core, its strings are different"
+                    .to_owned(),
+            ));
+        }
+
+        self.entries
+            .iter()
+            .map(|e| {
+                let key = (e.index as usize, e.sub as usize, e.language as usize);
+                if !existing.contains(&key) {
+                    return Err(Error::InvalidPatch(format!(
+                        "string {}:{} of language {} doesn't exist in the core",
+                        key.0, key.1, key.2
+                    )));
+                }
+                Ok((key.0, key.1, L::from(key.2), e.string.clone()))
+            })
+            .collect()
+    }
+
+    /// Number of strings in the patch.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns `true` if the patch doesn't change anything.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Read a patch from `reader`.
+    pub fn read<R: Read + Seek>(reader: &mut R) -> DResult<Self> {
+        Ok(Self::read_le(reader)?)
+    }
+
+    /// Write the patch to `writer`.
+    pub fn write<W: Write + Seek>(&self, writer: &mut W) -> DResult<()> {
+        Ok(self.write_le(writer)?)
+    }
+}
+
+/// A 64 bit FNV-1a hasher, unlike the hashers of std its output is fixed
+/// so it can be kept in patch files.
+struct Fnv1a(u64);
+
+impl Fnv1a {
+    const fn new() -> Self {
+        Self(0xcbf2_9ce4_8422_2325)
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 = (self.0 ^ *byte as u64).wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+
+    /// Hash the position and the string of `entry`.
+    fn entry<L: Into<usize> + Copy>(&mut self, entry: &StringEntry<'_, L>) {
+        for n in [
+            entry.index,
+            entry.sub,
+            entry.language.into(),
+            entry.string.len(),
+        ] {
+            self.write(&(n as u64).to_le_bytes());
+        }
+        self.write(entry.string.as_bytes());
+    }
+
+    const fn finish(&self) -> u64 {
+        self.0
+    }
+}
+
+fn mismatch<L: Display>(entry: &StringEntry<'_, L>) -> Error {
+    Error::InvalidPatch(format!(
+        "the cores don't have the same strings, first difference at {}:{} ({})",
+        entry.index, entry.sub, entry.language
+    ))
+}
+
+#[cfg(test)]
+mod test {
+    use std::io::Cursor;
+
+    use crate::games::hzd::{HZDLocal, Language};
+
+    use super::*;
+
+    #[test]
+    fn patch_round_trip() {
+        let reference = HZDLocal::synthetic(4).unwrap();
+        let mut modified = HZDLocal::synthetic(4).unwrap();
+        modified
+            .set_string(2, Language::French, 1, "changé".to_owned())
+            .unwrap();
+
+        let patch = modified.create_patch(&reference).unwrap();
+        assert_eq!(patch.len(), 1);
+
+        let mut buf = Cursor::new(Vec::new());
+        patch.write(&mut buf).unwrap();
+        buf.set_position(0);
+        let patch = Patch::read(&mut buf).unwrap();
+
+        let mut vanilla = HZDLocal::synthetic(4).unwrap();
+        assert_eq!(vanilla.apply_patch(&patch).unwrap(), 1);
+        assert_eq!(vanilla.to_bytes().unwrap(), modified.to_bytes().unwrap());
+
+        let mut other = HZDLocal::synthetic(1).unwrap();
+        assert!(matches!(
+            other.apply_patch(&patch),
+            Err(Error::InvalidPatch(_))
+        ));
+    }
+
+    #[test]
+    fn patch_is_only_applied_to_its_vanilla_core() {
+        let reference = HZDLocal::synthetic(4).unwrap();
+        let mut modified = HZDLocal::synthetic(4).unwrap();
+        modified
+            .set_string(2, Language::French, 1, "changé".to_owned())
+            .unwrap();
+        let patch = modified.create_patch(&reference).unwrap();
+
+        // same layout, but another version of the core
+        let mut updated = HZDLocal::synthetic(4).unwrap();
+        updated
+            .set_string(3, Language::English, 0, "updated".to_owned())
+            .unwrap();
+        let before = updated.to_bytes().unwrap();
+        assert!(matches!(
+            updated.apply_patch(&patch),
+            Err(Error::InvalidPatch(_))
+        ));
+        assert_eq!(updated.to_bytes().unwrap(), before);
+
+        // the fingerprint doesn't depend on the hasher of std
+        let mut hasher = Fnv1a::new();
+        hasher.write(b"a");
+        assert_eq!(hasher.finish(), 0xaf63_dc4c_8601_ec8c);
+    }
+}
@@ -0,0 +1,137 @@
+//! Transparent compression of exported files, picked from the extension of
+//! their path so `strings.json.gz` is a gzip compressed Json export.
+
+use std::{
+    fs::File,
+    io::{BufReader, BufWriter, Read, Write},
+    path::Path,
+};
+
+use crate::error::DResult;
+
+/// Compression of an exported file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Compression {
+    /// `.gz`
+    Gzip,
+    /// `.zst`
+    Zstd,
+}
+
+impl Compression {
+    /// Get the compression from the last extension of `path`, [`None`] if
+    /// the file isn't compressed.
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let ext = path.extension()?.to_str()?.to_lowercase();
+        match ext.as_str() {
+            "gz" => Some(Self::Gzip),
+            "zst" => Some(Self::Zstd),
+            _ => None,
+        }
+    }
+
+    /// The file extension used for this compression.
+    pub const fn extension(self) -> &'static str {
+        match self {
+            Self::Gzip => "gz",
+            Self::Zstd => "zst",
+        }
+    }
+}
+
+/// Writer of an exported file that compress the output if its path have
+/// the extension of a [`Compression`].
+///
+/// [`ExportWriter::finish`] should be called at the end, otherwise the end
+/// of a compressed stream may never be written.
+pub(crate) enum ExportWriter {
+    Plain(BufWriter<File>),
+    #[cfg(feature = "compression")]
+    Gzip(flate2::write::GzEncoder<BufWriter<File>>),
+    #[cfg(feature = "compression")]
+    Zstd(zstd::Encoder<'static, BufWriter<File>>),
+}
+
+impl ExportWriter {
+    pub(crate) fn create(path: &Path) -> DResult<Self> {
+        let file = || File::create(path).map(BufWriter::new);
+        Ok(match Compression::from_path(path) {
+            None => Self::Plain(file()?),
+            #[cfg(feature = "compression")]
+            Some(Compression::Gzip) => Self::Gzip(flate2::write::GzEncoder::new(
+                file()?,
+                flate2::Compression::default(),
+            )),
+            #[cfg(feature = "compression")]
+            Some(Compression::Zstd) => Self::Zstd(zstd::Encoder::new(file()?, 0)?),
+            #[cfg(not(feature = "compression"))]
+            Some(compression) => return Err(crate::Error::CompressionDisabled(compression)),
+        })
+    }
+
+    /// Write the end of the compressed stream and flush the file.
+    pub(crate) fn finish(self) -> std::io::Result<()> {
+        let mut writer = match self {
+            Self::Plain(writer) => writer,
+            #[cfg(feature = "compression")]
+            Self::Gzip(encoder) => encoder.finish()?,
+            #[cfg(feature = "compression")]
+            Self::Zstd(encoder) => encoder.finish()?,
+        };
+        writer.flush()
+    }
+}
+
+impl Write for ExportWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        match self {
+            Self::Plain(writer) => writer.write(buf),
+            #[cfg(feature = "compression")]
+            Self::Gzip(encoder) => encoder.write(buf),
+            #[cfg(feature = "compression")]
+            Self::Zstd(encoder) => encoder.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        match self {
+            Self::Plain(writer) => writer.flush(),
+            #[cfg(feature = "compression")]
+            Self::Gzip(encoder) => encoder.flush(),
+            #[cfg(feature = "compression")]
+            Self::Zstd(encoder) => encoder.flush(),
+        }
+    }
+}
+
+/// Open an exported file and decompress it if its path have the extension
+/// of a [`Compression`].
+pub(crate) fn open_export(path: &Path) -> DResult<BufReader<Box<dyn Read>>> {
+    let file = File::open(path)?;
+    let reader: Box<dyn Read> = match Compression::from_path(path) {
+        None => Box::new(file),
+        #[cfg(feature = "compression")]
+        Some(Compression::Gzip) => {
+            Box::new(flate2::read::MultiGzDecoder::new(BufReader::new(file)))
+        }
+        #[cfg(feature = "compression")]
+        Some(Compression::Zstd) => Box::new(zstd::Decoder::new(file)?),
+        #[cfg(not(feature = "compression"))]
+        Some(compression) => return Err(crate::Error::CompressionDisabled(compression)),
+    };
+    Ok(BufReader::new(reader))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn compression_from_path() {
+        let from = |p: &str| Compression::from_path(Path::new(p));
+        assert_eq!(from("strings.json.gz"), Some(Compression::Gzip));
+        assert_eq!(from("strings.TXT.ZST"), Some(Compression::Zstd));
+        assert_eq!(from("strings.json"), None);
+        assert_eq!(from("gz"), None);
+    }
+}
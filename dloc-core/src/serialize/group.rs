@@ -1,7 +1,7 @@
 use std::{
-    collections::BTreeSet,
-    fs::{create_dir_all, File},
-    io::{BufRead, BufReader, BufWriter},
+    collections::{BTreeSet, HashMap},
+    fs::{self, create_dir_all, File},
+    io::{self, BufReader, BufWriter, Cursor, Write},
     marker::PhantomData,
     ops::Range,
     path::{Path, PathBuf},
@@ -11,11 +11,19 @@ use serde::{Deserialize, Serialize};
 
 use crate::{
     error::{DResult, Error},
+    games::{
+        check_lengths, check_placeholders, compare_strings, detect::stream_companion,
+        StringSnapshot,
+    },
     logger::{Logger, ProgressIterator},
-    utils::{self, EofReplacor},
+    utils,
 };
 
-use super::{private, SerializeType};
+use super::{
+    compression::{open_export, ExportWriter},
+    key_value, ndjson, po, private, table, ImportOptions, ImportSummary, LineEnding, SerializeData,
+    SerializeType, TxtLineWriter, DEFAULT_LANGUAGE_SEPARATOR,
+};
 
 #[derive(Serialize, Deserialize)]
 struct TxtGroupDeserializeInfo<D, L: Ord> {
@@ -31,14 +39,147 @@ struct TxtGroupLocalDeInfo<D> {
     inner_info: D, // so much duplicate data, but I don't care for now
 }
 
+/// Adjusts the paths stored inside an exported file so they match the
+/// layout of the current input folder. `strip_prefix` is applied first,
+/// then `add_prefix`.
+#[derive(Debug, Clone, Default)]
+pub struct PathRemap {
+    /// Prefix to remove from the stored paths, paths that don't start
+    /// with it are left untouched.
+    pub strip_prefix: Option<PathBuf>,
+    /// Prefix to add to the stored paths.
+    pub add_prefix: Option<PathBuf>,
+}
+
+impl PathRemap {
+    fn apply(&self, path: PathBuf) -> PathBuf {
+        let path = match &self.strip_prefix {
+            Some(prefix) => match path.strip_prefix(prefix) {
+                Ok(p) => p.to_path_buf(),
+                Err(_) => path,
+            },
+            None => path,
+        };
+
+        match &self.add_prefix {
+            Some(prefix) => prefix.join(path),
+            None => path,
+        }
+    }
+}
+
+/// Where the core files of a group are read from and written to.
+enum CoreStorage {
+    /// Core files inside a folder, their paths are relative to it.
+    Dir(PathBuf),
+    /// Core files kept in memory, see [`DecimaGroup::from_memory`].
+    Memory {
+        cores: HashMap<PathBuf, Vec<u8>>,
+        written: HashMap<PathBuf, Vec<u8>>,
+    },
+}
+
+impl CoreStorage {
+    fn load<GAME: private::InternalGroupExtractor>(&self, path: &Path) -> DResult<GAME> {
+        match self {
+            Self::Dir(base_path) => {
+                GAME::internal_new(BufReader::new(File::open(base_path.join(path))?))
+            }
+            Self::Memory { cores, .. } => {
+                let Some(data) = cores.get(path) else {
+                    return Err(io::Error::new(
+                        io::ErrorKind::NotFound,
+                        format!("{} isn't in the group", path.display()),
+                    )
+                    .into());
+                };
+                GAME::internal_new(Cursor::new(data))
+            }
+        }
+    }
+
+    /// Write `game` to `path` and if `verify` is set read it back and compare
+    /// its strings with `game`. If `mtime_from` is set the written file gets
+    /// the modification time of that core.
+    fn write<GAME: private::InternalGroupExtractor>(
+        &mut self,
+        path: &Path,
+        game: &GAME,
+        verify: bool,
+        mtime_from: Option<&Path>,
+    ) -> DResult<()> {
+        let written: GAME = match self {
+            Self::Dir(base_path) => {
+                setup_output(path)?;
+                let mut writer = BufWriter::new(File::create(path)?);
+                game.internal_write(&mut writer)?;
+                writer.flush()?;
+
+                if let Some(source) = mtime_from {
+                    let modified = fs::metadata(base_path.join(source))?.modified()?;
+                    writer.get_ref().set_modified(modified)?;
+                }
+
+                if !verify {
+                    return Ok(());
+                }
+                GAME::internal_new(BufReader::new(File::open(path)?))?
+            }
+            Self::Memory { written, .. } => {
+                let mut writer = Cursor::new(Vec::new());
+                game.internal_write(&mut writer)?;
+                let data = written
+                    .entry(path.to_owned())
+                    .insert_entry(writer.into_inner());
+
+                if !verify {
+                    return Ok(());
+                }
+                GAME::internal_new(Cursor::new(data.get()))?
+            }
+        };
+
+        compare_strings(game.internal_strings(), written.internal_strings())
+            .map_err(|e| Error::VerifyFailed(format!("{}: {e}", path.display())))
+    }
+
+    /// Size of the core at `path` in bytes, without reading it.
+    fn size(&self, path: &Path) -> DResult<u64> {
+        match self {
+            Self::Dir(base_path) => Ok(fs::metadata(base_path.join(path))?.len()),
+            Self::Memory { cores, .. } => match cores.get(path) {
+                Some(data) => Ok(data.len() as u64),
+                None => Err(io::Error::new(
+                    io::ErrorKind::NotFound,
+                    format!("{} isn't in the group", path.display()),
+                )
+                .into()),
+            },
+        }
+    }
+
+    fn has_stream_companion(&self, path: &Path) -> bool {
+        match self {
+            Self::Dir(base_path) => stream_companion(base_path.join(path)).is_some(),
+            Self::Memory { .. } => false,
+        }
+    }
+}
+
 /// A group extractor for extracting all files inside Decima games to a format.
 pub struct DecimaGroup<GAME, LOGGER>
 where
     GAME: private::InternalGroupExtractor,
     LOGGER: Logger,
 {
-    base_path: PathBuf,
+    storage: CoreStorage,
     files: Vec<PathBuf>,
+    path_remap: PathRemap,
+    max_length: Option<usize>,
+    check_placeholders: bool,
+    verify_after_write: bool,
+    preserve_mtime: bool,
+    skip_deinfo: bool,
     logger: LOGGER,
     _phantom: PhantomData<GAME>,
 }
@@ -54,16 +195,71 @@ where
     ///
     /// ## Arguments:
     /// * `input`: input dir to load core files from.
-    pub fn new<P: AsRef<Path>>(input: P, mut logger: LOGGER) -> DResult<Self> {
+    pub fn new<P: AsRef<Path>>(input: P, logger: LOGGER) -> DResult<Self> {
+        Self::new_with_files(input, None, logger)
+    }
+
+    /// Creates a new DecimaGroup like [`Self::new`], but if `files` is given
+    /// only those files are used, in their order, instead of scanning the
+    /// input folder. Makes runs reproducible across machines.
+    ///
+    /// ## Arguments:
+    /// * `input`: input dir to load core files from.
+    /// * `files`: paths of the core files relative to `input`.
+    pub fn new_with_files<P: AsRef<Path>>(
+        input: P,
+        files: Option<Vec<PathBuf>>,
+        mut logger: LOGGER,
+    ) -> DResult<Self> {
         let input = input.as_ref();
 
-        logger.info("Generating file list from input folder.");
-        let files: Vec<PathBuf> = utils::generate_file_list(input, Some(&["core"]), usize::MAX)
-            .into_iter()
-            .map(|p| p.strip_prefix(input).map(Path::to_path_buf))
-            .collect::<Result<_, _>>()?;
-        logger.good("File list generated.");
+        let files = match files {
+            Some(files) => {
+                if let Some(missing) = files.iter().find(|f| !input.join(f).is_file()) {
+                    return Err(Error::ListedFileNotFound(missing.clone()));
+                }
+                files
+            }
+            None => {
+                logger.info("Generating file list from input folder.");
+                let files = utils::generate_file_list(input, Some(&["core"]), usize::MAX)?
+                    .into_iter()
+                    .map(|p| p.strip_prefix(input).map(Path::to_path_buf))
+                    .collect::<Result<_, _>>()?;
+                logger.good("File list generated.");
+                files
+            }
+        };
+
+        Self::with_storage(CoreStorage::Dir(input.to_owned()), files, logger)
+    }
+
+    /// Creates a new DecimaGroup from core files kept in memory by their path,
+    /// the cores are never read from disk and the ones made by import are kept
+    /// in memory too, see [`Self::take_written`]. Useful for tests and where
+    /// there is no file system.
+    ///
+    /// ## Arguments:
+    /// * `cores`: data of each core file by its path.
+    pub fn from_memory(cores: HashMap<PathBuf, Vec<u8>>, logger: LOGGER) -> DResult<Self> {
+        let mut files: Vec<PathBuf> = cores.keys().cloned().collect();
+        files.sort();
+
+        Self::with_storage(
+            CoreStorage::Memory {
+                cores,
+                written: HashMap::new(),
+            },
+            files,
+            logger,
+        )
+    }
 
+    fn with_storage(
+        storage: CoreStorage,
+        files: Vec<PathBuf>,
+        mut logger: LOGGER,
+    ) -> DResult<Self> {
         if files.is_empty() {
             return Err(Error::NoFileFound("core"));
         }
@@ -73,21 +269,264 @@ where
         }
 
         Ok(Self {
-            base_path: input.to_owned(),
+            storage,
             files,
+            path_remap: PathRemap::default(),
+            max_length: None,
+            check_placeholders: false,
+            verify_after_write: false,
+            preserve_mtime: false,
+            skip_deinfo: false,
             logger,
             _phantom: PhantomData,
         })
     }
 
+    /// Take the core files written by import by their output path, this is
+    /// always empty for a group that isn't made by [`Self::from_memory`].
+    pub fn take_written(&mut self) -> HashMap<PathBuf, Vec<u8>> {
+        match &mut self.storage {
+            CoreStorage::Dir(_) => HashMap::new(),
+            CoreStorage::Memory { written, .. } => std::mem::take(written),
+        }
+    }
+
+    /// Set the [`PathRemap`] used to match the paths stored in an exported
+    /// file against the files in input folder when importing.
+    pub fn set_path_remap(&mut self, path_remap: PathRemap) {
+        self.path_remap = path_remap;
+    }
+
+    /// Set the max number of characters a string can have, when importing
+    /// a warning is logged for every changed string longer than it.
+    pub fn set_max_length(&mut self, max_length: Option<usize>) {
+        self.max_length = max_length;
+    }
+
+    /// If set, importing logs a warning for every changed string that lost
+    /// or gained a placeholder, see [`check_placeholders`].
+    pub fn set_check_placeholders(&mut self, check_placeholders: bool) {
+        self.check_placeholders = check_placeholders;
+    }
+
+    /// If set, every core written in import is read back and checked to have
+    /// the same strings, see [`Error::VerifyFailed`].
+    pub fn set_verify_after_write(&mut self, verify_after_write: bool) {
+        self.verify_after_write = verify_after_write;
+    }
+
+    /// If set, every core written by an import keeps the modification time
+    /// of its input core, for tools that sync files by their modification
+    /// time. Cores kept in memory aren't affected.
+    pub fn set_preserve_mtime(&mut self, preserve_mtime: bool) {
+        self.preserve_mtime = preserve_mtime;
+    }
+
+    /// If set, [`Self::export_split`] doesn't write a deserialize info for
+    /// the txt files and [`Self::import_split`] makes it again from each
+    /// core. Such exports must have all languages without language names
+    /// or indices, and the export must not filter or sort resources since
+    /// import makes the deinfo from every resource of the core.
+    pub fn set_skip_deinfo(&mut self, skip_deinfo: bool) {
+        self.skip_deinfo = skip_deinfo;
+    }
+
+    /// Leave out every core file bigger than `max_size` bytes with a warning,
+    /// so a huge or broken core in an untrusted folder isn't parsed at all.
+    /// The sizes come from the file metadata, the cores aren't opened.
+    pub fn set_max_file_size(&mut self, max_size: u64) -> DResult<()> {
+        let mut kept = Vec::with_capacity(self.files.len());
+        for path in &self.files {
+            let size = self.storage.size(path)?;
+            if size > max_size {
+                self.logger.warn(format!(
+                    "Skipping {}, its size of {size} bytes is more than the max of {max_size} bytes.",
+                    path.display()
+                ));
+            } else {
+                kept.push(path.clone());
+            }
+        }
+
+        if kept.is_empty() {
+            return Err(Error::NoFileFound("core"));
+        }
+        self.files = kept;
+        Ok(())
+    }
+
+    /// The logger of the group, to log more messages after an export or import.
+    pub fn logger_mut(&mut self) -> &mut LOGGER {
+        &mut self.logger
+    }
+
+    /// Checks a group txt export against its deserialize info without
+    /// touching any core file, returns a list of all issues found.
+    ///
+    /// ## Arguments:
+    /// * `input`: path to the txt file, the deinfo should be next to it.
+    ///
+    /// ## Return:
+    /// Returns a [`DResult`] with the issues, an empty list means the
+    /// export is consistent.
+    pub fn check_txt_export<P: AsRef<Path>>(input: P) -> DResult<Vec<String>> {
+        let input = input.as_ref();
+
+        let lines = super::read_txt_lines(open_export(input)?, None)?;
+        let deinfo: TxtGroupDeserializeInfo<GAME::DeserializeInfo, GAME::Language> =
+            super::read_deinfo(input)?;
+
+        let mut issues = Vec::new();
+
+        if lines.len() != deinfo.count {
+            issues.push(format!(
+                "Line count doesn't match with deinfo, expected {} but got {}",
+                deinfo.count,
+                lines.len()
+            ));
+        }
+
+        for info in deinfo.info.iter() {
+            let Some(file_lines) = lines.get(info.range.clone()) else {
+                issues.push(format!(
+                    "Range {:?} of {} is out of bounds, input have {} lines",
+                    info.range,
+                    info.path.display(),
+                    lines.len()
+                ));
+                continue;
+            };
+
+            issues.extend(
+                GAME::internal_check_lines(file_lines.len(), &info.inner_info)
+                    .into_iter()
+                    .map(|i| format!("{}: {i}", info.path.display())),
+            );
+        }
+
+        Ok(issues)
+    }
+
+    /// Split a group txt export into one txt file for every core file inside
+    /// `output_dir`, each with its own deinfo. The result is the same as an
+    /// export with `split`, so it can be imported with [`Self::import_split`]
+    /// and no core file is needed.
+    ///
+    /// ## Arguments:
+    /// * `input`: path to the txt file, the deinfo should be next to it.
+    /// * `output_dir`: folder to write the files to.
+    ///
+    /// ## Return:
+    /// Returns a [`DResult`] with the number of written files.
+    pub fn split_txt_export<P: AsRef<Path>>(input: P, output_dir: P) -> DResult<usize> {
+        let input = input.as_ref();
+        let output_dir = output_dir.as_ref();
+
+        let lines = super::read_txt_lines(open_export(input)?, None)?;
+        let deinfo: TxtGroupDeserializeInfo<GAME::DeserializeInfo, GAME::Language> =
+            super::read_deinfo(input)?;
+
+        if lines.len() != deinfo.count {
+            return Err(Error::DeserializeError(format!(
+                "Line number doesn't match, expected {} but got {}",
+                deinfo.count,
+                lines.len()
+            )));
+        }
+
+        for info in deinfo.info.iter() {
+            let Some(file_lines) = lines.get(info.range.clone()) else {
+                return Err(Error::DeserializeError(format!(
+                    "Range {:?} of {} is out of bounds, input have {} lines",
+                    info.range,
+                    info.path.display(),
+                    lines.len()
+                )));
+            };
+
+            let mut output = output_dir.join(&info.path).into_os_string();
+            output.push(".txt");
+            let output = PathBuf::from(output);
+            setup_output(&output)?;
+
+            let mut writer = TxtLineWriter::new(BufWriter::new(File::create(&output)?), false);
+            for line in file_lines {
+                writer.write_line(line.clone())?;
+            }
+            writer.finish()?;
+
+            let writer = BufWriter::new(File::create(super::deinfo_path(&output))?);
+            serde_json::to_writer(writer, &info.inner_info)?;
+        }
+
+        Ok(deinfo.info.len())
+    }
+
+    /// Load every core file in the group and pass it to `f` together with
+    /// its path relative to the input folder, files without any local
+    /// resource are skipped.
+    pub fn for_each<F: FnMut(&Path, GAME)>(&mut self, mut f: F) -> DResult<()> {
+        for path in self
+            .files
+            .iter()
+            .progress(&mut self.logger, "Loading core files")
+        {
+            match self.storage.load(path) {
+                Ok(game) => f(path, game),
+                Err(Error::NoLocalResource) => continue,
+                Err(e) => return Err(e),
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Count the strings of the given `languages` in every core file of the
+    /// group without exporting anything.
+    ///
+    /// ## Arguments:
+    /// * `languages`: list of languages to count.
+    /// * `filters`: only count the strings an export with them writes.
+    ///
+    /// ## Return:
+    /// Returns a [`DResult`] with the total number of strings.
+    pub fn count_strings<L: AsRef<[GAME::Language]>>(
+        &mut self,
+        languages: L,
+        filters: &GAME::ExportFilters,
+    ) -> DResult<usize> {
+        let languages = languages.as_ref();
+
+        let mut count = 0;
+        for path in self
+            .files
+            .iter()
+            .progress(&mut self.logger, "Counting strings")
+        {
+            let local: GAME = match self.storage.load(path) {
+                Ok(r) => r,
+                Err(Error::NoLocalResource) => continue,
+                Err(e) => return Err(e),
+            };
+
+            count += local
+                .internal_exported_strings(filters)
+                .filter(|e| languages.contains(&e.language))
+                .count();
+        }
+
+        Ok(count)
+    }
+
     /// Exports the group data to the given output path in the specified serialization format.
-    /// Supported formats are JSON, YAML (if enabled) and plaintext. The plaintext format includes both the
+    /// Supported formats are JSON, YAML (if enabled), key-value and plaintext. The plaintext format includes both the
     /// extracted text data and metadata for deserializing (that will be saved next to txt).
     ///
     /// ## Arguments:
     /// * `output`: path to output file.
     /// * `languages`: list of languages to export.
     /// * [`serialize_type`](SerializeType): serialize the output to what type.
+    /// * `filters`: what gets exported from each core, see [`ExportFilters`](crate::games::ExportFilters).
     ///
     /// ## Return:
     /// Returns a [`DResult`] indicating whether serialization was successful or not.
@@ -96,60 +535,81 @@ where
         output: P,
         languages: L,
         serialize_type: SerializeType,
+        filters: &GAME::ExportFilters,
     ) -> DResult<()> {
         let output = output.as_ref();
         let languages = languages.as_ref();
 
+        // the paths are written as text, only a split export keeps them as file names
+        if let Some(path) = self.files.iter().find(|p| p.to_str().is_none()) {
+            return Err(Error::NonUtf8Path(path.clone()));
+        }
+
         match serialize_type {
-            SerializeType::Json => {
+            SerializeType::Json { indent, omit_empty } => {
                 self.logger.info("Target serialize format: Json.");
-                let locals = self.serialize_locals(languages)?;
-                let writer = BufWriter::new(File::create(output)?);
-                serde_json::to_writer_pretty(writer, &locals)?;
+                let locals = self.serialize_locals(languages, omit_empty, filters)?;
+                let mut writer = ExportWriter::create(output)?;
+                super::write_json(&mut writer, &locals, indent.as_deref())?;
+                writer.finish()?;
             }
             #[cfg(feature = "serialize_yaml")]
-            SerializeType::Yaml => {
+            SerializeType::Yaml { omit_empty } => {
                 self.logger.info("Target serialize format: Yaml.");
-                let locals = self.serialize_locals(languages)?;
-                let writer = BufWriter::new(File::create(output)?);
-                serde_yaml::to_writer(writer, &locals)?;
+                let locals = self.serialize_locals(languages, omit_empty, filters)?;
+                let mut writer = ExportWriter::create(output)?;
+                serde_yaml::to_writer(&mut writer, &locals)?;
+                writer.finish()?;
             }
-            SerializeType::Txt { add_language_names } => {
+            SerializeType::Txt {
+                add_language_names,
+                language_separator,
+                add_indices,
+                line_ending,
+            } => {
+                let language_separator = add_language_names.then(|| {
+                    language_separator
+                        .as_deref()
+                        .unwrap_or(DEFAULT_LANGUAGE_SEPARATOR)
+                });
                 self.logger.info("Target serialize format: Txt.");
-                let mut lines = Vec::with_capacity(self.files.len());
+                let mut file = ExportWriter::create(output)?;
+                let mut writer = TxtLineWriter::new(&mut file, line_ending.is_some());
                 let mut info = Vec::with_capacity(self.files.len());
-                let mut count = 0;
 
                 for path in self
                     .files
                     .iter()
                     .progress(&mut self.logger, "Exporting lines from core files")
                 {
-                    let reader = BufReader::new(File::open(self.base_path.join(path))?);
-                    let local = match GAME::internal_new(reader) {
+                    let local: GAME = match self.storage.load(path) {
                         Ok(r) => r,
                         Err(e) => match e {
                             Error::NoLocalResource => continue,
                             e => return Err(e),
                         },
                     };
-                    let (ilines, deinfo) =
-                        local.internal_serialize_to_lines(languages, add_language_names);
 
-                    let len = ilines.len();
-                    lines.extend(ilines);
+                    let start = writer.count();
+                    let deinfo = local.internal_serialize_lines_with(
+                        languages,
+                        filters,
+                        language_separator,
+                        add_indices,
+                        |line| writer.write_line(line),
+                    )?;
+
                     info.push(TxtGroupLocalDeInfo {
                         path: path.to_owned(),
-                        range: count..count + len,
+                        range: start..writer.count(),
                         inner_info: deinfo,
                     });
-                    count += len;
                 }
 
-                let lines: Vec<_> = lines.into_iter().map(EofReplacor::replace_eol).collect();
-                self.logger.info("Writing lines to output file.");
-                std::fs::write(output, lines.join("\n"))?;
-                self.logger.good("Write finished.");
+                let count = writer.count();
+                writer.finish()?;
+                file.finish()?;
+                self.logger.good("Lines written to output file.");
                 let deinfo = TxtGroupDeserializeInfo {
                     languages: BTreeSet::from_iter(languages.iter().copied()),
                     count,
@@ -158,68 +618,192 @@ where
 
                 self.logger
                     .info("Writing deserialize data to a file next to output.");
-                let path = output.with_extension(super::DEINFO_EXT);
+                let path = super::deinfo_path(output);
                 let writer = BufWriter::new(File::create(path)?);
                 serde_json::to_writer(writer, &deinfo)?;
                 self.logger.good("Write finished.");
             }
+            SerializeType::KeyValue
+            | SerializeType::Table
+            | SerializeType::Ndjson
+            | SerializeType::Po => {
+                self.logger
+                    .info(format!("Target serialize format: {serialize_type:?}."));
+                let mut writer = ExportWriter::create(output)?;
+                match serialize_type {
+                    SerializeType::Table => {
+                        table::write_header(&mut writer, languages, GAME::NOTES)?
+                    }
+                    SerializeType::Po => po::write_header(&mut writer)?,
+                    _ => (),
+                }
+
+                for path in self
+                    .files
+                    .iter()
+                    .progress(&mut self.logger, "Exporting strings from core files")
+                {
+                    let local: GAME = match self.storage.load(path) {
+                        Ok(r) => r,
+                        Err(Error::NoLocalResource) => continue,
+                        Err(e) => return Err(e),
+                    };
+
+                    let strings = local.internal_exported_strings(filters);
+                    if serialize_type == SerializeType::Ndjson {
+                        ndjson::write_strings(&mut writer, strings, languages, Some(path))?;
+                        continue;
+                    }
+                    if serialize_type == SerializeType::Po {
+                        po::write_strings(
+                            &mut writer,
+                            strings,
+                            languages,
+                            Some(path),
+                            |index, sub, language| {
+                                local.internal_string_comment(index, sub, language)
+                            },
+                        )?;
+                        continue;
+                    }
+
+                    writeln!(writer, "[{}]", path.display())?;
+                    match serialize_type {
+                        SerializeType::Table => {
+                            let notes = GAME::NOTES.then_some(|index, sub, language| {
+                                local.internal_string_extras(index, sub, language).0
+                            });
+                            table::write_rows(&mut writer, strings, languages, notes)?
+                        }
+                        _ => key_value::write_strings(
+                            &mut writer,
+                            local.internal_exported_strings(filters),
+                            languages,
+                        )?,
+                    }
+                }
+
+                writer.finish()?;
+            }
+            #[cfg(feature = "sqlite")]
+            SerializeType::Sqlite => {
+                self.logger.info("Target serialize format: Sqlite.");
+                let mut writer = super::sqlite::SqliteWriter::create(output)?;
+
+                for path in self
+                    .files
+                    .iter()
+                    .progress(&mut self.logger, "Exporting strings from core files")
+                {
+                    let local: GAME = match self.storage.load(path) {
+                        Ok(r) => r,
+                        Err(Error::NoLocalResource) => continue,
+                        Err(e) => return Err(e),
+                    };
+
+                    writer.write_strings(&local, languages, filters, Some(path))?;
+                }
+
+                writer.finish()?;
+            }
         }
         self.logger.good("Serialization finished.");
 
         Ok(())
     }
 
+    /// Serialize the locals of every core file in the group and return them
+    /// by their path relative to the input folder, nothing is written to disk.
+    /// Use it with [`NoLogger`](crate::logger::NoLogger) to also skip logs and progress.
+    ///
+    /// ## Arguments:
+    /// * `languages`: list of languages to export.
+    /// * `filters`: what gets exported from each core, see [`ExportFilters`](crate::games::ExportFilters).
+    ///
+    /// ## Return:
+    /// Returns a [`DResult`] with the serialized locals of each file.
+    pub fn export_to_map<L: AsRef<[GAME::Language]>>(
+        &mut self,
+        languages: L,
+        filters: &GAME::ExportFilters,
+    ) -> DResult<HashMap<PathBuf, GAME::Output>> {
+        self.serialize_locals(languages.as_ref(), false, filters)
+    }
+
+    /// Imports locals made by [`export_to_map`](Self::export_to_map) and
+    /// creates new core files in `output_dir`.
+    ///
+    /// ## Arguments:
+    /// * `locals`: the locals to import by their path relative to the input folder.
+    /// * `output_dir`: the output dir to save all new created files.
+    /// * `options`: how each local is checked, see [`ImportOptions`].
+    ///
+    /// ## Return:
+    /// Returns a [`DResult`] with an [`ImportSummary`] of what changed if
+    /// deserialization and creating new core files was successful.
+    pub fn import_from_map<P: AsRef<Path>>(
+        &mut self,
+        locals: HashMap<PathBuf, GAME::Output>,
+        output_dir: P,
+        options: ImportOptions,
+    ) -> DResult<ImportSummary> {
+        let summary = self.deserialize_locals(locals, output_dir.as_ref(), options)?;
+        self.logger.info(format!("Import summary: {summary}."));
+
+        Ok(summary)
+    }
+
     /// Imports previously exported group data from the given input path and deserialize
     /// them into game files in the output directory.
-    /// Supported formats are JSON, YAML (if enabled) and plaintext. The plaintext format requires the metadata file
+    /// Supported formats are JSON, YAML (if enabled), key-value and plaintext. The plaintext format requires the metadata file
     /// generated during export to be present for deserialization.
     ///
     /// ## Arguments:
     /// * `input`: the input serialized local file.
     /// * `output_dir`: the output dir to save all new created files.
     /// * [`serialize_type`](SerializeType): serialize the output to what type.
+    /// * `options`: how each local is checked, see [`ImportOptions`].
     ///
     /// ## Return:
-    /// Returns a [`DResult`] indicating whether deserialization and creating
-    /// new core files was successful or not.
+    /// Returns a [`DResult`] with an [`ImportSummary`] of what changed if
+    /// deserialization and creating new core files was successful.
     pub fn import<P: AsRef<Path>>(
         &mut self,
         input: P,
         output_dir: P,
         serialize_type: SerializeType,
-    ) -> DResult<()> {
+        options: ImportOptions,
+    ) -> DResult<ImportSummary> {
         let input = input.as_ref();
         let output_dir = output_dir.as_ref();
 
+        self.warn_stream_companions();
+
         self.logger.info("Opening input file.");
-        let reader = BufReader::new(File::open(input)?);
+        let reader = open_export(input)?;
         self.logger.good("Input file opened.");
 
-        match serialize_type {
-            SerializeType::Json => {
+        let summary = match serialize_type {
+            SerializeType::Json { .. } => {
                 self.logger.info("Deserialize from Json");
                 let locals = serde_json::from_reader(reader)?;
-                self.deserialize_locals(locals, output_dir)?;
+                self.deserialize_locals(locals, output_dir, options)?
             }
             #[cfg(feature = "serialize_yaml")]
-            SerializeType::Yaml => {
+            SerializeType::Yaml { .. } => {
                 self.logger.info("Deserialize from Yaml");
                 let locals = serde_yaml::from_reader(reader)?;
-                self.deserialize_locals(locals, output_dir)?;
+                self.deserialize_locals(locals, output_dir, options)?
             }
-            SerializeType::Txt { .. } => {
+            SerializeType::Txt { line_ending, .. } => {
                 self.logger.info("Deserialize from Txt");
                 self.logger.info("Reading lines from input file.");
-                let lines = reader
-                    .lines()
-                    .map(|s| s.map(EofReplacor::replace_eol_back))
-                    .collect::<std::io::Result<Vec<String>>>()?;
+                let lines = super::read_txt_lines(reader, line_ending)?;
                 self.logger.good("Reading lines finished.");
 
                 self.logger.info("Reading deserialize info.");
-                let reader = BufReader::new(File::open(input.with_extension(super::DEINFO_EXT))?);
                 let deinfo: TxtGroupDeserializeInfo<GAME::DeserializeInfo, GAME::Language> =
-                    serde_json::from_reader(reader)?;
+                    super::read_deinfo(input)?;
                 self.logger.info("Reading deserialize info finished.");
 
                 if lines.len() != deinfo.count {
@@ -230,12 +814,16 @@ where
                     )));
                 }
 
+                let mut summary = ImportSummary::default();
+                let mut warnings = Vec::new();
                 for info in deinfo.info.into_iter().progress(
                     &mut self.logger,
                     "Importing locals and creating new core files",
                 ) {
-                    if !self.files.contains(&info.path) {
+                    let path = self.path_remap.apply(info.path);
+                    if !self.files.contains(&path) {
                         // file not found in input folder
+                        summary.files_skipped += 1;
                         continue;
                     }
 
@@ -243,75 +831,458 @@ where
                         return Err(Error::DeserializeError(format!("Found invalid index when tried to read strings from input. max index: {}. are you sure you didn't modifed the data?", lines.len())));
                     };
 
-                    let reader = BufReader::new(File::open(self.base_path.join(&info.path))?);
-                    let mut game = GAME::internal_new(reader)?;
-                    game.internal_deserialize_and_update_from_lines(lines, info.inner_info)
-                        .map_err(|e| Error::DeserializeError(e.to_string()))?;
+                    let mut game: GAME = self.storage.load(&path)?;
+                    let before = super::snapshot(&game);
+                    game.internal_deserialize_and_update_from_lines(
+                        lines,
+                        info.inner_info,
+                        options,
+                    )
+                    .map_err(|e| Error::GameDeserialize(e.into()))?;
+                    summary.add_changes(&before, game.internal_strings());
+                    warnings.extend(length_warnings(&path, self.max_length, &before, &game));
+                    warnings.extend(placeholder_warnings(
+                        &path,
+                        self.check_placeholders,
+                        &before,
+                        &game,
+                    ));
+
+                    self.storage.write(
+                        &output_dir.join(&path),
+                        &game,
+                        self.verify_after_write,
+                        self.preserve_mtime.then_some(path.as_path()),
+                    )?;
+                    summary.files_written += 1;
+                }
 
-                    let path = output_dir.join(info.path);
-                    setup_output(&path)?;
-                    let writer = BufWriter::new(File::create(path)?);
-                    game.internal_write(writer)?;
+                for warning in warnings {
+                    self.logger.warn(warning);
                 }
+
+                summary
             }
-        }
+            SerializeType::KeyValue
+            | SerializeType::Table
+            | SerializeType::Ndjson
+            | SerializeType::Po => {
+                self.logger
+                    .info(format!("Deserialize from {serialize_type:?}"));
+                let sections = match serialize_type {
+                    SerializeType::Table => table::read::<_, GAME::Language>(reader)?,
+                    SerializeType::Ndjson => ndjson::read::<_, GAME::Language>(reader)?,
+                    SerializeType::Po => po::read::<_, GAME::Language>(reader)?,
+                    _ => key_value::read::<_, GAME::Language>(reader)?,
+                };
+                self.import_sections(sections, output_dir, &serialize_type)?
+            }
+            #[cfg(feature = "sqlite")]
+            SerializeType::Sqlite => {
+                self.logger.info("Deserialize from Sqlite");
+                let sections = super::sqlite::read::<GAME::Language>(input, GAME::GAME)?;
+                self.import_sections(sections, output_dir, &serialize_type)?
+            }
+        };
         self.logger
             .good("Deserialization and update finished and all new files saved to output folder.");
+        self.logger.info(format!("Import summary: {summary}."));
+
+        Ok(summary)
+    }
+
+    /// Exports every core file of the group to its own file inside `output_dir`,
+    /// keeping the folder layout of the input folder. Each file is named after
+    /// its core with the extension of the format added, like `a.core.json`,
+    /// txt exports get their own deserialize info next to them unless
+    /// [`Self::set_skip_deinfo`] is used.
+    ///
+    /// ## Arguments:
+    /// * `output_dir`: path to output folder.
+    /// * `languages`: list of languages to export.
+    /// * [`serialize_type`](SerializeType): serialize the output to what type.
+    /// * `filters`: what gets exported from each core, see [`ExportFilters`](crate::games::ExportFilters).
+    ///
+    /// ## Return:
+    /// Returns a [`DResult`] indicating whether serialization was successful or not.
+    pub fn export_split<P: AsRef<Path>, L: AsRef<[GAME::Language]>>(
+        &mut self,
+        output_dir: P,
+        languages: L,
+        serialize_type: SerializeType,
+        filters: &GAME::ExportFilters,
+    ) -> DResult<()> {
+        let output_dir = output_dir.as_ref();
+        let languages = languages.as_ref();
+        let extension = serialize_type.extension();
+
+        let skip_deinfo = match serialize_type {
+            SerializeType::Txt {
+                add_language_names,
+                add_indices,
+                ..
+            } if self.skip_deinfo => {
+                let all = GAME::ALL_LANGUAGES.iter().all(|l| languages.contains(l));
+                if add_language_names || add_indices || !all {
+                    return Err(Error::SerializeError("Txt exports without deinfo must have all languages and no language names or indices".to_owned()));
+                }
+                true
+            }
+            _ => false,
+        };
+        let normalize_eol = matches!(
+            serialize_type,
+            SerializeType::Txt {
+                line_ending: Some(_),
+                ..
+            }
+        );
+
+        for path in self
+            .files
+            .iter()
+            .progress(&mut self.logger, "Exporting core files to separate files")
+        {
+            let local: GAME = match self.storage.load(path) {
+                Ok(r) => r,
+                Err(Error::NoLocalResource) => continue,
+                Err(e) => return Err(e),
+            };
+
+            let mut output = output_dir.join(path).into_os_string();
+            output.push(".");
+            output.push(extension);
+            let output = PathBuf::from(output);
+            setup_output(&output)?;
+            if skip_deinfo {
+                let mut writer =
+                    TxtLineWriter::new(BufWriter::new(File::create(output)?), normalize_eol);
+                local.internal_serialize_lines_with(
+                    GAME::ALL_LANGUAGES,
+                    filters,
+                    None,
+                    false,
+                    |line| writer.write_line(line),
+                )?;
+                writer.finish()?;
+            } else {
+                local.serialize(output, languages, serialize_type.clone(), filters)?;
+            }
+        }
+        self.logger.good("Serialization finished.");
 
         Ok(())
     }
 
+    /// Imports the files made by [`export_split`](Self::export_split) back
+    /// and creates new core files in `output_dir`. Txt files without a
+    /// deserialize info are read like [`Self::set_skip_deinfo`] exports.
+    ///
+    /// ## Arguments:
+    /// * `input_dir`: the folder with the serialized local files.
+    /// * `output_dir`: the output dir to save all new created files.
+    /// * [`serialize_type`](SerializeType): the serialize type of the files.
+    /// * `options`: how each file is checked, see [`ImportOptions`].
+    ///
+    /// ## Return:
+    /// Returns a [`DResult`] with an [`ImportSummary`] of what changed if
+    /// deserialization and creating new core files was successful.
+    pub fn import_split<P: AsRef<Path>>(
+        &mut self,
+        input_dir: P,
+        output_dir: P,
+        serialize_type: SerializeType,
+        options: ImportOptions,
+    ) -> DResult<ImportSummary> {
+        let input_dir = input_dir.as_ref();
+        let output_dir = output_dir.as_ref();
+        let extension = serialize_type.extension();
+
+        self.warn_stream_companions();
+
+        self.logger
+            .info("Generating file list from exported folder.");
+        let exported: Vec<PathBuf> =
+            utils::generate_file_list(input_dir, Some(&[extension]), usize::MAX)?
+                .into_iter()
+                .filter(|p| !p.to_string_lossy().ends_with(super::DEINFO_EXT))
+                .collect();
+        if exported.is_empty() {
+            return Err(Error::NoFileFound(extension));
+        }
+        self.logger.good("File list generated.");
+
+        let mut summary = ImportSummary::default();
+        let mut warnings = Vec::new();
+        for file in exported.iter().progress(
+            &mut self.logger,
+            "Importing locals and creating new core files",
+        ) {
+            // `a.core.json` -> `a.core`
+            let path = file.strip_prefix(input_dir)?.with_extension("");
+            let path = self.path_remap.apply(path);
+            if !self.files.contains(&path) {
+                // file not found in input folder
+                summary.files_skipped += 1;
+                continue;
+            }
+
+            let mut game: GAME = self.storage.load(&path)?;
+            let before = super::snapshot(&game);
+            summary += match serialize_type {
+                SerializeType::Txt { line_ending, .. } if !super::deinfo_path(file).is_file() => {
+                    warnings.push(format!(
+                        "{} have no deinfo, it was read as an export of all languages without language names or indices.",
+                        file.display()
+                    ));
+                    import_without_deinfo(&mut game, file, line_ending, options)?
+                }
+                _ => game.deserialize_and_update(file, serialize_type.clone(), options)?,
+            };
+            warnings.extend(length_warnings(&path, self.max_length, &before, &game));
+            warnings.extend(placeholder_warnings(
+                &path,
+                self.check_placeholders,
+                &before,
+                &game,
+            ));
+
+            self.storage.write(
+                &output_dir.join(&path),
+                &game,
+                self.verify_after_write,
+                self.preserve_mtime.then_some(path.as_path()),
+            )?;
+            summary.files_written += 1;
+        }
+
+        for warning in warnings {
+            self.logger.warn(warning);
+        }
+
+        self.logger
+            .good("Deserialization and update finished and all new files saved to output folder.");
+        self.logger.info(format!("Import summary: {summary}."));
+
+        Ok(summary)
+    }
+
+    fn warn_stream_companions(&mut self) {
+        let streams = self
+            .files
+            .iter()
+            .filter(|p| self.storage.has_stream_companion(p))
+            .count();
+        if streams != 0 {
+            self.logger.warn(format!(
+                "{streams} core files have a companion .core.stream file, dloc doesn't update them so edited cutscene timings may get out of sync."
+            ));
+        }
+    }
+
     fn serialize_locals(
         &mut self,
         languages: &[GAME::Language],
-    ) -> DResult<std::collections::HashMap<PathBuf, GAME::Output>> {
-        let mut locals = std::collections::HashMap::with_capacity(self.files.len());
+        omit_empty: bool,
+        filters: &GAME::ExportFilters,
+    ) -> DResult<HashMap<PathBuf, GAME::Output>> {
+        let mut locals = HashMap::with_capacity(self.files.len());
         for path in self
             .files
             .iter()
             .progress(&mut self.logger, "Extracting locals")
         {
-            let reader = BufReader::new(File::open(self.base_path.join(path))?);
-            let game = match GAME::internal_new(reader) {
+            let game: GAME = match self.storage.load(path) {
                 Ok(r) => r,
                 Err(e) => match e {
                     Error::NoLocalResource => continue,
                     e => return Err(e),
                 },
             };
-            locals.insert(path.to_owned(), game.internal_serialize(languages));
+            locals.insert(
+                path.to_owned(),
+                game.internal_serialize(languages, omit_empty, filters),
+            );
         }
 
         Ok(locals)
     }
 
+    /// Import the strings of every section into its core and write the new
+    /// core files to `output_dir`.
+    fn import_sections(
+        &mut self,
+        sections: Vec<key_value::Section<GAME::Language>>,
+        output_dir: &Path,
+        serialize_type: &SerializeType,
+    ) -> DResult<ImportSummary> {
+        let mut summary = ImportSummary::default();
+        let mut warnings = Vec::new();
+        for section in sections.into_iter().progress(
+            &mut self.logger,
+            "Importing strings and creating new core files",
+        ) {
+            let Some(path) = section.path else {
+                if section.values.is_empty() {
+                    continue;
+                }
+                return Err(Error::DeserializeError(format!(
+                    "Found values before the first section in a group {} file",
+                    serialize_type.extension()
+                )));
+            };
+
+            let path = self.path_remap.apply(path);
+            if !self.files.contains(&path) {
+                // file not found in input folder
+                summary.files_skipped += 1;
+                continue;
+            }
+
+            let mut game: GAME = self.storage.load(&path)?;
+            let before = super::snapshot(&game);
+            for kv in section.values {
+                let error = |e| Error::DeserializeError(format!("{}: {e}", path.display()));
+                if let Some(note) = kv.note {
+                    game.internal_set_note(kv.index, kv.sub, kv.language, note)
+                        .map_err(error)?;
+                }
+                game.internal_set_string(kv.index, kv.sub, kv.language, kv.value)
+                    .map_err(error)?;
+            }
+            summary.add_changes(&before, game.internal_strings());
+            warnings.extend(length_warnings(&path, self.max_length, &before, &game));
+            warnings.extend(placeholder_warnings(
+                &path,
+                self.check_placeholders,
+                &before,
+                &game,
+            ));
+
+            self.storage.write(
+                &output_dir.join(&path),
+                &game,
+                self.verify_after_write,
+                self.preserve_mtime.then_some(path.as_path()),
+            )?;
+            summary.files_written += 1;
+        }
+
+        for warning in warnings {
+            self.logger.warn(warning);
+        }
+
+        Ok(summary)
+    }
+
     fn deserialize_locals(
         &mut self,
-        locals: std::collections::HashMap<PathBuf, GAME::Output>,
+        locals: HashMap<PathBuf, GAME::Output>,
         output_dir: &Path,
-    ) -> DResult<()> {
+        options: ImportOptions,
+    ) -> DResult<ImportSummary> {
+        let mut summary = ImportSummary::default();
+        let mut warnings = Vec::new();
         for (path, data) in locals
             .into_iter()
             .progress(&mut self.logger, "Importing and creating new core files")
         {
+            let path = self.path_remap.apply(path);
             if !self.files.contains(&path) {
                 // file not found in input folder
+                summary.files_skipped += 1;
                 continue;
             }
 
-            let reader = BufReader::new(File::open(self.base_path.join(&path))?);
-            let mut game = GAME::internal_new(reader)?;
-            game.internal_deserialize_and_update(data)
-                .map_err(|e| Error::DeserializeError(e.to_string()))?;
+            let mut game: GAME = self.storage.load(&path)?;
+            let before = super::snapshot(&game);
+            game.internal_deserialize_and_update(data, options)
+                .map_err(|e| Error::GameDeserialize(e.into()))?;
+            summary.add_changes(&before, game.internal_strings());
+            warnings.extend(length_warnings(&path, self.max_length, &before, &game));
+            warnings.extend(placeholder_warnings(
+                &path,
+                self.check_placeholders,
+                &before,
+                &game,
+            ));
 
-            let path = output_dir.join(path);
-            setup_output(&path)?;
-            let writer = BufWriter::new(File::create(path)?);
-            game.internal_write(writer)?;
+            self.storage.write(
+                &output_dir.join(&path),
+                &game,
+                self.verify_after_write,
+                self.preserve_mtime.then_some(path.as_path()),
+            )?;
+            summary.files_written += 1;
         }
 
-        Ok(())
+        for warning in warnings {
+            self.logger.warn(warning);
+        }
+
+        Ok(summary)
+    }
+}
+
+/// Import a split txt `file` that lost its deinfo, the deinfo is made again
+/// from `game` as if it was exported with all languages and no decorations.
+fn import_without_deinfo<GAME: private::InternalGroupExtractor>(
+    game: &mut GAME,
+    file: &Path,
+    line_ending: Option<LineEnding>,
+    options: ImportOptions,
+) -> DResult<ImportSummary> {
+    let before = super::snapshot(game);
+    let lines = super::read_txt_lines(BufReader::new(File::open(file)?), line_ending)?;
+    let (_, deinfo) = game.internal_serialize_to_lines(
+        GAME::ALL_LANGUAGES,
+        &GAME::ExportFilters::default(),
+        None,
+        false,
+    );
+
+    game.internal_deserialize_and_update_from_lines(&lines, deinfo, options)
+        .map_err(|e| Error::DeserializeError(format!("{}: {e}", file.display())))?;
+
+    let mut summary = ImportSummary::default();
+    summary.add_changes(&before, game.internal_strings());
+    Ok(summary)
+}
+
+/// Warnings for the strings that got longer than the max length in import,
+/// `before` is the [`string_snapshot`](crate::games::string_snapshot) taken before import.
+fn length_warnings<GAME: private::InternalGroupExtractor>(
+    path: &Path,
+    max_length: Option<usize>,
+    before: &StringSnapshot<GAME::Language>,
+    game: &GAME,
+) -> Vec<String> {
+    let Some(max_length) = max_length else {
+        return Vec::new();
+    };
+
+    check_lengths(before, game.internal_strings(), max_length)
+        .into_iter()
+        .map(|w| format!("{}: {w}", path.display()))
+        .collect()
+}
+
+/// Warnings for the strings that lost or gained a placeholder in import,
+/// `before` is the [`string_snapshot`](crate::games::string_snapshot) taken before import.
+fn placeholder_warnings<GAME: private::InternalGroupExtractor>(
+    path: &Path,
+    check: bool,
+    before: &StringSnapshot<GAME::Language>,
+    game: &GAME,
+) -> Vec<String> {
+    if !check {
+        return Vec::new();
     }
+
+    check_placeholders(before, game.internal_strings())
+        .into_iter()
+        .map(|w| format!("{}: {w}", path.display()))
+        .collect()
 }
 
 fn setup_output(path: &Path) -> std::io::Result<()> {
@@ -321,3 +1292,243 @@ fn setup_output(path: &Path) -> std::io::Result<()> {
 
     create_dir_all(path.with_file_name(""))
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        games::{
+            ds::{DSLocal, ExportFilters, Language, LOCALIZED_MAGIC},
+            synthetic,
+        },
+        logger::NoLogger,
+    };
+
+    /// A core with a single resource that has `text` in every language.
+    fn ds_core(text: &str) -> Vec<u8> {
+        let mut data = synthetic::uuid(0).to_vec();
+        for _ in Language::ALL_VARIANTS {
+            synthetic::push_u8_string(&mut data, text);
+            synthetic::push_u8_string(&mut data, "note");
+            data.push(0);
+        }
+        synthetic::chunk(LOCALIZED_MAGIC, data)
+    }
+
+    #[test]
+    fn in_memory_group_round_trip() {
+        let cores = HashMap::from([
+            (PathBuf::from("a.core"), ds_core("first")),
+            (PathBuf::from("sub/b.core"), ds_core("second")),
+        ]);
+        let mut group = DecimaGroup::<DSLocal, _>::from_memory(cores, NoLogger).unwrap();
+        assert_eq!(
+            group
+                .count_strings([Language::English], &ExportFilters::default())
+                .unwrap(),
+            2
+        );
+
+        let mut locals = group
+            .export_to_map([Language::English], &ExportFilters::default())
+            .unwrap();
+        assert_eq!(locals.len(), 2);
+        locals.get_mut(Path::new("sub/b.core")).unwrap()[0]
+            .strings
+            .insert(Language::English, "changed".to_owned());
+
+        group.set_verify_after_write(true);
+        let summary = group
+            .import_from_map(locals, "out", ImportOptions::default())
+            .unwrap();
+        assert_eq!(summary.strings_changed, 1);
+        assert_eq!(summary.files_written, 2);
+
+        let written = group.take_written();
+        let b = DSLocal::new(Cursor::new(&written[Path::new("out/sub/b.core")])).unwrap();
+        assert!(b
+            .strings()
+            .any(|e| e.language == Language::English && e.string == "changed"));
+        assert!(group.take_written().is_empty());
+    }
+
+    #[test]
+    fn path_remap_replaces_the_prefix() {
+        let remap = PathRemap {
+            strip_prefix: Some(PathBuf::from("old")),
+            add_prefix: Some(PathBuf::from("new")),
+        };
+        assert_eq!(
+            remap.apply(PathBuf::from("old/sub/a.core")),
+            Path::new("new/sub/a.core")
+        );
+
+        let remap = PathRemap {
+            strip_prefix: Some(PathBuf::from("old")),
+            add_prefix: None,
+        };
+        assert_eq!(
+            remap.apply(PathBuf::from("other/a.core")),
+            Path::new("other/a.core")
+        );
+    }
+
+    #[test]
+    fn import_applies_path_remap() {
+        let cores = HashMap::from([(PathBuf::from("sub/a.core"), ds_core("first"))]);
+        let mut group = DecimaGroup::<DSLocal, _>::from_memory(cores, NoLogger).unwrap();
+
+        // exported from a folder where the core was at `old/a.core`
+        let mut locals = group
+            .export_to_map([Language::English], &ExportFilters::default())
+            .unwrap();
+        let mut local = locals.remove(Path::new("sub/a.core")).unwrap();
+        local[0]
+            .strings
+            .insert(Language::English, "changed".to_owned());
+        let locals = HashMap::from([(PathBuf::from("old/a.core"), local)]);
+
+        let summary = group
+            .import_from_map(locals.clone(), "out", ImportOptions::default())
+            .unwrap();
+        assert_eq!(summary.files_skipped, 1);
+
+        group.set_path_remap(PathRemap {
+            strip_prefix: Some(PathBuf::from("old")),
+            add_prefix: Some(PathBuf::from("sub")),
+        });
+        let summary = group
+            .import_from_map(locals, "out", ImportOptions::default())
+            .unwrap();
+        assert_eq!(summary.files_skipped, 0);
+        assert_eq!(summary.strings_changed, 1);
+
+        let written = group.take_written();
+        let a = DSLocal::new(Cursor::new(&written[Path::new("out/sub/a.core")])).unwrap();
+        assert!(a
+            .strings()
+            .any(|e| e.language == Language::English && e.string == "changed"));
+    }
+
+    #[test]
+    fn for_each_skips_cores_without_local_resource() {
+        let cores = HashMap::from([
+            (PathBuf::from("a.core"), ds_core("first")),
+            (PathBuf::from("b.core"), synthetic::chunk(0xAB, Vec::new())),
+        ]);
+        // the group only borrows the logger
+        let mut logger = NoLogger;
+        let mut group = DecimaGroup::<DSLocal, _>::from_memory(cores, &mut logger).unwrap();
+
+        let mut seen = Vec::new();
+        group
+            .for_each(|path, core| seen.push((path.to_path_buf(), core.strings().count())))
+            .unwrap();
+        assert_eq!(
+            seen,
+            [(PathBuf::from("a.core"), Language::ALL_VARIANTS.len())]
+        );
+    }
+
+    #[test]
+    fn max_file_size_skips_big_cores() {
+        let big = ds_core(&"long text ".repeat(100));
+        let cores = HashMap::from([
+            (PathBuf::from("small.core"), ds_core("short")),
+            (PathBuf::from("big.core"), big.clone()),
+        ]);
+        let mut group = DecimaGroup::<DSLocal, _>::from_memory(cores, NoLogger).unwrap();
+        assert!(matches!(
+            group.set_max_file_size(10),
+            Err(Error::NoFileFound("core"))
+        ));
+
+        group.set_max_file_size(big.len() as u64 - 1).unwrap();
+        let locals = group
+            .export_to_map([Language::English], &ExportFilters::default())
+            .unwrap();
+        assert!(locals.keys().eq([Path::new("small.core")]));
+    }
+
+    #[test]
+    fn split_export_round_trip() {
+        let exported = tempfile::tempdir().unwrap();
+        let cores = HashMap::from([
+            (PathBuf::from("a.core"), ds_core("first")),
+            (PathBuf::from("sub/b.core"), ds_core("second")),
+        ]);
+        let mut group = DecimaGroup::<DSLocal, _>::from_memory(cores, NoLogger).unwrap();
+
+        let json = SerializeType::Json {
+            indent: None,
+            omit_empty: false,
+        };
+        group
+            .export_split(
+                exported.path(),
+                [Language::English],
+                json.clone(),
+                &ExportFilters::default(),
+            )
+            .unwrap();
+        assert!(exported.path().join("a.core.json").is_file());
+        let b = exported.path().join("sub/b.core.json");
+        let text = fs::read_to_string(&b).unwrap();
+        fs::write(&b, text.replace("second", "changed")).unwrap();
+
+        let summary = group
+            .import_split(
+                exported.path(),
+                Path::new("out"),
+                json,
+                ImportOptions::default(),
+            )
+            .unwrap();
+        assert_eq!(summary.strings_changed, 1);
+        assert_eq!(summary.files_written, 2);
+
+        let written = group.take_written();
+        let b = DSLocal::new(Cursor::new(&written[Path::new("out/sub/b.core")])).unwrap();
+        assert!(b
+            .strings()
+            .any(|e| e.language == Language::English && e.string == "changed"));
+    }
+
+    #[test]
+    fn split_txt_export_keeps_a_deinfo_per_file() {
+        let exported = tempfile::tempdir().unwrap();
+        let cores = HashMap::from([
+            (PathBuf::from("a.core"), ds_core("first")),
+            (PathBuf::from("b.core"), ds_core("second")),
+        ]);
+        let mut group = DecimaGroup::<DSLocal, _>::from_memory(cores, NoLogger).unwrap();
+
+        let txt = SerializeType::Txt {
+            add_language_names: true,
+            language_separator: None,
+            add_indices: false,
+            line_ending: None,
+        };
+        group
+            .export_split(
+                exported.path(),
+                Language::ALL_VARIANTS,
+                txt.clone(),
+                &ExportFilters::default(),
+            )
+            .unwrap();
+        let a = exported.path().join("a.core.txt");
+        assert!(super::super::deinfo_path(&a).is_file());
+
+        let summary = group
+            .import_split(
+                exported.path(),
+                Path::new("out"),
+                txt,
+                ImportOptions::default(),
+            )
+            .unwrap();
+        assert_eq!(summary.strings_changed, 0);
+        assert_eq!(summary.files_written, 2);
+    }
+}
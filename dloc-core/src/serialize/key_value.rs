@@ -0,0 +1,221 @@
+//! A flat `.properties` like format, each line is `index[:sub]:Language=value`.
+//!
+//! `sub` is only written for the lines of a cutscene other than the first one,
+//! and a missing `sub` is read back as `0`. In the value `\`, `=`, line feed and
+//! carriage return are escaped as `\\`, `\=`, `\n` and `\r`. Empty lines and
+//! lines starting with `#` are ignored. In group mode the lines of each core
+//! come after a `[path/to/file.core]` header.
+
+use std::{
+    fmt::Display,
+    io::{self, BufRead, Write},
+    path::PathBuf,
+};
+
+use crate::{
+    error::{DResult, Error},
+    games::StringEntry,
+};
+
+/// A parsed key-value line.
+pub(crate) struct KeyValue<L> {
+    pub index: usize,
+    pub sub: usize,
+    pub language: L,
+    pub value: String,
+    /// The translator note of the string, only the table format has them.
+    pub note: Option<String>,
+}
+
+/// A single line of a key-value file.
+enum Line<'a, L> {
+    Empty,
+    Section(&'a str),
+    Value(KeyValue<L>),
+}
+
+/// The values of a single core, `path` is [`None`] for the values that
+/// came before any section header.
+pub(crate) struct Section<L> {
+    pub path: Option<PathBuf>,
+    pub values: Vec<KeyValue<L>>,
+}
+
+/// Write every string in `strings` with one of the `languages` as a line.
+pub(crate) fn write_strings<'a, W, L>(
+    writer: &mut W,
+    strings: impl Iterator<Item = StringEntry<'a, L>>,
+    languages: &[L],
+) -> io::Result<()>
+where
+    W: Write,
+    L: Display + PartialEq,
+{
+    for entry in strings.filter(|e| languages.contains(&e.language)) {
+        match entry.sub {
+            0 => write!(writer, "{}:{}", entry.index, entry.language)?,
+            sub => write!(writer, "{}:{sub}:{}", entry.index, entry.language)?,
+        }
+        writeln!(writer, "={}", escape(entry.string))?;
+    }
+
+    Ok(())
+}
+
+/// Read all the lines and group the values by their section.
+pub(crate) fn read<R: BufRead, L: TryFrom<String>>(reader: R) -> DResult<Vec<Section<L>>> {
+    let mut sections = vec![Section {
+        path: None,
+        values: Vec::new(),
+    }];
+
+    for (number, line) in reader.lines().enumerate() {
+        let line = line?;
+        match parse_line(&line, number + 1).map_err(Error::DeserializeError)? {
+            Line::Empty => continue,
+            Line::Section(path) => sections.push(Section {
+                path: Some(PathBuf::from(path)),
+                values: Vec::new(),
+            }),
+            Line::Value(value) => {
+                // there is always at least one section
+                if let Some(section) = sections.last_mut() {
+                    section.values.push(value);
+                }
+            }
+        }
+    }
+
+    Ok(sections)
+}
+
+/// Parse a single line, `number` is only used in the error message.
+fn parse_line<L: TryFrom<String>>(line: &str, number: usize) -> Result<Line<'_, L>, String> {
+    let trimmed = line.trim_end_matches('\r');
+    if trimmed.trim().is_empty() || trimmed.starts_with('#') {
+        return Ok(Line::Empty);
+    }
+
+    if let Some(section) = trimmed.strip_prefix('[').and_then(|s| s.strip_suffix(']')) {
+        return Ok(Line::Section(section));
+    }
+
+    let error = |msg: &str| format!("Invalid key-value at line {number}: {msg}");
+
+    let Some((key, value)) = trimmed.split_once('=') else {
+        return Err(error("missing '='"));
+    };
+
+    let Some((index, rest)) = key.split_once(':') else {
+        return Err(error("key should be index[:sub]:Language"));
+    };
+    let (sub, language) = rest.split_once(':').unwrap_or(("0", rest));
+
+    let index = index.trim().parse().map_err(|_| error("invalid index"))?;
+    let sub = sub.trim().parse().map_err(|_| error("invalid sub index"))?;
+    let language = L::try_from(language.trim().to_owned())
+        .map_err(|_| error(&format!("invalid language {language}")))?;
+    let value = unescape(value).ok_or_else(|| error("invalid escape sequence"))?;
+
+    Ok(Line::Value(KeyValue {
+        index,
+        sub,
+        language,
+        value,
+        note: None,
+    }))
+}
+
+fn escape(str: &str) -> String {
+    let mut buf = String::with_capacity(str.len());
+    for ch in str.chars() {
+        match ch {
+            '\\' => buf.push_str("\\\\"),
+            '=' => buf.push_str("\\="),
+            '\n' => buf.push_str("\\n"),
+            '\r' => buf.push_str("\\r"),
+            ch => buf.push(ch),
+        }
+    }
+    buf
+}
+
+fn unescape(str: &str) -> Option<String> {
+    let mut buf = String::with_capacity(str.len());
+    let mut chars = str.chars();
+    while let Some(ch) = chars.next() {
+        if ch != '\\' {
+            buf.push(ch);
+            continue;
+        }
+
+        match chars.next()? {
+            '\\' => buf.push('\\'),
+            '=' => buf.push('='),
+            'n' => buf.push('\n'),
+            'r' => buf.push('\r'),
+            _ => return None,
+        }
+    }
+    Some(buf)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn parse(line: &str) -> Result<Line<'_, String>, String> {
+        parse_line(line, 1)
+    }
+
+    #[test]
+    fn separators_inside_the_value_round_trip() {
+        let string = "Time: 12:30 = soon\nnext: line\r\n";
+        let entry = StringEntry {
+            index: 4,
+            sub: 2,
+            language: "English".to_owned(),
+            string,
+        };
+        let mut written = Vec::new();
+        write_strings(
+            &mut written,
+            std::iter::once(entry),
+            &["English".to_owned()],
+        )
+        .unwrap();
+
+        let sections = read::<_, String>(written.as_slice()).unwrap();
+        let value = &sections[0].values[0];
+        assert_eq!((value.index, value.sub), (4, 2));
+        assert_eq!(value.language, "English");
+        assert_eq!(value.value, string);
+    }
+
+    #[test]
+    fn key_with_and_without_sub() {
+        let Ok(Line::Value(value)) = parse("3:English=a:b") else {
+            panic!("line should be a value");
+        };
+        assert_eq!(
+            (value.index, value.sub, value.value.as_str()),
+            (3, 0, "a:b")
+        );
+
+        let Ok(Line::Value(value)) = parse("3:1:English=") else {
+            panic!("line should be a value");
+        };
+        assert_eq!((value.index, value.sub, value.value.as_str()), (3, 1, ""));
+    }
+
+    #[test]
+    fn invalid_keys_are_rejected() {
+        assert!(parse("English=a").is_err());
+        assert!(parse("x:English=a").is_err());
+        assert!(parse("3:x:English=a").is_err());
+        assert!(parse("3:English").is_err());
+        assert!(parse("3:English=\\x\\q").is_err());
+        assert!(matches!(parse("[a.core]"), Ok(Line::Section("a.core"))));
+        assert!(matches!(parse("# note"), Ok(Line::Empty)));
+    }
+}
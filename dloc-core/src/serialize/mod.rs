@@ -1,24 +1,524 @@
 //! Serialize and deserialize related trait and functions
 
-pub use group::DecimaGroup;
+use std::{
+    collections::BTreeMap,
+    fmt::Display,
+    fs::File,
+    io::{BufRead, BufReader, Write},
+    ops::AddAssign,
+    path::{Path, PathBuf},
+};
+
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::ser::PrettyFormatter;
+
+use crate::{
+    error::DResult,
+    games::{changed_strings, string_snapshot, StringEntry, StringSnapshot, Uuid},
+};
+
+pub use crate::utils::{EofReplacor, LineEnding};
+pub use compression::Compression;
+pub(crate) use compression::{open_export, ExportWriter};
+pub use group::{DecimaGroup, PathRemap};
 pub use single::SerializeData;
 
+mod compression;
 mod group;
+mod key_value;
+mod ndjson;
+mod po;
 pub(crate) mod private;
 mod single;
+#[cfg(feature = "sqlite")]
+mod sqlite;
+mod table;
 
 const DEINFO_EXT: &str = "deinfo.json";
 
 /// An enum representing the different serialization formats supported.
 ///
-/// This includes JSON, plain text and Yaml (if enabled). The enum variants correspond to
-/// each of these formats.
-#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+/// This includes JSON, plain text, key-value, a table, Ndjson, PO, Yaml and SQLite (if enabled). The enum variants
+/// correspond to each of these formats.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
 pub enum SerializeType {
-    Json,
+    Json {
+        /// Indentation of each level, [`None`] means two spaces.
+        indent: Option<String>,
+        /// Leave the empty strings out of each resource and just list their
+        /// languages, import sets them back to empty.
+        omit_empty: bool,
+    },
     #[cfg(feature = "serialize_yaml")]
-    Yaml,
+    Yaml {
+        /// Same as the `omit_empty` of Json.
+        omit_empty: bool,
+    },
     Txt {
         add_language_names: bool,
+        /// Separator between the language name and the string, [`None`] means
+        /// [`DEFAULT_LANGUAGE_SEPARATOR`]. Only used with `add_language_names`.
+        language_separator: Option<String>,
+        /// Start each line with `[index] `, the index of its resource. Import
+        /// only strips the exact index of the resource so strings that start
+        /// with something similar are kept.
+        add_indices: bool,
+        /// Export every kind of line break as `<lf>` and import every escaped
+        /// line break as this line ending. [`None`] keeps `\r\n`, `\r` and
+        /// `\n` apart.
+        line_ending: Option<LineEnding>,
     },
+    /// `.properties` like lines in the form of `index[:sub]:Language=value`.
+    KeyValue,
+    /// A tab separated table with a row for each string and a column for
+    /// each language.
+    Table,
+    /// Newline delimited Json, a Json object for each string like
+    /// `{"index":0,"lang":"English","sub":0,"text":"..."}`.
+    Ndjson,
+    /// Gettext PO with a message for each string, import reads back the
+    /// `msgstr` of each message.
+    Po,
+    /// SQLite database with a row for each string, import only reads back
+    /// the text of the strings.
+    #[cfg(feature = "sqlite")]
+    Sqlite,
+}
+
+impl SerializeType {
+    /// The file extension used for this format.
+    pub const fn extension(&self) -> &'static str {
+        match self {
+            Self::Json { .. } => "json",
+            #[cfg(feature = "serialize_yaml")]
+            Self::Yaml { .. } => "yaml",
+            Self::Txt { .. } => "txt",
+            Self::KeyValue => "properties",
+            Self::Table => "tsv",
+            Self::Ndjson => "ndjson",
+            Self::Po => "po",
+            #[cfg(feature = "sqlite")]
+            Self::Sqlite => "sqlite",
+        }
+    }
+}
+
+/// Default separator between the language name and the string in txt lines.
+pub const DEFAULT_LANGUAGE_SEPARATOR: &str = ":: ";
+
+pub(crate) fn default_language_separator() -> String {
+    DEFAULT_LANGUAGE_SEPARATOR.to_owned()
+}
+
+/// Remove `{language}{separator}` from the start of `line`, the line is
+/// returned untouched if it doesn't start with that so strings that just
+/// happen to contain the separator aren't damaged.
+///
+/// Whitespace around the separator is optional, so `English::text`,
+/// `English:: text` and `English :: text` all give `text`. Only the
+/// whitespace of the separator itself is removed after it, `English::  text`
+/// gives ` text`. A separator made only of whitespace must match exactly.
+pub(crate) fn strip_language_name<'a>(
+    line: &'a str,
+    language: impl Display,
+    separator: &str,
+) -> &'a str {
+    let language = language.to_string();
+    let Some(rest) = line.strip_prefix(language.as_str()) else {
+        return line;
+    };
+
+    match separator.trim() {
+        "" => rest.strip_prefix(separator),
+        trimmed => rest.trim_start().strip_prefix(trimmed).map(|rest| {
+            // only the whitespace of the separator is removed, the string
+            // may start with whitespace of its own
+            let after = separator
+                .trim_start()
+                .strip_prefix(trimmed)
+                .unwrap_or_default();
+            rest.strip_prefix(after).unwrap_or(rest)
+        }),
+    }
+    .unwrap_or(line)
+}
+
+/// Format a single txt line, the language name is only added if
+/// `language_separator` is set.
+pub(crate) fn txt_line(
+    index: usize,
+    language: impl Display,
+    text: impl Display,
+    language_separator: Option<&str>,
+    add_indices: bool,
+) -> String {
+    match (add_indices, language_separator) {
+        (false, None) => text.to_string(),
+        (false, Some(separator)) => format!("{language}{separator}{text}"),
+        (true, None) => format!("[{index}] {text}"),
+        (true, Some(separator)) => format!("[{index}] {language}{separator}{text}"),
+    }
+}
+
+/// Remove what [`txt_line`] added to the start of `line`, each part is only
+/// removed if it exactly match.
+pub(crate) fn strip_txt_line<'a>(
+    line: &'a str,
+    index: usize,
+    language: impl Display,
+    language_separator: Option<&str>,
+    add_indices: bool,
+) -> &'a str {
+    let line = match add_indices {
+        true => line.strip_prefix(&format!("[{index}] ")).unwrap_or(line),
+        false => line,
+    };
+    match language_separator {
+        Some(separator) => strip_language_name(line, language, separator),
+        None => line,
+    }
+}
+
+/// How an import checks the export against the core, passed to every import
+/// function.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ImportOptions {
+    /// Fail a Txt, Json or Yaml import when the core doesn't have as many
+    /// resources as the one it was exported from, on by default. Imports of
+    /// another version of the core would write the strings to the wrong
+    /// resources. The exports of single strings don't record the resource
+    /// count, so they are never checked.
+    pub check_resource_count: bool,
+}
+
+impl Default for ImportOptions {
+    fn default() -> Self {
+        Self {
+            check_resource_count: true,
+        }
+    }
+}
+
+/// What an import changed, returned from the import functions so callers
+/// don't need to read the logs to show the result.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ImportSummary {
+    /// Number of resources that have at least one changed string.
+    pub resources_updated: usize,
+    /// Number of strings that changed.
+    pub strings_changed: usize,
+    /// Number of core files written to output, always `0` for
+    /// [`SerializeData`] as writing the core is up to the caller.
+    pub files_written: usize,
+    /// Number of files in the exported file that didn't exist in the input folder.
+    pub files_skipped: usize,
+}
+
+impl ImportSummary {
+    /// Count the strings and resources that changed, `before` is the
+    /// [`string_snapshot`] of the core before import.
+    fn add_changes<'a, L: Ord + Copy + 'a>(
+        &mut self,
+        before: &StringSnapshot<L>,
+        after: impl Iterator<Item = StringEntry<'a, L>>,
+    ) {
+        let mut last_index = None;
+        for (_, after) in changed_strings(before, after) {
+            self.strings_changed += 1;
+            // strings of a resource always come after each other
+            if last_index != Some(after.index) {
+                self.resources_updated += 1;
+                last_index = Some(after.index);
+            }
+        }
+    }
+}
+
+impl AddAssign for ImportSummary {
+    fn add_assign(&mut self, rhs: Self) {
+        self.resources_updated += rhs.resources_updated;
+        self.strings_changed += rhs.strings_changed;
+        self.files_written += rhs.files_written;
+        self.files_skipped += rhs.files_skipped;
+    }
+}
+
+impl Display for ImportSummary {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} resources updated, {} strings changed, {} files written, {} files skipped",
+            self.resources_updated, self.strings_changed, self.files_written, self.files_skipped
+        )
+    }
+}
+
+/// A string that differ between two exports, see
+/// [`SerializeData::diff_exports`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ExportDifference<L> {
+    /// Index of the resource.
+    pub index: usize,
+    /// Line index inside a cutscene, always `0` for other resources.
+    pub sub: usize,
+    pub language: L,
+    /// The string in the old export, [`None`] if it doesn't have it.
+    pub old: Option<String>,
+    /// The string in the new export, [`None`] if it doesn't have it.
+    pub new: Option<String>,
+}
+
+/// Fields of an exported resource that dloc doesn't know about, like notes
+/// or ids added by other tools. They are ignored on import and can be copied
+/// to a new export with [`SerializeData::serialize_keeping_fields`].
+pub type ExtraFields = BTreeMap<String, serde_json::Value>;
+
+/// Copy the [`ExtraFields`] of the `previous` resources to the resources of
+/// `data`, `fields` gives the recorded uuid, the index and the extra fields
+/// of a resource. A resource is found by its uuid if the previous one has
+/// it, otherwise by its index.
+pub(crate) fn copy_extra_fields<T>(
+    data: &mut [T],
+    previous: Vec<T>,
+    fields: impl Fn(&mut T) -> (Option<Uuid>, usize, &mut ExtraFields),
+) {
+    let mut by_uuid = BTreeMap::new();
+    let mut by_index = BTreeMap::new();
+    for mut local in previous {
+        let (uuid, index, extra) = fields(&mut local);
+        if extra.is_empty() {
+            continue;
+        }
+        let extra = std::mem::take(extra);
+        match uuid {
+            Some(uuid) => by_uuid.insert(uuid, extra),
+            None => by_index.insert(index, extra),
+        };
+    }
+
+    for local in data {
+        let (uuid, index, extra) = fields(local);
+        if let Some(previous) = uuid
+            .and_then(|u| by_uuid.remove(&u))
+            .or_else(|| by_index.remove(&index))
+        {
+            *extra = previous;
+        }
+    }
+}
+
+/// Copy of all the strings of `game`, used to see what an import changed.
+fn snapshot<T: private::InternalStringAccess>(game: &T) -> StringSnapshot<T::Language> {
+    string_snapshot(game.internal_strings())
+}
+
+/// Write `value` as pretty json with every level indented by `indent`,
+/// two spaces if its [`None`].
+pub(crate) fn write_json<W: Write, T: Serialize>(
+    writer: W,
+    value: &T,
+    indent: Option<&str>,
+) -> DResult<()> {
+    let formatter = PrettyFormatter::with_indent(indent.unwrap_or("  ").as_bytes());
+    let mut serializer = serde_json::Serializer::with_formatter(writer, formatter);
+    value.serialize(&mut serializer)?;
+    Ok(())
+}
+
+/// Read all the lines of a txt export and put back the escaped line breaks,
+/// all of them as `line_ending` if its set.
+///
+/// Files that an editor saved with `\r\n` line breaks or a UTF-8 BOM are
+/// read like the canonical export, see [`TxtLineWriter`].
+fn read_txt_lines<R: BufRead>(
+    reader: R,
+    line_ending: Option<LineEnding>,
+) -> std::io::Result<Vec<String>> {
+    reader
+        .lines()
+        .enumerate()
+        .map(|(i, s)| {
+            s.map(|mut s| {
+                if i == 0 && s.starts_with('\u{FEFF}') {
+                    s.remove(0);
+                }
+                match line_ending {
+                    Some(ending) => s.replace_eol_back_as(ending),
+                    None => s.replace_eol_back(),
+                }
+            })
+        })
+        .collect()
+}
+
+/// Path of the deserialize info saved next to the txt export at `path`, a
+/// compressed export uses the same one as an uncompressed export.
+pub fn deinfo_path(path: &Path) -> PathBuf {
+    match Compression::from_path(path) {
+        Some(_) => path.with_extension("").with_extension(DEINFO_EXT),
+        None => path.with_extension(DEINFO_EXT),
+    }
+}
+
+/// Read the deserialize info saved next to the txt `input`.
+fn read_deinfo<D: DeserializeOwned>(input: &Path) -> DResult<D> {
+    let reader = BufReader::new(File::open(deinfo_path(input))?);
+    Ok(serde_json::from_reader(reader)?)
+}
+
+/// Writes the lines of a txt export one by one while escaping the line
+/// breaks inside them, so we never need to keep all of them in memory.
+///
+/// Every line is terminated by a line break instead of only separating
+/// them, this way a last empty string still survive [`read_txt_lines`].
+///
+/// The output is the canonical txt export: UTF-8 without a BOM and a `\n`
+/// after every line on every platform, so the same core gives byte
+/// identical exports on Windows and Linux.
+struct TxtLineWriter<W: Write> {
+    writer: W,
+    count: usize,
+    /// Escape every kind of line break as `<lf>`.
+    normalize_eol: bool,
+}
+
+impl<W: Write> TxtLineWriter<W> {
+    fn new(writer: W, normalize_eol: bool) -> Self {
+        Self {
+            writer,
+            count: 0,
+            normalize_eol,
+        }
+    }
+
+    fn write_line(&mut self, line: String) -> std::io::Result<()> {
+        let line = if self.normalize_eol {
+            line.normalize_eol()
+        } else {
+            line.replace_eol()
+        };
+        self.writer.write_all(line.as_bytes())?;
+        self.writer.write_all(b"\n")?;
+        self.count += 1;
+        Ok(())
+    }
+
+    /// Number of lines written so far.
+    fn count(&self) -> usize {
+        self.count
+    }
+
+    fn finish(mut self) -> std::io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn txt_lines_round_trip() {
+        let lines = [
+            "first",
+            "ends with lf\n",
+            "ends with cr\r",
+            "ends with crlf\r\n",
+            "",
+            "",
+        ]
+        .map(String::from);
+
+        let mut writer = TxtLineWriter::new(Vec::new(), false);
+        for line in lines.iter().cloned() {
+            writer.write_line(line).unwrap();
+        }
+        assert_eq!(writer.count(), lines.len());
+        let output = writer.writer;
+
+        assert_eq!(read_txt_lines(output.as_slice(), None).unwrap(), lines);
+
+        // some editors on windows rewrite the file with crlf line breaks
+        let output = String::from_utf8(output).unwrap().replace('\n', "\r\n");
+        assert_eq!(read_txt_lines(output.as_bytes(), None).unwrap(), lines);
+
+        // and some add a bom
+        let output = format!("\u{FEFF}{output}");
+        assert_eq!(read_txt_lines(output.as_bytes(), None).unwrap(), lines);
+    }
+
+    #[test]
+    fn txt_line_round_trip() {
+        for (separator, add_indices) in [(None, true), (Some(":: "), true), (Some(":: "), false)] {
+            let line = txt_line(3, "English", "[3] text", separator, add_indices);
+            assert_eq!(
+                strip_txt_line(&line, 3, "English", separator, add_indices),
+                "[3] text"
+            );
+        }
+        assert_eq!(
+            strip_txt_line("[4] text", 3, "English", None, true),
+            "[4] text"
+        );
+    }
+
+    #[test]
+    fn strip_only_exact_language_name() {
+        let sep = DEFAULT_LANGUAGE_SEPARATOR;
+        for line in ["English::hello", "English:: hello", "English :: hello"] {
+            assert_eq!(strip_language_name(line, "English", sep), "hello");
+        }
+        assert_eq!(
+            strip_language_name("English::  hello", "English", sep),
+            " hello"
+        );
+        assert_eq!(
+            strip_language_name("Note:: hello", "English", sep),
+            "Note:: hello"
+        );
+        assert_eq!(
+            strip_language_name("English: hello", "English", sep),
+            "English: hello"
+        );
+        assert_eq!(
+            strip_language_name("English | a:: b", "English", " | "),
+            "a:: b"
+        );
+        assert_eq!(strip_language_name("English|a", "English", " | "), "a");
+        assert_eq!(strip_language_name("English\ta", "English", "\t"), "a");
+        assert_eq!(
+            strip_language_name("English a", "English", "\t"),
+            "English a"
+        );
+    }
+
+    #[test]
+    fn summary_counts_changed_resources_once() {
+        let entry = |index, sub, string| StringEntry {
+            index,
+            language: "English",
+            sub,
+            string,
+        };
+        let before = string_snapshot(
+            [
+                entry(0, 0, "a"),
+                entry(1, 0, "b"),
+                entry(1, 1, "c"),
+                entry(2, 0, "d"),
+            ]
+            .into_iter(),
+        );
+        let after = [
+            entry(0, 0, "a"),
+            entry(1, 0, "x"),
+            entry(1, 1, "y"),
+            entry(2, 0, "z"),
+        ];
+
+        let mut summary = ImportSummary::default();
+        summary.add_changes(&before, after.into_iter());
+        assert_eq!(summary.strings_changed, 3);
+        assert_eq!(summary.resources_updated, 2);
+    }
 }
@@ -0,0 +1,151 @@
+//! Newline delimited Json, each line is a single string as a Json object
+//! like `{"index":0,"lang":"English","sub":0,"text":"..."}`.
+//!
+//! `sub` is the line of a cutscene and always `0` for other resources, a
+//! missing `sub` is read back as `0`. Empty lines are ignored. In group mode
+//! every object starts with the `path` of its core.
+
+use std::{
+    borrow::Cow,
+    io::{self, BufRead, Write},
+    path::Path,
+};
+
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::{
+    error::{DResult, Error},
+    games::StringEntry,
+};
+
+use super::key_value::{KeyValue, Section};
+
+#[derive(Serialize, Deserialize)]
+struct Line<'a, L> {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    path: Option<Cow<'a, Path>>,
+    index: usize,
+    lang: L,
+    #[serde(default)]
+    sub: usize,
+    text: Cow<'a, str>,
+}
+
+/// Write every string in `strings` with one of the `languages` as a line,
+/// `path` is only given in group mode.
+pub(crate) fn write_strings<'a, W, L>(
+    writer: &mut W,
+    strings: impl Iterator<Item = StringEntry<'a, L>>,
+    languages: &[L],
+    path: Option<&Path>,
+) -> io::Result<()>
+where
+    W: Write,
+    L: Serialize + PartialEq,
+{
+    for entry in strings.filter(|e| languages.contains(&e.language)) {
+        let line = Line {
+            path: path.map(Cow::Borrowed),
+            index: entry.index,
+            lang: entry.language,
+            sub: entry.sub,
+            text: Cow::Borrowed(entry.string),
+        };
+        serde_json::to_writer(&mut *writer, &line)?;
+        writeln!(writer)?;
+    }
+
+    Ok(())
+}
+
+/// Read all the lines and group the values of each core by its path, the
+/// lines without a path are in the first section.
+pub(crate) fn read<R: BufRead, L: DeserializeOwned>(reader: R) -> DResult<Vec<Section<L>>> {
+    let mut sections = vec![Section {
+        path: None,
+        values: Vec::new(),
+    }];
+
+    for (number, line) in reader.lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let line: Line<'static, L> = serde_json::from_str(&line).map_err(|e| {
+            Error::DeserializeError(format!("Invalid Json at line {}: {e}", number + 1))
+        })?;
+        let path = line.path.map(Cow::into_owned);
+        // there is always at least one section
+        if sections.last().is_some_and(|s| s.path != path) {
+            sections.push(Section {
+                path,
+                values: Vec::new(),
+            });
+        }
+        if let Some(section) = sections.last_mut() {
+            section.values.push(KeyValue {
+                index: line.index,
+                sub: line.sub,
+                language: line.lang,
+                value: line.text.into_owned(),
+                note: None,
+            });
+        }
+    }
+
+    Ok(sections)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::games::hzd::Language;
+
+    use super::*;
+
+    #[test]
+    fn lines_round_trip() {
+        let strings = [
+            StringEntry {
+                index: 1,
+                language: Language::English,
+                sub: 0,
+                string: "say \"hi\"\nthen leave",
+            },
+            StringEntry {
+                index: 2,
+                language: Language::Arabic,
+                sub: 3,
+                string: "",
+            },
+        ];
+
+        let mut output = Vec::new();
+        let languages = [Language::English, Language::Arabic];
+        write_strings(&mut output, strings.into_iter(), &languages, None).unwrap();
+        write_strings(
+            &mut output,
+            strings.into_iter(),
+            &[Language::Arabic],
+            Some(Path::new("a/b.core")),
+        )
+        .unwrap();
+        assert_eq!(
+            String::from_utf8_lossy(&output).lines().next(),
+            Some(r#"{"index":1,"lang":"English","sub":0,"text":"say \"hi\"\nthen leave"}"#)
+        );
+
+        let sections = read::<_, Language>(output.as_slice()).unwrap();
+        assert_eq!(sections.len(), 2);
+        assert_eq!(sections[0].path, None);
+        assert_eq!(sections[0].values.len(), 2);
+        assert_eq!(sections[0].values[0].value, strings[0].string);
+        assert_eq!(sections[1].path.as_deref(), Some(Path::new("a/b.core")));
+        assert_eq!(sections[1].values[0].sub, 3);
+        assert_eq!(sections[1].values[0].language, Language::Arabic);
+
+        let missing_sub = r#"{"index":4,"lang":"English","text":"x"}"#;
+        let sections = read::<_, Language>(missing_sub.as_bytes()).unwrap();
+        assert_eq!(sections[0].values[0].sub, 0);
+    }
+}
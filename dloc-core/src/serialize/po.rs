@@ -0,0 +1,305 @@
+//! Gettext PO, each string is a message with `index:sub:Language` as its
+//! `msgctxt`, so the messages stay unique even when the strings are the same.
+//!
+//! `sub` is the line of a cutscene and always `0` for other resources. The
+//! `msgid` and the `msgstr` both start as the exported string, import only
+//! reads the `msgstr`. The `#.` comment above each message gives translators
+//! some context, the DS note of the string or the HZD resource it's from. In
+//! group mode the messages of each core come after a `#: path/to/file.core`
+//! reference.
+
+use std::{
+    fmt::Display,
+    io::{self, BufRead, Write},
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    error::{DResult, Error},
+    games::StringEntry,
+};
+
+use super::key_value::{KeyValue, Section};
+
+/// Write the header entry of the file, every PO file starts with it.
+pub(crate) fn write_header<W: Write>(writer: &mut W) -> io::Result<()> {
+    writeln!(writer, "msgid \"\"")?;
+    writeln!(writer, "msgstr \"\"")?;
+    writeln!(writer, "\"Content-Type: text/plain; charset=UTF-8\\n\"")?;
+    writeln!(writer)
+}
+
+/// Write every string in `strings` with one of the `languages` as a message,
+/// `comment` gives the translator comment of a string and `path` is only
+/// given in group mode.
+pub(crate) fn write_strings<'a, W, L, C>(
+    writer: &mut W,
+    strings: impl Iterator<Item = StringEntry<'a, L>>,
+    languages: &[L],
+    path: Option<&Path>,
+    comment: C,
+) -> io::Result<()>
+where
+    W: Write,
+    L: Display + PartialEq + Copy + 'a,
+    C: Fn(usize, usize, L) -> Option<String>,
+{
+    if let Some(path) = path {
+        writeln!(writer, "#: {}", path.display())?;
+        writeln!(writer)?;
+    }
+
+    for entry in strings.filter(|e| languages.contains(&e.language)) {
+        if let Some(comment) = comment(entry.index, entry.sub, entry.language) {
+            for line in comment.lines() {
+                writeln!(writer, "#. {line}")?;
+            }
+        }
+        let string = escape(entry.string);
+        writeln!(
+            writer,
+            "msgctxt \"{}:{}:{}\"",
+            entry.index, entry.sub, entry.language
+        )?;
+        writeln!(writer, "msgid \"{string}\"")?;
+        writeln!(writer, "msgstr \"{string}\"")?;
+        writeln!(writer)?;
+    }
+
+    Ok(())
+}
+
+/// The keyword of the last line that was read, continuation lines are added
+/// to its string.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Field {
+    Context,
+    Id,
+    Str,
+}
+
+/// A message that is being read.
+#[derive(Default)]
+struct Message {
+    context: Option<String>,
+    value: Option<String>,
+}
+
+/// Read all the messages and group them by the core reference they come
+/// after, the messages before any reference are in the first section.
+/// Messages without a context, like the header, are skipped.
+pub(crate) fn read<R: BufRead, L: TryFrom<String>>(reader: R) -> DResult<Vec<Section<L>>> {
+    let mut sections = vec![Section {
+        path: None,
+        values: Vec::new(),
+    }];
+    let mut message = Message::default();
+    let mut field = None;
+
+    for (number, line) in reader.lines().enumerate() {
+        let line = line?;
+        let line = line.trim();
+        let error = |msg: &str| {
+            Error::DeserializeError(format!("Invalid PO at line {}: {msg}", number + 1))
+        };
+
+        if let Some(path) = line.strip_prefix("#:") {
+            push_message(&mut sections, &mut message)?;
+            sections.push(Section {
+                path: Some(PathBuf::from(path.trim())),
+                values: Vec::new(),
+            });
+            field = None;
+            continue;
+        }
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let (keyword, rest) = match line.split_once(' ') {
+            Some((keyword, rest)) if !line.starts_with('"') => (Some(keyword), rest.trim()),
+            _ => (None, line),
+        };
+        let string = rest
+            .strip_prefix('"')
+            .and_then(|s| s.strip_suffix('"'))
+            .and_then(unescape)
+            .ok_or_else(|| error("expected a quoted string"))?;
+
+        match keyword {
+            Some("msgctxt") => {
+                push_message(&mut sections, &mut message)?;
+                message.context = Some(string);
+                field = Some(Field::Context);
+            }
+            Some("msgid") => {
+                if field != Some(Field::Context) {
+                    push_message(&mut sections, &mut message)?;
+                }
+                field = Some(Field::Id);
+            }
+            Some("msgstr") => {
+                message.value = Some(string);
+                field = Some(Field::Str);
+            }
+            Some(keyword) => return Err(error(&format!("unknown keyword {keyword}"))),
+            None => match field {
+                Some(Field::Context) => message.context.get_or_insert_default().push_str(&string),
+                Some(Field::Str) => message.value.get_or_insert_default().push_str(&string),
+                Some(Field::Id) => (),
+                None => return Err(error("string without a keyword")),
+            },
+        }
+    }
+    push_message(&mut sections, &mut message)?;
+
+    Ok(sections)
+}
+
+/// Add the finished `message` to the last section and start a new one.
+fn push_message<L: TryFrom<String>>(
+    sections: &mut [Section<L>],
+    message: &mut Message,
+) -> DResult<()> {
+    let Message { context, value } = std::mem::take(message);
+    let Some(context) = context else {
+        return Ok(());
+    };
+    let error = |msg: &str| Error::DeserializeError(format!("Invalid PO message {context}: {msg}"));
+
+    let mut parts = context.splitn(3, ':');
+    let (Some(index), Some(sub), Some(language)) = (parts.next(), parts.next(), parts.next())
+    else {
+        return Err(error("msgctxt should be index:sub:Language"));
+    };
+    let index = index.parse().map_err(|_| error("invalid index"))?;
+    let sub = sub.parse().map_err(|_| error("invalid sub index"))?;
+    let language = L::try_from(language.to_owned()).map_err(|_| error("invalid language"))?;
+    let value = value.ok_or_else(|| error("missing msgstr"))?;
+
+    // there is always at least one section
+    if let Some(section) = sections.last_mut() {
+        section.values.push(KeyValue {
+            index,
+            sub,
+            language,
+            value,
+            note: None,
+        });
+    }
+    Ok(())
+}
+
+fn escape(str: &str) -> String {
+    let mut buf = String::with_capacity(str.len());
+    for ch in str.chars() {
+        match ch {
+            '\\' => buf.push_str("\\\\"),
+            '"' => buf.push_str("\\\""),
+            '\t' => buf.push_str("\\t"),
+            '\n' => buf.push_str("\\n"),
+            '\r' => buf.push_str("\\r"),
+            ch => buf.push(ch),
+        }
+    }
+    buf
+}
+
+fn unescape(str: &str) -> Option<String> {
+    let mut buf = String::with_capacity(str.len());
+    let mut chars = str.chars();
+    while let Some(ch) = chars.next() {
+        if ch != '\\' {
+            buf.push(ch);
+            continue;
+        }
+
+        match chars.next()? {
+            '\\' => buf.push('\\'),
+            '"' => buf.push('"'),
+            't' => buf.push('\t'),
+            'n' => buf.push('\n'),
+            'r' => buf.push('\r'),
+            _ => return None,
+        }
+    }
+    Some(buf)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::games::hzd::Language;
+
+    use super::*;
+
+    #[test]
+    fn messages_round_trip() {
+        let strings = [
+            StringEntry {
+                index: 1,
+                language: Language::English,
+                sub: 0,
+                string: "say \"hi\"\n\tthen leave\\",
+            },
+            StringEntry {
+                index: 2,
+                language: Language::Arabic,
+                sub: 3,
+                string: "",
+            },
+        ];
+        let comment = |index, _, _| (index == 1).then(|| "first\nsecond".to_owned());
+
+        let mut output = Vec::new();
+        let languages = [Language::English, Language::Arabic];
+        write_header(&mut output).unwrap();
+        write_strings(&mut output, strings.into_iter(), &languages, None, comment).unwrap();
+        write_strings(
+            &mut output,
+            strings.into_iter(),
+            &[Language::Arabic],
+            Some(Path::new("a/b.core")),
+            comment,
+        )
+        .unwrap();
+        let text = String::from_utf8(output.clone()).unwrap();
+        assert!(text.contains(
+            "#. first\n#. second\nmsgctxt \"1:0:English\"\nmsgid \"say \\\"hi\\\"\\n\\tthen leave\\\\\"\n"
+        ));
+
+        let sections = read::<_, Language>(output.as_slice()).unwrap();
+        assert_eq!(sections.len(), 2);
+        assert_eq!(sections[0].path, None);
+        assert_eq!(sections[0].values.len(), 2);
+        assert_eq!(sections[0].values[0].value, strings[0].string);
+        assert_eq!(sections[1].path.as_deref(), Some(Path::new("a/b.core")));
+        assert_eq!(sections[1].values.len(), 1);
+        assert_eq!(sections[1].values[0].sub, 3);
+        assert_eq!(sections[1].values[0].language, Language::Arabic);
+    }
+
+    #[test]
+    fn only_msgstr_is_read() {
+        let po = concat!(
+            "msgctxt \"4:0:English\"\n",
+            "msgid \"old\"\n",
+            "msgstr \"\"\n",
+            "\"new \"\n",
+            "\"text\"\n",
+        );
+        let sections = read::<_, Language>(po.as_bytes()).unwrap();
+        assert_eq!(sections[0].values[0].value, "new text");
+
+        for invalid in [
+            "msgctxt \"4:English\"\nmsgstr \"x\"\n",
+            "msgctxt \"4:0:English\"\nmsgid \"x\"\n",
+            "msgctxt \"4:0:English\"\nmsgstr \"\\q\"\n",
+            "msgctxt 4:0:English\n",
+        ] {
+            assert!(
+                read::<_, Language>(invalid.as_bytes()).is_err(),
+                "{invalid}"
+            );
+        }
+    }
+}
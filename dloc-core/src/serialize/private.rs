@@ -1,41 +1,76 @@
 //! Private module containing types needed for serialization and de-serialization.
 
 use std::{
+    convert::Infallible,
     error::Error,
     fmt::Display,
-    fs::File,
-    io::{BufReader, BufWriter},
+    io::{Read, Seek, Write},
 };
 
 use serde::{de::DeserializeOwned, Serialize};
 
-use crate::error::DResult;
+use crate::{error::DResult, games::StringEntry};
+
+use super::ImportOptions;
 
 /// In order for a game to be usable in group extractor and importer it should
 /// impl this trait.
-pub trait InternalGroupExtractor: InternalDataSerializer + InternalPlainTextDataSerializer {
-    fn internal_new(reader: BufReader<File>) -> DResult<Self>;
-    fn internal_write(&self, writer: BufWriter<File>) -> DResult<()>;
+pub trait InternalGroupExtractor:
+    InternalDataSerializer + InternalPlainTextDataSerializer + InternalStringAccess
+{
+    fn internal_new<R: Read + Seek>(reader: R) -> DResult<Self>;
+    fn internal_write<W: Write + Seek>(&self, writer: &mut W) -> DResult<()>;
 }
 
 /// A trait that provides information about the language and error types
 /// used for serialization. This allows the serialization logic to be generic
 /// over different language and error types.
 pub trait InternalSerializerBase: Sized {
-    type Language: Copy + Display + Ord + Serialize + DeserializeOwned;
-    type Error: Error;
+    type Language: Copy + Display + Ord + Serialize + DeserializeOwned + TryFrom<String> + 'static;
+    type Error: Error + Send + Sync + 'static;
+    /// What an export writes, the default exports every string.
+    type ExportFilters: Default;
+
+    /// Every language of the game, in their order inside the core.
+    const ALL_LANGUAGES: &'static [Self::Language];
+    /// Short name of the game, like `HZD`.
+    const GAME: &'static str;
 }
 
 /// Trait for internal serialization and deserialization of data.
 /// Implementors must define the data type and error type.
 /// Provides methods for:
 /// - Serializing the data to a serializable type
+/// - Serializing just the languages that differ from a reference
 /// - Deserializing the data and updating the implementor
 pub trait InternalDataSerializer: InternalSerializerBase {
     type Output: Serialize + DeserializeOwned;
 
-    fn internal_serialize(&self, languages: &[Self::Language]) -> Self::Output;
-    fn internal_deserialize_and_update(&mut self, data: Self::Output) -> Result<(), Self::Error>;
+    fn internal_serialize(
+        &self,
+        languages: &[Self::Language],
+        omit_empty: bool,
+        filters: &Self::ExportFilters,
+    ) -> Self::Output;
+    fn internal_serialize_changed(
+        &self,
+        reference: &Self,
+        languages: &[Self::Language],
+        filters: &Self::ExportFilters,
+    ) -> Result<Self::Output, Self::Error>;
+    fn internal_deserialize_and_update(
+        &mut self,
+        data: Self::Output,
+        options: ImportOptions,
+    ) -> Result<(), Self::Error>;
+
+    /// Every string of the exported `data` as `(index, sub, language, string)`,
+    /// used to compare exports without the core.
+    fn internal_flatten(data: Self::Output) -> Vec<(usize, usize, Self::Language, String)>;
+
+    /// Copy the fields dloc doesn't know from the resources of a `previous`
+    /// export to the same resources of `data`.
+    fn internal_copy_extra_fields(data: &mut Self::Output, previous: Self::Output);
 }
 
 /// Serializes and Deserialize data to and from a vector of lines and
@@ -44,15 +79,114 @@ pub trait InternalDataSerializer: InternalSerializerBase {
 pub trait InternalPlainTextDataSerializer: InternalSerializerBase {
     type DeserializeInfo: Serialize + DeserializeOwned;
 
+    /// Serialize the data to lines and pass each line to `sink` as soon
+    /// as its ready, so callers can write them without keeping all of
+    /// them in memory. Only the resources that pass `filters` are
+    /// serialized. If `language_separator` is set each line starts
+    /// with the language name followed by it, and with `add_indices`
+    /// the lines start with the resource index before that.
+    fn internal_serialize_lines_with<E, F>(
+        &self,
+        languages: &[Self::Language],
+        filters: &Self::ExportFilters,
+        language_separator: Option<&str>,
+        add_indices: bool,
+        sink: F,
+    ) -> Result<Self::DeserializeInfo, E>
+    where
+        F: FnMut(String) -> Result<(), E>;
+
     fn internal_serialize_to_lines(
         &self,
         languages: &[Self::Language],
-        add_language_names: bool,
-    ) -> (Vec<String>, Self::DeserializeInfo);
+        filters: &Self::ExportFilters,
+        language_separator: Option<&str>,
+        add_indices: bool,
+    ) -> (Vec<String>, Self::DeserializeInfo) {
+        let mut lines = Vec::new();
+        let Ok(deinfo) = self.internal_serialize_lines_with::<Infallible, _>(
+            languages,
+            filters,
+            language_separator,
+            add_indices,
+            |line| {
+                lines.push(line);
+                Ok(())
+            },
+        );
+        (lines, deinfo)
+    }
 
     fn internal_deserialize_and_update_from_lines(
         &mut self,
         lines: &[String],
         deinfo: Self::DeserializeInfo,
+        options: ImportOptions,
     ) -> Result<(), Self::Error>;
+
+    /// Check the deinfo against the number of lines without needing the
+    /// original data and return every issue found.
+    fn internal_check_lines(line_count: usize, deinfo: &Self::DeserializeInfo) -> Vec<String>;
+}
+
+/// Access to every single string of the data, used by the key-value
+/// serializer and the import checks.
+pub trait InternalStringAccess: InternalSerializerBase {
+    /// Whether the strings of the game have translator notes, the table
+    /// format adds a note column for each language when they do.
+    const NOTES: bool = false;
+
+    fn internal_strings(&self) -> Box<dyn Iterator<Item = StringEntry<'_, Self::Language>> + '_>;
+
+    /// The strings that an export of single strings writes, only the ones
+    /// that pass the export `filters`.
+    fn internal_exported_strings<'a>(
+        &'a self,
+        filters: &'a Self::ExportFilters,
+    ) -> Box<dyn Iterator<Item = StringEntry<'a, Self::Language>> + 'a>;
+
+    /// Replace a single string, `sub` is the line index inside a cutscene
+    /// and should be `0` for other resources.
+    fn internal_set_string(
+        &mut self,
+        index: usize,
+        sub: usize,
+        language: Self::Language,
+        string: String,
+    ) -> Result<(), Self::Error>;
+
+    /// The translator note and the timing of a single string, for the games
+    /// that have them.
+    fn internal_string_extras(
+        &self,
+        _index: usize,
+        _sub: usize,
+        _language: Self::Language,
+    ) -> (Option<&str>, Option<u64>) {
+        (None, None)
+    }
+
+    /// A comment about a single string to give translators some context, the
+    /// translator note for the games that have them.
+    fn internal_string_comment(
+        &self,
+        index: usize,
+        sub: usize,
+        language: Self::Language,
+    ) -> Option<String> {
+        let (note, _) = self.internal_string_extras(index, sub, language);
+        note.filter(|n| !n.is_empty()).map(str::to_owned)
+    }
+
+    /// Replace the translator note of a single string, ignored by the games
+    /// without notes.
+    fn internal_set_note(
+        &mut self,
+        _index: usize,
+        _sub: usize,
+        _language: Self::Language,
+        _note: String,
+    ) -> Result<(), Self::Error> {
+        Ok(())
+    }
 }
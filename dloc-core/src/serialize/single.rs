@@ -1,15 +1,19 @@
 use std::{
+    collections::BTreeMap,
     fs::File,
-    io::{BufRead, BufReader, BufWriter},
+    io::{BufRead, BufWriter, Write},
     path::Path,
 };
 
-use crate::{
-    error::{DResult, Error},
-    utils::EofReplacor,
-};
+use serde::Serialize;
+
+use crate::error::{DResult, Error};
 
-use super::{private, SerializeType};
+use super::{
+    compression::{open_export, ExportWriter},
+    key_value, ndjson, po, private, table, ExportDifference, ImportOptions, ImportSummary,
+    SerializeType, TxtLineWriter, DEFAULT_LANGUAGE_SEPARATOR,
+};
 
 /// Serializes and deserializes data for types that implement the
 /// SerializeData trait. This allows serialization to various formats
@@ -17,7 +21,9 @@ use super::{private, SerializeType};
 /// specified.
 pub trait SerializeData<T>: Sized
 where
-    T: private::InternalDataSerializer + private::InternalPlainTextDataSerializer,
+    T: private::InternalDataSerializer
+        + private::InternalPlainTextDataSerializer
+        + private::InternalStringAccess,
 {
     /// Serializes the data to the given output path in the specified
     /// serialization format. `languages` specifies the language to include.
@@ -26,6 +32,7 @@ where
     /// * `output`: The path to serialize the data to.
     /// * `languages`: The languages to serialize.
     /// * [`serialize_type`](SerializeType): The serialization format.
+    /// * `filters`: What gets serialized, see [`ExportFilters`](crate::games::ExportFilters).
     ///
     /// ## Return:
     /// Returns a [`DResult`] indicating whether serialization was successful or not.
@@ -34,6 +41,102 @@ where
         output: P,
         languages: L,
         serialize_type: SerializeType,
+        filters: &T::ExportFilters,
+    ) -> DResult<()>;
+
+    /// Same as [`serialize`](Self::serialize) but writes to `writer` instead
+    /// of a file. Txt isn't supported as it needs a deserialize info file.
+    ///
+    /// ## Arguments:
+    /// * `writer`: The writer to serialize the data to.
+    /// * `languages`: The languages to serialize.
+    /// * [`serialize_type`](SerializeType): The serialization format.
+    /// * `filters`: What gets serialized, see [`ExportFilters`](crate::games::ExportFilters).
+    ///
+    /// ## Return:
+    /// Returns a [`DResult`] indicating whether serialization was successful or not.
+    fn serialize_to_writer<L: AsRef<[T::Language]>, W: Write>(
+        &self,
+        writer: W,
+        languages: L,
+        serialize_type: SerializeType,
+        filters: &T::ExportFilters,
+    ) -> DResult<()>;
+
+    /// Checks a txt export against its deserialize info without touching
+    /// any core file, returns a list of all issues found.
+    ///
+    /// ## Arguments:
+    /// * `input`: path to the txt file, the deinfo should be next to it.
+    ///
+    /// ## Return:
+    /// Returns a [`DResult`] with the issues, an empty list means the
+    /// export is consistent.
+    fn check_txt_export<P: AsRef<Path>>(input: P) -> DResult<Vec<String>>;
+
+    /// Compares two single core exports without the core and returns every
+    /// string that differ between them, in the order of their resource,
+    /// language and line. Txt isn't supported as it needs the core.
+    ///
+    /// ## Arguments:
+    /// * `old`: path to the first export.
+    /// * `new`: path to the second export.
+    /// * [`serialize_type`](SerializeType): The serialization format of both.
+    ///
+    /// ## Return:
+    /// Returns a [`DResult`] with the differences, an empty list means both
+    /// exports have the same strings.
+    fn diff_exports<P: AsRef<Path>>(
+        old: P,
+        new: P,
+        serialize_type: SerializeType,
+    ) -> DResult<Vec<ExportDifference<T::Language>>>;
+
+    /// Serializes just the languages that differ from `reference` for
+    /// each resource, resources without any change are skipped.
+    /// Only Json and Yaml are supported as the txt format needs the same
+    /// languages for every resource.
+    ///
+    /// ## Arguments:
+    /// * `reference`: The baseline to compare against, it should be the same core.
+    /// * `output`: The path to serialize the data to.
+    /// * `languages`: The languages to compare and serialize.
+    /// * [`serialize_type`](SerializeType): The serialization format.
+    /// * `filters`: What gets serialized, see [`ExportFilters`](crate::games::ExportFilters).
+    ///
+    /// ## Return:
+    /// Returns a [`DResult`] indicating whether serialization was successful or not.
+    fn serialize_changed<L: AsRef<[T::Language]>, P: AsRef<Path>>(
+        &self,
+        reference: &T,
+        output: P,
+        languages: L,
+        serialize_type: SerializeType,
+        filters: &T::ExportFilters,
+    ) -> DResult<()>;
+
+    /// Serializes like [`serialize`](Self::serialize) and copies the fields
+    /// that dloc doesn't know from the resources of a `previous` export, so
+    /// notes or ids added by other tools survive a new export. Resources are
+    /// matched by their uuid if it was recorded, otherwise by their index.
+    /// Only Json and Yaml are supported, `previous` should have the same format.
+    ///
+    /// ## Arguments:
+    /// * `previous`: The export to copy the unknown fields from.
+    /// * `output`: The path to serialize the data to.
+    /// * `languages`: The languages to serialize.
+    /// * [`serialize_type`](SerializeType): The serialization format.
+    /// * `filters`: What gets serialized, see [`ExportFilters`](crate::games::ExportFilters).
+    ///
+    /// ## Return:
+    /// Returns a [`DResult`] indicating whether serialization was successful or not.
+    fn serialize_keeping_fields<L: AsRef<[T::Language]>, P: AsRef<Path>>(
+        &self,
+        previous: P,
+        output: P,
+        languages: L,
+        serialize_type: SerializeType,
+        filters: &T::ExportFilters,
     ) -> DResult<()>;
 
     /// Deserializes data from the given input path and updates self
@@ -43,87 +146,445 @@ where
     /// ## Arguments:
     /// * `input`: path to input file to deserialize from.
     /// * [`serialize_type`](SerializeType): The serialization format.
+    /// * `options`: How the input is checked, see [`ImportOptions`].
     ///
     /// ## Return:
-    /// Returns a [`DResult`] indicating whether deserialization and update
-    /// was successful.
+    /// Returns a [`DResult`] with an [`ImportSummary`] of what changed if
+    /// deserialization and update was successful.
     fn deserialize_and_update<P: AsRef<Path>>(
         &mut self,
         input: P,
         serialize_type: SerializeType,
-    ) -> DResult<()>;
+        options: ImportOptions,
+    ) -> DResult<ImportSummary>;
+
+    /// Same as [`deserialize_and_update`](Self::deserialize_and_update) but
+    /// reads from `reader` instead of a file. Txt isn't supported as it
+    /// needs a deserialize info file.
+    ///
+    /// ## Arguments:
+    /// * `reader`: reader to deserialize from.
+    /// * [`serialize_type`](SerializeType): The serialization format.
+    /// * `options`: How the input is checked, see [`ImportOptions`].
+    ///
+    /// ## Return:
+    /// Returns a [`DResult`] with an [`ImportSummary`] of what changed if
+    /// deserialization and update was successful.
+    fn deserialize_and_update_from_reader<R: BufRead>(
+        &mut self,
+        reader: R,
+        serialize_type: SerializeType,
+        options: ImportOptions,
+    ) -> DResult<ImportSummary>;
 }
 
 impl<T> SerializeData<T> for T
 where
-    T: private::InternalDataSerializer + private::InternalPlainTextDataSerializer,
+    T: private::InternalDataSerializer
+        + private::InternalPlainTextDataSerializer
+        + private::InternalStringAccess,
 {
     fn serialize<L: AsRef<[<T>::Language]>, P: AsRef<Path>>(
         &self,
         output: P,
         languages: L,
         serialize_type: SerializeType,
+        filters: &T::ExportFilters,
     ) -> DResult<()> {
         let output = output.as_ref();
+        #[cfg(feature = "sqlite")]
+        if serialize_type == SerializeType::Sqlite {
+            let mut writer = super::sqlite::SqliteWriter::create(output)?;
+            writer.write_strings(self, languages.as_ref(), filters, None)?;
+            return writer.finish();
+        }
+
+        let SerializeType::Txt {
+            add_language_names,
+            language_separator,
+            add_indices,
+            line_ending,
+        } = serialize_type
+        else {
+            let mut writer = ExportWriter::create(output)?;
+            self.serialize_to_writer(&mut writer, languages, serialize_type, filters)?;
+            return Ok(writer.finish()?);
+        };
+
+        let language_separator = add_language_names.then(|| {
+            language_separator
+                .as_deref()
+                .unwrap_or(DEFAULT_LANGUAGE_SEPARATOR)
+        });
+        let mut file = ExportWriter::create(output)?;
+        let mut writer = TxtLineWriter::new(&mut file, line_ending.is_some());
+        let deinfo = self.internal_serialize_lines_with(
+            languages.as_ref(),
+            filters,
+            language_separator,
+            add_indices,
+            |line| writer.write_line(line),
+        )?;
+        writer.finish()?;
+        file.finish()?;
+
+        let writer = BufWriter::new(File::create(super::deinfo_path(output))?);
+        serde_json::to_writer(writer, &deinfo)?;
+
+        Ok(())
+    }
+
+    fn serialize_to_writer<L: AsRef<[<T>::Language]>, W: Write>(
+        &self,
+        mut writer: W,
+        languages: L,
+        serialize_type: SerializeType,
+        filters: &T::ExportFilters,
+    ) -> DResult<()> {
         match serialize_type {
-            SerializeType::Json => {
-                let value = self.internal_serialize(languages.as_ref());
-                let writer = BufWriter::new(File::create(output)?);
-                serde_json::to_writer_pretty(writer, &value)?;
+            SerializeType::Json { indent, omit_empty } => {
+                let value = self.internal_serialize(languages.as_ref(), omit_empty, filters);
+                super::write_json(&mut writer, &value, indent.as_deref())?;
             }
             #[cfg(feature = "serialize_yaml")]
-            SerializeType::Yaml => {
-                let value = self.internal_serialize(languages.as_ref());
-                let writer = BufWriter::new(File::create(output)?);
-                serde_yaml::to_writer(writer, &value)?;
+            SerializeType::Yaml { omit_empty } => {
+                let value = self.internal_serialize(languages.as_ref(), omit_empty, filters);
+                serde_yaml::to_writer(&mut writer, &value)?;
             }
-            SerializeType::Txt { add_language_names } => {
-                let (lines, deinfo) =
-                    self.internal_serialize_to_lines(languages.as_ref(), add_language_names);
-                let lines: Vec<_> = lines.into_iter().map(EofReplacor::replace_eol).collect();
-
-                std::fs::write(output, lines.join("\n"))?;
-                let writer =
-                    BufWriter::new(File::create(output.with_extension(super::DEINFO_EXT))?);
-                serde_json::to_writer(writer, &deinfo)?;
+            SerializeType::Txt { .. } => {
+                return Err(Error::UnsupportedSerializeType(serialize_type));
+            }
+            #[cfg(feature = "sqlite")]
+            SerializeType::Sqlite => {
+                return Err(Error::UnsupportedSerializeType(serialize_type));
+            }
+            SerializeType::KeyValue => {
+                key_value::write_strings(
+                    &mut writer,
+                    self.internal_exported_strings(filters),
+                    languages.as_ref(),
+                )?;
+            }
+            SerializeType::Table => {
+                let notes = T::NOTES.then_some(|index, sub, language| {
+                    self.internal_string_extras(index, sub, language).0
+                });
+                table::write_strings(
+                    &mut writer,
+                    self.internal_exported_strings(filters),
+                    languages.as_ref(),
+                    notes,
+                )?;
+            }
+            SerializeType::Ndjson => {
+                let strings = self.internal_exported_strings(filters);
+                ndjson::write_strings(&mut writer, strings, languages.as_ref(), None)?;
+            }
+            SerializeType::Po => {
+                po::write_header(&mut writer)?;
+                po::write_strings(
+                    &mut writer,
+                    self.internal_exported_strings(filters),
+                    languages.as_ref(),
+                    None,
+                    |index, sub, language| self.internal_string_comment(index, sub, language),
+                )?;
             }
         }
+        writer.flush()?;
 
         Ok(())
     }
 
+    fn check_txt_export<P: AsRef<Path>>(input: P) -> DResult<Vec<String>> {
+        let input = input.as_ref();
+
+        let lines = super::read_txt_lines(open_export(input)?, None)?;
+        let deinfo = super::read_deinfo(input)?;
+
+        Ok(T::internal_check_lines(lines.len(), &deinfo))
+    }
+
+    fn diff_exports<P: AsRef<Path>>(
+        old: P,
+        new: P,
+        serialize_type: SerializeType,
+    ) -> DResult<Vec<ExportDifference<T::Language>>> {
+        let read = |path: &Path| read_export_strings::<T, _>(open_export(path)?, &serialize_type);
+        let old = read(old.as_ref())?;
+        let mut new = read(new.as_ref())?;
+
+        let mut differences = Vec::new();
+        for ((index, language, sub), old) in old {
+            let new = new.remove(&(index, language, sub));
+            if new.as_ref() != Some(&old) {
+                differences.push(ExportDifference {
+                    index,
+                    sub,
+                    language,
+                    old: Some(old),
+                    new,
+                });
+            }
+        }
+        differences.extend(
+            new.into_iter()
+                .map(|((index, language, sub), new)| ExportDifference {
+                    index,
+                    sub,
+                    language,
+                    old: None,
+                    new: Some(new),
+                }),
+        );
+        differences.sort_by_key(|d| (d.index, d.language, d.sub));
+
+        Ok(differences)
+    }
+
+    fn serialize_changed<L: AsRef<[<T>::Language]>, P: AsRef<Path>>(
+        &self,
+        reference: &T,
+        output: P,
+        languages: L,
+        serialize_type: SerializeType,
+        filters: &T::ExportFilters,
+    ) -> DResult<()> {
+        let value = self
+            .internal_serialize_changed(reference, languages.as_ref(), filters)
+            .map_err(|e| Error::SerializeError(e.to_string()))?;
+
+        write_serde_export(&value, output.as_ref(), serialize_type)
+    }
+
+    fn serialize_keeping_fields<L: AsRef<[<T>::Language]>, P: AsRef<Path>>(
+        &self,
+        previous: P,
+        output: P,
+        languages: L,
+        serialize_type: SerializeType,
+        filters: &T::ExportFilters,
+    ) -> DResult<()> {
+        let reader = open_export(previous.as_ref())?;
+        let (mut value, previous) = match serialize_type {
+            SerializeType::Json { omit_empty, .. } => (
+                self.internal_serialize(languages.as_ref(), omit_empty, filters),
+                serde_json::from_reader(reader)?,
+            ),
+            #[cfg(feature = "serialize_yaml")]
+            SerializeType::Yaml { omit_empty } => (
+                self.internal_serialize(languages.as_ref(), omit_empty, filters),
+                serde_yaml::from_reader(reader)?,
+            ),
+            _ => return Err(Error::UnsupportedSerializeType(serialize_type)),
+        };
+        T::internal_copy_extra_fields(&mut value, previous);
+
+        write_serde_export(&value, output.as_ref(), serialize_type)
+    }
+
     fn deserialize_and_update<P: AsRef<Path>>(
         &mut self,
         input: P,
         serialize_type: SerializeType,
-    ) -> DResult<()> {
+        options: ImportOptions,
+    ) -> DResult<ImportSummary> {
         let input = input.as_ref();
+        #[cfg(feature = "sqlite")]
+        if serialize_type == SerializeType::Sqlite {
+            let before = super::snapshot(self);
+            let sections = super::sqlite::read::<T::Language>(input, T::GAME)?;
+            set_section_strings(self, sections, &serialize_type)?;
+
+            let mut summary = ImportSummary::default();
+            summary.add_changes(&before, self.internal_strings());
+            return Ok(summary);
+        }
+
+        let reader = open_export(input)?;
+        let SerializeType::Txt { line_ending, .. } = serialize_type else {
+            return self.deserialize_and_update_from_reader(reader, serialize_type, options);
+        };
 
-        let reader = BufReader::new(File::open(input)?);
-        let data = match serialize_type {
-            SerializeType::Json => serde_json::from_reader(reader)?,
+        let before = super::snapshot(self);
+        let lines = super::read_txt_lines(reader, line_ending)?;
+        let deinfo = super::read_deinfo(input)?;
+
+        self.internal_deserialize_and_update_from_lines(&lines, deinfo, options)
+            .map_err(|e| Error::GameDeserialize(e.into()))?;
+
+        let mut summary = ImportSummary::default();
+        summary.add_changes(&before, self.internal_strings());
+
+        Ok(summary)
+    }
+
+    fn deserialize_and_update_from_reader<R: BufRead>(
+        &mut self,
+        reader: R,
+        serialize_type: SerializeType,
+        options: ImportOptions,
+    ) -> DResult<ImportSummary> {
+        let before = super::snapshot(self);
+
+        match serialize_type {
+            SerializeType::Json { .. } => {
+                let data = serde_json::from_reader(reader)?;
+                self.internal_deserialize_and_update(data, options)
+                    .map_err(|e| Error::GameDeserialize(e.into()))?;
+            }
             #[cfg(feature = "serialize_yaml")]
-            SerializeType::Yaml => serde_yaml::from_reader(reader)?,
+            SerializeType::Yaml { .. } => {
+                let data = serde_yaml::from_reader(reader)?;
+                self.internal_deserialize_and_update(data, options)
+                    .map_err(|e| Error::GameDeserialize(e.into()))?;
+            }
             SerializeType::Txt { .. } => {
-                let lines = reader
-                    .lines()
-                    .map(|s| s.map(EofReplacor::replace_eol_back))
-                    .collect::<std::io::Result<Vec<String>>>()?;
+                return Err(Error::UnsupportedSerializeType(serialize_type));
+            }
+            SerializeType::KeyValue
+            | SerializeType::Table
+            | SerializeType::Ndjson
+            | SerializeType::Po => {
+                let sections = match serialize_type {
+                    SerializeType::Table => table::read::<_, T::Language>(reader)?,
+                    SerializeType::Ndjson => ndjson::read::<_, T::Language>(reader)?,
+                    SerializeType::Po => po::read::<_, T::Language>(reader)?,
+                    _ => key_value::read::<_, T::Language>(reader)?,
+                };
+                set_section_strings(self, sections, &serialize_type)?;
+            }
+            #[cfg(feature = "sqlite")]
+            SerializeType::Sqlite => {
+                return Err(Error::UnsupportedSerializeType(serialize_type));
+            }
+        }
 
-                let reader = BufReader::new(File::open(input.with_extension(super::DEINFO_EXT))?);
+        let mut summary = ImportSummary::default();
+        summary.add_changes(&before, self.internal_strings());
 
-                let deinfo = serde_json::from_reader(reader)?;
+        Ok(summary)
+    }
+}
 
-                self.internal_deserialize_and_update_from_lines(&lines, deinfo)
-                    .map_err(|e| Error::DeserializeError(e.to_string()))?;
+/// Set every string of `sections`, they should all belong to a single core
+/// and have no path.
+fn set_section_strings<T: private::InternalStringAccess>(
+    data: &mut T,
+    sections: Vec<key_value::Section<T::Language>>,
+    serialize_type: &SerializeType,
+) -> DResult<()> {
+    for section in sections {
+        if let Some(path) = section.path {
+            return Err(Error::DeserializeError(format!(
+                "Found section {} in a single core {} file",
+                path.display(),
+                serialize_type.extension()
+            )));
+        }
 
-                return Ok(());
+        for kv in section.values {
+            if let Some(note) = kv.note {
+                data.internal_set_note(kv.index, kv.sub, kv.language, note)
+                    .map_err(|e| Error::GameDeserialize(e.into()))?;
             }
-        };
+            data.internal_set_string(kv.index, kv.sub, kv.language, kv.value)
+                .map_err(|e| Error::GameDeserialize(e.into()))?;
+        }
+    }
 
-        self.internal_deserialize_and_update(data)
-            .map_err(|e| Error::DeserializeError(e.to_string()))?;
+    Ok(())
+}
 
-        Ok(())
+/// Strings of an export keyed by their resource index, language and line index.
+type ExportStrings<L> = BTreeMap<(usize, L, usize), String>;
+
+/// Read every string of a single core export.
+fn read_export_strings<T, R>(
+    reader: R,
+    serialize_type: &SerializeType,
+) -> DResult<ExportStrings<T::Language>>
+where
+    T: private::InternalDataSerializer,
+    R: BufRead,
+{
+    let strings = match serialize_type {
+        SerializeType::Json { .. } => T::internal_flatten(serde_json::from_reader(reader)?),
+        #[cfg(feature = "serialize_yaml")]
+        SerializeType::Yaml { .. } => T::internal_flatten(serde_yaml::from_reader(reader)?),
+        SerializeType::Txt { .. } => {
+            return Err(Error::UnsupportedSerializeType(serialize_type.clone()));
+        }
+        #[cfg(feature = "sqlite")]
+        SerializeType::Sqlite => {
+            return Err(Error::UnsupportedSerializeType(serialize_type.clone()));
+        }
+        SerializeType::KeyValue
+        | SerializeType::Table
+        | SerializeType::Ndjson
+        | SerializeType::Po => {
+            let sections = match serialize_type {
+                SerializeType::Table => table::read::<_, T::Language>(reader)?,
+                SerializeType::Ndjson => ndjson::read::<_, T::Language>(reader)?,
+                SerializeType::Po => po::read::<_, T::Language>(reader)?,
+                _ => key_value::read::<_, T::Language>(reader)?,
+            };
+            let mut strings = Vec::new();
+            for section in sections {
+                if let Some(path) = section.path {
+                    return Err(Error::DeserializeError(format!(
+                        "Found section {} in a single core {} file",
+                        path.display(),
+                        serialize_type.extension()
+                    )));
+                }
+                strings.extend(
+                    section
+                        .values
+                        .into_iter()
+                        .map(|kv| (kv.index, kv.sub, kv.language, kv.value)),
+                );
+            }
+            strings
+        }
+    };
+
+    Ok(strings
+        .into_iter()
+        .map(|(index, sub, language, string)| ((index, language, sub), string))
+        .collect())
+}
+
+/// Write `value` to `output` as Json or Yaml, other formats aren't supported.
+fn write_serde_export<V: Serialize>(
+    value: &V,
+    output: &Path,
+    serialize_type: SerializeType,
+) -> DResult<()> {
+    match serialize_type {
+        SerializeType::Json { ref indent, .. } => {
+            let mut writer = ExportWriter::create(output)?;
+            super::write_json(&mut writer, value, indent.as_deref())?;
+            writer.finish()?;
+        }
+        #[cfg(feature = "serialize_yaml")]
+        SerializeType::Yaml { .. } => {
+            let mut writer = ExportWriter::create(output)?;
+            serde_yaml::to_writer(&mut writer, value)?;
+            writer.finish()?;
+        }
+        SerializeType::Txt { .. }
+        | SerializeType::KeyValue
+        | SerializeType::Table
+        | SerializeType::Ndjson
+        | SerializeType::Po => {
+            return Err(Error::UnsupportedSerializeType(serialize_type));
+        }
+        #[cfg(feature = "sqlite")]
+        SerializeType::Sqlite => {
+            return Err(Error::UnsupportedSerializeType(serialize_type));
+        }
     }
+
+    Ok(())
 }
@@ -0,0 +1,138 @@
+//! SQLite database with a row for each string in the `strings` table:
+//!
+//! | game | path | index | sub | language | text | note | timing |
+//! |------|------|-------|-----|----------|------|------|--------|
+//!
+//! `path` is only set in group mode and `sub` is the line of a cutscene,
+//! always `0` for other resources. `note` is the translator note of DS
+//! strings and `timing` the timing of HZD cutscene lines, they are exported
+//! for reference and import only reads back the `text`.
+
+use std::path::{Path, PathBuf};
+
+use rusqlite::{params, Connection};
+
+use crate::{
+    error::{DResult, Error},
+    serialize::private::InternalStringAccess,
+};
+
+use super::key_value::{KeyValue, Section};
+
+/// Writer of the rows of an export, every row is written in a single
+/// transaction that is committed by [`SqliteWriter::finish`].
+pub(crate) struct SqliteWriter {
+    conn: Connection,
+}
+
+impl SqliteWriter {
+    /// Create the database at `path`, an existing file is replaced.
+    pub(crate) fn create(path: &Path) -> DResult<Self> {
+        if path.exists() {
+            std::fs::remove_file(path)?;
+        }
+
+        let conn = Connection::open(path)?;
+        conn.execute_batch(
+            r#"CREATE TABLE strings (
+                game TEXT NOT NULL,
+                path TEXT,
+                "index" INTEGER NOT NULL,
+                sub INTEGER NOT NULL,
+                language TEXT NOT NULL,
+                text TEXT NOT NULL,
+                note TEXT,
+                timing INTEGER
+            );
+            BEGIN;"#,
+        )?;
+
+        Ok(Self { conn })
+    }
+
+    /// Write every string of `data` with one of the `languages` as a row,
+    /// `path` is only given in group mode.
+    pub(crate) fn write_strings<T: InternalStringAccess>(
+        &mut self,
+        data: &T,
+        languages: &[T::Language],
+        filters: &T::ExportFilters,
+        path: Option<&Path>,
+    ) -> DResult<()> {
+        let mut insert = self.conn.prepare_cached(
+            r#"INSERT INTO strings (game, path, "index", sub, language, text, note, timing)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)"#,
+        )?;
+        let path = path.map(|p| p.to_string_lossy());
+
+        for entry in data
+            .internal_exported_strings(filters)
+            .filter(|e| languages.contains(&e.language))
+        {
+            let (note, timing) =
+                data.internal_string_extras(entry.index, entry.sub, entry.language);
+            insert.execute(params![
+                T::GAME,
+                path,
+                entry.index,
+                entry.sub,
+                entry.language.to_string(),
+                entry.string,
+                note,
+                timing.map(|t| t as i64),
+            ])?;
+        }
+
+        Ok(())
+    }
+
+    /// Commit the rows and close the database.
+    pub(crate) fn finish(self) -> DResult<()> {
+        self.conn.execute_batch("COMMIT;")?;
+        self.conn.close().map_err(|(_, e)| e)?;
+        Ok(())
+    }
+}
+
+/// Read the rows of `game` and group them by the path of their core, the
+/// rows without a path are in the first section.
+pub(crate) fn read<L: TryFrom<String>>(path: &Path, game: &str) -> DResult<Vec<Section<L>>> {
+    let conn = Connection::open(path)?;
+    let mut select = conn.prepare(
+        r#"SELECT path, "index", sub, language, text FROM strings
+        WHERE game = ?1 ORDER BY path, "index", sub"#,
+    )?;
+    let mut rows = select.query(params![game])?;
+
+    let mut sections = vec![Section {
+        path: None,
+        values: Vec::new(),
+    }];
+
+    while let Some(row) = rows.next()? {
+        let path = row.get::<_, Option<String>>(0)?.map(PathBuf::from);
+        let language: String = row.get(3)?;
+        let language = L::try_from(language.clone()).map_err(|_| {
+            Error::DeserializeError(format!("Invalid language {language} in the database"))
+        })?;
+
+        // there is always at least one section
+        if sections.last().is_some_and(|s| s.path != path) {
+            sections.push(Section {
+                path,
+                values: Vec::new(),
+            });
+        }
+        if let Some(section) = sections.last_mut() {
+            section.values.push(KeyValue {
+                index: row.get(1)?,
+                sub: row.get(2)?,
+                language,
+                value: row.get(4)?,
+                note: None,
+            });
+        }
+    }
+
+    Ok(sections)
+}
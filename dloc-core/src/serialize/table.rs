@@ -0,0 +1,398 @@
+//! A wide tab separated table, each row is a single string of a resource with
+//! a column for every language, so translations can be compared side by side.
+//!
+//! The first row is the header of `index`, `sub` and the language names
+//! separated by tabs, `sub` is the line of a cutscene and always `0` for
+//! other resources. Games with translator notes get a `note:Language` column
+//! for each language after the language columns. The note columns are
+//! optional on import, a note is only written to its own language and a
+//! blank note keeps the note of the core. In the cells `\`, tab, line feed
+//! and carriage return are escaped as `\\`, `\t`, `\n` and `\r`, a cell of
+//! `\N` means the string doesn't exist in that language and is skipped on
+//! import. In group mode the rows of each core come after a
+//! `[path/to/file.core]` line like the key-value format.
+
+use std::{
+    collections::BTreeMap,
+    fmt::Display,
+    io::{self, BufRead, Write},
+    path::PathBuf,
+};
+
+use crate::{
+    error::{DResult, Error},
+    games::StringEntry,
+};
+
+use super::key_value::{KeyValue, Section};
+
+/// Cell of a string that doesn't exist in a language.
+const MISSING: &str = "\\N";
+
+/// Prefix of the header of a note column, followed by the language name.
+const NOTE_PREFIX: &str = "note:";
+
+/// Write the header row with a column for each of the `languages`, and a
+/// note column for each of them if `notes` is set.
+pub(crate) fn write_header<W: Write, L: Display>(
+    writer: &mut W,
+    languages: &[L],
+    notes: bool,
+) -> io::Result<()> {
+    write!(writer, "index\tsub")?;
+    for language in languages {
+        write!(writer, "\t{language}")?;
+    }
+    if notes {
+        for language in languages {
+            write!(writer, "\t{NOTE_PREFIX}{language}")?;
+        }
+    }
+    writeln!(writer)
+}
+
+/// Write a row for every string in `strings` with the columns of `languages`,
+/// `notes` gives the note of a string when the header has note columns.
+pub(crate) fn write_rows<'a, W, L, N>(
+    writer: &mut W,
+    strings: impl Iterator<Item = StringEntry<'a, L>>,
+    languages: &[L],
+    notes: Option<N>,
+) -> io::Result<()>
+where
+    W: Write,
+    L: Ord + Copy + 'a,
+    N: Fn(usize, usize, L) -> Option<&'a str>,
+{
+    let mut rows: BTreeMap<(usize, usize), BTreeMap<L, &str>> = BTreeMap::new();
+    for entry in strings.filter(|e| languages.contains(&e.language)) {
+        rows.entry((entry.index, entry.sub))
+            .or_default()
+            .insert(entry.language, entry.string);
+    }
+
+    for ((index, sub), strings) in rows {
+        write!(writer, "{index}\t{sub}")?;
+        for language in languages {
+            match strings.get(language) {
+                Some(string) => write!(writer, "\t{}", escape(string))?,
+                None => write!(writer, "\t{MISSING}")?,
+            }
+        }
+        if let Some(notes) = &notes {
+            for &language in languages {
+                if strings.contains_key(&language) {
+                    let note = notes(index, sub, language).unwrap_or_default();
+                    write!(writer, "\t{}", escape(note))?;
+                } else {
+                    write!(writer, "\t{MISSING}")?;
+                }
+            }
+        }
+        writeln!(writer)?;
+    }
+
+    Ok(())
+}
+
+/// Write the header and the rows of a single core.
+pub(crate) fn write_strings<'a, W, L, N>(
+    writer: &mut W,
+    strings: impl Iterator<Item = StringEntry<'a, L>>,
+    languages: &[L],
+    notes: Option<N>,
+) -> io::Result<()>
+where
+    W: Write,
+    L: Ord + Copy + Display + 'a,
+    N: Fn(usize, usize, L) -> Option<&'a str>,
+{
+    write_header(writer, languages, notes.is_some())?;
+    write_rows(writer, strings, languages, notes)
+}
+
+/// Read all the rows and group their cells by the section they are in.
+pub(crate) fn read<R: BufRead, L: TryFrom<String> + Copy + PartialEq>(
+    reader: R,
+) -> DResult<Vec<Section<L>>> {
+    let mut sections = vec![Section {
+        path: None,
+        values: Vec::new(),
+    }];
+    let mut languages: Option<Vec<L>> = None;
+    let mut note_languages = Vec::new();
+
+    for (number, line) in reader.lines().enumerate() {
+        let line = line?;
+        let line = line.trim_end_matches('\r');
+        let error = |msg: String| {
+            Error::DeserializeError(format!("Invalid table row at line {}: {msg}", number + 1))
+        };
+
+        if line.is_empty() {
+            continue;
+        }
+
+        if let Some(path) = line.strip_prefix('[').and_then(|s| s.strip_suffix(']')) {
+            sections.push(Section {
+                path: Some(PathBuf::from(path)),
+                values: Vec::new(),
+            });
+            continue;
+        }
+
+        let mut cells = line.split('\t');
+        let Some(languages) = &languages else {
+            if (cells.next(), cells.next()) != (Some("index"), Some("sub")) {
+                return Err(error(
+                    "the first row should be the index\tsub\tLanguage... header".to_owned(),
+                ));
+            }
+            let parse = |c: &str| {
+                L::try_from(c.to_owned()).map_err(|_| error(format!("invalid language {c}")))
+            };
+            let cells: Vec<_> = cells.collect();
+            let split = cells
+                .iter()
+                .position(|c| c.starts_with(NOTE_PREFIX))
+                .unwrap_or(cells.len());
+            note_languages = cells[split..]
+                .iter()
+                .map(|c| match c.strip_prefix(NOTE_PREFIX) {
+                    Some(c) => parse(c),
+                    None => Err(error(format!("language column {c} after the note columns"))),
+                })
+                .collect::<DResult<_>>()?;
+            languages = Some(
+                cells[..split]
+                    .iter()
+                    .map(|c| parse(c))
+                    .collect::<DResult<_>>()?,
+            );
+            continue;
+        };
+
+        let index = cells
+            .next()
+            .and_then(|c| c.parse().ok())
+            .ok_or_else(|| error("invalid index".to_owned()))?;
+        let sub = cells
+            .next()
+            .and_then(|c| c.parse().ok())
+            .ok_or_else(|| error("invalid sub index".to_owned()))?;
+        let cells: Vec<_> = cells.collect();
+        if cells.len() != languages.len() + note_languages.len() {
+            return Err(error(format!(
+                "expected {} language and {} note columns but found {}",
+                languages.len(),
+                note_languages.len(),
+                cells.len()
+            )));
+        }
+        let (cells, note_cells) = cells.split_at(languages.len());
+
+        // there is always at least one section
+        let Some(section) = sections.last_mut() else {
+            continue;
+        };
+        for (&language, &cell) in languages.iter().zip(cells) {
+            if cell == MISSING {
+                continue;
+            }
+            let value =
+                unescape(cell).ok_or_else(|| error("invalid escape sequence".to_owned()))?;
+            let note = note_languages
+                .iter()
+                .zip(note_cells)
+                .find(|(&l, &c)| l == language && c != MISSING)
+                .map(|(_, c)| {
+                    unescape(c).ok_or_else(|| error("invalid escape sequence".to_owned()))
+                })
+                .transpose()?
+                .filter(|n| !n.is_empty());
+            section.values.push(KeyValue {
+                index,
+                sub,
+                language,
+                value,
+                note,
+            });
+        }
+    }
+
+    Ok(sections)
+}
+
+fn escape(str: &str) -> String {
+    let mut buf = String::with_capacity(str.len());
+    for ch in str.chars() {
+        match ch {
+            '\\' => buf.push_str("\\\\"),
+            '\t' => buf.push_str("\\t"),
+            '\n' => buf.push_str("\\n"),
+            '\r' => buf.push_str("\\r"),
+            ch => buf.push(ch),
+        }
+    }
+    buf
+}
+
+fn unescape(str: &str) -> Option<String> {
+    let mut buf = String::with_capacity(str.len());
+    let mut chars = str.chars();
+    while let Some(ch) = chars.next() {
+        if ch != '\\' {
+            buf.push(ch);
+            continue;
+        }
+
+        match chars.next()? {
+            '\\' => buf.push('\\'),
+            't' => buf.push('\t'),
+            'n' => buf.push('\n'),
+            'r' => buf.push('\r'),
+            _ => return None,
+        }
+    }
+    Some(buf)
+}
+
+#[cfg(test)]
+mod test {
+    use std::path::Path;
+
+    use crate::games::hzd::Language;
+
+    use super::*;
+
+    type Notes = fn(usize, usize, Language) -> Option<&'static str>;
+
+    #[test]
+    fn escape_round_trip() {
+        for string in [
+            "",
+            "plain",
+            "ünï ✓",
+            "tab\there",
+            "line\nbreak\r\n",
+            "\\N",
+            "\\",
+        ] {
+            let escaped = escape(string);
+            assert!(!escaped.contains(['\t', '\n', '\r']), "{string:?}");
+            assert_eq!(unescape(&escaped).as_deref(), Some(string));
+        }
+        assert_eq!(escape("a\tb\\"), "a\\tb\\\\");
+        assert_eq!(unescape("\\q"), None);
+        assert_eq!(unescape("trailing\\"), None);
+    }
+
+    #[test]
+    fn header_has_a_column_per_language() {
+        let mut output = Vec::new();
+        write_header(&mut output, &[Language::English, Language::Arabic], false).unwrap();
+        assert_eq!(output, b"index\tsub\tEnglish\tArabic\n");
+
+        let mut output = Vec::new();
+        write_header(&mut output, &[Language::French, Language::German], true).unwrap();
+        assert_eq!(
+            output,
+            b"index\tsub\tFrench\tGerman\tnote:French\tnote:German\n"
+        );
+    }
+
+    #[test]
+    fn rows_round_trip() {
+        let strings = [
+            StringEntry {
+                index: 1,
+                language: Language::English,
+                sub: 0,
+                string: "tab\there\nand a new line\r\n",
+            },
+            StringEntry {
+                index: 1,
+                language: Language::French,
+                sub: 0,
+                string: "back\\slash",
+            },
+            StringEntry {
+                index: 2,
+                language: Language::French,
+                sub: 3,
+                string: "",
+            },
+        ];
+        let languages = [Language::English, Language::French];
+
+        let mut output = Vec::new();
+        write_strings(&mut output, strings.into_iter(), &languages, None::<Notes>).unwrap();
+        writeln!(output, "[a/b.core]").unwrap();
+        write_rows(
+            &mut output,
+            strings[1..].iter().copied(),
+            &languages,
+            None::<Notes>,
+        )
+        .unwrap();
+        let text = String::from_utf8(output.clone()).unwrap();
+        assert_eq!(text.lines().count(), 6);
+        assert!(text.contains("\n2\t3\t\\N\t\n"));
+
+        let sections = read::<_, Language>(output.as_slice()).unwrap();
+        assert_eq!(sections.len(), 2);
+        let values: Vec<_> = sections[0]
+            .values
+            .iter()
+            .map(|v| (v.index, v.sub, v.language, v.value.as_str()))
+            .collect();
+        assert_eq!(
+            values,
+            strings.map(|e| (e.index, e.sub, e.language, e.string))
+        );
+        assert_eq!(sections[1].path.as_deref(), Some(Path::new("a/b.core")));
+        assert_eq!(sections[1].values.len(), 2);
+    }
+
+    #[test]
+    fn notes_are_read_for_their_language() {
+        let text = "index\tsub\tEnglish\tFrench\tnote:French\tnote:English\n\
+                    1\t0\thi\tsalut\tune\\tnote\t\n\
+                    2\t0\t\\N\tencore\t\\N\ttwo\n";
+        let sections = read::<_, Language>(text.as_bytes()).unwrap();
+        let notes: Vec<_> = sections[0]
+            .values
+            .iter()
+            .map(|v| (v.index, v.language, v.note.as_deref()))
+            .collect();
+        assert_eq!(
+            notes,
+            [
+                (1, Language::English, None),
+                (1, Language::French, Some("une\tnote")),
+                (2, Language::French, None),
+            ]
+        );
+    }
+
+    #[test]
+    fn invalid_rows_are_rejected() {
+        for invalid in [
+            "English\n",
+            "index\tsub\tKlingon\n",
+            "index\tsub\tEnglish\nx\t0\ta\n",
+            "index\tsub\tEnglish\n1\tx\ta\n",
+            "index\tsub\tEnglish\n1\t0\ta\tb\n",
+            "index\tsub\tEnglish\n1\t0\ta\\q\n",
+            "index\tsub\tnote:English\tEnglish\n",
+            "index\tsub\tEnglish\tnote:Klingon\n",
+            "index\tsub\tEnglish\tnote:English\n1\t0\ta\n",
+            "index\tsub\tEnglish\tnote:English\n1\t0\ta\tb\\q\n",
+        ] {
+            assert!(
+                read::<_, Language>(invalid.as_bytes()).is_err(),
+                "{invalid:?}"
+            );
+        }
+    }
+}
@@ -13,10 +13,6 @@ pub trait EnumKey: From<usize> + Into<usize> {
     const LEN: usize;
 }
 
-pub trait Array {
-    type List;
-}
-
 /// A fixed size map implementation that guarantees every possible key exists.
 /// Uses an enum as the key type to provide a finite key space.
 /// The map stores values in an array indexed by the enum keys.
@@ -57,6 +53,11 @@ impl<const N: usize, K: EnumKey, V> FixedMap<N, K, V> {
     pub fn get_mut(&mut self, key: K) -> &mut V {
         &mut self.inner[key.into()]
     }
+
+    /// Swap the values of the keys `a` and `b`.
+    pub fn swap(&mut self, a: K, b: K) {
+        self.inner.swap(a.into(), b.into());
+    }
 }
 
 impl<const N: usize, K: EnumKey, V> ops::Index<K> for FixedMap<N, K, V> {
@@ -205,12 +206,13 @@ macro_rules! count {
 
 pub(crate) use count;
 
-/// A helper macro to generate a Enum map.
+/// A helper macro to generate a Enum map, `Name, Error;` names the enum and
+/// the error its `FromStr` returns, a tuple struct made from the given name.
 macro_rules! enum_map {
     (
         $(#[doc = $comment:literal])?
         $(#[derive($($derive_name:ident),+)])?
-        $name:ident;
+        $name:ident, $error:path;
         $($variant_name:ident = $idx:literal),+
         $(,)?
     ) => {
@@ -249,6 +251,14 @@ macro_rules! enum_map {
             }
         }
 
+        impl std::str::FromStr for $name {
+            type Err = $error;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                Self::try_from(s.to_owned()).map_err(|_| $error(s.to_owned()))
+            }
+        }
+
         impl From<$name> for usize {
             fn from(value: $name) -> Self {
                 value as usize
@@ -268,12 +278,13 @@ use serde::{de::Visitor, Deserialize, Serialize};
 
 #[cfg(test)]
 mod test {
-    use std::usize;
-
     use super::*;
 
+    #[derive(Debug, PartialEq)]
+    pub struct InvalidKey(String);
+
     enum_map!(
-        Key;
+        Key, InvalidKey;
 
         One = 0,
         Two = 1,
@@ -315,4 +326,11 @@ mod test {
 
         assert_eq!(15, MAP.iter().map(|(_, v)| *v).sum::<i32>());
     }
+
+    #[test]
+    fn parse_key() {
+        assert_eq!("three".parse(), Ok(Key::Three));
+        assert_eq!("Five".parse(), Ok(Key::Five));
+        assert_eq!("Six".parse::<Key>(), Err(InvalidKey("Six".to_owned())));
+    }
 }
@@ -1,5 +1,6 @@
 use std::{
-    ffi::OsStr,
+    collections::hash_map::DefaultHasher,
+    hash::{Hash, Hasher},
     path::{Path, PathBuf},
 };
 
@@ -8,6 +9,13 @@ pub use fixed_map::*;
 mod fixed_map;
 pub mod types;
 
+/// Get the hash of `value` using the [`DefaultHasher`].
+pub(crate) fn hash_of<T: Hash>(value: &T) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    value.hash(&mut hasher);
+    hasher.finish()
+}
+
 /// Generate a file list from input `path`
 ///
 /// # Inputs
@@ -15,37 +23,107 @@ pub mod types;
 /// * `ext`: file extension to filter files with, `None` mean no filter
 /// * `depth`: depth of the search, normally you should pass [`usize::MAX`] here
 /// # Return
-/// This function will return a `Vec` of `PathBuf`
+/// This function will return a `Vec` of `PathBuf`, or the first error met
+/// while walking the folder so unreadable files are never skipped silently.
 #[cfg(feature = "serialize")]
 pub(crate) fn generate_file_list(
     path: impl AsRef<Path>,
     extension: Option<&[&str]>,
     depth: usize,
-) -> Vec<PathBuf> {
-    walkdir::WalkDir::new(path)
-        .max_depth(depth)
-        .into_iter()
-        .filter_map(|f| {
-            let f = f.ok()?;
-            if f.path().is_dir() {
-                return None;
-            }
-            let Some(ext) = extension else {
-                return Some(f.path().to_path_buf()); // No filter
-            };
-            let file_ext = f.path().extension().and_then(OsStr::to_str)?;
-            if ext.contains(&file_ext) {
-                return Some(f.path().to_path_buf());
-            }
-            None
-        })
-        .collect()
+) -> crate::DResult<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    for entry in walkdir::WalkDir::new(path).max_depth(depth) {
+        let entry = entry.map_err(std::io::Error::from)?;
+        if entry.path().is_dir() {
+            continue;
+        }
+        if extension.is_none_or(|ext| has_extension(entry.path(), ext)) {
+            files.push(entry.into_path());
+        }
+    }
+    Ok(files)
+}
+
+/// Returns `true` if the extension of `path` is one of `extensions`, ignoring
+/// the ascii case like Windows does. The extension is compared without
+/// converting the path to UTF-8, so files with non UTF-8 names are kept.
+#[cfg(feature = "serialize")]
+pub(crate) fn has_extension(path: &Path, extensions: &[&str]) -> bool {
+    path.extension()
+        .is_some_and(|e| extensions.iter().any(|ext| e.eq_ignore_ascii_case(ext)))
+}
+
+/// Deserialize a `usize` that may be given as a number or as a string
+/// holding a number, some JSON tools turn every number into string.
+#[cfg(feature = "serialize")]
+pub(crate) fn deserialize_index<'de, D>(deserializer: D) -> Result<usize, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    struct IndexVisitor;
+
+    impl<'de> serde::de::Visitor<'de> for IndexVisitor {
+        type Value = usize;
+
+        fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+            formatter.write_str("an index as number or string")
+        }
+
+        fn visit_u64<E: serde::de::Error>(self, v: u64) -> Result<Self::Value, E> {
+            usize::try_from(v).map_err(E::custom)
+        }
+
+        fn visit_i64<E: serde::de::Error>(self, v: i64) -> Result<Self::Value, E> {
+            usize::try_from(v).map_err(E::custom)
+        }
+
+        fn visit_str<E: serde::de::Error>(self, v: &str) -> Result<Self::Value, E> {
+            v.trim().parse().map_err(E::custom)
+        }
+    }
+
+    deserializer.deserialize_any(IndexVisitor)
+}
+
+/// The line break that every escaped line break is put back as when
+/// importing with normalized line endings.
+#[cfg(feature = "serialize")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum LineEnding {
+    /// `\n`
+    Lf,
+    /// `\r\n`
+    CrLf,
+    /// `\r`
+    Cr,
 }
 
+#[cfg(feature = "serialize")]
+impl LineEnding {
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Lf => "\n",
+            Self::CrLf => "\r\n",
+            Self::Cr => "\r",
+        }
+    }
+}
+
+/// Escape the line breaks of a string so it fits in a single txt line,
+/// and put them back when reading it. A `<` of the string that starts one
+/// of the codes is escaped as `<lt>`, so strings that have the codes as
+/// text come back as they were.
 #[cfg(feature = "serialize")]
 pub trait EofReplacor {
     fn replace_eol(self) -> Self;
     fn replace_eol_back(self) -> Self;
+    /// Like [`EofReplacor::replace_eol`] but every kind of line break is
+    /// escaped as `<lf>`, so `\r\n` from one editor and `\n` from another
+    /// look the same.
+    fn normalize_eol(self) -> Self;
+    /// Like [`EofReplacor::replace_eol_back`] but every escaped line break
+    /// is put back as `ending`.
+    fn replace_eol_back_as(self, ending: LineEnding) -> Self;
 }
 
 // this impl may seem a little more complicated then its need to
@@ -54,68 +132,128 @@ pub trait EofReplacor {
 #[cfg(feature = "serialize")]
 impl EofReplacor for String {
     fn replace_eol(self) -> Self {
-        if !self.contains(['\r', '\n']) {
-            return self;
-        }
+        escape_eol(self, ["<cf>", "<cr>", "<lf>"])
+    }
+
+    fn replace_eol_back(self) -> Self {
+        unescape_eol(self, ["\r\n", "\n", "\r"])
+    }
+
+    fn normalize_eol(self) -> Self {
+        escape_eol(self, ["<lf>"; 3])
+    }
+
+    fn replace_eol_back_as(self, ending: LineEnding) -> Self {
+        unescape_eol(self, [ending.as_str(); 3])
+    }
+}
+
+/// The escape codes without their leading `<`, `<lt>` is used to escape a
+/// literal `<` that would otherwise be read back as the start of a code.
+#[cfg(feature = "serialize")]
+const CODES: [&str; 4] = ["cf>", "lf>", "cr>", "lt>"];
 
-        let mut buf = String::with_capacity(self.len());
-        let mut chars = self.chars();
-
-        while let Some(ch) = chars.next() {
-            match ch {
-                '\r' => {
-                    let br = match chars.as_str().strip_prefix('\n') {
-                        Some(s) => {
-                            chars = s.chars();
-                            "<cf>"
-                        }
-                        _ => "<cr>",
-                    };
-                    buf.push_str(br);
-                }
-                '\n' => buf.push_str("<lf>"),
-                ch => buf.push(ch),
+/// Returns `true` if `string` starts with one of the [`CODES`].
+#[cfg(feature = "serialize")]
+fn starts_with_code(string: &str) -> bool {
+    CODES.iter().any(|c| string.starts_with(c))
+}
+
+/// Returns `true` if `string` has one of the [`CODES`] in it.
+#[cfg(feature = "serialize")]
+fn has_code(string: &str) -> bool {
+    string
+        .match_indices('<')
+        .any(|(i, _)| starts_with_code(&string[i + 1..]))
+}
+
+/// Replace `\r\n`, `\r` and `\n` in `string` with the `codes` in this order,
+/// and a literal `<` that starts a code with `<lt>` so it stays as it is
+/// when the string is unescaped.
+#[cfg(feature = "serialize")]
+fn escape_eol(string: String, [crlf, cr, lf]: [&str; 3]) -> String {
+    if !string.contains(['\r', '\n']) && !has_code(&string) {
+        return string;
+    }
+
+    let mut buf = String::with_capacity(string.len());
+    let mut chars = string.chars();
+
+    while let Some(ch) = chars.next() {
+        match ch {
+            '\r' => {
+                let br = match chars.as_str().strip_prefix('\n') {
+                    Some(s) => {
+                        chars = s.chars();
+                        crlf
+                    }
+                    _ => cr,
+                };
+                buf.push_str(br);
             }
+            '\n' => buf.push_str(lf),
+            '<' if starts_with_code(chars.as_str()) => buf.push_str("<lt>"),
+            ch => buf.push(ch),
         }
+    }
 
-        buf
+    buf
+}
+
+/// Replace `<cf>`, `<lf>` and `<cr>` in `string` with the `breaks` in this
+/// order, and `<lt>` with `<`.
+#[cfg(feature = "serialize")]
+fn unescape_eol(string: String, [cf, lf, cr]: [&str; 3]) -> String {
+    if !has_code(&string) {
+        return string;
     }
 
-    fn replace_eol_back(self) -> Self {
-        if !(self.contains("<cf>") || self.contains("<lf>") || self.contains("<cr>")) {
-            return self;
-        }
+    let mut buf = String::with_capacity(string.len());
+    let mut chars = string.chars();
 
-        let mut buf = String::with_capacity(self.len());
-        let mut chars = self.chars();
-
-        while let Some(ch) = chars.next() {
-            if ch == '<' {
-                let mut is_code = true;
-                match chars.as_str().get(0..3) {
-                    Some("cf>") => buf.push_str("\r\n"),
-                    Some("lf>") => buf.push('\n'),
-                    Some("cr>") => buf.push('\r'),
-                    _ => is_code = false,
-                }
-
-                if is_code {
-                    chars = chars.as_str()[3..].chars();
-                    continue;
-                }
+    while let Some(ch) = chars.next() {
+        if ch == '<' {
+            let mut is_code = true;
+            match chars.as_str().get(0..3) {
+                Some("cf>") => buf.push_str(cf),
+                Some("lf>") => buf.push_str(lf),
+                Some("cr>") => buf.push_str(cr),
+                Some("lt>") => buf.push('<'),
+                _ => is_code = false,
             }
 
-            buf.push(ch);
+            if is_code {
+                chars = chars.as_str()[3..].chars();
+                continue;
+            }
         }
 
-        buf
+        buf.push(ch);
     }
+
+    buf
 }
 
 #[cfg(all(feature = "serialize", test))]
 mod test {
     use super::*;
 
+    #[test]
+    fn deserialize_index_from_number_or_string() {
+        #[derive(serde::Deserialize)]
+        struct Local {
+            #[serde(deserialize_with = "deserialize_index")]
+            index: usize,
+        }
+
+        let local: Local = serde_json::from_str(r#"{"index": 42}"#).unwrap();
+        assert_eq!(local.index, 42);
+        let local: Local = serde_json::from_str(r#"{"index": "42"}"#).unwrap();
+        assert_eq!(local.index, 42);
+        assert!(serde_json::from_str::<Local>(r#"{"index": "abc"}"#).is_err());
+        assert!(serde_json::from_str::<Local>(r#"{"index": -1}"#).is_err());
+    }
+
     #[test]
     fn replace_eof() {
         assert_eq!(
@@ -144,4 +282,68 @@ mod test {
             "This<hf>Test",
         );
     }
+
+    #[test]
+    fn eol_round_trip() {
+        for str in [
+            "no line break",
+            "ends with lf\n",
+            "ends with cr\r",
+            "ends with crlf\r\n",
+            "\r\r\n\n\r",
+            "\nstarts with lf",
+            "not a code <lf",
+            "ends with <",
+            "abc<lf>",
+            "literal <cr> and <cf> codes\n",
+            "escaped <lt> and <<lf>>",
+        ] {
+            let escaped = str.to_owned().replace_eol();
+            assert!(!escaped.contains(['\r', '\n']));
+            assert_eq!(escaped.replace_eol_back(), str);
+        }
+    }
+
+    #[test]
+    fn literal_codes_are_escaped() {
+        assert_eq!(String::from("abc<lf>").replace_eol(), "abc<lt>lf>");
+        assert_eq!(String::from("a<lt>\n").replace_eol(), "a<lt>lt><lf>");
+        assert_eq!(String::from("abc<lt>lf>").replace_eol_back(), "abc<lf>");
+        assert_eq!(String::from("<lt>cr>\r").normalize_eol(), "<lt>lt>cr><lf>");
+    }
+
+    #[test]
+    fn normalized_eol() {
+        let escaped = String::from("a\r\nb\rc\nd").normalize_eol();
+        assert_eq!(escaped, "a<lf>b<lf>c<lf>d");
+        assert_eq!(
+            escaped.clone().replace_eol_back_as(LineEnding::CrLf),
+            "a\r\nb\r\nc\r\nd"
+        );
+        assert_eq!(
+            String::from("a<cf>b<cr>c").replace_eol_back_as(LineEnding::Lf),
+            "a\nb\nc"
+        );
+        assert_eq!(escaped.replace_eol_back(), "a\nb\nc\nd");
+    }
+
+    #[test]
+    fn extension_of_unicode_paths() {
+        let core = &["core"][..];
+        assert!(has_extension(Path::new("ローカライズ/Ünïcödé.core"), core));
+        assert!(has_extension(Path::new("C:\\Spiele\\ΕΛΛΗΝΙΚΆ.CORE"), core));
+        assert!(!has_extension(Path::new("ローカライズ/core"), core));
+        assert!(!has_extension(Path::new("ローカライズ.core.json"), core));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn extension_of_non_utf8_paths() {
+        use std::os::unix::ffi::OsStrExt;
+
+        let name = std::ffi::OsStr::from_bytes(b"\xff\xfe.core");
+        assert!(has_extension(Path::new(name), &["core"]));
+        let extension = std::ffi::OsStr::from_bytes(b"name.\xffcore");
+        assert!(!has_extension(Path::new(extension), &["core"]));
+    }
 }
@@ -7,6 +7,8 @@ use std::{
 
 use binrw::{args, BinRead, BinResult, BinWrite};
 
+use crate::error::{InvalidString, StringEncoding};
+
 /// A string type that stores a UTF-16 encoded string with a 32-bit length prefix.
 ///
 /// Implements [`BinRead`] and [`BinWrite`] for serialization, as well as [`Deref`]
@@ -37,12 +39,16 @@ impl BinRead for U16String {
         endian: binrw::Endian,
         _: Self::Args<'_>,
     ) -> BinResult<Self> {
+        let start = reader.stream_position()?;
         let len = u32::read_options(reader, endian, ())?;
         let bytes = <Vec<u16>>::read_options(reader, endian, args! { count: len as usize })?;
 
-        let string = String::from_utf16(&bytes).map_err(|e| binrw::Error::Custom {
-            pos: reader.stream_position().unwrap_or_default(),
-            err: Box::new(e),
+        let string = String::from_utf16(&bytes).map_err(|_| binrw::Error::Custom {
+            pos: start,
+            err: Box::new(InvalidString {
+                position: start,
+                kind: StringEncoding::Utf16,
+            }),
         })?;
 
         Ok(Self(string))
@@ -7,6 +7,8 @@ use std::{
 
 use binrw::{BinRead, BinResult, BinWrite};
 
+use crate::error::{InvalidString, StringEncoding};
+
 /// A string type that stores a UTF-8 encoded string with a 16-bit length prefix.
 ///
 /// Implements [`BinRead`] and [`BinWrite`] for serialization, as well as [`Deref`]
@@ -29,13 +31,17 @@ impl BinRead for U8String {
         endian: binrw::Endian,
         _: Self::Args<'_>,
     ) -> BinResult<Self> {
+        let start = reader.stream_position()?;
         let len = u16::read_options(reader, endian, ())?;
         let mut buf = vec![0_u8; len as usize];
         reader.read_exact(&mut buf)?;
 
-        let string = String::from_utf8(buf).map_err(|e| binrw::Error::Custom {
-            pos: reader.stream_position().unwrap_or_default(),
-            err: Box::new(e),
+        let string = String::from_utf8(buf).map_err(|_| binrw::Error::Custom {
+            pos: start,
+            err: Box::new(InvalidString {
+                position: start,
+                kind: StringEncoding::Utf8,
+            }),
         })?;
 
         Ok(Self(string))